@@ -0,0 +1,20 @@
+#![no_main]
+
+use app_store_server_library::receipt_utility::{receipt_attributes, ReceiptAttributes};
+use libfuzzer_sys::fuzz_target;
+
+fn walk(attributes: ReceiptAttributes<'_>, depth: usize) {
+    for attribute in attributes.flatten() {
+        if depth < 4 {
+            if let Ok(nested) = attribute.nested_attributes() {
+                walk(nested, depth + 1);
+            }
+        }
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(attributes) = receipt_attributes(data) {
+        walk(attributes, 0);
+    }
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use app_store_server_library::receipt_utility::{
+    extract_transaction_id_from_app_receipt, extract_transaction_id_from_transaction_receipt,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = extract_transaction_id_from_app_receipt(data);
+    let _ = extract_transaction_id_from_transaction_receipt(data);
+});
@@ -0,0 +1,20 @@
+#![no_main]
+
+use app_store_server_library::primitives::environment::Environment;
+use app_store_server_library::signed_data_verifier::SignedDataVerifier;
+use libfuzzer_sys::fuzz_target;
+use std::sync::OnceLock;
+
+// A Production verifier decodes and parses the x5c chain of every payload before rejecting it.
+fn verifier() -> &'static SignedDataVerifier {
+    static VERIFIER: OnceLock<SignedDataVerifier> = OnceLock::new();
+    VERIFIER.get_or_init(|| SignedDataVerifier::new(vec![], Environment::Production, "com.example".to_string(), Some(1234)))
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(signed_payload) = std::str::from_utf8(data) else { return };
+    let verifier = verifier();
+    let _ = verifier.precheck(signed_payload);
+    let _ = verifier.verify_signed_payload(signed_payload);
+    let _ = verifier.verify_and_decode_notification(signed_payload);
+});
@@ -0,0 +1,230 @@
+use crate::notification_router::{NotificationRouter, NotificationRouterError};
+use crate::primitives::decoded_notification::DecodedNotification;
+use crate::signed_data_verifier::{SignedDataVerifier, SignedDataVerifierError};
+use crate::webhook::{parse_signed_payload_with_limit, WebhookBodyError, DEFAULT_MAX_BODY_SIZE};
+use actix_web::body::{to_bytes_limited, BodyStream};
+use actix_web::dev::Payload;
+use actix_web::http::header::CONTENT_LENGTH;
+use actix_web::http::StatusCode;
+use actix_web::{web, FromRequest, HttpRequest, HttpResponse, ResponseError};
+use std::fmt::Display;
+use std::future::Future;
+use std::pin::Pin;
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ActixNotificationError {
+    #[error("MissingVerifierError: [No web::Data<SignedDataVerifier> registered on the app]")]
+    MissingVerifierError,
+
+    #[error("InvalidBodyError: [{0}]")]
+    InvalidBodyError(String),
+
+    #[error("WebhookBodyError: [{0}]")]
+    WebhookBodyError(#[from] WebhookBodyError),
+
+    #[error("VerificationError: [{0}]")]
+    VerificationError(#[from] SignedDataVerifierError),
+}
+
+impl ResponseError for ActixNotificationError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ActixNotificationError::MissingVerifierError => StatusCode::INTERNAL_SERVER_ERROR,
+            ActixNotificationError::InvalidBodyError(_) => StatusCode::BAD_REQUEST,
+            ActixNotificationError::WebhookBodyError(WebhookBodyError::BodyTooLargeError { .. }) => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            ActixNotificationError::WebhookBodyError(_) => StatusCode::BAD_REQUEST,
+            ActixNotificationError::VerificationError(_) => StatusCode::FORBIDDEN,
+        }
+    }
+}
+
+/// The limit on the size of notification request bodies, registered on the app with `app_data`.
+///
+/// Without it, bodies are limited to [`DEFAULT_MAX_BODY_SIZE`](crate::webhook::DEFAULT_MAX_BODY_SIZE).
+/// The limit applies instead of the `PayloadConfig` of the app.
+///
+/// ```ignore
+/// App::new().app_data(NotificationBodyConfig::default().max_body_size(64 * 1024))
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct NotificationBodyConfig {
+    max_body_size: usize,
+}
+
+impl NotificationBodyConfig {
+    /// Sets the maximum accepted size of a request body, in bytes.
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+}
+
+impl Default for NotificationBodyConfig {
+    fn default() -> Self {
+        NotificationBodyConfig { max_body_size: DEFAULT_MAX_BODY_SIZE }
+    }
+}
+
+/// Reads the signed payload from the body of a request, limited by the [`NotificationBodyConfig`] of the app.
+fn read_signed_payload(
+    req: &HttpRequest,
+    payload: Payload,
+) -> impl Future<Output = Result<String, ActixNotificationError>> {
+    let max_body_size = req.app_data::<NotificationBodyConfig>().copied().unwrap_or_default().max_body_size;
+    let content_length = req
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+
+    async move {
+        let body = match to_bytes_limited(BodyStream::new(payload), max_body_size).await {
+            Ok(body) => body.map_err(|e| ActixNotificationError::InvalidBodyError(e.to_string()))?,
+            Err(_) => {
+                // A body without a length is only read up to the limit.
+                let size = content_length.unwrap_or(max_body_size.saturating_add(1));
+                return Err(WebhookBodyError::BodyTooLargeError { size, limit: max_body_size }.into());
+            }
+        };
+
+        Ok(parse_signed_payload_with_limit(&body, max_body_size)?)
+    }
+}
+
+/// An extractor for a verified and decoded App Store Server Notification.
+///
+/// Reads the `signedPayload` from the request body and verifies it, along with its signed transaction
+/// and renewal info, using the `web::Data<SignedDataVerifier>` registered on the app. Requests whose
+/// body is malformed are rejected with `400 Bad Request`, those larger than the limit of the
+/// [`NotificationBodyConfig`] with `413 Payload Too Large`, and those that fail verification with
+/// `403 Forbidden`.
+#[derive(Debug, Clone)]
+pub struct VerifiedNotification(pub DecodedNotification);
+
+impl FromRequest for VerifiedNotification {
+    type Error = ActixNotificationError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let verifier = req.app_data::<web::Data<SignedDataVerifier>>().cloned();
+        let signed_payload = read_signed_payload(req, payload.take());
+
+        Box::pin(async move {
+            let verifier = verifier.ok_or(ActixNotificationError::MissingVerifierError)?;
+            let signed_payload = signed_payload.await?;
+
+            Ok(VerifiedNotification(verifier.verify_and_decode_notification_full(&signed_payload)?))
+        })
+    }
+}
+
+/// A handler that dispatches notifications through the `web::Data<NotificationRouter<E>>` registered on the app.
+///
+/// Responds with `200 OK` once a handler succeeded or when no handler is registered for the notification,
+/// `400 Bad Request` or `403 Forbidden` for malformed or unverifiable payloads, `413 Payload Too Large` for bodies
+/// larger than the limit of the [`NotificationBodyConfig`], and `500 Internal Server Error`
+/// when the handler or the router's deduper fails, so that the App Store retries the notification.
+///
+/// ```ignore
+/// App::new()
+///     .app_data(web::Data::new(router))
+///     .route("/notifications", web::post().to(dispatch_notification::<MyError>))
+/// ```
+pub async fn dispatch_notification<E: Display + 'static>(
+    router: web::Data<NotificationRouter<E>>,
+    req: HttpRequest,
+    payload: web::Payload,
+) -> HttpResponse {
+    let signed_payload = match read_signed_payload(&req, payload.into_inner()).await {
+        Ok(signed_payload) => signed_payload,
+        Err(error) => return error.error_response(),
+    };
+
+    match router.dispatch(&signed_payload).await {
+        Ok(_) => HttpResponse::Ok().finish(),
+        Err(NotificationRouterError::VerificationError(error)) => {
+            ActixNotificationError::VerificationError(error).error_response()
+        }
+        Err(NotificationRouterError::HandlerError(error)) => {
+            HttpResponse::InternalServerError().body(error.to_string())
+        }
+        Err(NotificationRouterError::DeduplicationError(error)) => HttpResponse::InternalServerError().body(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::notification_type_v2::NotificationTypeV2;
+    use crate::test_utils::{local_testing_verifier, signed_notification_fixture};
+    use actix_web::{test, App};
+    use serde_json::json;
+
+    async fn notification_type(notification: VerifiedNotification) -> String {
+        format!("{:?}", notification.0.notification_type())
+    }
+
+    #[actix_web::test]
+    async fn test_verified_notification_extractor() {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(local_testing_verifier()))
+                .route("/", web::post().to(notification_type)),
+        )
+        .await;
+
+        let signed_payload = signed_notification_fixture(&NotificationTypeV2::Test, None);
+        let request = test::TestRequest::post().uri("/").set_json(json!({ "signedPayload": signed_payload }));
+        let response = test::call_service(&app, request.to_request()).await;
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!("Test", test::read_body(response).await);
+
+        let request = test::TestRequest::post().uri("/").set_json(json!({ "signedPayload": "invalid" }));
+        assert_eq!(StatusCode::FORBIDDEN, test::call_service(&app, request.to_request()).await.status());
+
+        let request = test::TestRequest::post().uri("/").set_payload("{");
+        assert_eq!(StatusCode::BAD_REQUEST, test::call_service(&app, request.to_request()).await.status());
+
+        let request = test::TestRequest::post().uri("/").set_payload(vec![b' '; DEFAULT_MAX_BODY_SIZE + 1]);
+        assert_eq!(StatusCode::PAYLOAD_TOO_LARGE, test::call_service(&app, request.to_request()).await.status());
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(local_testing_verifier()))
+                .app_data(NotificationBodyConfig::default().max_body_size(8))
+                .route("/", web::post().to(notification_type)),
+        )
+        .await;
+        let request = test::TestRequest::post().uri("/").set_json(json!({ "signedPayload": "x" }));
+        assert_eq!(StatusCode::PAYLOAD_TOO_LARGE, test::call_service(&app, request.to_request()).await.status());
+    }
+
+    #[actix_web::test]
+    async fn test_dispatch_notification() {
+        let router = NotificationRouter::new(local_testing_verifier())
+            .on(NotificationTypeV2::Refund, |_| async { Err("refund failed".to_string()) });
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(router))
+                .route("/", web::post().to(dispatch_notification::<String>)),
+        )
+        .await;
+
+        let call = |notification_type: NotificationTypeV2| {
+            let signed_payload = signed_notification_fixture(&notification_type, None);
+            test::TestRequest::post().uri("/").set_json(json!({ "signedPayload": signed_payload })).to_request()
+        };
+
+        assert_eq!(StatusCode::OK, test::call_service(&app, call(NotificationTypeV2::Test)).await.status());
+        assert_eq!(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            test::call_service(&app, call(NotificationTypeV2::Refund)).await.status()
+        );
+
+        let request = test::TestRequest::post().uri("/").set_payload(vec![b' '; DEFAULT_MAX_BODY_SIZE + 1]);
+        assert_eq!(StatusCode::PAYLOAD_TOO_LARGE, test::call_service(&app, request.to_request()).await.status());
+    }
+}
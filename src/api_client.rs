@@ -1,10 +1,8 @@
 use std::collections::HashMap;
 use std::fmt;
-use chrono::{Utc};
 use serde::{Serialize, Deserialize};
 use reqwest::{Client, RequestBuilder, Method};
-use jsonwebtoken::{Header, Algorithm, encode, EncodingKey};
-use reqwest::header::HeaderMap;
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, USER_AGENT};
 use crate::primitives::check_test_notification_response::CheckTestNotificationResponse;
 use crate::primitives::consumption_request::ConsumptionRequest;
 use crate::primitives::environment::Environment;
@@ -15,13 +13,28 @@ use crate::primitives::mass_extend_renewal_date_request::MassExtendRenewalDateRe
 use crate::primitives::mass_extend_renewal_date_status_response::MassExtendRenewalDateStatusResponse;
 use crate::primitives::notification_history_request::NotificationHistoryRequest;
 use crate::primitives::notification_history_response::NotificationHistoryResponse;
+use crate::primitives::jws_transaction_decoded_payload::JWSTransactionDecodedPayload;
 use crate::primitives::order_lookup_response::OrderLookupResponse;
+use crate::primitives::order_lookup_status::OrderLookupStatus;
+use crate::primitives::original_transaction_id::OriginalTransactionId;
 use crate::primitives::refund_history_response::RefundHistoryResponse;
+use crate::primitives::response_body_v2_decoded_payload::ResponseBodyV2DecodedPayload;
+use crate::primitives::send_attempt_item::SendAttemptItem;
 use crate::primitives::send_test_notification_response::SendTestNotificationResponse;
 use crate::primitives::status::Status;
 use crate::primitives::status_response::StatusResponse;
 use crate::primitives::transaction_history_request::TransactionHistoryRequest;
+use crate::primitives::timestamp;
+use crate::primitives::transaction_id::TransactionId;
 use crate::primitives::transaction_info_response::TransactionInfoResponse;
+use crate::jwt_issuer::AppStoreJwtIssuer;
+use crate::signed_data_verifier::{SignedDataVerifier, SignedDataVerifierError};
+use crate::key_ring::KeyRing;
+use crate::signer::{AsyncSigner, EcdsaSigner, KeyBytes, KeyFormat, SignFuture, SignerError};
+use std::sync::Arc;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct APIException {
@@ -29,6 +42,87 @@ pub struct APIException {
     pub api_error: Option<APIError>,
     pub raw_api_error: Option<i64>,
     pub error_message: Option<String>,
+    /// The number of seconds the `Retry-After` header of the response asked to wait before retrying, if it had one.
+    #[serde(default)]
+    pub retry_after: Option<u64>,
+    /// The start of the response body, up to [`MAX_RAW_BODY_SIZE`](Self::MAX_RAW_BODY_SIZE) bytes, for debugging
+    /// responses that the library couldn't decode.
+    #[serde(default)]
+    pub raw_body: Option<Vec<u8>>,
+    /// The values of the [`SELECTED_HEADERS`](Self::SELECTED_HEADERS) the response had, by lowercase name.
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// The path of the field that couldn't be decoded, such as `data[0].lastTransactions[1].status`,
+    /// when the response body didn't match the expected model.
+    #[serde(default)]
+    pub json_path: Option<String>,
+    /// Whether the status and error come from a response of the App Store, rather than from a failure of
+    /// the client itself, such as signing the token, building or sending the request, or decoding a
+    /// successful response. Client failures are reported with the status 500.
+    #[serde(default)]
+    pub from_response: bool,
+}
+
+impl APIException {
+    /// The number of bytes of the response body that are kept in [`raw_body`](Self::raw_body).
+    pub const MAX_RAW_BODY_SIZE: usize = 4096;
+
+    /// The response headers that are kept in [`headers`](Self::headers).
+    pub const SELECTED_HEADERS: &'static [&'static str] = &["content-type", "retry-after", Self::REQUEST_ID_HEADER];
+
+    /// The response header with the ID Apple assigned to the request, to quote when contacting Apple about it.
+    pub const REQUEST_ID_HEADER: &'static str = "x-apple-request-uuid";
+
+    /// The ID Apple assigned to the request, if the response had one.
+    pub fn request_id(&self) -> Option<&str> {
+        self.headers.get(Self::REQUEST_ID_HEADER).map(String::as_str)
+    }
+
+    /// Whether the request can succeed when sent again later, because the App Store rate limited it,
+    /// failed with a server error, or returned an error that Apple documents as retryable.
+    ///
+    /// Failures of the client itself aren't retryable, since they fail the same way every time.
+    pub fn is_retryable(&self) -> bool {
+        if !self.from_response {
+            return false;
+        }
+
+        self.http_status_code == 429
+            || (500..600).contains(&self.http_status_code)
+            || self.api_error.as_ref().is_some_and(APIError::is_retryable)
+    }
+
+    /// Creates the exception for a failure of the client itself, reported with the status 500.
+    fn client_failure(error_message: String) -> Self {
+        APIException {
+            http_status_code: 500,
+            api_error: None,
+            raw_api_error: None,
+            error_message: Some(error_message),
+            retry_after: None,
+            raw_body: None,
+            headers: HashMap::new(),
+            json_path: None,
+            from_response: false,
+        }
+    }
+
+    /// Creates the exception for a response whose body couldn't be read, keeping the selected headers.
+    fn body_read_failure(headers: &HeaderMap) -> Self {
+        APIException {
+            headers: selected_headers(headers),
+            ..APIException::client_failure("Failed to read HTTP response body".to_string())
+        }
+    }
+
+    /// The delay the App Store suggested before retrying, or `None` if the request isn't retryable
+    /// or the response didn't suggest one.
+    pub fn retry_delay(&self) -> Option<Duration> {
+        if !self.is_retryable() {
+            return None;
+        }
+        self.retry_after.map(Duration::from_secs)
+    }
 }
 
 impl fmt::Display for APIException {
@@ -47,9 +141,58 @@ impl fmt::Display for APIException {
     }
 }
 
+#[derive(thiserror::Error, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ConfigurationError {
+    #[error("InvalidSigningKeyError: [{source} (detected format: {format})]")]
+    InvalidSigningKeyError { format: KeyFormat, source: SignerError },
+
+    #[error("TransportError: [{0}]")]
+    TransportError(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum MassExtensionPollError {
+    #[error("APIError: [{0}]")]
+    APIError(#[source] APIException),
+
+    #[error("TimeoutError: [The renewal date extension didn't complete in time]")]
+    TimeoutError { last_status: MassExtendRenewalDateStatusResponse },
+}
+
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum TestNotificationCheckError {
+    #[error("APIError: [{0}]")]
+    APIError(#[source] APIException),
+
+    #[error("VerificationError: [{0}]")]
+    VerificationError(#[source] SignedDataVerifierError),
+
+    #[error("MissingTokenError: [The response to the test notification request didn't include a test notification token]")]
+    MissingTokenError,
+
+    #[error("TimeoutError: [The App Store didn't record an attempt to send the test notification in time]")]
+    TimeoutError { test_notification_token: String },
+}
+
+/// The test notification sent by [`AppStoreServerAPIClient::check_test_notification`] and the results of sending it to your server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestNotificationCheck {
+    pub test_notification_token: String,
+
+    /// The verified and decoded test notification.
+    pub notification: ResponseBodyV2DecodedPayload,
+
+    /// The attempts the App Store recorded to send the notification to your server, with their results.
+    ///
+    /// [sendAttemptItem](https://developer.apple.com/documentation/appstoreserverapi/sendattemptitem)
+    pub send_attempts: Vec<SendAttemptItem>,
+}
+
 #[cfg(test)]
 use http::Response;
-use serde_json::Value;
 use crate::primitives::error_payload::{APIError, ErrorPayload};
 
 impl std::error::Error for APIException {}
@@ -59,60 +202,279 @@ type RequestVerifier = fn(&reqwest::Request, Option<&[u8]>) -> ();
 #[cfg(test)]
 type RequestOverride = dyn Fn(&reqwest::Request, Option<&[u8]>) -> http::Response<Vec<u8>>;
 
+/// The value of a query parameter, serialized without first being formatted into a `String`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum QueryValue<'a> {
+    Str(&'a str),
+    Int(i64),
+    Bool(bool),
+}
+
+impl Serialize for QueryValue<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            QueryValue::Str(value) => serializer.serialize_str(value),
+            QueryValue::Int(value) => serializer.serialize_i64(*value),
+            QueryValue::Bool(value) => serializer.serialize_bool(*value),
+        }
+    }
+}
+
+/// Query parameters in the order they are sent, borrowing their values from the request.
+type Query<'a> = Vec<(&'static str, QueryValue<'a>)>;
+
+/// The status and headers of a response from the App Store Server API, passed to the observer registered
+/// with [`AppStoreServerAPIClient::on_response`].
+#[derive(Debug)]
+pub struct ResponseMetadata<'a> {
+    pub url: &'a str,
+    pub http_status_code: u16,
+    pub headers: &'a HeaderMap,
+}
+
+/// An observer of every response the client receives.
+pub type ResponseObserver = dyn Fn(&ResponseMetadata<'_>) + Send + Sync;
+
+/// Connection settings of the HTTP client that sends the requests, applied with
+/// [`AppStoreServerAPIClient::transport`].
+///
+/// Settings left unset keep the defaults of `reqwest`. Keeping idle connections open and pinging them
+/// over HTTP/2 avoids reconnecting to Apple between the bursts of requests of a backfill.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TransportConfig {
+    pool_max_idle_per_host: Option<usize>,
+    pool_idle_timeout: Option<Duration>,
+    http2_keep_alive_interval: Option<Duration>,
+    http2_keep_alive_timeout: Option<Duration>,
+    http2_keep_alive_while_idle: bool,
+    tcp_nodelay: Option<bool>,
+    tcp_keepalive: Option<Duration>,
+}
+
+impl TransportConfig {
+    /// Sets the maximum number of idle connections kept open to Apple's servers.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Sets how long an idle connection is kept open.
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Sends HTTP/2 keep-alive pings at this interval.
+    pub fn http2_keep_alive_interval(mut self, interval: Duration) -> Self {
+        self.http2_keep_alive_interval = Some(interval);
+        self
+    }
+
+    /// Sets how long to wait for the acknowledgement of a keep-alive ping before closing the connection.
+    pub fn http2_keep_alive_timeout(mut self, timeout: Duration) -> Self {
+        self.http2_keep_alive_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets whether keep-alive pings are also sent on connections without open requests.
+    pub fn http2_keep_alive_while_idle(mut self, enabled: bool) -> Self {
+        self.http2_keep_alive_while_idle = enabled;
+        self
+    }
+
+    /// Sets the `TCP_NODELAY` option of the connections.
+    pub fn tcp_nodelay(mut self, enabled: bool) -> Self {
+        self.tcp_nodelay = Some(enabled);
+        self
+    }
+
+    /// Sets the interval of the TCP keep-alive probes of the connections.
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.tcp_keepalive = Some(interval);
+        self
+    }
+
+    fn build_client(&self) -> Result<Client, ConfigurationError> {
+        let mut builder = Client::builder().http2_keep_alive_while_idle(self.http2_keep_alive_while_idle);
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(interval) = self.http2_keep_alive_interval {
+            builder = builder.http2_keep_alive_interval(interval);
+        }
+        if let Some(timeout) = self.http2_keep_alive_timeout {
+            builder = builder.http2_keep_alive_timeout(timeout);
+        }
+        if let Some(enabled) = self.tcp_nodelay {
+            builder = builder.tcp_nodelay(enabled);
+        }
+        if let Some(interval) = self.tcp_keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+        builder.build().map_err(|error| ConfigurationError::TransportError(error.to_string()))
+    }
+}
+
+/// An error that kept a [`Transport`] from receiving a response.
+pub type TransportError = Box<dyn std::error::Error + Send + Sync>;
+
+/// The future returned by [`Transport::send`].
+pub type TransportFuture<'a> = Pin<Box<dyn Future<Output = Result<reqwest::Response, TransportError>> + Send + 'a>>;
+
+/// Sends the requests of an [`AppStoreServerAPIClient`] in place of its HTTP client, installed with
+/// [`AppStoreServerAPIClient::with_transport`].
+///
+/// `client` is the HTTP client the request would otherwise be sent with, so that a transport can
+/// decorate it, for instance to record the responses.
+pub trait Transport: Send + Sync {
+    fn send<'a>(&'a self, client: &'a Client, request: reqwest::Request) -> TransportFuture<'a>;
+}
+
+/// A client for the App Store Server API.
+///
+/// Clones share the token issuer, the connection pool and the response observer, so a client can be
+/// stored in the state of a web framework and cloned per request.
+#[derive(Clone)]
 pub struct AppStoreServerAPIClient {
     base_url: String,
-    signing_key: Vec<u8>,
-    key_id: String,
-    issuer_id: String,
-    bundle_id: String,
+    token_issuer: Arc<AppStoreJwtIssuer>,
     client: Client,
+    response_observer: Option<Arc<ResponseObserver>>,
+    transport: Option<Arc<dyn Transport>>,
     #[cfg(test)]
-    request_override: Box<RequestOverride>,
+    request_override: Arc<RequestOverride>,
 }
 
 impl AppStoreServerAPIClient {
     #[cfg(not(test))]
     pub fn new(signing_key: Vec<u8>, key_id: &str, issuer_id: &str, bundle_id: &str, environment: Environment) -> Self {
+        Self::with_signer(pem_signer(signing_key), key_id, issuer_id, bundle_id, environment)
+    }
+
+    #[cfg(test)]
+    pub fn new(signing_key: Vec<u8>, key_id: &str, issuer_id: &str, bundle_id: &str, environment: Environment, request_override: Box<RequestOverride>) -> Self {
+        Self::with_signer(pem_signer(signing_key), key_id, issuer_id, bundle_id, environment, request_override)
+    }
+
+    /// Creates a client like [`new`](Self::new), but fails right away if the signing key can't be parsed
+    /// instead of failing every request.
+    ///
+    /// # Returns
+    ///
+    /// - `Err(ConfigurationError::InvalidSigningKeyError)` with the detected format of the key if it isn't a valid P-256 private key.
+    #[cfg(not(test))]
+    pub fn try_new(signing_key: Vec<u8>, key_id: &str, issuer_id: &str, bundle_id: &str, environment: Environment) -> Result<Self, ConfigurationError> {
+        Ok(Self::with_signer(checked_signer(signing_key)?, key_id, issuer_id, bundle_id, environment))
+    }
+
+    #[cfg(test)]
+    pub fn try_new(signing_key: Vec<u8>, key_id: &str, issuer_id: &str, bundle_id: &str, environment: Environment, request_override: Box<RequestOverride>) -> Result<Self, ConfigurationError> {
+        Ok(Self::with_signer(checked_signer(signing_key)?, key_id, issuer_id, bundle_id, environment, request_override))
+    }
+
+    /// Creates a client that signs its authorization tokens with the given signer,
+    /// such as one backed by a key management service or an HSM.
+    #[cfg(not(test))]
+    pub fn with_signer(signer: Arc<dyn AsyncSigner>, key_id: &str, issuer_id: &str, bundle_id: &str, environment: Environment) -> Self {
+        Self::with_key_ring(Arc::new(KeyRing::with_key(key_id, signer)), issuer_id, bundle_id, environment)
+    }
+
+    #[cfg(test)]
+    pub fn with_signer(signer: Arc<dyn AsyncSigner>, key_id: &str, issuer_id: &str, bundle_id: &str, environment: Environment, request_override: Box<RequestOverride>) -> Self {
+        Self::with_key_ring(Arc::new(KeyRing::with_key(key_id, signer)), issuer_id, bundle_id, environment, request_override)
+    }
+
+    /// Creates a client that signs its authorization tokens with the active key of the key ring.
+    ///
+    /// Keys added to or retired from the shared key ring take effect on the next request.
+    #[cfg(not(test))]
+    pub fn with_key_ring(keys: Arc<KeyRing>, issuer_id: &str, bundle_id: &str, environment: Environment) -> Self {
+        Self::with_jwt_issuer(AppStoreJwtIssuer::new(keys, issuer_id).bundle_id(bundle_id), environment)
+    }
+
+    #[cfg(test)]
+    pub fn with_key_ring(keys: Arc<KeyRing>, issuer_id: &str, bundle_id: &str, environment: Environment, request_override: Box<RequestOverride>) -> Self {
+        Self::with_jwt_issuer(AppStoreJwtIssuer::new(keys, issuer_id).bundle_id(bundle_id), environment, request_override)
+    }
+
+    /// Creates a client that authorizes its requests with tokens from `token_issuer`,
+    /// which needs to include the bundle ID of the app.
+    ///
+    /// Pass an `Arc` to keep refreshing the issuer's token in the background with
+    /// [`AppStoreJwtIssuer::spawn_refresh`].
+    #[cfg(not(test))]
+    pub fn with_jwt_issuer(token_issuer: impl Into<Arc<AppStoreJwtIssuer>>, environment: Environment) -> Self {
+        let token_issuer = token_issuer.into();
         let base_url = environment.base_url();
         let client = Client::new();
-        Self { base_url, signing_key, key_id: key_id.to_string(), issuer_id: issuer_id.to_string(), bundle_id: bundle_id.to_string(), client }
+        Self { base_url, token_issuer, client, response_observer: None, transport: None }
     }
 
     #[cfg(test)]
-    pub fn new(signing_key: Vec<u8>, key_id: &str, issuer_id: &str, bundle_id: &str, environment: Environment, request_override: Box<RequestOverride>) -> Self {
+    pub fn with_jwt_issuer(token_issuer: impl Into<Arc<AppStoreJwtIssuer>>, environment: Environment, request_override: Box<RequestOverride>) -> Self {
+        let token_issuer = token_issuer.into();
         let base_url = environment.base_url();
         let client = Client::new();
-        Self { base_url, signing_key, key_id: key_id.to_string(), issuer_id: issuer_id.to_string(), bundle_id: bundle_id.to_string(), client, request_override}
+        Self { base_url, token_issuer, client, response_observer: None, transport: None, request_override: request_override.into() }
+    }
+
+    /// Replaces the HTTP client with one built from `config`.
+    ///
+    /// # Returns
+    ///
+    /// - `Err(ConfigurationError::TransportError)` if the HTTP client can't be built, for instance when its TLS backend fails to initialize.
+    pub fn transport(mut self, config: &TransportConfig) -> Result<Self, ConfigurationError> {
+        self.client = config.build_client()?;
+        Ok(self)
     }
 
-    fn generate_token(&self) -> String {
-        let future_time = Utc::now() + chrono::Duration::minutes(5);
-        let key_id = (&self.key_id).to_string();
+    /// Sends every request through `transport` instead of the HTTP client.
+    pub fn with_transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
 
-        let mut header = Header::new(Algorithm::ES256);
-        header.kid = Some(key_id);
+    /// Calls `observer` with the status and headers of every response, successful or not, before it is decoded.
+    ///
+    /// Use it to log the identifiers Apple attaches to responses, so that support requests can refer to
+    /// the exact request.
+    pub fn on_response<F>(mut self, observer: F) -> Self
+    where
+        F: Fn(&ResponseMetadata<'_>) + Send + Sync + 'static,
+    {
+        self.response_observer = Some(Arc::new(observer));
+        self
+    }
 
-        let claims = Claims {
-            bid: &self.bundle_id,
-            iss: &self.issuer_id,
-            aud: "appstoreconnect-v1",
-            exp: future_time.timestamp(),
-        };
+    fn observe_response(&self, url: &str, http_status_code: u16, headers: &HeaderMap) {
+        if let Some(observer) = &self.response_observer {
+            observer(&ResponseMetadata { url, http_status_code, headers });
+        }
+    }
 
-        encode(&header, &claims, &EncodingKey::from_ec_pem(self.signing_key.as_slice()).unwrap()).unwrap()
+    async fn generate_token(&self) -> Result<String, SignerError> {
+        self.token_issuer.token().await
     }
 
-    fn build_request(&self, path: &str, method: Method) -> RequestBuilder {
-        let url = format!("{}{}", self.base_url, path);
+    async fn build_request(&self, path: &str, method: Method) -> Result<RequestBuilder, APIException> {
+        let mut url = String::with_capacity(self.base_url.len() + path.len());
+        url.push_str(&self.base_url);
+        url.push_str(path);
 
-        let mut headers = HeaderMap::new();
-        headers.append("User-Agent", "app-store-server-library/rust/1.0.0".parse().unwrap());
-        headers.append("Authorization", format!("Bearer {}", self.generate_token()).parse().unwrap());
-        headers.append("Accept", "application/json".parse().unwrap());
+        let token = self.generate_token().await
+            .map_err(|e| APIException::client_failure(format!("Failed to sign the authorization token: {}", e)))?;
 
-        self.client
+        let mut headers = HeaderMap::with_capacity(3);
+        headers.append(USER_AGENT, HeaderValue::from_static("app-store-server-library/rust/1.0.0"));
+        headers.append(AUTHORIZATION, HeaderValue::try_from(format!("Bearer {}", token)).unwrap());
+        headers.append(ACCEPT, HeaderValue::from_static("application/json"));
+
+        Ok(self.client
             .request(method, url)
-            .headers(headers)
+            .headers(headers))
     }
 
     async fn make_request_with_response_body<Res>(&self, request: RequestBuilder) -> Result<Res, APIException>
@@ -120,11 +482,17 @@ impl AppStoreServerAPIClient {
             Res: for<'de> Deserialize<'de>
     {
         let response = self.make_request(request).await?;
-        let json_result = response.json::<Res>().await.map_err(|_| APIException {
-            http_status_code: 500,
-            api_error: None,
-            raw_api_error: None,
-            error_message: Some("Failed to deserialize response JSON".to_string()),
+        let headers = response.headers().clone();
+        let body = response.bytes().await.map_err(|_| APIException::body_read_failure(&headers))?;
+        let json_result = serde_json::from_slice::<Res>(&body).map_err(|error| {
+            let json_path = json_path_at(&body, error.line(), error.column());
+            let error_message = format!("Failed to deserialize response JSON at {}: {}", json_path, error);
+            APIException {
+                raw_body: Some(truncated_body(&body)),
+                headers: selected_headers(&headers),
+                json_path: Some(json_path),
+                ..APIException::client_failure(error_message)
+            }
         })?;
         Ok(json_result)
     }
@@ -134,75 +502,69 @@ impl AppStoreServerAPIClient {
         Ok(())
     }
 
+    async fn send(&self, request: reqwest::Request) -> Result<reqwest::Response, APIException> {
+        let response = match &self.transport {
+            Some(transport) => transport.send(&self.client, request).await,
+            None => self.client.execute(request).await.map_err(TransportError::from),
+        };
+
+        response.map_err(|_| APIException::client_failure("Failed to send HTTP request".to_string()))
+    }
+
     #[cfg(not(test))]
     async fn make_request(&self, request: RequestBuilder) -> Result<reqwest::Response, APIException> {
-        let response = request.send().await;
-
-        match response {
-            Ok(response) => {
-                let status_code = response.status().as_u16();
-
-                if status_code >= 200 && status_code < 300 {
-                    Ok(response)
-                } else if let Ok(json_error) = response.json::<ErrorPayload>().await {
-                    let error_code = json_error.error_code.clone();
-                    let error_message = json_error.error_message.clone();
-                    Err(APIException {
-                        http_status_code: status_code,
-                        api_error: error_code,
-                        raw_api_error: (&json_error).raw_error_code(),
-                        error_message: error_message,
-                    })
-                } else {
-                    Err(APIException {
-                        http_status_code: 500,
-                        api_error: None,
-                        raw_api_error: None,
-                        error_message: Some("Failed to send HTTP request".to_string()),
-                    })
-                }
-            }
-            Err(_) => Err(APIException {
-                http_status_code: 500,
-                api_error: None,
-                raw_api_error: None,
-                error_message: Some("Failed to send HTTP request".to_string()),
-            }),
+        let request = request.build().map_err(|_| APIException::client_failure("Failed to build HTTP request".to_string()))?;
+
+        let response = self.send(request).await?;
+        self.handle_response(response).await
+    }
+
+    #[cfg(not(test))]
+    async fn handle_response(&self, response: reqwest::Response) -> Result<reqwest::Response, APIException> {
+        let status_code = response.status().as_u16();
+        self.observe_response(response.url().as_str(), status_code, response.headers());
+        if (200..300).contains(&status_code) {
+            return Ok(response);
         }
+
+        let headers = response.headers().clone();
+        let body = response.bytes().await.map_err(|_| APIException::body_read_failure(&headers))?;
+        Err(error_from_response(status_code, &headers, &body))
     }
 
     #[cfg(test)]
     async fn make_request(&self, request: RequestBuilder) -> Result<Response<Vec<u8>>, APIException>
     {
         let request = request.build().unwrap();
-        let body_encoded = match request.body() {
-            None => None,
-            Some(body) => body.as_bytes()
+        let url = request.url().clone();
+        let response = match self.transport {
+            Some(_) => {
+                let response = self.send(request).await?;
+                let mut builder = Response::builder().status(response.status());
+                for (name, value) in response.headers() {
+                    builder = builder.header(name, value);
+                }
+                let headers = response.headers().clone();
+                let body = response.bytes().await.map_err(|_| APIException::body_read_failure(&headers))?;
+                builder.body(body.to_vec()).unwrap()
+            }
+            None => {
+                let body_encoded = match request.body() {
+                    None => None,
+                    Some(body) => body.as_bytes()
+                };
+                (self.request_override)(&request, body_encoded)
+            }
         };
-        let response = (self.request_override)(&request, body_encoded);
 
         let status_code = response.status().as_u16();
-
-        if status_code >= 200 && status_code < 300 {
-            Ok(response)
-        } else if let Ok(json_error) = response.json::<ErrorPayload>().await {
-            let error_code = json_error.error_code.clone();
-            let error_message = json_error.error_message.clone();
-
-            Err(APIException {
-                http_status_code: status_code,
-                api_error: error_code,
-                raw_api_error: (&json_error).raw_error_code(),
-                error_message: error_message,
-            })
-        } else {
-            Err(APIException {
-                http_status_code: 500,
-                api_error: None,
-                raw_api_error: None,
-                error_message: Some("Failed to send HTTP request".to_string()),
-            })
+        self.observe_response(url.as_str(), status_code, response.headers());
+        if (200..300).contains(&status_code) {
+            return Ok(response);
         }
+
+        let headers = response.headers().clone();
+        Err(error_from_response(status_code, &headers, response.body()))
     }
 
     /// Uses a subscription's product identifier to extend the renewal date for all of its eligible active subscribers.
@@ -221,7 +583,7 @@ impl AppStoreServerAPIClient {
     ///
     /// Throws an `APIException` if a response was returned indicating the request could not be processed.
     pub async fn extend_renewal_date_for_all_active_subscribers(&self, mass_extend_renewal_date_request: &MassExtendRenewalDateRequest) -> Result<MassExtendRenewalDateStatusResponse, APIException> {
-        let req = self.build_request("/inApps/v1/subscriptions/extend/mass", Method::POST)
+        let req = self.build_request("/inApps/v1/subscriptions/extend/mass", Method::POST).await?
             .json(&mass_extend_renewal_date_request);
         self.make_request_with_response_body(req).await
     }
@@ -242,9 +604,9 @@ impl AppStoreServerAPIClient {
     /// # Errors
     ///
     /// Returns an `APIError` if the request could not be processed.
-    pub async fn extend_subscription_renewal_date(&self, original_transaction_id: &str, extend_renewal_date_request: &ExtendRenewalDateRequest) -> Result<ExtendRenewalDateResponse, APIException> {
+    pub async fn extend_subscription_renewal_date(&self, original_transaction_id: &OriginalTransactionId, extend_renewal_date_request: &ExtendRenewalDateRequest) -> Result<ExtendRenewalDateResponse, APIException> {
         let path = format!("/inApps/v1/subscriptions/extend/{}", original_transaction_id);
-        let req = self.build_request(path.as_str(), Method::PUT)
+        let req = self.build_request(path.as_str(), Method::PUT).await?
             .json(&extend_renewal_date_request);
         self.make_request_with_response_body(req).await
     }
@@ -265,17 +627,16 @@ impl AppStoreServerAPIClient {
     /// # Errors
     ///
     /// Returns an `APIError` if the request could not be processed.
-    pub async fn get_all_subscription_statuses(&self, transaction_id: &str, status: Option<&Vec<Status>>) -> Result<StatusResponse, APIException> {
-        let mut query_parameters: Vec<(&str, String)> = vec![];
+    pub async fn get_all_subscription_statuses(&self, transaction_id: &TransactionId, status: Option<&Vec<Status>>) -> Result<StatusResponse, APIException> {
+        let mut query_parameters: Query = vec![];
         if let Some(status) = status {
             for item in status {
-                let value = ("status", item.raw_value().to_string());
-                query_parameters.push(value);
+                query_parameters.push(("status", QueryValue::Int(item.raw_value().into())));
             }
         }
 
         let path = format!("/inApps/v1/subscriptions/{}", transaction_id);
-        let req = self.build_request(path.as_str(), Method::GET)
+        let req = self.build_request(path.as_str(), Method::GET).await?
             .query(&query_parameters);
         self.make_request_with_response_body(req).await
     }
@@ -299,13 +660,13 @@ impl AppStoreServerAPIClient {
     /// * `RefundHistoryRequestNotFoundError` (Status Code: 4040009) - An error that indicates the server didn't find a subscription-renewal-date extension request for the request identifier and product identifier you provided.
     /// * `RefundHistoryServerError` (Status Code: 5000000) - An error that indicates a server error occurred during the request processing.
     ///
-    pub async fn get_refund_history(&self, transaction_id: &str, revision: &str) -> Result<RefundHistoryResponse, APIException> {
-        let mut query_parameters: HashMap<&str, &str> = HashMap::new();
+    pub async fn get_refund_history(&self, transaction_id: &TransactionId, revision: &str) -> Result<RefundHistoryResponse, APIException> {
+        let mut query_parameters: Query = vec![];
         if !revision.is_empty() {
-            query_parameters.insert("revision", revision);
+            query_parameters.push(("revision", QueryValue::Str(revision)));
         }
         let path = format!("/inApps/v2/refund/lookup/{}", transaction_id);
-        let req = self.build_request(path.as_str(), Method::GET)
+        let req = self.build_request(path.as_str(), Method::GET).await?
             .query(&query_parameters);
         self.make_request_with_response_body(req).await
     }
@@ -330,10 +691,44 @@ impl AppStoreServerAPIClient {
     ///
     pub async fn get_status_of_subscription_renewal_date_extensions(&self, request_identifier: &str, product_id: &str) -> Result<MassExtendRenewalDateStatusResponse, APIException> {
         let path = format!("/inApps/v1/subscriptions/extend/mass/{}/{}", product_id, request_identifier);
-        let req = self.build_request(path.as_str(), Method::GET);
+        let req = self.build_request(path.as_str(), Method::GET).await?;
         self.make_request_with_response_body(req).await
     }
 
+    /// Polls the status of a renewal date extension request until it completes.
+    ///
+    /// The status is fetched with [`get_status_of_subscription_renewal_date_extensions`](Self::get_status_of_subscription_renewal_date_extensions),
+    /// waiting `poll_interval` after the first attempt and doubling the wait after each attempt, up to eight times `poll_interval`.
+    /// The interval is at least one second.
+    ///
+    /// # Arguments
+    ///
+    /// * `product_id` - The product identifier of the auto-renewable subscription that you request a renewal-date extension for.
+    /// * `request_identifier` - The UUID that represents your request to the Extend Subscription Renewal Dates for All Active Subscribers endpoint.
+    /// * `poll_interval` - The wait before the second attempt.
+    /// * `timeout` - How long to keep polling before giving up.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(MassExtendRenewalDateStatusResponse)` with the final counts once `complete` is true.
+    /// - `Err(MassExtensionPollError::APIError)` if a status request fails.
+    /// - `Err(MassExtensionPollError::TimeoutError)` with the last status if the request didn't complete within `timeout`.
+    pub async fn await_mass_extension_completion(&self, product_id: &str, request_identifier: &str, poll_interval: Duration, timeout: Duration) -> Result<MassExtendRenewalDateStatusResponse, MassExtensionPollError> {
+        let mut backoff = PollBackoff::new(poll_interval, timeout);
+
+        loop {
+            let status = self.get_status_of_subscription_renewal_date_extensions(request_identifier, product_id).await
+                .map_err(MassExtensionPollError::APIError)?;
+            if status.complete == Some(true) {
+                return Ok(status);
+            }
+
+            if !backoff.wait().await {
+                return Err(MassExtensionPollError::TimeoutError { last_status: status });
+            }
+        }
+    }
+
     /// Check the status of the test App Store server notification sent to your server.
     ///
     /// [Apple Documentation](https://developer.apple.com/documentation/appstoreserverapi/get_test_notification_status)
@@ -356,17 +751,62 @@ impl AppStoreServerAPIClient {
         test_notification_token: &str,
     ) -> Result<CheckTestNotificationResponse, APIException> {
         let path = format!("/inApps/v1/notifications/test/{}", test_notification_token);
-        let req = self.build_request(path.as_str(), Method::GET);
+        let req = self.build_request(path.as_str(), Method::GET).await?;
         self.make_request_with_response_body(req).await
     }
 
+    /// Requests a test notification and polls its status until the App Store records an attempt to send it to your server.
+    ///
+    /// This checks that the server's notification URL is configured and reachable. The status is fetched with
+    /// [`get_test_notification_status`](Self::get_test_notification_status), waiting `poll_interval` after the first
+    /// attempt and doubling the wait after each attempt, up to eight times `poll_interval`, which is at least one second. The status isn't found
+    /// until the App Store has tried to send the notification, so `TestNotificationNotFoundError` responses are polled again.
+    ///
+    /// # Arguments
+    ///
+    /// * `verifier` - The verifier that the signed test notification is verified and decoded with.
+    /// * `poll_interval` - The wait before the second attempt.
+    /// * `timeout` - How long to keep polling before giving up.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(TestNotificationCheck)` with the decoded notification and its send attempts.
+    /// - `Err(TestNotificationCheckError::APIError)` if a request fails.
+    /// - `Err(TestNotificationCheckError::VerificationError)` if the test notification fails verification.
+    /// - `Err(TestNotificationCheckError::MissingTokenError)` if the App Store didn't return a test notification token.
+    /// - `Err(TestNotificationCheckError::TimeoutError)` if no send attempt was recorded within `timeout`.
+    pub async fn check_test_notification(&self, verifier: &SignedDataVerifier, poll_interval: Duration, timeout: Duration) -> Result<TestNotificationCheck, TestNotificationCheckError> {
+        let mut backoff = PollBackoff::new(poll_interval, timeout);
+        let test_notification_token = self.request_test_notification().await
+            .map_err(TestNotificationCheckError::APIError)?
+            .test_notification_token
+            .ok_or(TestNotificationCheckError::MissingTokenError)?;
+
+        loop {
+            match self.get_test_notification_status(&test_notification_token).await {
+                Ok(CheckTestNotificationResponse { signed_payload: Some(signed_payload), send_attempts: Some(send_attempts) }) if !send_attempts.is_empty() => {
+                    let notification = verifier.verify_and_decode_notification(&signed_payload)
+                        .map_err(TestNotificationCheckError::VerificationError)?;
+                    return Ok(TestNotificationCheck { test_notification_token, notification, send_attempts });
+                }
+                Ok(_) => {}
+                Err(error) if error.api_error == Some(APIError::TestNotificationNotFound) => {}
+                Err(error) => return Err(TestNotificationCheckError::APIError(error)),
+            }
+
+            if !backoff.wait().await {
+                return Err(TestNotificationCheckError::TimeoutError { test_notification_token });
+            }
+        }
+    }
+
     /// Get the transaction history for a given transaction ID.
     ///
     /// This method is deprecated. Please use `get_transaction_history_with_version` instead.
     ///
     /// # Arguments
     ///
-    /// * `transaction_id` - The identifier of the transaction to retrieve the history for.
+    /// * `transaction_id` - The identifier of the transaction to retrieve the history for, which may be an original or app transaction identifier.
     /// * `revision` - An optional revision string to specify the starting point of the transaction history.
     /// * `transaction_history_request` - The request object containing additional parameters for the transaction history.
     ///
@@ -380,7 +820,7 @@ impl AppStoreServerAPIClient {
     #[deprecated(note = "Use `get_transaction_history_with_version` instead.")]
     pub async fn get_transaction_history(
         &self,
-        transaction_id: &str,
+        transaction_id: &TransactionId,
         revision: Option<&str>,
         transaction_history_request: TransactionHistoryRequest,
     ) -> Result<HistoryResponse, APIException> {
@@ -410,12 +850,12 @@ impl AppStoreServerAPIClient {
         pagination_token: &str,
         notification_history_request: &NotificationHistoryRequest,
     ) -> Result<NotificationHistoryResponse, APIException> {
-        let mut query_parameters: HashMap<&str, &str> = HashMap::new();
+        let mut query_parameters: Query = vec![];
         if !pagination_token.is_empty() {
-            query_parameters.insert("paginationToken", pagination_token);
+            query_parameters.push(("paginationToken", QueryValue::Str(pagination_token)));
         }
 
-        let req = self.build_request("/inApps/v1/notifications/history", Method::POST)
+        let req = self.build_request("/inApps/v1/notifications/history", Method::POST).await?
             .query(&query_parameters)
             .json(&notification_history_request);
         self.make_request_with_response_body(req).await
@@ -427,7 +867,7 @@ impl AppStoreServerAPIClient {
     ///
     /// # Arguments
     ///
-    /// * `transaction_id` - The identifier of a transaction that belongs to the customer, and which may be an original transaction identifier.
+    /// * `transaction_id` - The identifier of a transaction that belongs to the customer, and which may be an original transaction identifier or, converted with `TransactionId::from`, an app transaction identifier.
     /// * `revision` - A token you provide to get the next set of up to 20 transactions. All responses include a revision token. Note: For requests that use the revision token, include the same query parameters from the initial request. Use the revision token from the previous HistoryResponse.
     /// * `transaction_history_request` - The request body that includes the start and end dates, and optional query constraints.
     ///
@@ -442,59 +882,57 @@ impl AppStoreServerAPIClient {
     ///
     pub async fn get_transaction_history_with_version(
         &self,
-        transaction_id: &str,
+        transaction_id: &TransactionId,
         revision: Option<&str>,
         transaction_history_request: &TransactionHistoryRequest,
         version: GetTransactionHistoryVersion,
     ) -> Result<HistoryResponse, APIException> {
-        let mut query_parameters: Vec<(&str, Value)> = vec![];
+        let mut query_parameters: Query = vec![];
 
         if let Some(rev) = revision {
-            query_parameters.push(("revision", rev.into()));
+            query_parameters.push(("revision", QueryValue::Str(rev)));
         }
 
         if let Some(start_date) = transaction_history_request.start_date {
-            let start_date = start_date.timestamp_millis().to_string();
-            query_parameters.push(("startDate", start_date.into()));
+            query_parameters.push(("startDate", QueryValue::Int(timestamp::to_millis(start_date))));
         }
 
         if let Some(end_date) = transaction_history_request.end_date {
-            let end_date = end_date.timestamp_millis().to_string();
-            query_parameters.push(("endDate", end_date.into()));
+            query_parameters.push(("endDate", QueryValue::Int(timestamp::to_millis(end_date))));
         }
 
         if let Some(product_ids) = &transaction_history_request.product_ids {
             for item in product_ids {
-                query_parameters.push(("productId", item.as_str().into()));
+                query_parameters.push(("productId", QueryValue::Str(item)));
             }
         }
 
         if let Some(product_types) = &transaction_history_request.product_types {
             for item in product_types {
-                query_parameters.push(("productType", item.raw_value().to_string().into()));
+                query_parameters.push(("productType", QueryValue::Str(item.raw_value())));
             }
         }
 
         if let Some(sort) = &transaction_history_request.sort {
-            query_parameters.push(("sort", sort.raw_value().to_string().into()));
+            query_parameters.push(("sort", QueryValue::Str(sort.raw_value())));
         }
 
         if let Some(subscription_group_ids) = &transaction_history_request.subscription_group_identifiers {
             for item in subscription_group_ids {
-                query_parameters.push(("subscriptionGroupIdentifier", item.as_str().into()));
+                query_parameters.push(("subscriptionGroupIdentifier", QueryValue::Str(item)));
             }
         }
 
         if let Some(ownership_type) = &transaction_history_request.in_app_ownership_type {
-            query_parameters.push(("inAppOwnershipType", ownership_type.raw_value().to_string().into()));
+            query_parameters.push(("inAppOwnershipType", QueryValue::Str(ownership_type.raw_value())));
         }
 
-        if let Some(revoked) = &transaction_history_request.revoked {
-            query_parameters.push(("revoked", revoked.to_string().into()));
+        if let Some(revoked) = transaction_history_request.revoked {
+            query_parameters.push(("revoked", QueryValue::Bool(revoked)));
         }
 
         let path = format!("/inApps/{}/history/{}", version.as_str(), transaction_id);
-        let req = self.build_request(path.as_str(), Method::GET)
+        let req = self.build_request(path.as_str(), Method::GET).await?
             .query(&query_parameters);
         self.make_request_with_response_body(req).await
     }
@@ -514,9 +952,9 @@ impl AppStoreServerAPIClient {
     /// # Errors
     ///
     /// Returns an `APIException` if the request could not be processed.
-    pub async fn get_transaction_info(&self, transaction_id: &str) -> Result<TransactionInfoResponse, APIException> {
+    pub async fn get_transaction_info(&self, transaction_id: &TransactionId) -> Result<TransactionInfoResponse, APIException> {
         let path = format!("/inApps/v1/transactions/{}", transaction_id);
-        let req = self.build_request(path.as_str(), Method::GET);
+        let req = self.build_request(path.as_str(), Method::GET).await?;
         self.make_request_with_response_body(req).await
     }
 
@@ -537,10 +975,31 @@ impl AppStoreServerAPIClient {
     /// Returns an `APIException` if the request could not be processed.
     pub async fn look_up_order_id(&self, order_id: &str) -> Result<OrderLookupResponse, APIException> {
         let path = format!("/inApps/v1/lookup/{}", order_id);
-        let req = self.build_request(path.as_str(), Method::GET);
+        let req = self.build_request(path.as_str(), Method::GET).await?;
         self.make_request_with_response_body(req).await
     }
 
+    /// Get a customer's in-app purchases from a receipt using the order ID, verifying and decoding the signed transactions.
+    ///
+    /// # Arguments
+    ///
+    /// * `order_id` - The order ID for in-app purchases that belong to the customer.
+    /// * `verifier` - The verifier for the signed transactions.
+    ///
+    /// # Returns
+    ///
+    /// The order lookup status, and the result of verifying each of the signed transactions as returned by
+    /// [`OrderLookupResponse::decode_all`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an `APIException` if the request could not be processed.
+    pub async fn look_up_order_id_decoded(&self, order_id: &str, verifier: &SignedDataVerifier) -> Result<(OrderLookupStatus, Vec<Result<JWSTransactionDecodedPayload, SignedDataVerifierError>>), APIException> {
+        let response = self.look_up_order_id(order_id).await?;
+        let transactions = response.decode_all(verifier);
+        Ok((response.status, transactions))
+    }
+
     /// Ask App Store Server Notifications to send a test notification to your server.
     ///
     /// [Documentation](https://developer.apple.com/documentation/appstoreserverapi/request_a_test_notification)
@@ -554,7 +1013,7 @@ impl AppStoreServerAPIClient {
     /// Returns an `APIException` if the request could not be processed.
     pub async fn request_test_notification(&self) -> Result<SendTestNotificationResponse, APIException> {
         let path = "/inApps/v1/notifications/test";
-        let req = self.build_request(path, Method::POST);
+        let req = self.build_request(path, Method::POST).await?;
         self.make_request_with_response_body(req).await
     }
 
@@ -570,9 +1029,9 @@ impl AppStoreServerAPIClient {
     /// # Errors
     ///
     /// Returns an `APIException` if the request could not be processed.
-    pub async fn send_consumption_data(&self, transaction_id: &str, consumption_request: &ConsumptionRequest) -> Result<(), APIException> {
+    pub async fn send_consumption_data(&self, transaction_id: &TransactionId, consumption_request: &ConsumptionRequest) -> Result<(), APIException> {
         let path = format!("/inApps/v1/transactions/consumption/{}", transaction_id);
-        let req = self.build_request(path.as_str(), Method::PUT)
+        let req = self.build_request(path.as_str(), Method::PUT).await?
             .json(consumption_request);
         self.make_request_without_response_body(req).await
     }
@@ -596,27 +1055,187 @@ impl GetTransactionHistoryVersion {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct Claims<'a> {
-    bid: &'a str,
-    iss: &'a str,
-    aud: &'a str,
-    exp: i64,
+/// Creates the exception for a response with an error status, keeping the start of the body and the
+/// selected headers when the body isn't an error payload.
+fn error_from_response(status_code: u16, headers: &HeaderMap, body: &[u8]) -> APIException {
+    match serde_json::from_slice::<ErrorPayload>(body) {
+        Ok(json_error) => APIException {
+            http_status_code: status_code,
+            raw_api_error: json_error.raw_error_code(),
+            api_error: json_error.error_code,
+            error_message: json_error.error_message,
+            retry_after: retry_after(headers),
+            raw_body: Some(truncated_body(body)),
+            headers: selected_headers(headers),
+            json_path: None,
+            from_response: true,
+        },
+        Err(_) => APIException {
+            http_status_code: status_code,
+            api_error: None,
+            raw_api_error: None,
+            error_message: Some("Failed to deserialize error response JSON".to_string()),
+            retry_after: retry_after(headers),
+            raw_body: Some(truncated_body(body)),
+            headers: selected_headers(headers),
+            json_path: None,
+            from_response: true,
+        },
+    }
 }
 
-#[cfg(test)]
-use serde::de::DeserializeOwned;
+fn truncated_body(body: &[u8]) -> Vec<u8> {
+    body[..body.len().min(APIException::MAX_RAW_BODY_SIZE)].to_vec()
+}
+
+fn selected_headers(headers: &HeaderMap) -> HashMap<String, String> {
+    APIException::SELECTED_HEADERS
+        .iter()
+        .filter_map(|name| Some((name.to_string(), headers.get(*name)?.to_str().ok()?.to_string())))
+        .collect()
+}
+
+/// Finds the path of the value that ends at a position of a JSON document, in the format `data[0].status`,
+/// or `.` for the document itself. The position is the one-based line and column reported by `serde_json`.
+fn json_path_at(body: &[u8], line: usize, column: usize) -> String {
+    enum Frame {
+        Object(Option<String>),
+        Array(usize),
+    }
+
+    let line_start = body
+        .iter()
+        .enumerate()
+        .filter(|(_, byte)| **byte == b'\n')
+        .nth(line.saturating_sub(2))
+        .map_or(0, |(index, _)| index + 1);
+    let end = if line <= 1 { column } else { line_start + column }.min(body.len());
+
+    let mut frames = Vec::new();
+    let mut last_string = None;
+    let mut index = 0;
+    while index < end {
+        match body[index] {
+            b'"' => {
+                let start = index + 1;
+                index += 1;
+                while index < body.len() && body[index] != b'"' {
+                    index += if body[index] == b'\\' { 2 } else { 1 };
+                }
+                last_string = Some(String::from_utf8_lossy(&body[start..index.min(body.len())]).into_owned());
+            }
+            b':' => {
+                if let Some(Frame::Object(key)) = frames.last_mut() {
+                    *key = last_string.take();
+                }
+            }
+            b',' => {
+                if let Some(Frame::Array(element)) = frames.last_mut() {
+                    *element += 1;
+                }
+            }
+            b'{' => frames.push(Frame::Object(None)),
+            b'[' => frames.push(Frame::Array(0)),
+            b'}' | b']' => {
+                frames.pop();
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+
+    let mut path = String::new();
+    for frame in &frames {
+        match frame {
+            Frame::Object(Some(key)) => {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+            }
+            Frame::Object(None) => {}
+            Frame::Array(element) => path.push_str(&format!("[{}]", element)),
+        }
+    }
+    if path.is_empty() {
+        path.push('.');
+    }
+    path
+}
+
+/// Reads the `Retry-After` header of a response given in seconds. Dates aren't supported.
+fn retry_after(headers: &HeaderMap) -> Option<u64> {
+    headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim().parse().ok()
+}
+
+/// The shortest wait between polls, so that a zero interval doesn't poll the App Store without pausing.
+const MIN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The waits between polls, starting at the poll interval and doubling up to eight times it, until a deadline.
+struct PollBackoff {
+    poll_interval: Duration,
+    wait: Duration,
+    deadline: Option<tokio::time::Instant>,
+}
+
+impl PollBackoff {
+    fn new(poll_interval: Duration, timeout: Duration) -> Self {
+        let poll_interval = poll_interval.max(MIN_POLL_INTERVAL);
+        // A timeout too long to represent never expires.
+        let deadline = tokio::time::Instant::now().checked_add(timeout);
+        PollBackoff { poll_interval, wait: poll_interval, deadline }
+    }
+
+    /// Waits until the next poll, or returns `false` without waiting if the deadline has passed.
+    async fn wait(&mut self) -> bool {
+        let now = tokio::time::Instant::now();
+        let wait = match self.deadline {
+            Some(deadline) if now >= deadline => return false,
+            Some(deadline) => self.wait.min(deadline - now),
+            None => self.wait,
+        };
+
+        tokio::time::sleep(wait).await;
+        self.wait = self.wait.saturating_mul(2).min(self.poll_interval.saturating_mul(8));
+        true
+    }
+}
+
+/// Wraps a PEM or DER-encoded key in a signer, deferring an invalid key to the first request
+/// so that `new` stays infallible.
+fn pem_signer(signing_key: Vec<u8>) -> Arc<dyn AsyncSigner> {
+    let signing_key = KeyBytes::from(signing_key);
+    match EcdsaSigner::from_key(signing_key.as_slice()) {
+        Ok(signer) => Arc::new(signer),
+        Err(error) => Arc::new(InvalidKeySigner(error)),
+    }
+}
+
+fn checked_signer(signing_key: Vec<u8>) -> Result<Arc<dyn AsyncSigner>, ConfigurationError> {
+    let signing_key = KeyBytes::from(signing_key);
+    match EcdsaSigner::from_key(signing_key.as_slice()) {
+        Ok(signer) => Ok(Arc::new(signer)),
+        Err(source) => Err(ConfigurationError::InvalidSigningKeyError { format: KeyFormat::detect(signing_key.as_slice()), source }),
+    }
+}
+
+struct InvalidKeySigner(SignerError);
+
+impl AsyncSigner for InvalidKeySigner {
+    fn sign<'a>(&'a self, _message: &'a [u8]) -> SignFuture<'a> {
+        Box::pin(async move { Err(self.0.clone()) })
+    }
+}
 
 #[cfg(test)]
 trait ResponseExt {
-    async fn json<T: DeserializeOwned>(self) -> serde_json::Result<T>;
+    async fn bytes(self) -> Result<Vec<u8>, std::convert::Infallible>;
 }
 
 #[cfg(test)]
 impl ResponseExt for Response<Vec<u8>> {
-    async fn json<T: DeserializeOwned>(self) -> serde_json::Result<T> {
-        let body = std::str::from_utf8(self.body().as_slice()).unwrap();
-        serde_json::from_str(body)
+    async fn bytes(self) -> Result<Vec<u8>, std::convert::Infallible> {
+        Ok(self.into_body())
     }
 }
 
@@ -626,10 +1245,10 @@ mod tests {
     use base64::Engine;
     use http::StatusCode;
     use serde_json::Value;
-    use chrono::DateTime;
     use uuid::Uuid;
     use base64::prelude::BASE64_STANDARD_NO_PAD;
     use crate::primitives::account_tenure::AccountTenure;
+    use crate::primitives::app_transaction_id::AppTransactionId;
     use crate::primitives::consumption_status::ConsumptionStatus;
     use crate::primitives::delivery_status::DeliveryStatus;
     use crate::primitives::extend_reason_code::ExtendReasonCode;
@@ -639,7 +1258,6 @@ mod tests {
     use crate::primitives::lifetime_dollars_refunded::LifetimeDollarsRefunded;
     use crate::primitives::notification_history_response_item::NotificationHistoryResponseItem;
     use crate::primitives::notification_type_v2::NotificationTypeV2;
-    use crate::primitives::order_lookup_status::OrderLookupStatus;
     use crate::primitives::platform::Platform;
     use crate::primitives::play_time::PlayTime;
     use crate::primitives::refund_preference::RefundPreference;
@@ -695,11 +1313,11 @@ mod tests {
             request_identifier: Some("fdf964a4-233b-486c-aac1-97d8d52688ac".to_string()),
         };
 
-        let response = client.extend_subscription_renewal_date("4124214", &extend_renewal_date_request).await.unwrap();
+        let response = client.extend_subscription_renewal_date(&OriginalTransactionId::from("4124214"), &extend_renewal_date_request).await.unwrap();
         assert_eq!("2312412", response.original_transaction_id.unwrap().as_str());
         assert_eq!("9993", response.web_order_line_item_id.unwrap().as_str());
         assert_eq!(true, response.success.unwrap());
-        assert_eq!(1698148900, response.effective_date.unwrap().timestamp());
+        assert_eq!(1698148900000, timestamp::to_millis(response.effective_date.unwrap()));
     }
 
     #[tokio::test]
@@ -711,7 +1329,7 @@ mod tests {
         }));
 
         let statuses = vec![Status::Expired, Status::Active];
-        let response = client.get_all_subscription_statuses("4321", Some(&statuses)).await.unwrap();
+        let response = client.get_all_subscription_statuses(&TransactionId::from("4321"), Some(&statuses)).await.unwrap();
 
         assert_eq!(Environment::LocalTesting, response.environment.unwrap());
         assert_eq!("com.example", response.bundle_id.as_str());
@@ -722,13 +1340,13 @@ mod tests {
             last_transactions: Some(vec![
                 LastTransactionsItem {
                     status: Status::Active.into(),
-                    original_transaction_id: "3749183".to_string().into(),
+                    original_transaction_id: Some("3749183".into()),
                     signed_transaction_info: "signed_transaction_one".to_string().into(),
                     signed_renewal_info: "signed_renewal_one".to_string().into(),
                 },
                 LastTransactionsItem {
                     status: Status::Revoked.into(),
-                    original_transaction_id: "5314314134".to_string().into(),
+                    original_transaction_id: Some("5314314134".into()),
                     signed_transaction_info: "signed_transaction_two".to_string().into(),
                     signed_renewal_info: "signed_renewal_two".to_string().into(),
                 },
@@ -740,7 +1358,7 @@ mod tests {
             last_transactions: vec![
                 LastTransactionsItem {
                     status: Status::Expired.into(),
-                    original_transaction_id: "3413453".to_string().into(),
+                    original_transaction_id: Some("3413453".into()),
                     signed_transaction_info: "signed_transaction_three".to_string().into(),
                     signed_renewal_info: "signed_renewal_three".to_string().into(),
                 },
@@ -758,7 +1376,7 @@ mod tests {
             assert!(req.body().is_none());
         }));
 
-        let response = client.get_refund_history("555555", "revision_input").await.unwrap();
+        let response = client.get_refund_history(&TransactionId::from("555555"), "revision_input").await.unwrap();
 
         assert_eq!(vec!["signed_transaction_one", "signed_transaction_two"], response.signed_transactions);
         assert_eq!("revision_output", response.revision);
@@ -777,11 +1395,57 @@ mod tests {
 
         assert_eq!("20fba8a0-2b80-4a7d-a17f-85c1854727f8", response.request_identifier.unwrap().as_str());
         assert_eq!(true, response.complete.unwrap());
-        assert_eq!(1698148900, response.complete_date.unwrap().timestamp());
+        assert_eq!(1698148900000, timestamp::to_millis(response.complete_date.unwrap()));
         assert_eq!(30, response.succeeded_count.unwrap());
         assert_eq!(2, response.failed_count.unwrap());
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn test_await_mass_extension_completion() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let key = fs::read("assets/testSigningKey.p8").expect("Failed to read file");
+        let complete = fs::read("assets/models/getStatusOfSubscriptionRenewalDateExtensionsResponse.json").expect("Failed to read file");
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let client = AppStoreServerAPIClient::new(key, "keyId", "issuerId", "com.example", Environment::LocalTesting, Box::new(move |req: &reqwest::Request, _body: Option<&[u8]>| {
+            assert_eq!("https://local-testing-base-url/inApps/v1/subscriptions/extend/mass/com.example.product/20fba8a0-2b80-4a7d-a17f-85c1854727f8", req.url().as_str());
+            let body = match counter.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => br#"{"requestIdentifier": "20fba8a0-2b80-4a7d-a17f-85c1854727f8", "complete": false}"#.to_vec(),
+                _ => complete.clone(),
+            };
+            http::response::Builder::new().header("Content-Type", "application/json").status(StatusCode::OK).body(body).unwrap()
+        }));
+
+        let poll = |timeout| client.await_mass_extension_completion("com.example.product", "20fba8a0-2b80-4a7d-a17f-85c1854727f8", Duration::from_secs(10), timeout);
+
+        let error = poll(Duration::from_secs(5)).await.unwrap_err();
+        assert!(matches!(error, MassExtensionPollError::TimeoutError { last_status } if last_status.complete == Some(false)));
+
+        let response = poll(Duration::from_secs(60)).await.unwrap();
+        assert_eq!(3, attempts.load(Ordering::SeqCst));
+        assert_eq!(30, response.succeeded_count.unwrap());
+        assert_eq!(2, response.failed_count.unwrap());
+
+        attempts.store(0, Ordering::SeqCst);
+        let start = tokio::time::Instant::now();
+        client.await_mass_extension_completion("com.example.product", "20fba8a0-2b80-4a7d-a17f-85c1854727f8", Duration::ZERO, Duration::MAX).await.unwrap();
+        assert_eq!(3, attempts.load(Ordering::SeqCst));
+        assert_eq!(Duration::from_secs(3), start.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_check_test_notification_without_token() {
+        use crate::test_utils::local_testing_verifier;
+
+        let client = app_store_server_api_client("{}".to_string(), StatusCode::OK, Some(|req, _body| {
+            assert_eq!("https://local-testing-base-url/inApps/v1/notifications/test", req.url().as_str());
+        }));
+
+        let error = client.check_test_notification(&local_testing_verifier(), Duration::from_secs(1), Duration::from_secs(30)).await.unwrap_err();
+        assert!(matches!(error, TestNotificationCheckError::MissingTokenError));
+    }
+
     #[tokio::test]
     async fn test_get_test_notification_status() {
         let client = app_store_server_api_client_with_body_from_file("assets/models/getTestNotificationStatusResponse.json", StatusCode::OK, Some(|req, _body| {
@@ -795,11 +1459,11 @@ mod tests {
 
         let send_attempt_items = vec![
             SendAttemptItem {
-                attempt_date: DateTime::from_timestamp(1698148900, 0),
+                attempt_date: timestamp::from_millis(1698148900000),
                 send_attempt_result: SendAttemptResult::NoResponse.into(),
             },
             SendAttemptItem {
-                attempt_date: DateTime::from_timestamp(1698148950, 0),
+                attempt_date: timestamp::from_millis(1698148950000),
                 send_attempt_result: SendAttemptResult::Success.into(),
             },
         ];
@@ -822,11 +1486,11 @@ mod tests {
         }));
 
         let notification_history_request = NotificationHistoryRequest {
-            start_date:DateTime::from_timestamp(1698148900, 0),
-            end_date:DateTime::from_timestamp(1698148950, 0),
+            start_date:timestamp::from_millis(1698148900000),
+            end_date:timestamp::from_millis(1698148950000),
             notification_type: NotificationTypeV2::Subscribed.into(),
             notification_subtype: Subtype::InitialBuy.into(),
-            transaction_id: "999733843".to_string().into(),
+            transaction_id: Some("999733843".into()),
             only_failures: true.into(),
         };
 
@@ -839,11 +1503,11 @@ mod tests {
                 signed_payload: "signed_payload_one".to_string().into(),
                 send_attempts: vec![
                     SendAttemptItem {
-                        attempt_date: DateTime::from_timestamp(1698148900, 0),
+                        attempt_date: timestamp::from_millis(1698148900000),
                         send_attempt_result: SendAttemptResult::NoResponse.into(),
                     },
                     SendAttemptItem {
-                        attempt_date: DateTime::from_timestamp(1698148950, 0),
+                        attempt_date: timestamp::from_millis(1698148950000),
                         send_attempt_result: SendAttemptResult::Success.into(),
                     },
                 ].into(),
@@ -852,7 +1516,7 @@ mod tests {
                 signed_payload: "signed_payload_two".to_string().into(),
                 send_attempts: vec![
                     SendAttemptItem {
-                        attempt_date: DateTime::from_timestamp(1698148800, 0),
+                        attempt_date: timestamp::from_millis(1698148800000),
                         send_attempt_result: SendAttemptResult::CircularRedirect.into(),
                     },
                 ].into(),
@@ -877,8 +1541,8 @@ mod tests {
         );
 
         let request = TransactionHistoryRequest {
-            start_date: DateTime::from_timestamp(123, 455000000),
-            end_date: DateTime::from_timestamp(123, 456000000),
+            start_date: timestamp::from_millis(123455),
+            end_date: timestamp::from_millis(123456),
             product_ids: Some(vec!["com.example.1".to_string(), "com.example.2".to_string()]),
             product_types: Some(vec![ProductType::Consumable, ProductType::AutoRenewable]),
             sort: Some(Order::Ascending),
@@ -891,7 +1555,7 @@ mod tests {
         };
 
         let response = client
-            .get_transaction_history("1234", Some("revision_input"), request)
+            .get_transaction_history(&TransactionId::from("1234"), Some("revision_input"), request)
             .await
             .unwrap();
 
@@ -938,8 +1602,8 @@ mod tests {
         );
 
         let request = TransactionHistoryRequest {
-            start_date: DateTime::from_timestamp(123, 455000000),
-            end_date: DateTime::from_timestamp(123, 456000000),
+            start_date: timestamp::from_millis(123455),
+            end_date: timestamp::from_millis(123456),
             product_ids: Some(vec!["com.example.1".to_string(), "com.example.2".to_string()]),
             product_types: Some(vec![ProductType::Consumable, ProductType::AutoRenewable]),
             sort: Some(Order::Ascending),
@@ -952,7 +1616,7 @@ mod tests {
         };
 
         let response = client
-            .get_transaction_history_with_version("1234", Some("revision_input"), &request, GetTransactionHistoryVersion::V2)
+            .get_transaction_history_with_version(&TransactionId::from("1234"), Some("revision_input"), &request, GetTransactionHistoryVersion::V2)
             .await
             .unwrap();
 
@@ -964,6 +1628,21 @@ mod tests {
         assert_eq!(vec!["signed_transaction_value", "signed_transaction_value2"], response.signed_transactions.unwrap());
     }
 
+    #[tokio::test]
+    async fn test_get_transaction_history_by_app_transaction_id() {
+        let client = app_store_server_api_client_with_body_from_file("assets/models/transactionHistoryResponse.json", StatusCode::OK, Some(|req, _body| {
+            assert_eq!("/inApps/v2/history/704482411307769224", req.url().path());
+        }));
+
+        let app_transaction_id = AppTransactionId::from("704482411307769224");
+        let response = client
+            .get_transaction_history_with_version(&app_transaction_id.into(), None, &TransactionHistoryRequest::default(), GetTransactionHistoryVersion::V2)
+            .await
+            .unwrap();
+
+        assert_eq!(Some(true), response.has_more);
+    }
+
     #[tokio::test]
     async fn test_get_transaction_info() {
         let client = app_store_server_api_client_with_body_from_file("assets/models/transactionInfoResponse.json", StatusCode::OK, Some(|req, _body| {
@@ -972,7 +1651,7 @@ mod tests {
             assert!(req.body().is_none());
         }));
 
-        let response = client.get_transaction_info("1234").await.unwrap();
+        let response = client.get_transaction_info(&TransactionId::from("1234")).await.unwrap();
         assert_eq!("signed_transaction_info_value", response.signed_transaction_info.unwrap());
     }
 
@@ -989,6 +1668,24 @@ mod tests {
         assert_eq!(vec!["signed_transaction_one", "signed_transaction_two"], response.signed_transactions);
     }
 
+    #[tokio::test]
+    async fn test_look_up_order_id_decoded() {
+        use crate::test_utils::{local_testing_verifier, signed_transaction_fixture};
+
+        let body = serde_json::json!({
+            "status": 0,
+            "signedTransactions": [signed_transaction_fixture(&NotificationTypeV2::OneTimeCharge, "1001"), "signed_transaction_two"],
+        });
+        let client = app_store_server_api_client(body.to_string(), StatusCode::OK, Some(|req, _body| {
+            assert_eq!("https://local-testing-base-url/inApps/v1/lookup/W002182", req.url().as_str());
+        }));
+
+        let (status, transactions) = client.look_up_order_id_decoded("W002182", &local_testing_verifier()).await.unwrap();
+        assert_eq!(OrderLookupStatus::Valid, status);
+        assert_eq!(Some("1001"), transactions[0].as_ref().unwrap().transaction_id.as_deref());
+        assert!(transactions[1].is_err());
+    }
+
     #[tokio::test]
     async fn test_request_test_notification() {
         let client = app_store_server_api_client_with_body_from_file("assets/models/requestTestNotificationResponse.json", StatusCode::OK, Some(|req, _body| {
@@ -1037,7 +1734,7 @@ mod tests {
             refund_preference: RefundPreference::NoPreference.into(),
         };
 
-        let _ = client.send_consumption_data("49571273", &consumption_request).await.unwrap();
+        let _ = client.send_consumption_data(&TransactionId::from("49571273"), &consumption_request).await.unwrap();
     }
 
     #[tokio::test]
@@ -1061,13 +1758,13 @@ mod tests {
             assert_eq!("ES256", header["alg"].as_str().unwrap());
         }));
 
-        let _ = client.get_transaction_info("1234").await;
+        let _ = client.get_transaction_info(&TransactionId::from("1234")).await;
     }
 
     #[tokio::test]
     async fn test_api_error() {
         let client = app_store_server_api_client_with_body_from_file("assets/models/apiException.json", StatusCode::INTERNAL_SERVER_ERROR, None);
-        let result = client.get_transaction_info("1234").await;
+        let result = client.get_transaction_info(&TransactionId::from("1234")).await;
 
         match result {
             Ok(_) => {
@@ -1085,7 +1782,7 @@ mod tests {
     #[tokio::test]
     async fn test_api_too_many_requests() {
         let client = app_store_server_api_client_with_body_from_file("assets/models/apiTooManyRequestsException.json", StatusCode::TOO_MANY_REQUESTS, None);
-        let result = client.get_transaction_info("1234").await;
+        let result = client.get_transaction_info(&TransactionId::from("1234")).await;
 
         match result {
             Ok(_) => {
@@ -1099,10 +1796,128 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_retryable_errors() {
+        let key = fs::read("assets/testSigningKey.p8").expect("Failed to read file");
+        let body = fs::read("assets/models/apiTooManyRequestsException.json").expect("Failed to read file");
+        let client = AppStoreServerAPIClient::new(key, "keyId", "issuerId", "com.example", Environment::LocalTesting, Box::new(move |_req: &reqwest::Request, _body: Option<&[u8]>| {
+            http::response::Builder::new()
+                .header("Content-Type", "application/json")
+                .header("Retry-After", "120")
+                .status(StatusCode::TOO_MANY_REQUESTS)
+                .body(body.clone())
+                .unwrap()
+        }));
+
+        let error = client.get_transaction_info(&TransactionId::from("1234")).await.unwrap_err();
+        assert!(error.is_retryable());
+        assert_eq!(Some(Duration::from_secs(120)), error.retry_delay());
+
+        let client = app_store_server_api_client_with_body_from_file("assets/models/apiException.json", StatusCode::INTERNAL_SERVER_ERROR, None);
+        let error = client.get_transaction_info(&TransactionId::from("1234")).await.unwrap_err();
+        assert!(error.is_retryable());
+        assert_eq!(None, error.retry_delay());
+
+        let client = app_store_server_api_client_with_body_from_file("assets/models/apiUnknownError.json", StatusCode::BAD_REQUEST, None);
+        let error = client.get_transaction_info(&TransactionId::from("1234")).await.unwrap_err();
+        assert!(!error.is_retryable());
+
+        let client = app_store_server_api_client_with_body_from_file("assets/models/transactionHistoryResponseWithMalformedAppAppleId.json", StatusCode::OK, None);
+        let error = client.get_transaction_history_with_version(&TransactionId::from("1234"), None, &TransactionHistoryRequest::default(), GetTransactionHistoryVersion::V2).await.unwrap_err();
+        assert_eq!(500, error.http_status_code);
+        assert!(!error.is_retryable());
+        assert_eq!(None, error.retry_delay());
+
+        let key = b"-----BEGIN PUBLIC KEY-----\nMAA=\n-----END PUBLIC KEY-----\n".to_vec();
+        let client = AppStoreServerAPIClient::new(key, "keyId", "issuerId", "com.example", Environment::LocalTesting, Box::new(|_, _| unreachable!()));
+        let error = client.get_transaction_info(&TransactionId::from("1234")).await.unwrap_err();
+        assert_eq!(500, error.http_status_code);
+        assert!(!error.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_undocumented_error_response() {
+        let client = app_store_server_api_client("<html>Bad Gateway</html>".to_string(), StatusCode::BAD_GATEWAY, None);
+        let error = client.get_transaction_info(&TransactionId::from("1234")).await.unwrap_err();
+
+        assert_eq!(502, error.http_status_code);
+        assert_eq!(None, error.api_error);
+        assert_eq!(Some(b"<html>Bad Gateway</html>".to_vec()), error.raw_body);
+        assert_eq!(Some("application/json"), error.headers.get("content-type").map(String::as_str));
+    }
+
+    #[tokio::test]
+    async fn test_on_response() {
+        use std::sync::Mutex;
+
+        let responses = Arc::new(Mutex::new(Vec::new()));
+        let observed = responses.clone();
+        let client = app_store_server_api_client_with_body_from_file("assets/models/apiException.json", StatusCode::INTERNAL_SERVER_ERROR, None)
+            .on_response(move |response| {
+                let content_type = response.headers.get("Content-Type").unwrap().to_str().unwrap().to_string();
+                observed.lock().unwrap().push((response.url.to_string(), response.http_status_code, content_type));
+            });
+
+        assert!(client.get_transaction_info(&TransactionId::from("1234")).await.is_err());
+        assert_eq!(
+            vec![("https://local-testing-base-url/inApps/v1/transactions/1234".to_string(), 500, "application/json".to_string())],
+            *responses.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_json_path_at() {
+        let body = br#"{"bundleId": "com.example", "data": [{"lastTransactions": [{"status": 1}, {"status": "x"}]}]}"#;
+        let error = serde_json::from_slice::<StatusResponse>(body).unwrap_err();
+        assert_eq!("data[0].lastTransactions[1].status", json_path_at(body, error.line(), error.column()));
+
+        let body = b"{\n  \"data\": [],\n  \"appAppleId\": \"x\"\n}";
+        let error = serde_json::from_slice::<StatusResponse>(body).unwrap_err();
+        assert_eq!("appAppleId", json_path_at(body, error.line(), error.column()));
+
+        let error = serde_json::from_slice::<StatusResponse>(br#"{"data": []}"#).unwrap_err();
+        assert_eq!(".", json_path_at(br#"{"data": []}"#, error.line(), error.column()));
+    }
+
+    #[test]
+    fn test_try_new_with_invalid_key() {
+        let key = b"-----BEGIN PUBLIC KEY-----\nMAA=\n-----END PUBLIC KEY-----\n".to_vec();
+        let result = AppStoreServerAPIClient::try_new(key, "keyId", "issuerId", "com.example", Environment::LocalTesting, Box::new(|_, _| unreachable!()));
+
+        match result {
+            Err(ConfigurationError::InvalidSigningKeyError { format, source }) => {
+                assert_eq!(KeyFormat::OtherPem("PUBLIC KEY".to_string()), format);
+                assert!(matches!(source, SignerError::UnsupportedKeyFormatError(_)));
+            }
+            Ok(_) => panic!("Expected the key to be rejected"),
+            Err(error) => panic!("Unexpected error {}", error),
+        }
+
+        let key = fs::read("assets/testSigningKey.p8").expect("Failed to read file");
+        assert!(AppStoreServerAPIClient::try_new(key, "keyId", "issuerId", "com.example", Environment::LocalTesting, Box::new(|_, _| unreachable!())).is_ok());
+    }
+
+    #[test]
+    fn test_transport() {
+        let config = TransportConfig::default()
+            .pool_max_idle_per_host(8)
+            .pool_idle_timeout(Duration::from_secs(90))
+            .http2_keep_alive_interval(Duration::from_secs(30))
+            .http2_keep_alive_timeout(Duration::from_secs(10))
+            .http2_keep_alive_while_idle(true)
+            .tcp_nodelay(true)
+            .tcp_keepalive(Duration::from_secs(60));
+        assert_eq!(Some(Duration::from_secs(30)), config.http2_keep_alive_interval);
+
+        let key = fs::read("assets/testSigningKey.p8").expect("Failed to read file");
+        let client = AppStoreServerAPIClient::new(key, "keyId", "issuerId", "com.example", Environment::LocalTesting, Box::new(|_, _| unreachable!()));
+        assert!(client.transport(&config).is_ok());
+    }
+
     #[tokio::test]
     async fn test_api_unknown_error() {
         let client = app_store_server_api_client_with_body_from_file("assets/models/apiUnknownError.json", StatusCode::BAD_REQUEST, None);
-        let result = client.get_transaction_info("1234").await;
+        let result = client.get_transaction_info(&TransactionId::from("1234")).await;
 
         match result {
             Ok(_) => {
@@ -1122,8 +1937,8 @@ mod tests {
         let client = app_store_server_api_client_with_body_from_file("assets/models/transactionHistoryResponseWithMalformedEnvironment.json", StatusCode::OK, None);
 
         let request = TransactionHistoryRequest {
-            start_date: DateTime::from_timestamp(123, 455000000),
-            end_date: DateTime::from_timestamp(123, 456000000),
+            start_date: timestamp::from_millis(123455),
+            end_date: timestamp::from_millis(123456),
             product_ids: vec!["com.example.1".to_string(), "com.example.2".to_string()].into(),
             product_types: vec![ProductType::Consumable, ProductType::AutoRenewable].into(),
             sort: Some(Order::Ascending),
@@ -1132,8 +1947,8 @@ mod tests {
             revoked: Some(false),
         };
 
-        let result = client.get_transaction_history("1234", Some("revision_input"), request).await.unwrap();
-        assert_eq!(Environment::Unknown, result.environment.unwrap());
+        let result = client.get_transaction_history(&TransactionId::from("1234"), Some("revision_input"), request).await.unwrap();
+        assert_eq!(Environment::Unknown("LocalTestingxxx".to_string()), result.environment.unwrap());
     }
 
     #[tokio::test]
@@ -1141,8 +1956,8 @@ mod tests {
         let client = app_store_server_api_client_with_body_from_file("assets/models/transactionHistoryResponseWithMalformedAppAppleId.json", StatusCode::OK, None);
 
         let request = TransactionHistoryRequest {
-            start_date: DateTime::from_timestamp(123, 455000000),
-            end_date: DateTime::from_timestamp(123, 456000000),
+            start_date: timestamp::from_millis(123455),
+            end_date: timestamp::from_millis(123456),
             product_ids: vec!["com.example.1".to_string(), "com.example.2".to_string()].into(),
             product_types: vec![ProductType::Consumable, ProductType::AutoRenewable].into(),
             sort: Some(Order::Ascending),
@@ -1151,7 +1966,7 @@ mod tests {
             revoked: Some(false),
         };
 
-        let result = client.get_transaction_history("1234", Some("revision_input"), request).await;
+        let result = client.get_transaction_history(&TransactionId::from("1234"), Some("revision_input"), request).await;
         match result {
             Ok(_) => {
                 assert!(false, "Unexpected response type");
@@ -1160,11 +1975,76 @@ mod tests {
                 assert_eq!(500, error.http_status_code);
                 assert_eq!(None, error.api_error);
                 assert_eq!(None, error.raw_api_error);
-                assert_eq!("Failed to deserialize response JSON", error.error_message.unwrap());
+                assert_eq!(Some("hasMore"), error.json_path.as_deref());
+                assert!(error.error_message.unwrap().starts_with("Failed to deserialize response JSON at hasMore: invalid type: integer `1`, expected a boolean"));
             }
         }
     }
 
+    struct FailingBody;
+
+    impl http_body::Body for FailingBody {
+        type Data = &'static [u8];
+        type Error = std::io::Error;
+
+        fn poll_frame(
+            self: Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Option<Result<http_body::Frame<Self::Data>, Self::Error>>> {
+            std::task::Poll::Ready(Some(Err(std::io::Error::other("connection reset"))))
+        }
+    }
+
+    struct FailingBodyTransport(StatusCode);
+
+    impl Transport for FailingBodyTransport {
+        fn send<'a>(&'a self, _client: &'a Client, _request: reqwest::Request) -> TransportFuture<'a> {
+            let response = http::Response::builder()
+                .status(self.0)
+                .header("Content-Type", "application/json")
+                .body(reqwest::Body::wrap(FailingBody))
+                .unwrap();
+            Box::pin(async move { Ok(reqwest::Response::from(response)) })
+        }
+    }
+
+    struct ErrorResponseTransport;
+
+    impl Transport for ErrorResponseTransport {
+        fn send<'a>(&'a self, _client: &'a Client, _request: reqwest::Request) -> TransportFuture<'a> {
+            let response = http::Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .header("Content-Type", "application/json")
+                .header("X-Apple-Request-UUID", "5b1fb6e3-a40c-4d8c-9b3e-6f6e2d1f4c2a")
+                .body(r#"{"errorCode": 5000000, "errorMessage": "An unknown error occurred."}"#)
+                .unwrap();
+            Box::pin(async move { Ok(reqwest::Response::from(response)) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_id() {
+        let client = app_store_server_api_client(String::new(), StatusCode::OK, None)
+            .with_transport(ErrorResponseTransport);
+        let error = client.get_transaction_info(&TransactionId::from("1234")).await.unwrap_err();
+
+        assert_eq!(500, error.http_status_code);
+        assert_eq!(Some("5b1fb6e3-a40c-4d8c-9b3e-6f6e2d1f4c2a"), error.request_id());
+    }
+
+    #[tokio::test]
+    async fn test_response_body_read_failure() {
+        for status in [StatusCode::OK, StatusCode::INTERNAL_SERVER_ERROR] {
+            let client = app_store_server_api_client(String::new(), StatusCode::OK, None)
+                .with_transport(FailingBodyTransport(status));
+            let error = client.get_transaction_info(&TransactionId::from("1234")).await.unwrap_err();
+
+            assert_eq!(Some("Failed to read HTTP response body"), error.error_message.as_deref());
+            assert!(!error.from_response);
+            assert_eq!(Some("application/json"), error.headers.get("content-type").map(String::as_str));
+        }
+    }
+
     fn app_store_server_api_client_with_body_from_file(path: &str, status: http::StatusCode, request_verifier: Option<RequestVerifier>) -> AppStoreServerAPIClient {
         let body = fs::read_to_string(path)
             .expect("Failed to read file");
@@ -1,12 +1,20 @@
 use crate::chain_verifier::ChainVerificationFailureReason::{CertificateExpired, InvalidCertificate, InvalidChainLength, InvalidEffectiveDate};
+use jsonwebtoken::DecodingKey;
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 use thiserror::Error;
 
 use x509_parser::certificate::X509Certificate;
 use x509_parser::der_parser::asn1_rs::oid;
 use x509_parser::error::X509Error;
 use x509_parser::prelude::{ASN1Time, FromDer};
+use x509_parser::signature_algorithm::SignatureAlgorithm;
+use x509_parser::x509::{AlgorithmIdentifier, SubjectPublicKeyInfo};
 
 #[derive(Error, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum ChainVerifierError {
     #[error("VerificationFailure: [{0}]")]
     VerificationFailure(ChainVerificationFailureReason),
@@ -19,6 +27,7 @@ pub enum ChainVerifierError {
 }
 
 #[derive(Error, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum ChainVerificationFailureReason {
     #[error("InvalidAppIdentifier")]
     InvalidAppIdentifier,
@@ -35,7 +44,7 @@ pub enum ChainVerificationFailureReason {
     #[error("InvalidChain")]
     InvalidChain,
 
-    #[error("InvalidEnvironment")]
+    #[error("InvalidEffectiveDate")]
     InvalidEffectiveDate,
 
     #[error("CertificateExpired")]
@@ -83,6 +92,26 @@ pub fn verify_chain(
     root_certificates: &Vec<Vec<u8>>,
     effective_date: Option<u64>,
 ) -> Result<Vec<u8>, ChainVerifierError> {
+    verify_chain_to_root(certificates, root_certificates, effective_date).map(|leaf| leaf.public_key)
+}
+
+/// The keys of the leaf certificate of a chain verified by [`verify_chain_to_root`].
+struct VerifiedLeaf {
+    /// The DER-encoded `SubjectPublicKeyInfo` of the leaf certificate.
+    public_key: Vec<u8>,
+    /// The `subjectPublicKey` of the `SubjectPublicKeyInfo`, which is the EC point JWS signatures are verified with.
+    subject_public_key: Vec<u8>,
+    /// The index of the root certificate that the chain was verified against.
+    root_index: usize,
+}
+
+/// Verifies a certificate chain as [`verify_chain`] does, returning the keys of the parsed leaf
+/// certificate and the root certificate that the chain was verified against.
+fn verify_chain_to_root(
+    certificates: &[Vec<u8>],
+    root_certificates: &[Vec<u8>],
+    effective_date: Option<u64>,
+) -> Result<VerifiedLeaf, ChainVerifierError> {
     if root_certificates.is_empty() {
         return Err(ChainVerifierError::VerificationFailure(InvalidCertificate));
     }
@@ -116,26 +145,26 @@ pub fn verify_chain(
         return Err(ChainVerifierError::VerificationFailure(InvalidCertificate));
     };
 
-    let mut root_certificate: Option<X509Certificate> = None;
+    let mut root_certificate: Option<(usize, X509Certificate)> = None;
 
-    for cert in root_certificates {
-        let Ok(cert) = X509Certificate::from_der(&cert) else {
+    for (index, cert) in root_certificates.iter().enumerate() {
+        let Ok(cert) = X509Certificate::from_der(cert) else {
             return Err(ChainVerifierError::VerificationFailure(InvalidCertificate));
         };
 
-        match intermediate_certificate.verify_signature(Some(cert.1.public_key())) {
+        match verify_signature(&intermediate_certificate, cert.1.public_key()) {
             Ok(_) => (),
             Err(_) => continue,
         }
 
-        root_certificate = Some(cert.1)
+        root_certificate = Some((index, cert.1))
     }
 
-    let Some(root_certificate) = root_certificate else {
+    let Some((root_index, root_certificate)) = root_certificate else {
         return Err(ChainVerifierError::VerificationFailure(InvalidCertificate));
     };
 
-    leaf_certificate.verify_signature(Some(intermediate_certificate.public_key()))?;
+    verify_signature(&leaf_certificate, intermediate_certificate.public_key())?;
 
     if let Some(date) = effective_date {
         let Ok(time) = ASN1Time::from_timestamp(i64::try_from(date).unwrap()) else {
@@ -152,8 +181,255 @@ pub fn verify_chain(
         }
     }
 
-    let k = leaf_certificate.public_key().raw.to_vec();
-    Ok(k)
+    let public_key = leaf_certificate.public_key();
+    Ok(VerifiedLeaf {
+        public_key: public_key.raw.to_vec(),
+        subject_public_key: public_key.subject_public_key.data.to_vec(),
+        root_index,
+    })
+}
+
+/// A certificate chain verified by [`ChainVerifier::verify_detailed`].
+#[derive(Debug)]
+pub struct VerifiedChain<'a> {
+    /// The parsed leaf certificate, which signed the payload.
+    pub leaf_certificate: X509Certificate<'a>,
+
+    /// The DER-encoded `SubjectPublicKeyInfo` of the leaf certificate, to verify the JWS signature with.
+    pub public_key: Vec<u8>,
+
+    /// The DER-encoded certificates of the chain, from the leaf to the root certificate of the
+    /// verifier that the chain was verified against.
+    pub chain: Vec<&'a [u8]>,
+}
+
+/// The algorithms that certificates in a chain may be signed with.
+///
+/// Covers ECDSA over P-256 and P-384 with SHA-256 and SHA-384, RSA PKCS#1 v1.5 and RSA-PSS with
+/// SHA-256, SHA-384 and SHA-512, as well as the legacy algorithms of older roots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CertificateSignatureAlgorithm {
+    EcdsaP256Sha256,
+    EcdsaP256Sha384,
+    EcdsaP384Sha256,
+    EcdsaP384Sha384,
+    RsaPkcs1Sha1,
+    RsaPkcs1Sha256,
+    RsaPkcs1Sha384,
+    RsaPkcs1Sha512,
+    RsaPssSha256,
+    RsaPssSha384,
+    RsaPssSha512,
+    Ed25519,
+}
+
+impl CertificateSignatureAlgorithm {
+    /// Detects the algorithm of a certificate signature from its algorithm identifier and, for ECDSA,
+    /// the curve of the issuer's public key.
+    fn detect(signature_algorithm: &AlgorithmIdentifier, issuer_public_key: &SubjectPublicKeyInfo) -> Result<Self, X509Error> {
+        use CertificateSignatureAlgorithm::*;
+
+        let algorithm = &signature_algorithm.algorithm;
+        if *algorithm == oid!(1.2.840.10045.4.3.2) || *algorithm == oid!(1.2.840.10045.4.3.3) {
+            let sha384 = *algorithm == oid!(1.2.840.10045.4.3.3);
+            let curve = issuer_public_key.algorithm.parameters.as_ref().and_then(|parameters| parameters.as_oid().ok());
+            return match curve {
+                Some(curve) if curve == oid!(1.2.840.10045.3.1.7) => Ok(if sha384 { EcdsaP256Sha384 } else { EcdsaP256Sha256 }),
+                Some(curve) if curve == oid!(1.3.132.0.34) => Ok(if sha384 { EcdsaP384Sha384 } else { EcdsaP384Sha256 }),
+                _ => Err(X509Error::SignatureUnsupportedAlgorithm),
+            };
+        }
+
+        if *algorithm == oid!(1.2.840.113549.1.1.5) || *algorithm == oid!(1.3.14.3.2.29) {
+            Ok(RsaPkcs1Sha1)
+        } else if *algorithm == oid!(1.2.840.113549.1.1.11) {
+            Ok(RsaPkcs1Sha256)
+        } else if *algorithm == oid!(1.2.840.113549.1.1.12) {
+            Ok(RsaPkcs1Sha384)
+        } else if *algorithm == oid!(1.2.840.113549.1.1.13) {
+            Ok(RsaPkcs1Sha512)
+        } else if *algorithm == oid!(1.2.840.113549.1.1.10) {
+            Self::detect_rsa_pss(signature_algorithm)
+        } else if *algorithm == oid!(1.3.101.112) {
+            Ok(Ed25519)
+        } else {
+            Err(X509Error::SignatureUnsupportedAlgorithm)
+        }
+    }
+
+    /// Detects the hash of an RSA-PSS signature. Only the parameters that `ring` verifies are
+    /// supported: the MGF1 mask with the same hash, and a salt as long as the hash.
+    fn detect_rsa_pss(signature_algorithm: &AlgorithmIdentifier) -> Result<Self, X509Error> {
+        let SignatureAlgorithm::RSASSA_PSS(parameters) = SignatureAlgorithm::try_from(signature_algorithm)? else {
+            return Err(X509Error::SignatureUnsupportedAlgorithm);
+        };
+
+        let hash = parameters.hash_algorithm_oid();
+        let (algorithm, hash_length) = if *hash == oid!(2.16.840.1.101.3.4.2.1) {
+            (CertificateSignatureAlgorithm::RsaPssSha256, 32)
+        } else if *hash == oid!(2.16.840.1.101.3.4.2.2) {
+            (CertificateSignatureAlgorithm::RsaPssSha384, 48)
+        } else if *hash == oid!(2.16.840.1.101.3.4.2.3) {
+            (CertificateSignatureAlgorithm::RsaPssSha512, 64)
+        } else {
+            return Err(X509Error::SignatureUnsupportedAlgorithm);
+        };
+
+        let mask = parameters.mask_gen_algorithm()?;
+        if mask.mgf != oid!(1.2.840.113549.1.1.8) || mask.hash != *hash || parameters.salt_length() != hash_length {
+            return Err(X509Error::SignatureUnsupportedAlgorithm);
+        }
+        Ok(algorithm)
+    }
+
+    fn verification_algorithm(self) -> &'static dyn ring::signature::VerificationAlgorithm {
+        use ring::signature;
+
+        match self {
+            CertificateSignatureAlgorithm::EcdsaP256Sha256 => &signature::ECDSA_P256_SHA256_ASN1,
+            CertificateSignatureAlgorithm::EcdsaP256Sha384 => &signature::ECDSA_P256_SHA384_ASN1,
+            CertificateSignatureAlgorithm::EcdsaP384Sha256 => &signature::ECDSA_P384_SHA256_ASN1,
+            CertificateSignatureAlgorithm::EcdsaP384Sha384 => &signature::ECDSA_P384_SHA384_ASN1,
+            CertificateSignatureAlgorithm::RsaPkcs1Sha1 => &signature::RSA_PKCS1_1024_8192_SHA1_FOR_LEGACY_USE_ONLY,
+            CertificateSignatureAlgorithm::RsaPkcs1Sha256 => &signature::RSA_PKCS1_2048_8192_SHA256,
+            CertificateSignatureAlgorithm::RsaPkcs1Sha384 => &signature::RSA_PKCS1_2048_8192_SHA384,
+            CertificateSignatureAlgorithm::RsaPkcs1Sha512 => &signature::RSA_PKCS1_2048_8192_SHA512,
+            CertificateSignatureAlgorithm::RsaPssSha256 => &signature::RSA_PSS_2048_8192_SHA256,
+            CertificateSignatureAlgorithm::RsaPssSha384 => &signature::RSA_PSS_2048_8192_SHA384,
+            CertificateSignatureAlgorithm::RsaPssSha512 => &signature::RSA_PSS_2048_8192_SHA512,
+            CertificateSignatureAlgorithm::Ed25519 => &signature::ED25519,
+        }
+    }
+}
+
+/// Verifies the signature of a certificate with the public key of its issuer.
+fn verify_signature(certificate: &X509Certificate, issuer_public_key: &SubjectPublicKeyInfo) -> Result<(), X509Error> {
+    let algorithm = CertificateSignatureAlgorithm::detect(&certificate.signature_algorithm, issuer_public_key)?;
+    ring::signature::UnparsedPublicKey::new(algorithm.verification_algorithm(), &issuer_public_key.subject_public_key.data)
+        .verify(certificate.tbs_certificate.as_ref(), &certificate.signature_value.data)
+        .map_err(|_| X509Error::SignatureVerificationError)
+}
+
+/// Verifies certificate chains against a fixed set of root certificates, caching the public keys of
+/// the chains it has verified.
+///
+/// The App Store signs with few certificates, so most chains of a bulk workload, such as a backfill of
+/// transaction history, are verified once. [`verify_chains`](Self::verify_chains) verifies the rest on
+/// a bounded number of threads that share the cache.
+pub struct ChainVerifier {
+    root_certificates: Vec<Vec<u8>>,
+    effective_date: Option<u64>,
+    cache: Mutex<HashMap<Vec<Vec<u8>>, CachedChain>>,
+}
+
+/// The keys of the leaf certificate of a chain verified by a [`ChainVerifier`].
+#[derive(Clone)]
+struct CachedChain {
+    public_key: Vec<u8>,
+    decoding_key: DecodingKey,
+}
+
+impl ChainVerifier {
+    /// The number of verified chains kept before the cache is cleared.
+    pub const MAX_CACHED_CHAINS: usize = 256;
+
+    pub fn new(root_certificates: Vec<Vec<u8>>, effective_date: Option<u64>) -> Self {
+        ChainVerifier { root_certificates, effective_date, cache: Mutex::new(HashMap::new()) }
+    }
+
+    /// Verifies a certificate chain as [`verify_chain`] does, returning the cached public key of the
+    /// leaf certificate if the chain was already verified.
+    pub fn verify(&self, certificates: &[Vec<u8>]) -> Result<Vec<u8>, ChainVerifierError> {
+        self.verify_cached(certificates).map(|cached| cached.public_key)
+    }
+
+    /// Verifies a certificate chain as [`verify`](Self::verify) does, returning the key that JWS
+    /// signatures of the leaf certificate are verified with, built from the `subjectPublicKey` of the
+    /// leaf certificate parsed during verification.
+    pub fn verify_decoding_key(&self, certificates: &[Vec<u8>]) -> Result<DecodingKey, ChainVerifierError> {
+        self.verify_cached(certificates).map(|cached| cached.decoding_key)
+    }
+
+    fn verify_cached(&self, certificates: &[Vec<u8>]) -> Result<CachedChain, ChainVerifierError> {
+        if let Some(cached) = self.cache.lock().unwrap().get(certificates) {
+            return Ok(cached.clone());
+        }
+
+        let leaf = verify_chain_to_root(certificates, &self.root_certificates, self.effective_date)?;
+        let decoding_key = DecodingKey::from_ec_der(&leaf.subject_public_key);
+        let cached = CachedChain { public_key: leaf.public_key, decoding_key };
+
+        let mut cache = self.cache.lock().unwrap();
+        if cache.len() >= Self::MAX_CACHED_CHAINS {
+            cache.clear();
+        }
+        cache.insert(certificates.to_vec(), cached.clone());
+        Ok(cached)
+    }
+
+    /// The number of verified chains in the cache.
+    #[cfg(test)]
+    pub(crate) fn cached_chains(&self) -> usize {
+        self.cache.lock().unwrap().len()
+    }
+
+    /// Verifies a certificate chain as [`verify_chain`] does, returning the parsed leaf certificate,
+    /// its public key and the verified chain, for verifying JWS signatures with another library.
+    ///
+    /// # Arguments
+    ///
+    /// * `certificates` - The DER-encoded certificates of the `x5c` header of a JWS, from the leaf to the root.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(VerifiedChain)` if the chain is valid and ends at one of the verifier's root certificates.
+    /// - `Err(ChainVerifierError)` if the chain verification fails for any reason.
+    ///
+    /// Unlike [`verify`](Self::verify), the result isn't cached.
+    pub fn verify_detailed<'a>(&'a self, certificates: &'a [Vec<u8>]) -> Result<VerifiedChain<'a>, ChainVerifierError> {
+        let VerifiedLeaf { public_key, root_index, .. } = verify_chain_to_root(certificates, &self.root_certificates, self.effective_date)?;
+        let Ok((_, leaf_certificate)) = X509Certificate::from_der(&certificates[0]) else {
+            return Err(ChainVerifierError::VerificationFailure(InvalidCertificate));
+        };
+
+        let chain = vec![
+            certificates[0].as_slice(),
+            certificates[1].as_slice(),
+            self.root_certificates[root_index].as_slice(),
+        ];
+        Ok(VerifiedChain { leaf_certificate, public_key, chain })
+    }
+
+    /// Verifies certificate chains on up to `workers` threads, returning the results in the order of `chains`.
+    pub fn verify_chains(
+        &self,
+        chains: &[Vec<Vec<u8>>],
+        workers: NonZeroUsize,
+    ) -> Vec<Result<Vec<u8>, ChainVerifierError>> {
+        let next = AtomicUsize::new(0);
+        let workers = workers.get().min(chains.len());
+
+        let mut results: Vec<(usize, Result<Vec<u8>, ChainVerifierError>)> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut results = Vec::new();
+                        loop {
+                            let index = next.fetch_add(1, Ordering::Relaxed);
+                            let Some(chain) = chains.get(index) else { break };
+                            results.push((index, self.verify(chain)));
+                        }
+                        results
+                    })
+                })
+                .collect();
+
+            handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+        });
+
+        results.sort_by_key(|(index, _)| *index);
+        results.into_iter().map(|(_, result)| result).collect()
+    }
 }
 
 #[cfg(test)]
@@ -184,6 +460,12 @@ mod tests {
     const REAL_APPLE_INTERMEDIATE_BASE64_ENCODED: &str = "MIIDFjCCApygAwIBAgIUIsGhRwp0c2nvU4YSycafPTjzbNcwCgYIKoZIzj0EAwMwZzEbMBkGA1UEAwwSQXBwbGUgUm9vdCBDQSAtIEczMSYwJAYDVQQLDB1BcHBsZSBDZXJ0aWZpY2F0aW9uIEF1dGhvcml0eTETMBEGA1UECgwKQXBwbGUgSW5jLjELMAkGA1UEBhMCVVMwHhcNMjEwMzE3MjAzNzEwWhcNMzYwMzE5MDAwMDAwWjB1MUQwQgYDVQQDDDtBcHBsZSBXb3JsZHdpZGUgRGV2ZWxvcGVyIFJlbGF0aW9ucyBDZXJ0aWZpY2F0aW9uIEF1dGhvcml0eTELMAkGA1UECwwCRzYxEzARBgNVBAoMCkFwcGxlIEluYy4xCzAJBgNVBAYTAlVTMHYwEAYHKoZIzj0CAQYFK4EEACIDYgAEbsQKC94PrlWmZXnXgtxzdVJL8T0SGYngDRGpngn3N6PT8JMEb7FDi4bBmPhCnZ3/sq6PF/cGcKXWsL5vOteRhyJ45x3ASP7cOB+aao90fcpxSv/EZFbniAbNgZGhIhpIo4H6MIH3MBIGA1UdEwEB/wQIMAYBAf8CAQAwHwYDVR0jBBgwFoAUu7DeoVgziJqkipnevr3rr9rLJKswRgYIKwYBBQUHAQEEOjA4MDYGCCsGAQUFBzABhipodHRwOi8vb2NzcC5hcHBsZS5jb20vb2NzcDAzLWFwcGxlcm9vdGNhZzMwNwYDVR0fBDAwLjAsoCqgKIYmaHR0cDovL2NybC5hcHBsZS5jb20vYXBwbGVyb290Y2FnMy5jcmwwHQYDVR0OBBYEFD8vlCNR01DJmig97bB85c+lkGKZMA4GA1UdDwEB/wQEAwIBBjAQBgoqhkiG92NkBgIBBAIFADAKBggqhkjOPQQDAwNoADBlAjBAXhSq5IyKogMCPtw490BaB677CaEGJXufQB/EqZGd6CSjiCtOnuMTbXVXmxxcxfkCMQDTSPxarZXvNrkxU3TkUMI33yzvFVVRT4wxWJC994OsdcZ4+RGNsYDyR5gmdr0nDGg=";
     const REAL_APPLE_SIGNING_CERTIFICATE_BASE64_ENCODED: &str = "MIIEMDCCA7agAwIBAgIQaPoPldvpSoEH0lBrjDPv9jAKBggqhkjOPQQDAzB1MUQwQgYDVQQDDDtBcHBsZSBXb3JsZHdpZGUgRGV2ZWxvcGVyIFJlbGF0aW9ucyBDZXJ0aWZpY2F0aW9uIEF1dGhvcml0eTELMAkGA1UECwwCRzYxEzARBgNVBAoMCkFwcGxlIEluYy4xCzAJBgNVBAYTAlVTMB4XDTIxMDgyNTAyNTAzNFoXDTIzMDkyNDAyNTAzM1owgZIxQDA+BgNVBAMMN1Byb2QgRUNDIE1hYyBBcHAgU3RvcmUgYW5kIGlUdW5lcyBTdG9yZSBSZWNlaXB0IFNpZ25pbmcxLDAqBgNVBAsMI0FwcGxlIFdvcmxkd2lkZSBEZXZlbG9wZXIgUmVsYXRpb25zMRMwEQYDVQQKDApBcHBsZSBJbmMuMQswCQYDVQQGEwJVUzBZMBMGByqGSM49AgEGCCqGSM49AwEHA0IABOoTcaPcpeipNL9eQ06tCu7pUcwdCXdN8vGqaUjd58Z8tLxiUC0dBeA+euMYggh1/5iAk+FMxUFmA2a1r4aCZ8SjggIIMIICBDAMBgNVHRMBAf8EAjAAMB8GA1UdIwQYMBaAFD8vlCNR01DJmig97bB85c+lkGKZMHAGCCsGAQUFBwEBBGQwYjAtBggrBgEFBQcwAoYhaHR0cDovL2NlcnRzLmFwcGxlLmNvbS93d2RyZzYuZGVyMDEGCCsGAQUFBzABhiVodHRwOi8vb2NzcC5hcHBsZS5jb20vb2NzcDAzLXd3ZHJnNjAyMIIBHgYDVR0gBIIBFTCCAREwggENBgoqhkiG92NkBQYBMIH+MIHDBggrBgEFBQcCAjCBtgyBs1JlbGlhbmNlIG9uIHRoaXMgY2VydGlmaWNhdGUgYnkgYW55IHBhcnR5IGFzc3VtZXMgYWNjZXB0YW5jZSBvZiB0aGUgdGhlbiBhcHBsaWNhYmxlIHN0YW5kYXJkIHRlcm1zIGFuZCBjb25kaXRpb25zIG9mIHVzZSwgY2VydGlmaWNhdGUgcG9saWN5IGFuZCBjZXJ0aWZpY2F0aW9uIHByYWN0aWNlIHN0YXRlbWVudHMuMDYGCCsGAQUFBwIBFipodHRwOi8vd3d3LmFwcGxlLmNvbS9jZXJ0aWZpY2F0ZWF1dGhvcml0eS8wHQYDVR0OBBYEFCOCmMBq//1L5imvVmqX1oCYeqrMMA4GA1UdDwEB/wQEAwIHgDAQBgoqhkiG92NkBgsBBAIFADAKBggqhkjOPQQDAwNoADBlAjEAl4JB9GJHixP2nuibyU1k3wri5psGIxPME05sFKq7hQuzvbeyBu82FozzxmbzpogoAjBLSFl0dZWIYl2ejPV+Di5fBnKPu8mymBQtoE/H2bES0qAs8bNueU3CBjjh1lwnDsI=";
     const EFFECTIVE_DATE: u64 = 1681312846;
+    const RSA_ROOT_BASE64_ENCODED: &str = "MIIDFzCCAf+gAwIBAgIUbFNFuMYTlDxQQH62lScdktLqcdAwDQYJKoZIhvcNAQELBQAwEzERMA8GA1UEAwwIUlNBIFJvb3QwHhcNMjYxMDE0MTA1NTQ3WhcNMzYxMDExMTA1NTQ3WjATMREwDwYDVQQDDAhSU0EgUm9vdDCCASIwDQYJKoZIhvcNAQEBBQADggEPADCCAQoCggEBAKC1HCD1gwAc1ELt4xd/rplZrClGRS217UhfA5Ifi32ak+2Il5C9V3cVIyZZyV5ttBoiMn6Ws9ky27e9QbIhEmwe7SI8VeQLoL7COf8PKwlRUygnjvqyWYcAs5odgemJESScXRAa5NOr3mQcP4zEs92ynLUsQBQMk1B+FLclEyJd6FPkrf3QcALtTYheuUAL5DmeZ707VTSjwN8UeCsQtJPTrwg4INpe/SYt3N47omQmsZWE7m3OKU0TR4xONOrCALppdD4GsddLxwtk6SFetLX/yqM5GUTpZwvlXdiIM0saLSUTiflwY5s936vXuOGozVrxtHGgG6n6m34CCHM7oCUCAwEAAaNjMGEwHQYDVR0OBBYEFNUDuqAhQRl6nXfmOhBRgCHnIZFnMB8GA1UdIwQYMBaAFNUDuqAhQRl6nXfmOhBRgCHnIZFnMA8GA1UdEwEB/wQFMAMBAf8wDgYDVR0PAQH/BAQDAgIEMA0GCSqGSIb3DQEBCwUAA4IBAQA4uoMVedjudBi0mJAn5jWYo7nTMuNwsjBysQuJyRLIAMu45ypJxEoE3dNxgkctLY/WVw+EFjvrXXmprZfJiMvNFpqbt0zUalXW9x4bI4gAOOiv4nzgCC4NFrz4HzAKCprVb+LzQgbDlGGLR2OkznzWIXCRgkfk9xgAh4quajxbgfyhZNLlGcM4pPry2poAppCnzMTJ7LQwCAGNOUIUtZlFrN9acme/t3QCemHnESa3KUSU4zf63i4i0xkoeasB5WN+GmnFLaLKMty/YY49tIFRejebOHJhdVGUVc8Je9GQ2KbT4RMoYLZfMV1Vi/qgZPXs4Jw0+dazvHwVGiaklIT2";
+    const RSA_PSS_SHA256_INTERMEDIATE_BASE64_ENCODED: &str = "MIIDoDCCAlSgAwIBAgIUUmaohuHaFoceo7hlaCkumKUfxAkwQQYJKoZIhvcNAQEKMDSgDzANBglghkgBZQMEAgEFAKEcMBoGCSqGSIb3DQEBCDANBglghkgBZQMEAgEFAKIDAgEgMBMxETAPBgNVBAMMCFJTQSBSb290MB4XDTI2MTAxNDEwNTU0N1oXDTM2MTAxMTEwNTU0N1owHzEdMBsGA1UEAwwUUlNBLVBTUyBJbnRlcm1lZGlhdGUwggEiMA0GCSqGSIb3DQEBAQUAA4IBDwAwggEKAoIBAQDdLisiDGPW1pMfNhiEYwaEA9e0rxon0WtJBQAKgS4W+ZI+8FPYuARZuKkMq0pBcIR+44Kje1AmFs3tBQYZKvZGF6yotZvLyQHzOYDlvQfZPY6uU/+/s00oAsGuOH2nNiHnYXHJjb9ZmTjO9W5JpLr7tSSIIZVQb8aSZHxHIMw6F4bYUnnqlp32krKWxi7xZsJUTcGskXCLWMRhLBzjzcAlqr92h+HR68OBsCoN2y+zEo4TO3LmO0nb2NPMET7f1t47zSGOjT1t4AqPE4L6MxfYQN1pJfJF646M3jHvaPFRhWxVO+kBYEHsVYbnwO3QjDgm1CfaJ+eDCVidGVP9InYHAgMBAAGjeDB2MBIGA1UdEwEB/wQIMAYBAf8CAQAwDgYDVR0PAQH/BAQDAgIEMBAGCiqGSIb3Y2QGAgEEAgUAMB0GA1UdDgQWBBTOoxbg4Dd8jFDC7NAYQQqYb4F/ATAfBgNVHSMEGDAWgBTVA7qgIUEZep135joQUYAh5yGRZzBBBgkqhkiG9w0BAQowNKAPMA0GCWCGSAFlAwQCAQUAoRwwGgYJKoZIhvcNAQEIMA0GCWCGSAFlAwQCAQUAogMCASADggEBAFKb67f40YKYOfAzh3kM8N3DyPdYB7S+voB30XzAWblnGPpXRuhoeHgFVCdh6hfITfugv/iWP/PNg1FkMucp0ED3Um83bbqn7WtPFMCQFVFt8dmwDuZ1xcGIufRbjHJ/PItoLDvftuVoaqxbazVdAxreGD/QxSKI9OiA1iNeBa4nnvbrjBlun+DkiallZYD6wSV/hdEjL/tBirhnecQC3Kuuyfsg1v87CNhEHAbpbkSho0j9GBcwHR4BY40UJ4oBX8N/eusL8I1/lEkjPbcvve+4IjQFE7G+Z3bUjBRpC72IcQzOeO5pChvnrfLTwQuuN6MZ41D+SFSgL+7kPWDMQcw=";
+    const RSA_PSS_SHA384_LEAF_BASE64_ENCODED: &str = "MIICyDCCAXygAwIBAgIUfndcgG+w3hS1xvE9V35FaRkpc+swQQYJKoZIhvcNAQEKMDSgDzANBglghkgBZQMEAgIFAKEcMBoGCSqGSIb3DQEBCDANBglghkgBZQMEAgIFAKIDAgEwMB8xHTAbBgNVBAMMFFJTQS1QU1MgSW50ZXJtZWRpYXRlMB4XDTI2MTAxNDEwNTU0N1oXDTM2MTAxMTEwNTU0N1owDzENMAsGA1UEAwwETGVhZjBZMBMGByqGSM49AgEGCCqGSM49AwEHA0IABKnvDMh5qNSaBmqaGNQgsvGSh9CwBrdkIpjrSvja39+8eBxwLZ/dc6kPQ5INcFQbNj7zsNJ+orjYHysg/Mz2swijbzBtMAkGA1UdEwQCMAAwDgYDVR0PAQH/BAQDAgeAMBAGCiqGSIb3Y2QGCwEEAgUAMB0GA1UdDgQWBBS2udLE300YZUmL0Pt35AKD+495QDAfBgNVHSMEGDAWgBTOoxbg4Dd8jFDC7NAYQQqYb4F/ATBBBgkqhkiG9w0BAQowNKAPMA0GCWCGSAFlAwQCAgUAoRwwGgYJKoZIhvcNAQEIMA0GCWCGSAFlAwQCAgUAogMCATADggEBAHG1qE+LgqkAeO4od6LzxvO1IvGEWPoVN2Xqpm3q0j242igwGn6Ixke8BzzxLsTCZOH8Nijz8kgCoNGG3nycsAM7ZEc0HUUwDi49IIt34UvZgAGvRxjPsB2jsBGO6WDD6bpRI/aZHBoOiR9iyCBBKvJqxDS6EFxe1Ndjedq9GmN//RkKo9dykHVZyuNL6VMprBngwl++ecxElbhf8lQaXyTvnqTnzwhWjFdJdTpIgNnolm/0sL9vNe3soLR+LWArmepYGDjFV6GjjK09ucdFTFu0VwhZXK5YYH7XgKEAoU+Jw+AX9W2NFN6omcWGlX+99uABTxou0AtmyB5DxCIAAwg=";
+    const RSA_PSS_LEAF_PUBLIC_KEY_BASE64_ENCODED: &str = "MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEqe8MyHmo1JoGapoY1CCy8ZKH0LAGt2QimOtK+Nrf37x4HHAtn91zqQ9Dkg1wVBs2PvOw0n6iuNgfKyD8zPazCA==";
+    const RSA_PSS_EFFECTIVE_DATE: u64 = 1800000000;
+
 
     #[test]
     fn test_valid_chain_without_ocsp() -> Result<(), ChainVerifierError> {
@@ -337,6 +619,42 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_generated_chain() {
+        let authority = crate::test_utils::TestCertificateAuthority::generate_with_validity(1_700_000_000, 1_800_000_000);
+        let roots = vec![authority.root_certificate.clone()];
+
+        assert!(verify_chain(&authority.certificate_chain(), &roots, Some(1_750_000_000)).is_ok());
+        assert_eq!(
+            Err(ChainVerifierError::VerificationFailure(CertificateExpired)),
+            verify_chain(&authority.certificate_chain(), &roots, Some(1_900_000_000))
+        );
+    }
+
+    #[test]
+    fn test_chain_verifier_verify_chains() {
+        let root = ROOT_CA_BASE64_ENCODED.as_der_bytes().unwrap();
+        let leaf = LEAF_CERT_BASE64_ENCODED.as_der_bytes().unwrap();
+        let intermediate = INTERMEDIATE_CA_BASE64_ENCODED.as_der_bytes().unwrap();
+        let valid = vec![leaf.clone(), intermediate.clone(), root.clone()];
+        let too_short = vec![leaf, intermediate];
+
+        let verifier = ChainVerifier::new(vec![root], Some(EFFECTIVE_DATE));
+        let chains = vec![valid.clone(), too_short, valid];
+        let results = verifier.verify_chains(&chains, NonZeroUsize::new(2).unwrap());
+
+        let public_key = LEAF_CERT_PUBLIC_KEY_BASE64_ENCODED.as_der_bytes().unwrap();
+        assert_eq!(
+            vec![
+                Ok(public_key.clone()),
+                Err(ChainVerifierError::VerificationFailure(InvalidChainLength)),
+                Ok(public_key),
+            ],
+            results
+        );
+        assert_eq!(1, verifier.cache.lock().unwrap().len());
+    }
+
     #[test]
     fn test_apple_chain_is_valid_multi_root() -> Result<(), ChainVerifierError> {
         let root = REAL_APPLE_ROOT_BASE64_ENCODED.as_der_bytes()?;
@@ -354,4 +672,40 @@ mod tests {
         let _public_key = verify_chain(&chain, &multi_root, Some(EFFECTIVE_DATE))?;
         Ok(())
     }
+
+    #[test]
+    fn test_verify_detailed() -> Result<(), ChainVerifierError> {
+        let root = REAL_APPLE_ROOT_BASE64_ENCODED.as_der_bytes()?;
+        let intermediate = REAL_APPLE_INTERMEDIATE_BASE64_ENCODED.as_der_bytes()?;
+        let leaf = REAL_APPLE_SIGNING_CERTIFICATE_BASE64_ENCODED.as_der_bytes()?;
+        let chain = vec![leaf.clone(), intermediate.clone(), root.clone()];
+
+        let multi_root: Vec<_> = REAL_APPLE_MULTI_ROOT_BASE64_ENCODED.into_iter().map(|str| str.as_der_bytes().unwrap()).collect();
+        let verifier = ChainVerifier::new(multi_root, Some(EFFECTIVE_DATE));
+        let verified = verifier.verify_detailed(&chain)?;
+
+        assert_eq!(verifier.verify(&chain)?, verified.public_key);
+        assert_eq!(verified.leaf_certificate.public_key().raw, verified.public_key.as_slice());
+        assert!(verified.leaf_certificate.subject().to_string().contains("Prod ECC Mac App Store and iTunes Store Receipt Signing"));
+        assert_eq!(vec![leaf.as_slice(), intermediate.as_slice(), root.as_slice()], verified.chain);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rsa_pss_chain() -> Result<(), ChainVerifierError> {
+        let root = RSA_ROOT_BASE64_ENCODED.as_der_bytes()?;
+        let intermediate = RSA_PSS_SHA256_INTERMEDIATE_BASE64_ENCODED.as_der_bytes()?;
+        let leaf = RSA_PSS_SHA384_LEAF_BASE64_ENCODED.as_der_bytes()?;
+        let chain = vec![leaf, intermediate, root.clone()];
+
+        let public_key = verify_chain(&chain, &vec![root], Some(RSA_PSS_EFFECTIVE_DATE))?;
+        assert_eq!(RSA_PSS_LEAF_PUBLIC_KEY_BASE64_ENCODED.as_der_bytes()?, public_key);
+
+        let other_root = ROOT_CA_BASE64_ENCODED.as_der_bytes()?;
+        assert_eq!(
+            Err(ChainVerifierError::VerificationFailure(InvalidCertificate)),
+            verify_chain(&chain, &vec![other_root], Some(RSA_PSS_EFFECTIVE_DATE))
+        );
+        Ok(())
+    }
 }
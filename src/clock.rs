@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A source of the current time.
+///
+/// The token issuer and the in-memory caches read the time from a clock rather than from the system,
+/// so that tests can freeze it with a [`FrozenClock`] and check expiry and refresh deterministically.
+pub trait Clock: Debug + Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system clock, used unless another clock is set.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when it is set or advanced.
+#[derive(Debug)]
+pub struct FrozenClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl FrozenClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        FrozenClock { now: Mutex::new(now) }
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration_to_chrono(duration);
+    }
+}
+
+impl Clock for FrozenClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap()
+    }
+}
+
+pub(crate) fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+/// Converts a duration, saturating at the largest duration chrono can represent.
+pub(crate) fn duration_to_chrono(duration: Duration) -> chrono::Duration {
+    chrono::Duration::from_std(duration).unwrap_or(chrono::Duration::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frozen_clock() {
+        let start = DateTime::from_timestamp(1698148800, 0).unwrap();
+        let clock = FrozenClock::new(start);
+        assert_eq!(start, clock.now());
+
+        clock.advance(Duration::from_secs(90));
+        assert_eq!(1698148890, clock.now().timestamp());
+
+        clock.set(start);
+        assert_eq!(start, clock.now());
+    }
+}
@@ -0,0 +1,264 @@
+//! Errors limited to the error codes that each App Store Server API endpoint documents.
+//!
+//! Each error converts from the [`APIException`] returned by the matching method of
+//! [`AppStoreServerAPIClient`](crate::api_client::AppStoreServerAPIClient), so that application code
+//! can match on the failures the endpoint can actually return:
+//!
+//! ```ignore
+//! match client.get_transaction_info(&transaction_id).await.map_err(GetTransactionInfoError::from) {
+//!     Ok(response) => { /* ... */ }
+//!     Err(GetTransactionInfoError::TransactionIdNotFound(_)) => { /* ... */ }
+//!     Err(error) => return Err(error.into()),
+//! }
+//! ```
+//!
+//! Every error has a `RateLimitExceeded` variant for `429 Too Many Requests` responses and an `Other`
+//! variant for the responses with an error code the endpoint doesn't document, or without one.
+//! Error codes that have a retryable counterpart, such as `AccountNotFoundRetryable`, map to the same
+//! variant; use [`APIException::is_retryable`] to tell them apart.
+use crate::api_client::APIException;
+use crate::primitives::error_payload::APIError;
+use std::fmt;
+
+/// Defines an error with a variant for each listed error code, followed by `|` and the error codes
+/// that map to the same variant.
+macro_rules! endpoint_error {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident = $code:ident $(| $alias:ident)*,
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug)]
+        pub enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant(APIException),
+            )*
+            /// The request exceeded the rate limit.
+            ///
+            /// [RateLimitExceededError](https://developer.apple.com/documentation/appstoreserverapi/ratelimitexceedederror)
+            RateLimitExceeded(APIException),
+            /// An error the endpoint doesn't document, a server error, or a failure to send the request.
+            Other(APIException),
+        }
+
+        impl $name {
+            /// The exception the error was created from.
+            pub fn exception(&self) -> &APIException {
+                match self {
+                    $($name::$variant(exception) => exception,)*
+                    $name::RateLimitExceeded(exception) | $name::Other(exception) => exception,
+                }
+            }
+
+            fn variant_name(&self) -> &'static str {
+                match self {
+                    $($name::$variant(_) => stringify!($variant),)*
+                    $name::RateLimitExceeded(_) => "RateLimitExceeded",
+                    $name::Other(_) => "Other",
+                }
+            }
+        }
+
+        impl From<APIException> for $name {
+            fn from(exception: APIException) -> Self {
+                match exception.api_error {
+                    $(Some(APIError::$code $(| APIError::$alias)*) => $name::$variant(exception),)*
+                    Some(APIError::RateLimitExceeded) => $name::RateLimitExceeded(exception),
+                    _ if exception.http_status_code == 429 => $name::RateLimitExceeded(exception),
+                    _ => $name::Other(exception),
+                }
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}: [{}]", self.variant_name(), self.exception())
+            }
+        }
+
+        impl std::error::Error for $name {
+            fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                Some(self.exception())
+            }
+        }
+    };
+}
+
+endpoint_error! {
+    /// The errors of [Get Transaction Info](https://developer.apple.com/documentation/appstoreserverapi/get_transaction_info).
+    pub enum GetTransactionInfoError {
+        InvalidTransactionId = InvalidTransactionId,
+        TransactionIdNotFound = TransactionIdNotFound,
+    }
+}
+
+endpoint_error! {
+    /// The errors of [Get Transaction History](https://developer.apple.com/documentation/appstoreserverapi/get_transaction_history).
+    pub enum GetTransactionHistoryError {
+        InvalidTransactionId = InvalidTransactionId,
+        InvalidRequestRevision = InvalidRequestRevision,
+        InvalidStartDate = InvalidStartDate,
+        InvalidEndDate = InvalidEndDate,
+        InvalidProductType = InvalidProductType,
+        InvalidProductId = InvalidProductId,
+        InvalidSubscriptionGroupIdentifier = InvalidSubscriptionGroupIdentifier,
+        InvalidSort = InvalidSort,
+        InvalidInAppOwnershipType = InvalidInAppOwnershipType,
+        InvalidRevoked = InvalidRevoked | InvalidExcludeRevoked,
+        AccountNotFound = AccountNotFound | AccountNotFoundRetryable,
+        AppNotFound = AppNotFound | AppNotFoundRetryable,
+        TransactionIdNotFound = TransactionIdNotFound,
+    }
+}
+
+endpoint_error! {
+    /// The errors of [Get All Subscription Statuses](https://developer.apple.com/documentation/appstoreserverapi/get_all_subscription_statuses).
+    pub enum GetAllSubscriptionStatusesError {
+        InvalidTransactionId = InvalidTransactionId,
+        InvalidStatus = InvalidStatus,
+        AccountNotFound = AccountNotFound | AccountNotFoundRetryable,
+        AppNotFound = AppNotFound | AppNotFoundRetryable,
+        TransactionIdNotFound = TransactionIdNotFound,
+    }
+}
+
+endpoint_error! {
+    /// The errors of [Get Refund History](https://developer.apple.com/documentation/appstoreserverapi/get_refund_history).
+    pub enum GetRefundHistoryError {
+        InvalidTransactionId = InvalidTransactionId,
+        InvalidRequestRevision = InvalidRequestRevision,
+        AccountNotFound = AccountNotFound | AccountNotFoundRetryable,
+        AppNotFound = AppNotFound | AppNotFoundRetryable,
+        TransactionIdNotFound = TransactionIdNotFound,
+    }
+}
+
+endpoint_error! {
+    /// The errors of [Extend a Subscription Renewal Date](https://developer.apple.com/documentation/appstoreserverapi/extend_a_subscription_renewal_date).
+    pub enum ExtendSubscriptionRenewalDateError {
+        InvalidOriginalTransactionId = InvalidOriginalTransactionId,
+        InvalidExtendByDays = InvalidExtendByDays,
+        InvalidExtendReasonCode = InvalidExtendReasonCode,
+        InvalidRequestIdentifier = InvalidRequestIdentifier,
+        SubscriptionExtensionIneligible = SubscriptionExtensionIneligible,
+        SubscriptionMaxExtension = SubscriptionMaxExtension,
+        FamilySharedSubscriptionExtensionIneligible = FamilySharedSubscriptionExtensionIneligible,
+        AccountNotFound = AccountNotFound | AccountNotFoundRetryable,
+        AppNotFound = AppNotFound | AppNotFoundRetryable,
+        OriginalTransactionIdNotFound = OriginalTransactionIdNotFound | OriginalTransactionIdNotFoundRetryable,
+    }
+}
+
+endpoint_error! {
+    /// The errors of [Extend Subscription Renewal Dates for All Active Subscribers](https://developer.apple.com/documentation/appstoreserverapi/extend_subscription_renewal_dates_for_all_active_subscribers).
+    pub enum ExtendRenewalDateForAllActiveSubscribersError {
+        InvalidExtendByDays = InvalidExtendByDays,
+        InvalidExtendReasonCode = InvalidExtendReasonCode,
+        InvalidRequestIdentifier = InvalidRequestIdentifier,
+        InvalidProductId = InvalidProductId,
+        InvalidEmptyStorefrontCountryCodeList = InvalidEmptyStorefrontCountryCodeList,
+        InvalidStorefrontCountryCode = InvalidStorefrontCountryCode,
+    }
+}
+
+endpoint_error! {
+    /// The errors of [Get Status of Subscription Renewal Date Extensions](https://developer.apple.com/documentation/appstoreserverapi/get_status_of_subscription_renewal_date_extensions).
+    pub enum GetStatusOfSubscriptionRenewalDateExtensionsError {
+        InvalidRequestIdentifier = InvalidRequestIdentifier,
+        InvalidProductId = InvalidProductId,
+        StatusRequestNotFound = StatusRequestNotFound,
+    }
+}
+
+endpoint_error! {
+    /// The errors of [Get Notification History](https://developer.apple.com/documentation/appstoreserverapi/get_notification_history).
+    pub enum GetNotificationHistoryError {
+        InvalidPaginationToken = InvalidPaginationToken,
+        PaginationTokenExpired = PaginationTokenExpired,
+        InvalidStartDate = InvalidStartDate,
+        InvalidEndDate = InvalidEndDate,
+        StartDateTooFarInPast = StartDateTooFarInPast,
+        StartDateAfterEndDate = StartDateAfterEndDate,
+        InvalidNotificationType = InvalidNotificationType,
+        MultipleFiltersSupplied = MultipleFiltersSupplied,
+        InvalidTransactionId = InvalidTransactionId,
+        ServerNotificationUrlNotFound = ServerNotificationUrlNotFound,
+    }
+}
+
+endpoint_error! {
+    /// The errors of [Request a Test Notification](https://developer.apple.com/documentation/appstoreserverapi/request_a_test_notification).
+    pub enum RequestTestNotificationError {
+        ServerNotificationUrlNotFound = ServerNotificationUrlNotFound,
+    }
+}
+
+endpoint_error! {
+    /// The errors of [Get Test Notification Status](https://developer.apple.com/documentation/appstoreserverapi/get_test_notification_status).
+    pub enum GetTestNotificationStatusError {
+        InvalidTestNotificationToken = InvalidTestNotificationToken,
+        TestNotificationNotFound = TestNotificationNotFound,
+    }
+}
+
+endpoint_error! {
+    /// The errors of [Send Consumption Information](https://developer.apple.com/documentation/appstoreserverapi/send_consumption_information).
+    pub enum SendConsumptionDataError {
+        InvalidTransactionId = InvalidTransactionId,
+        InvalidAccountTenure = InvalidAccountTenure,
+        InvalidAppAccountToken = InvalidAppAccountToken,
+        InvalidConsumptionStatus = InvalidConsumptionStatus,
+        InvalidCustomerConsented = InvalidCustomerConsented,
+        InvalidDeliveryStatus = InvalidDeliveryStatus,
+        InvalidLifetimeDollarsPurchased = InvalidLifetimeDollarsPurchased,
+        InvalidLifetimeDollarsRefunded = InvalidLifetimeDollarsRefunded,
+        InvalidPlatform = InvalidPlatform,
+        InvalidPlayTime = InvalidPlayTime,
+        InvalidSampleContentProvided = InvalidSampleContentProvided,
+        InvalidUserStatus = InvalidUserStatus,
+        InvalidTransactionTypeNotSupported = InvalidTransactionTypeNotSupported,
+        TransactionIdNotFound = TransactionIdNotFound,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn exception(http_status_code: u16, api_error: Option<APIError>) -> APIException {
+        APIException {
+            http_status_code,
+            raw_api_error: api_error.clone().map(|api_error| api_error as i64),
+            api_error,
+            error_message: None,
+            retry_after: None,
+            raw_body: None,
+            headers: HashMap::new(),
+            json_path: None,
+            from_response: true,
+        }
+    }
+
+    #[test]
+    fn test_from_api_exception() {
+        let error = GetTransactionInfoError::from(exception(404, Some(APIError::TransactionIdNotFound)));
+        assert!(matches!(error, GetTransactionInfoError::TransactionIdNotFound(_)));
+        assert_eq!("TransactionIdNotFound: [APIException: HTTP Status Code 404, API Error: TransactionIdNotFound, Raw API Error: 4040010]", error.to_string());
+
+        let error = GetRefundHistoryError::from(exception(404, Some(APIError::AccountNotFoundRetryable)));
+        assert!(matches!(&error, GetRefundHistoryError::AccountNotFound(exception) if exception.is_retryable()));
+
+        assert!(matches!(GetTransactionInfoError::from(exception(429, None)), GetTransactionInfoError::RateLimitExceeded(_)));
+        assert!(matches!(
+            GetTransactionInfoError::from(exception(400, Some(APIError::InvalidSort))),
+            GetTransactionInfoError::Other(_)
+        ));
+    }
+}
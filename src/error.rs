@@ -0,0 +1,72 @@
+#[cfg(feature = "api-client")]
+use crate::api_client::{APIException, ConfigurationError};
+use crate::chain_verifier::ChainVerifierError;
+use crate::jws_signature_creator::JWSSignatureCreatorError;
+use crate::promotional_offer_signature_creator::PromotionalOfferSignatureCreatorError;
+#[cfg(feature = "receipt-utility")]
+use crate::receipt_utility::ReceiptUtilityError;
+use crate::signed_data_verifier::SignedDataVerifierError;
+use crate::signer::SignerError;
+use crate::webhook::WebhookBodyError;
+
+/// An error of any part of the library, for applications that use several of them and bubble up one error type.
+///
+/// Each variant converts from the error of one part, so that `?` works across them.
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("SignedDataVerifierError: [{0}]")]
+    SignedDataVerifierError(#[from] SignedDataVerifierError),
+
+    #[error("ChainVerifierError: [{0}]")]
+    ChainVerifierError(#[from] ChainVerifierError),
+
+    #[error("SignerError: [{0}]")]
+    SignerError(#[from] SignerError),
+
+    #[error("JWSSignatureCreatorError: [{0}]")]
+    JWSSignatureCreatorError(#[from] JWSSignatureCreatorError),
+
+    #[error("PromotionalOfferSignatureCreatorError: [{0}]")]
+    PromotionalOfferSignatureCreatorError(#[from] PromotionalOfferSignatureCreatorError),
+
+    #[error("WebhookBodyError: [{0}]")]
+    WebhookBodyError(#[from] WebhookBodyError),
+
+    #[cfg(feature = "receipt-utility")]
+    #[error("ReceiptUtilityError: [{0}]")]
+    ReceiptUtilityError(#[from] ReceiptUtilityError),
+
+    #[cfg(feature = "api-client")]
+    #[error("ConfigurationError: [{0}]")]
+    ConfigurationError(#[from] ConfigurationError),
+
+    #[cfg(feature = "api-client")]
+    #[error("APIError: [{0}]")]
+    APIError(#[source] Box<APIException>),
+}
+
+#[cfg(feature = "api-client")]
+impl From<APIException> for Error {
+    fn from(error: APIException) -> Self {
+        Error::APIError(Box::new(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::local_testing_verifier;
+
+    fn decode(signed_transaction: &str) -> Result<(), Error> {
+        local_testing_verifier().verify_and_decode_signed_transaction(signed_transaction)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_from() {
+        let error = decode("invalid").unwrap_err();
+        assert!(matches!(error, Error::SignedDataVerifierError(_)));
+        assert!(std::error::Error::source(&error).is_some());
+    }
+}
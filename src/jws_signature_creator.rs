@@ -0,0 +1,373 @@
+use crate::primitives::advanced_commerce::advanced_commerce_in_app_request::AdvancedCommerceInAppRequest;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use crate::clock::{system_clock, Clock};
+use crate::key_ring::KeyRing;
+use crate::signer::{encode_jws, EcdsaSigner, KeyBytes, Signer, SignerError};
+use crate::utils::{validate_bundle_id, validate_issuer_id, validate_key_id};
+use std::sync::Arc;
+use serde_json::{Map, Value};
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum JWSSignatureCreatorError {
+    #[error("InternalSignerError: [{0}]")]
+    InternalSignerError(#[from] SignerError),
+
+    #[error("InternalSerializationError: [{0}]")]
+    InternalSerializationError(String),
+
+    #[error("InvalidConfigurationError: [{0}]")]
+    InvalidConfigurationError(String),
+}
+
+/// The key and identifiers collected by the JWS signature creator builders.
+struct JWSSignatureCreatorConfiguration {
+    key_id: Option<String>,
+    issuer_id: Option<String>,
+    bundle_id: String,
+    private_key: Option<KeyBytes>,
+    signer: Option<Arc<dyn Signer>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl JWSSignatureCreatorConfiguration {
+    fn new(bundle_id: &str) -> Self {
+        JWSSignatureCreatorConfiguration {
+            key_id: None,
+            issuer_id: None,
+            bundle_id: bundle_id.to_string(),
+            private_key: None,
+            signer: None,
+            clock: system_clock(),
+        }
+    }
+
+    fn build(self, audience: &'static str) -> Result<JWSSignatureCreator, JWSSignatureCreatorError> {
+        let missing = |name: &str| JWSSignatureCreatorError::InvalidConfigurationError(format!("Missing {}", name));
+
+        let key_id = self.key_id.ok_or_else(|| missing("key ID"))?;
+        let issuer_id = self.issuer_id.ok_or_else(|| missing("issuer ID"))?;
+        validate_key_id(&key_id).map_err(JWSSignatureCreatorError::InvalidConfigurationError)?;
+        validate_issuer_id(&issuer_id).map_err(JWSSignatureCreatorError::InvalidConfigurationError)?;
+        validate_bundle_id(&self.bundle_id).map_err(JWSSignatureCreatorError::InvalidConfigurationError)?;
+
+        let signer: Arc<dyn Signer> = match (self.signer, self.private_key) {
+            (Some(signer), _) => signer,
+            (None, Some(private_key)) => Arc::new(EcdsaSigner::from_key(private_key.as_slice())?),
+            (None, None) => return Err(missing("private key")),
+        };
+
+        let keys = Arc::new(KeyRing::with_key(&key_id, signer).clock(self.clock.clone()));
+        Ok(JWSSignatureCreator::new(audience, keys, issuer_id, self.bundle_id).clock(self.clock))
+    }
+}
+
+/// Creates JWS signatures with the claims shared by all StoreKit signed requests.
+struct JWSSignatureCreator {
+    audience: &'static str,
+    keys: Arc<KeyRing<dyn Signer>>,
+    issuer_id: String,
+    bundle_id: String,
+    clock: Arc<dyn Clock>,
+}
+
+impl JWSSignatureCreator {
+    fn new(
+        audience: &'static str,
+        keys: Arc<KeyRing<dyn Signer>>,
+        issuer_id: String,
+        bundle_id: String,
+    ) -> Self {
+        JWSSignatureCreator {
+            audience,
+            keys,
+            issuer_id,
+            bundle_id,
+            clock: system_clock(),
+        }
+    }
+
+    fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    fn create_signature(&self, feature_specific_claims: Map<String, Value>) -> Result<String, JWSSignatureCreatorError> {
+        let mut claims = feature_specific_claims;
+        claims.insert("bid".to_string(), Value::from(self.bundle_id.as_str()));
+        claims.insert("iss".to_string(), Value::from(self.issuer_id.as_str()));
+        claims.insert("aud".to_string(), Value::from(self.audience));
+        claims.insert("iat".to_string(), Value::from(self.clock.now().timestamp()));
+        claims.insert("nonce".to_string(), Value::from(uuid::Uuid::new_v4().to_string()));
+
+        let (key_id, signer) = self.keys.active_key()?;
+        Ok(encode_jws(signer.as_ref(), &key_id, &claims)?)
+    }
+}
+
+/// Struct responsible for creating signatures for Advanced Commerce API in-app requests.
+pub struct AdvancedCommerceInAppSignatureCreator {
+    creator: JWSSignatureCreator,
+}
+
+/// Builder for [`AdvancedCommerceInAppSignatureCreator`] that validates its configuration up front.
+pub struct AdvancedCommerceInAppSignatureCreatorBuilder {
+    configuration: JWSSignatureCreatorConfiguration,
+}
+
+impl AdvancedCommerceInAppSignatureCreatorBuilder {
+    /// Sets your private key from App Store Connect, as PEM or DER-encoded PKCS#8 or SEC1.
+    pub fn private_key(mut self, private_key: &[u8]) -> Self {
+        self.configuration.private_key = Some(KeyBytes::from(private_key.to_vec()));
+        self.configuration.signer = None;
+        self
+    }
+
+    /// Sets a signer holding your private key, replacing any private key set before.
+    pub fn signer(mut self, signer: Arc<dyn Signer>) -> Self {
+        self.configuration.signer = Some(signer);
+        self.configuration.private_key = None;
+        self
+    }
+
+    /// Sets your private key ID from App Store Connect.
+    pub fn key_id(mut self, key_id: &str) -> Self {
+        self.configuration.key_id = Some(key_id.to_string());
+        self
+    }
+
+    /// Sets your issuer ID from the Keys page in App Store Connect.
+    pub fn issuer_id(mut self, issuer_id: &str) -> Self {
+        self.configuration.issuer_id = Some(issuer_id.to_string());
+        self
+    }
+
+    /// Sets the clock that the issue time of the signatures is read from.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.configuration.clock = clock;
+        self
+    }
+
+    /// Validates the configuration and creates the `AdvancedCommerceInAppSignatureCreator`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidConfigurationError` if an identifier is missing or malformed or no key was set,
+    /// and `InternalSignerError` if the private key can't be read.
+    pub fn build(self) -> Result<AdvancedCommerceInAppSignatureCreator, JWSSignatureCreatorError> {
+        Ok(AdvancedCommerceInAppSignatureCreator {
+            creator: self.configuration.build("advanced-commerce-api")?,
+        })
+    }
+}
+
+impl AdvancedCommerceInAppSignatureCreator {
+    /// Creates a builder that validates the key, key ID, issuer ID and bundle ID when building.
+    pub fn builder(bundle_id: &str) -> AdvancedCommerceInAppSignatureCreatorBuilder {
+        AdvancedCommerceInAppSignatureCreatorBuilder {
+            configuration: JWSSignatureCreatorConfiguration::new(bundle_id),
+        }
+    }
+
+    /// Creates a new `AdvancedCommerceInAppSignatureCreator` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `signing_key`: Your private key downloaded from App Store Connect, as PEM-encoded PKCS#8 or SEC1.
+    /// * `key_id`: Your private key ID from App Store Connect.
+    /// * `issuer_id`: Your issuer ID from the Keys page in App Store Connect.
+    /// * `bundle_id`: Your app's bundle ID.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `AdvancedCommerceInAppSignatureCreator` instance or an error.
+    pub fn new(
+        signing_key: &str,
+        key_id: String,
+        issuer_id: String,
+        bundle_id: String,
+    ) -> Result<Self, JWSSignatureCreatorError> {
+        let signer = EcdsaSigner::from_key(signing_key.as_bytes())?;
+
+        Ok(Self::with_signer(Arc::new(signer), key_id, issuer_id, bundle_id))
+    }
+
+    /// Creates a new `AdvancedCommerceInAppSignatureCreator` that signs with the given signer,
+    /// such as one backed by a key management service or an HSM.
+    ///
+    /// # Arguments
+    ///
+    /// * `signer`: The signer holding your private key from App Store Connect.
+    /// * `key_id`: Your private key ID from App Store Connect.
+    /// * `issuer_id`: Your issuer ID from the Keys page in App Store Connect.
+    /// * `bundle_id`: Your app's bundle ID.
+    pub fn with_signer(signer: Arc<dyn Signer>, key_id: String, issuer_id: String, bundle_id: String) -> Self {
+        Self::with_key_ring(Arc::new(KeyRing::with_key(&key_id, signer)), issuer_id, bundle_id)
+    }
+
+    /// Creates a new `AdvancedCommerceInAppSignatureCreator` that signs with the active key of the key ring.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys`: The key ring holding your private keys from App Store Connect.
+    /// * `issuer_id`: Your issuer ID from the Keys page in App Store Connect.
+    /// * `bundle_id`: Your app's bundle ID.
+    pub fn with_key_ring(keys: Arc<KeyRing<dyn Signer>>, issuer_id: String, bundle_id: String) -> Self {
+        AdvancedCommerceInAppSignatureCreator {
+            creator: JWSSignatureCreator::new("advanced-commerce-api", keys, issuer_id, bundle_id),
+        }
+    }
+
+    /// Sets the clock that the issue time of the signatures is read from.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.creator = self.creator.clock(clock);
+        self
+    }
+
+    /// Creates a signed Advanced Commerce in-app request.
+    ///
+    /// # Arguments
+    ///
+    /// * `advanced_commerce_in_app_request`: The request to be signed.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the signed JWS, which is passed to StoreKit, or an error.
+    pub fn create_signature<T: AdvancedCommerceInAppRequest>(
+        &self,
+        advanced_commerce_in_app_request: &T,
+    ) -> Result<String, JWSSignatureCreatorError> {
+        let request = serde_json::to_vec(advanced_commerce_in_app_request)
+            .map_err(|e| JWSSignatureCreatorError::InternalSerializationError(e.to_string()))?;
+
+        let mut claims = Map::new();
+        claims.insert("request".to_string(), Value::from(STANDARD.encode(request)));
+
+        self.creator.create_signature(claims)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+    use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct TestInAppRequest {
+        #[serde(rename = "testValue")]
+        test_value: String,
+    }
+
+    impl AdvancedCommerceInAppRequest for TestInAppRequest {}
+
+    #[test]
+    fn test_advanced_commerce_in_app_signature_creator() {
+        let signing_key = include_str!("../assets/testSigningKey.p8");
+        let creator = AdvancedCommerceInAppSignatureCreator::new(
+            signing_key,
+            "keyId".to_string(),
+            "issuerId".to_string(),
+            "bundleId".to_string(),
+        )
+        .unwrap();
+
+        let signature = creator
+            .create_signature(&TestInAppRequest { test_value: "testValue".to_string() })
+            .unwrap();
+
+        let header = jsonwebtoken::decode_header(&signature).unwrap();
+        assert_eq!(Algorithm::ES256, header.alg);
+        assert_eq!(Some("keyId".to_string()), header.kid);
+
+        let der = pem::parse(signing_key).unwrap();
+        let key_pair = EcdsaKeyPair::from_pkcs8(
+            &ECDSA_P256_SHA256_FIXED_SIGNING,
+            der.contents(),
+            &ring::rand::SystemRandom::new(),
+        )
+        .unwrap();
+        let decoding_key = DecodingKey::from_ec_der(key_pair.public_key().as_ref());
+
+        let mut validation = Validation::new(Algorithm::ES256);
+        validation.set_audience(&["advanced-commerce-api"]);
+        validation.set_issuer(&["issuerId"]);
+        validation.set_required_spec_claims(&["aud", "iss"]);
+
+        let claims = jsonwebtoken::decode::<Map<String, Value>>(&signature, &decoding_key, &validation)
+            .unwrap()
+            .claims;
+
+        assert_eq!(Some("bundleId"), claims["bid"].as_str());
+        assert!(claims["iat"].is_i64());
+        assert!(claims["nonce"].as_str().is_some());
+
+        let request = STANDARD.decode(claims["request"].as_str().unwrap()).unwrap();
+        let request: Map<String, Value> = serde_json::from_slice(&request).unwrap();
+        assert_eq!(Some("testValue"), request["testValue"].as_str());
+    }
+
+    #[test]
+    fn test_advanced_commerce_in_app_signature_creator_invalid_key() {
+        let result = AdvancedCommerceInAppSignatureCreator::new(
+            "invalid",
+            "keyId".to_string(),
+            "issuerId".to_string(),
+            "bundleId".to_string(),
+        );
+
+        assert!(matches!(result, Err(JWSSignatureCreatorError::InternalSignerError(SignerError::UnsupportedKeyFormatError(_)))));
+    }
+
+    #[test]
+    fn test_builder_validation() {
+        let signing_key = include_bytes!("../assets/testSigningKey.p8");
+        let builder = || {
+            AdvancedCommerceInAppSignatureCreator::builder("com.example")
+                .private_key(signing_key)
+                .key_id("2X9R4HXF34")
+                .issuer_id("57246542-96fe-1a63-e053-0824d011072a")
+        };
+
+        let creator = builder().build().unwrap();
+        assert!(creator.create_signature(&TestInAppRequest { test_value: "testValue".to_string() }).is_ok());
+
+        let cases = [
+            builder().key_id("keyId").build(),
+            builder().issuer_id("issuerId").build(),
+            AdvancedCommerceInAppSignatureCreator::builder("com..example")
+                .private_key(signing_key)
+                .key_id("2X9R4HXF34")
+                .issuer_id("57246542-96fe-1a63-e053-0824d011072a")
+                .build(),
+            AdvancedCommerceInAppSignatureCreator::builder("com.example").private_key(signing_key).build(),
+        ];
+        for result in cases {
+            assert!(matches!(result, Err(JWSSignatureCreatorError::InvalidConfigurationError(_))));
+        }
+
+        let result = builder().private_key(b"invalid").build();
+        assert!(matches!(result, Err(JWSSignatureCreatorError::InternalSignerError(_))));
+    }
+
+    #[test]
+    fn test_issued_at_reads_the_clock() {
+        use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+        use crate::clock::FrozenClock;
+
+        let creator = AdvancedCommerceInAppSignatureCreator::builder("com.example")
+            .private_key(include_bytes!("../assets/testSigningKey.p8"))
+            .key_id("2X9R4HXF34")
+            .issuer_id("57246542-96fe-1a63-e053-0824d011072a")
+            .clock(Arc::new(FrozenClock::new(chrono::DateTime::from_timestamp(1698148800, 0).unwrap())))
+            .build()
+            .unwrap();
+
+        let signature = creator.create_signature(&TestInAppRequest { test_value: "testValue".to_string() }).unwrap();
+        let payload = URL_SAFE_NO_PAD.decode(signature.split('.').nth(1).unwrap()).unwrap();
+        let claims: Map<String, Value> = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(Some(1698148800), claims["iat"].as_i64());
+    }
+}
@@ -0,0 +1,279 @@
+use crate::clock::{duration_to_chrono, system_clock, Clock};
+use crate::key_ring::KeyRing;
+use crate::signer::{encode_jws_async, AsyncSigner, SignerError};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The audience of tokens for the App Store Server API and the App Store Connect API.
+pub const APP_STORE_CONNECT_AUDIENCE: &str = "appstoreconnect-v1";
+
+#[derive(Serialize)]
+struct Claims<'a> {
+    iss: &'a str,
+    iat: i64,
+    exp: i64,
+    aud: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bid: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scope: Option<&'a [String]>,
+}
+
+struct CachedToken {
+    token: String,
+    expires_at: DateTime<Utc>,
+}
+
+/// Mints the ES256 JSON Web Tokens that authorize requests to Apple's APIs.
+///
+/// Tokens are signed with the active key of a [`KeyRing`] and carry the issuer ID of the key's team.
+/// The App Store Server API requires the bundle ID of the app in each token; the App Store Connect API
+/// accepts tokens without one, optionally limited to a `scope` of requests.
+///
+/// [`token`](Self::token) reuses a token until it is about to expire. With the `token-refresh`
+/// feature, [`spawn_refresh`](Self::spawn_refresh) replaces it in the background beforehand, so that
+/// requests never wait for a signature.
+///
+/// [Generating JSON Web Tokens for API requests](https://developer.apple.com/documentation/appstoreserverapi/generating_json_web_tokens_for_api_requests)
+pub struct AppStoreJwtIssuer {
+    keys: Arc<KeyRing>,
+    issuer_id: String,
+    bundle_id: Option<String>,
+    audience: String,
+    scope: Option<Vec<String>>,
+    ttl: Duration,
+    refresh_margin: Duration,
+    clock: Arc<dyn Clock>,
+    cache: Mutex<Option<CachedToken>>,
+}
+
+impl AppStoreJwtIssuer {
+    /// The lifetime of the tokens unless set with [`ttl`](Self::ttl).
+    pub const DEFAULT_TTL: Duration = Duration::from_secs(5 * 60);
+
+    /// How long before its expiry a cached token is replaced, unless set with [`refresh_margin`](Self::refresh_margin).
+    pub const DEFAULT_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+    /// Creates an issuer that signs with the active key of `keys`, for the App Store Connect audience.
+    pub fn new(keys: Arc<KeyRing>, issuer_id: &str) -> Self {
+        AppStoreJwtIssuer {
+            keys,
+            issuer_id: issuer_id.to_string(),
+            bundle_id: None,
+            audience: APP_STORE_CONNECT_AUDIENCE.to_string(),
+            scope: None,
+            ttl: Self::DEFAULT_TTL,
+            refresh_margin: Self::DEFAULT_REFRESH_MARGIN,
+            clock: system_clock(),
+            cache: Mutex::new(None),
+        }
+    }
+
+    /// Creates an issuer that signs with a single key.
+    pub fn with_signer(signer: Arc<dyn AsyncSigner>, key_id: &str, issuer_id: &str) -> Self {
+        Self::new(Arc::new(KeyRing::with_key(key_id, signer)), issuer_id)
+    }
+
+    /// Adds the `bid` claim, which the App Store Server API requires.
+    pub fn bundle_id(mut self, bundle_id: &str) -> Self {
+        self.bundle_id = Some(bundle_id.to_string());
+        self
+    }
+
+    /// Replaces the `aud` claim.
+    pub fn audience(mut self, audience: &str) -> Self {
+        self.audience = audience.to_string();
+        self
+    }
+
+    /// Adds the `scope` claim, limiting the tokens to the listed requests, such as `GET /v1/apps`.
+    pub fn scope(mut self, scope: Vec<String>) -> Self {
+        self.scope = Some(scope);
+        self
+    }
+
+    /// Sets how long the tokens are valid. Apple rejects tokens that are valid for more than 60 minutes,
+    /// or 20 minutes for most App Store Connect API requests.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets how long before its expiry a cached token is replaced.
+    pub fn refresh_margin(mut self, refresh_margin: Duration) -> Self {
+        self.refresh_margin = refresh_margin;
+        self
+    }
+
+    /// Sets the clock that the issue time, the expiry of the cached token and the active key are read from.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Returns the cached token, or issues and caches a new one if the cached token is within the
+    /// refresh margin of its expiry.
+    pub async fn token(&self) -> Result<String, SignerError> {
+        if let Some(cached) = self.cache.lock().unwrap().as_ref() {
+            if self.clock.now() + duration_to_chrono(self.refresh_margin) < cached.expires_at {
+                return Ok(cached.token.clone());
+            }
+        }
+        self.refresh().await
+    }
+
+    /// Issues a new token and caches it for [`token`](Self::token).
+    pub async fn refresh(&self) -> Result<String, SignerError> {
+        let issued_at = self.clock.now();
+        let token = self.issue_at(issued_at).await?;
+        let expires_at = issued_at + duration_to_chrono(self.ttl);
+        *self.cache.lock().unwrap() = Some(CachedToken { token: token.clone(), expires_at });
+        Ok(token)
+    }
+
+    /// Spawns a task on the current tokio runtime that refreshes the cached token a refresh margin
+    /// before it expires, retrying every second when signing fails. The task stops once the issuer is dropped.
+    #[cfg(feature = "token-refresh")]
+    pub fn spawn_refresh(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let issuer = Arc::downgrade(self);
+        tokio::spawn(async move {
+            loop {
+                let Some(strong) = issuer.upgrade() else { break };
+                let wait = match strong.refresh().await {
+                    Ok(_) => strong.ttl.saturating_sub(strong.refresh_margin).max(Duration::from_secs(1)),
+                    Err(_) => Duration::from_secs(1),
+                };
+                drop(strong);
+                tokio::time::sleep(wait).await;
+            }
+        })
+    }
+
+    /// Creates a token that is valid from now until the end of its lifetime.
+    pub async fn issue(&self) -> Result<String, SignerError> {
+        self.issue_at(self.clock.now()).await
+    }
+
+    async fn issue_at(&self, now: DateTime<Utc>) -> Result<String, SignerError> {
+        let issued_at = now.timestamp();
+        let claims = Claims {
+            iss: &self.issuer_id,
+            iat: issued_at,
+            exp: issued_at + self.ttl.as_secs() as i64,
+            aud: &self.audience,
+            bid: self.bundle_id.as_deref(),
+            scope: self.scope.as_deref(),
+        };
+
+        let (key_id, signer) = self.keys.active_key_at(now)?;
+        encode_jws_async(signer.as_ref(), &key_id, &claims).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer::{EcdsaSigner, SignFuture, Signer};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+    use serde_json::{Map, Value};
+
+    #[tokio::test]
+    async fn test_issue() {
+        let signer = EcdsaSigner::from_pem(include_bytes!("../assets/testSigningKey.p8")).unwrap();
+        let decoding_key = DecodingKey::from_ec_der(&signer.public_key().unwrap());
+        let issuer = AppStoreJwtIssuer::with_signer(Arc::new(signer), "keyId", "issuerId")
+            .scope(vec!["GET /v1/apps".to_string()])
+            .ttl(Duration::from_secs(60));
+
+        let token = issuer.issue().await.unwrap();
+        assert_eq!(Some("keyId".to_string()), jsonwebtoken::decode_header(&token).unwrap().kid);
+
+        let mut validation = Validation::new(Algorithm::ES256);
+        validation.set_audience(&[APP_STORE_CONNECT_AUDIENCE]);
+        let claims = jsonwebtoken::decode::<Map<String, Value>>(&token, &decoding_key, &validation).unwrap().claims;
+        assert_eq!("issuerId", claims["iss"]);
+        assert_eq!(60, claims["exp"].as_i64().unwrap() - claims["iat"].as_i64().unwrap());
+        assert_eq!(serde_json::json!(["GET /v1/apps"]), claims["scope"]);
+        assert!(!claims.contains_key("bid"));
+    }
+
+    struct CountingSigner(EcdsaSigner, Arc<AtomicUsize>);
+
+    impl AsyncSigner for CountingSigner {
+        fn sign<'a>(&'a self, message: &'a [u8]) -> SignFuture<'a> {
+            self.1.fetch_add(1, Ordering::SeqCst);
+            AsyncSigner::sign(&self.0, message)
+        }
+    }
+
+    fn counting_issuer() -> (AppStoreJwtIssuer, Arc<AtomicUsize>) {
+        let signer = EcdsaSigner::from_pem(include_bytes!("../assets/testSigningKey.p8")).unwrap();
+        let signatures = Arc::new(AtomicUsize::new(0));
+        let signer = CountingSigner(signer, signatures.clone());
+        (AppStoreJwtIssuer::with_signer(Arc::new(signer), "keyId", "issuerId"), signatures)
+    }
+
+    #[tokio::test]
+    async fn test_token_cache() {
+        let (issuer, signatures) = counting_issuer();
+
+        let token = issuer.token().await.unwrap();
+        assert_eq!(token, issuer.token().await.unwrap());
+        assert_eq!(1, signatures.load(Ordering::SeqCst));
+
+        issuer.refresh().await.unwrap();
+        issuer.token().await.unwrap();
+        assert_eq!(2, signatures.load(Ordering::SeqCst));
+
+        let issuer = issuer.refresh_margin(AppStoreJwtIssuer::DEFAULT_TTL);
+        issuer.token().await.unwrap();
+        issuer.token().await.unwrap();
+        assert_eq!(4, signatures.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_frozen_clock() {
+        use crate::clock::FrozenClock;
+
+        let (issuer, signatures) = counting_issuer();
+        let clock = Arc::new(FrozenClock::new(DateTime::from_timestamp(1698148800, 0).unwrap()));
+        let issuer = issuer.clock(clock.clone());
+
+        let token = issuer.token().await.unwrap();
+        let claims: Map<String, Value> = serde_json::from_slice(
+            &base64::Engine::decode(&base64::engine::general_purpose::URL_SAFE_NO_PAD, token.split('.').nth(1).unwrap()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(1698148800, claims["iat"]);
+        assert_eq!(1698148800 + 300, claims["exp"]);
+
+        clock.advance(Duration::from_secs(239));
+        assert_eq!(token, issuer.token().await.unwrap());
+        clock.advance(Duration::from_secs(1));
+        assert_ne!(token, issuer.token().await.unwrap());
+        assert_eq!(2, signatures.load(Ordering::SeqCst));
+    }
+
+    #[cfg(feature = "token-refresh")]
+    #[tokio::test(start_paused = true)]
+    async fn test_spawn_refresh() {
+        let (issuer, signatures) = counting_issuer();
+        let issuer = Arc::new(issuer.ttl(Duration::from_secs(60)).refresh_margin(Duration::from_secs(50)));
+
+        let task = issuer.spawn_refresh();
+        tokio::time::sleep(Duration::from_millis(1)).await;
+        issuer.token().await.unwrap();
+        assert_eq!(1, signatures.load(Ordering::SeqCst));
+
+        tokio::time::sleep(Duration::from_secs(11)).await;
+        issuer.token().await.unwrap();
+        assert_eq!(2, signatures.load(Ordering::SeqCst));
+
+        drop(issuer);
+        tokio::time::sleep(Duration::from_secs(11)).await;
+        assert!(task.is_finished());
+    }
+}
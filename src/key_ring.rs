@@ -0,0 +1,235 @@
+use crate::clock::{system_clock, Clock};
+use crate::signer::{AsyncSigner, Signer, SignerError};
+use chrono::{DateTime, Utc};
+use std::sync::{Arc, RwLock};
+
+struct KeyEntry<S: ?Sized> {
+    key_id: String,
+    signer: Arc<S>,
+    active_from: Option<DateTime<Utc>>,
+    retired_at: Option<DateTime<Utc>>,
+}
+
+impl<S: ?Sized> KeyEntry<S> {
+    fn is_usable(&self, now: DateTime<Utc>) -> bool {
+        self.retired_at.is_none_or(|retired_at| now < retired_at)
+    }
+
+    fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.is_usable(now) && self.active_from.is_none_or(|active_from| active_from <= now)
+    }
+}
+
+/// A set of signing keys identified by their App Store Connect key ID.
+///
+/// New tokens and signatures are created with the active key: the usable key whose
+/// activation time was reached most recently, or the last one added when several keys are
+/// active from the start. During a key rotation, register the new key with a future activation
+/// time and retire the old key once the new one has taken over, without recreating the clients
+/// that share the key ring.
+///
+/// The type parameter selects between [`AsyncSigner`] keys, used by the API client, and
+/// [`Signer`] keys, used by the signature creators.
+pub struct KeyRing<S: ?Sized = dyn AsyncSigner> {
+    keys: RwLock<Vec<KeyEntry<S>>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl<S: ?Sized> Default for KeyRing<S> {
+    fn default() -> Self {
+        KeyRing { keys: RwLock::new(Vec::new()), clock: system_clock() }
+    }
+}
+
+impl<S: ?Sized> KeyRing<S> {
+    /// Creates an empty key ring.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a key ring holding a single key.
+    pub fn with_key(key_id: &str, signer: Arc<S>) -> Self {
+        let key_ring = Self::new();
+        key_ring.add_key(key_id, signer);
+        key_ring
+    }
+
+    /// Sets the clock that decides which keys are active and which have been retired.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Adds a key that is active immediately, replacing any key with the same ID.
+    pub fn add_key(&self, key_id: &str, signer: Arc<S>) {
+        self.insert(key_id, signer, None);
+    }
+
+    /// Adds a key that becomes active at `active_from`, replacing any key with the same ID.
+    ///
+    /// Until then, the key can only be selected explicitly by its key ID.
+    pub fn add_key_active_from(&self, key_id: &str, signer: Arc<S>, active_from: DateTime<Utc>) {
+        self.insert(key_id, signer, Some(active_from));
+    }
+
+    /// Stops using a key after `retired_at`, leaving it usable for the remaining grace period.
+    ///
+    /// Returns `false` if the key ring doesn't contain a key with this ID.
+    pub fn retire_key(&self, key_id: &str, retired_at: DateTime<Utc>) -> bool {
+        let mut keys = self.keys.write().unwrap();
+        match keys.iter_mut().find(|entry| entry.key_id == key_id) {
+            Some(entry) => {
+                entry.retired_at = Some(retired_at);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes a key immediately.
+    ///
+    /// Returns `false` if the key ring doesn't contain a key with this ID.
+    pub fn remove_key(&self, key_id: &str) -> bool {
+        let mut keys = self.keys.write().unwrap();
+        let count = keys.len();
+        keys.retain(|entry| entry.key_id != key_id);
+        keys.len() != count
+    }
+
+    /// The IDs of all keys that haven't been retired.
+    pub fn key_ids(&self) -> Vec<String> {
+        let now = self.clock.now();
+        self.keys
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|entry| entry.is_usable(now))
+            .map(|entry| entry.key_id.clone())
+            .collect()
+    }
+
+    /// The ID and signer of the key currently used for new signatures.
+    pub fn active_key(&self) -> Result<(String, Arc<S>), SignerError> {
+        self.active_key_at(self.clock.now())
+    }
+
+    /// The ID of the key currently used for new signatures.
+    pub fn active_key_id(&self) -> Result<String, SignerError> {
+        Ok(self.active_key()?.0)
+    }
+
+    /// The signer for a key that hasn't been retired.
+    pub fn key(&self, key_id: &str) -> Result<Arc<S>, SignerError> {
+        let now = self.clock.now();
+        self.keys
+            .read()
+            .unwrap()
+            .iter()
+            .find(|entry| entry.key_id == key_id && entry.is_usable(now))
+            .map(|entry| entry.signer.clone())
+            .ok_or_else(|| SignerError::UnknownKeyError(key_id.to_string()))
+    }
+
+    /// The ID and signer of the key used for new signatures at `now`.
+    pub fn active_key_at(&self, now: DateTime<Utc>) -> Result<(String, Arc<S>), SignerError> {
+        let keys = self.keys.read().unwrap();
+        let mut active: Option<&KeyEntry<S>> = None;
+
+        for entry in keys.iter().filter(|entry| entry.is_active(now)) {
+            if active.is_none_or(|active| active.active_from <= entry.active_from) {
+                active = Some(entry);
+            }
+        }
+
+        active
+            .map(|entry| (entry.key_id.clone(), entry.signer.clone()))
+            .ok_or_else(|| SignerError::UnknownKeyError("No active signing key".to_string()))
+    }
+
+    fn insert(&self, key_id: &str, signer: Arc<S>, active_from: Option<DateTime<Utc>>) {
+        let mut keys = self.keys.write().unwrap();
+        keys.retain(|entry| entry.key_id != key_id);
+        keys.push(KeyEntry { key_id: key_id.to_string(), signer, active_from, retired_at: None });
+    }
+}
+
+impl KeyRing<dyn Signer> {
+    /// Adds a private key that is active immediately, in any format accepted by [`EcdsaSigner::from_key`](crate::signer::EcdsaSigner::from_key).
+    pub fn add_private_key(&self, key_id: &str, private_key: &[u8]) -> Result<(), SignerError> {
+        let signer = crate::signer::EcdsaSigner::from_key(private_key)?;
+        self.add_key(key_id, Arc::new(signer));
+        Ok(())
+    }
+}
+
+impl KeyRing<dyn AsyncSigner> {
+    /// Adds a private key that is active immediately, in any format accepted by [`EcdsaSigner::from_key`](crate::signer::EcdsaSigner::from_key).
+    pub fn add_private_key(&self, key_id: &str, private_key: &[u8]) -> Result<(), SignerError> {
+        let signer = crate::signer::EcdsaSigner::from_key(private_key)?;
+        self.add_key(key_id, Arc::new(signer));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FrozenClock;
+    use chrono::Duration;
+
+    fn signer() -> Arc<dyn Signer> {
+        Arc::new(crate::signer::EcdsaSigner::from_pem(include_bytes!("../assets/testSigningKey.p8")).unwrap())
+    }
+
+    #[test]
+    fn test_active_key() {
+        let key_ring: KeyRing<dyn Signer> = KeyRing::new();
+        assert!(matches!(key_ring.active_key(), Err(SignerError::UnknownKeyError(_))));
+
+        key_ring.add_key("old", signer());
+        key_ring.add_key("new", signer());
+        assert_eq!("new", key_ring.active_key_id().unwrap());
+
+        assert!(key_ring.remove_key("new"));
+        assert!(!key_ring.remove_key("new"));
+        assert_eq!("old", key_ring.active_key_id().unwrap());
+    }
+
+    #[test]
+    fn test_rotation_with_grace_period() {
+        let now = DateTime::from_timestamp(1698148800, 0).unwrap();
+        let key_ring: KeyRing<dyn Signer> = KeyRing::with_key("old", signer()).clock(Arc::new(FrozenClock::new(now)));
+        key_ring.add_key_active_from("new", signer(), now + Duration::hours(1));
+
+        assert_eq!("old", key_ring.active_key_at(now).unwrap().0);
+        assert_eq!("new", key_ring.active_key_at(now + Duration::hours(2)).unwrap().0);
+        assert!(key_ring.key("new").is_ok());
+
+        assert!(key_ring.retire_key("old", now + Duration::hours(3)));
+        assert!(key_ring.key("old").is_ok());
+        assert_eq!("new", key_ring.active_key_at(now + Duration::hours(4)).unwrap().0);
+
+        assert!(key_ring.retire_key("old", now - Duration::seconds(1)));
+        assert!(matches!(key_ring.key("old"), Err(SignerError::UnknownKeyError(_))));
+        assert_eq!(vec!["new".to_string()], key_ring.key_ids());
+        assert!(!key_ring.retire_key("missing", now));
+    }
+
+    #[test]
+    fn test_key_ring_reads_the_clock() {
+        let now = DateTime::from_timestamp(1698148800, 0).unwrap();
+        let clock = Arc::new(FrozenClock::new(now));
+        let key_ring: KeyRing<dyn Signer> = KeyRing::with_key("old", signer()).clock(clock.clone());
+        key_ring.add_key_active_from("new", signer(), now + Duration::hours(1));
+        assert!(key_ring.retire_key("old", now + Duration::hours(2)));
+        assert_eq!("old", key_ring.active_key_id().unwrap());
+
+        clock.advance(std::time::Duration::from_secs(3600));
+        assert_eq!("new", key_ring.active_key_id().unwrap());
+        assert!(key_ring.key("old").is_ok());
+
+        clock.advance(std::time::Duration::from_secs(3600));
+        assert!(matches!(key_ring.key("old"), Err(SignerError::UnknownKeyError(_))));
+        assert_eq!(vec!["new".to_string()], key_ring.key_ids());
+    }
+}
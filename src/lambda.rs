@@ -0,0 +1,158 @@
+use crate::notification_router::{NotificationRouter, NotificationRouterError};
+use crate::primitives::decoded_notification::DecodedNotification;
+use crate::signed_data_verifier::{SignedDataVerifier, SignedDataVerifierError};
+use crate::webhook::{parse_signed_payload_with_limit, WebhookBodyError, DEFAULT_MAX_BODY_SIZE};
+use lambda_http::http::StatusCode;
+use lambda_http::{Body, Request, Response};
+use std::fmt::Display;
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum LambdaNotificationError {
+    #[error("WebhookBodyError: [{0}]")]
+    WebhookBodyError(#[from] WebhookBodyError),
+
+    #[error("VerificationError: [{0}]")]
+    VerificationError(#[from] SignedDataVerifierError),
+}
+
+impl LambdaNotificationError {
+    /// The status the request is answered with.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            LambdaNotificationError::WebhookBodyError(WebhookBodyError::BodyTooLargeError { .. }) => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            LambdaNotificationError::WebhookBodyError(_) => StatusCode::BAD_REQUEST,
+            LambdaNotificationError::VerificationError(_) => StatusCode::FORBIDDEN,
+        }
+    }
+
+    /// The response the request is answered with.
+    pub fn to_response(&self) -> Response<Body> {
+        response(self.status_code(), self.to_string())
+    }
+}
+
+/// Verifies and decodes the App Store Server Notification in the body of a Lambda HTTP event,
+/// limiting bodies to [`DEFAULT_MAX_BODY_SIZE`](crate::webhook::DEFAULT_MAX_BODY_SIZE).
+///
+/// See [`verified_notification_with_limit`].
+pub fn verified_notification(
+    verifier: &SignedDataVerifier,
+    request: &Request,
+) -> Result<DecodedNotification, LambdaNotificationError> {
+    verified_notification_with_limit(verifier, request, DEFAULT_MAX_BODY_SIZE)
+}
+
+/// Verifies and decodes the App Store Server Notification in the body of a Lambda HTTP event.
+///
+/// Reads the `signedPayload` from the event body, which the runtime has already decoded from Base64
+/// if needed, and verifies it along with its signed transaction and renewal info. Answer errors with
+/// [`LambdaNotificationError::to_response`].
+pub fn verified_notification_with_limit(
+    verifier: &SignedDataVerifier,
+    request: &Request,
+    max_body_size: usize,
+) -> Result<DecodedNotification, LambdaNotificationError> {
+    let signed_payload = parse_signed_payload_with_limit(request.body(), max_body_size)?;
+    Ok(verifier.verify_and_decode_notification_full(&signed_payload)?)
+}
+
+/// Dispatches the notification in the body of a Lambda HTTP event through `router`, limiting bodies
+/// to [`DEFAULT_MAX_BODY_SIZE`](crate::webhook::DEFAULT_MAX_BODY_SIZE).
+///
+/// See [`dispatch_notification_with_limit`].
+pub async fn dispatch_notification<E: Display>(router: &NotificationRouter<E>, request: &Request) -> Response<Body> {
+    dispatch_notification_with_limit(router, request, DEFAULT_MAX_BODY_SIZE).await
+}
+
+/// Dispatches the notification in the body of a Lambda HTTP event through `router`.
+///
+/// Responds with `200 OK` once a handler succeeded or when no handler is registered for the notification,
+/// `400 Bad Request` or `403 Forbidden` for malformed or unverifiable payloads, `413 Payload Too Large` for
+/// bodies larger than `max_body_size` bytes, and `500 Internal Server Error` when the handler or the
+/// router's deduper fails, so that the App Store retries the notification.
+///
+/// ```ignore
+/// lambda_http::run(service_fn(|request: Request| async {
+///     Ok::<_, Error>(dispatch_notification(&router, &request).await)
+/// }))
+/// .await
+/// ```
+pub async fn dispatch_notification_with_limit<E: Display>(
+    router: &NotificationRouter<E>,
+    request: &Request,
+    max_body_size: usize,
+) -> Response<Body> {
+    let signed_payload = match parse_signed_payload_with_limit(request.body(), max_body_size) {
+        Ok(signed_payload) => signed_payload,
+        Err(error) => return LambdaNotificationError::from(error).to_response(),
+    };
+
+    match router.dispatch(&signed_payload).await {
+        Ok(_) => response(StatusCode::OK, String::new()),
+        Err(NotificationRouterError::VerificationError(error)) => {
+            LambdaNotificationError::VerificationError(error).to_response()
+        }
+        Err(NotificationRouterError::HandlerError(error)) => {
+            response(StatusCode::INTERNAL_SERVER_ERROR, error.to_string())
+        }
+        Err(NotificationRouterError::DeduplicationError(error)) => response(StatusCode::INTERNAL_SERVER_ERROR, error),
+    }
+}
+
+fn response(status: StatusCode, body: String) -> Response<Body> {
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = status;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::notification_type_v2::NotificationTypeV2;
+    use crate::test_utils::{local_testing_verifier, signed_notification_fixture};
+    use serde_json::json;
+
+    fn request(body: impl Into<Body>) -> Request {
+        Request::new(body.into())
+    }
+
+    fn notification_request(notification_type: NotificationTypeV2) -> Request {
+        let signed_payload = signed_notification_fixture(&notification_type, None);
+        request(json!({ "signedPayload": signed_payload }).to_string())
+    }
+
+    #[test]
+    fn test_verified_notification() {
+        let verifier = local_testing_verifier();
+
+        let notification = verified_notification(&verifier, &notification_request(NotificationTypeV2::Test)).unwrap();
+        assert_eq!(&NotificationTypeV2::Test, notification.notification_type());
+
+        let error = verified_notification(&verifier, &request(json!({ "signedPayload": "invalid" }).to_string())).unwrap_err();
+        assert_eq!(StatusCode::FORBIDDEN, error.status_code());
+
+        let error = verified_notification(&verifier, &request("{")).unwrap_err();
+        assert_eq!(StatusCode::BAD_REQUEST, error.status_code());
+
+        let error = verified_notification_with_limit(&verifier, &notification_request(NotificationTypeV2::Test), 8).unwrap_err();
+        assert_eq!(StatusCode::PAYLOAD_TOO_LARGE, error.to_response().status());
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_notification() {
+        let router = NotificationRouter::new(local_testing_verifier())
+            .on(NotificationTypeV2::Refund, |_| async { Err("refund failed".to_string()) });
+
+        assert_eq!(StatusCode::OK, dispatch_notification(&router, &notification_request(NotificationTypeV2::Test)).await.status());
+
+        let response = dispatch_notification(&router, &notification_request(NotificationTypeV2::Refund)).await;
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+        assert_eq!(b"refund failed", response.body().as_ref());
+
+        let response = dispatch_notification(&router, &request(vec![b' '; DEFAULT_MAX_BODY_SIZE + 1])).await;
+        assert_eq!(StatusCode::PAYLOAD_TOO_LARGE, response.status());
+    }
+}
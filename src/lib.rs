@@ -1,7 +1,20 @@
 pub mod chain_verifier;
+pub mod clock;
+pub mod error;
+pub mod jws_signature_creator;
+pub mod jwt_issuer;
+pub mod key_ring;
+pub mod notification_deduper;
+pub mod notification_v1;
+pub mod notification_processor;
+pub mod notification_reorder;
+pub mod notification_router;
 pub mod primitives;
 pub mod promotional_offer_signature_creator;
+pub mod signer;
 pub mod signed_data_verifier;
+pub mod subscription_group;
+pub mod webhook;
 mod utils;
 
 #[cfg(feature = "receipt-utility")]
@@ -10,4 +23,29 @@ pub mod receipt_utility;
 #[cfg(feature = "api-client")]
 pub mod api_client;
 
+#[cfg(feature = "api-client")]
+pub mod endpoint_error;
+
+#[cfg(feature = "api-client")]
+pub mod notification_backfill;
+
+#[cfg(feature = "actix")]
+pub mod actix;
+
+#[cfg(feature = "tower")]
+pub mod tower;
+
+#[cfg(feature = "warp")]
+pub mod warp;
+
+#[cfg(feature = "lambda")]
+pub mod lambda;
+
+#[cfg(feature = "rocket")]
+pub mod rocket;
+
+#[cfg(all(feature = "api-client", any(test, feature = "test-utils")))]
+pub mod simulator;
 
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
@@ -0,0 +1,162 @@
+use crate::api_client::{APIException, AppStoreServerAPIClient};
+use crate::notification_router::{DispatchOutcome, NotificationRouter, NotificationRouterError};
+use crate::primitives::notification_history_request::NotificationHistoryRequest;
+use crate::primitives::timestamp::Timestamp;
+use crate::primitives::transaction_id::TransactionId;
+
+/// A notification from the history that couldn't be verified or whose handler failed.
+#[derive(Debug, PartialEq)]
+pub struct BackfillFailure<E> {
+    /// The signed payload of the notification, which can be dispatched again later.
+    pub signed_payload: String,
+    pub error: NotificationRouterError<E>,
+}
+
+/// The outcome of replaying the notification history.
+#[derive(Debug, PartialEq)]
+pub struct BackfillReport<E> {
+    pub handled: usize,
+    pub unhandled: usize,
+    pub duplicates: usize,
+    pub failures: Vec<BackfillFailure<E>>,
+}
+
+impl<E> Default for BackfillReport<E> {
+    fn default() -> Self {
+        BackfillReport { handled: 0, unhandled: 0, duplicates: 0, failures: Vec::new() }
+    }
+}
+
+/// Re-fetches notifications with the Get Notification History endpoint and dispatches them again
+/// through a [`NotificationRouter`], to recover from periods when the server couldn't receive them.
+///
+/// Register a [`NotificationDeduper`](crate::notification_deduper::NotificationDeduper) on the router
+/// so that notifications that were already received aren't processed twice.
+///
+/// [Get Notification History](https://developer.apple.com/documentation/appstoreserverapi/get_notification_history)
+pub struct NotificationBackfill<'a, E> {
+    client: &'a AppStoreServerAPIClient,
+    router: &'a NotificationRouter<E>,
+}
+
+impl<'a, E> NotificationBackfill<'a, E> {
+    pub fn new(client: &'a AppStoreServerAPIClient, router: &'a NotificationRouter<E>) -> Self {
+        NotificationBackfill { client, router }
+    }
+
+    /// Replays the notifications sent between `start_date` and `end_date`, optionally only those for a transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_date` - The start of the window; the App Store keeps the notification history for 180 days.
+    /// * `end_date` - The end of the window.
+    /// * `transaction_id` - The identifier of a transaction whose notifications to replay.
+    pub async fn replay(
+        &self,
+        start_date: Timestamp,
+        end_date: Timestamp,
+        transaction_id: Option<&TransactionId>,
+    ) -> Result<BackfillReport<E>, APIException> {
+        let request = NotificationHistoryRequest {
+            start_date: Some(start_date),
+            end_date: Some(end_date),
+            notification_type: None,
+            notification_subtype: None,
+            transaction_id: transaction_id.cloned(),
+            only_failures: None,
+        };
+        self.replay_request(&request).await
+    }
+
+    /// Replays every page of the notification history matching `request`.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(BackfillReport)` counting the dispatch outcomes, with the notifications that failed to dispatch.
+    /// - `Err(APIException)` if a page of the history couldn't be fetched.
+    pub async fn replay_request(&self, request: &NotificationHistoryRequest) -> Result<BackfillReport<E>, APIException> {
+        let mut report = BackfillReport::default();
+        let mut pagination_token = String::new();
+
+        loop {
+            let response = self.client.get_notification_history(&pagination_token, request).await?;
+
+            for item in response.notification_history.into_iter().flatten() {
+                let Some(signed_payload) = item.signed_payload else {
+                    continue;
+                };
+
+                match self.router.dispatch(&signed_payload).await {
+                    Ok(DispatchOutcome::Handled) => report.handled += 1,
+                    Ok(DispatchOutcome::Unhandled) => report.unhandled += 1,
+                    Ok(DispatchOutcome::Duplicate) => report.duplicates += 1,
+                    Err(error) => report.failures.push(BackfillFailure { signed_payload, error }),
+                }
+            }
+
+            match response.pagination_token {
+                Some(token) if response.has_more == Some(true) => pagination_token = token,
+                _ => return Ok(report),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notification_deduper::InMemoryNotificationDeduper;
+    use crate::primitives::environment::Environment;
+    use crate::primitives::notification_type_v2::NotificationTypeV2;
+    use crate::primitives::timestamp::{self, Duration};
+    use crate::test_utils::{local_testing_verifier, signed_notification_fixture};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_replay() {
+        let did_renew = signed_notification_fixture(&NotificationTypeV2::DidRenew, None);
+        let refund = signed_notification_fixture(&NotificationTypeV2::Refund, None);
+        let test = signed_notification_fixture(&NotificationTypeV2::Test, None);
+
+        let request_override = move |req: &reqwest::Request, _body: Option<&[u8]>| {
+            let body = if req.url().query().is_none() {
+                json!({
+                    "paginationToken": "page-2",
+                    "hasMore": true,
+                    "notificationHistory": [{ "signedPayload": did_renew }, { "signedPayload": "invalid" }]
+                })
+            } else {
+                assert_eq!(Some("paginationToken=page-2"), req.url().query());
+                json!({
+                    "hasMore": false,
+                    "notificationHistory": [{ "signedPayload": did_renew }, { "signedPayload": refund }, { "signedPayload": test }]
+                })
+            };
+
+            http::response::Builder::new()
+                .header("Content-Type", "application/json")
+                .status(http::StatusCode::OK)
+                .body(body.to_string().into_bytes())
+                .unwrap()
+        };
+        let key = std::fs::read("assets/testSigningKey.p8").expect("Failed to read file");
+        let client = AppStoreServerAPIClient::new(key, "keyId", "issuerId", "com.example", Environment::LocalTesting, Box::new(request_override));
+
+        let router = NotificationRouter::new(local_testing_verifier())
+            .deduper(Arc::new(InMemoryNotificationDeduper::default()))
+            .on(NotificationTypeV2::DidRenew, |_| async { Ok::<(), String>(()) })
+            .on(NotificationTypeV2::Refund, |_| async { Ok(()) });
+
+        let report = NotificationBackfill::new(&client, &router)
+            .replay(timestamp::now() - Duration::days(1), timestamp::now(), None)
+            .await
+            .unwrap();
+
+        assert_eq!(2, report.handled);
+        assert_eq!(1, report.unhandled);
+        assert_eq!(1, report.duplicates);
+        assert_eq!(1, report.failures.len());
+        assert_eq!("invalid", report.failures[0].signed_payload);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::clock::{duration_to_chrono, system_clock, Clock};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The future returned by [`NotificationDeduper`] methods.
+pub type DeduperFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, String>> + Send + 'a>>;
+
+/// A store of the notifications that were already processed, keyed by `notificationUUID`.
+///
+/// The App Store retries a notification until the server responds with a success status, so the
+/// same notification may arrive several times. Implement this trait over a shared database or cache
+/// to deduplicate across server instances.
+///
+/// [notificationUUID](https://developer.apple.com/documentation/appstoreservernotifications/notificationuuid)
+pub trait NotificationDeduper: Send + Sync {
+    /// Records the notification as being processed.
+    ///
+    /// Returns `false` if the notification was already claimed, in which case it must not be processed again.
+    fn claim<'a>(&'a self, notification_uuid: &'a str) -> DeduperFuture<'a, bool>;
+
+    /// Forgets a claimed notification whose processing failed, so that a retry is processed.
+    fn release<'a>(&'a self, notification_uuid: &'a str) -> DeduperFuture<'a, ()>;
+}
+
+/// A [`NotificationDeduper`] that keeps the claimed notifications in memory for a limited time.
+///
+/// Suited to a single server instance. Claims are forgotten after `ttl`, and the store is purged
+/// of expired claims as new ones are added.
+#[derive(Debug)]
+pub struct InMemoryNotificationDeduper {
+    ttl: Duration,
+    clock: Arc<dyn Clock>,
+    claims: Mutex<HashMap<String, DateTime<Utc>>>,
+}
+
+impl InMemoryNotificationDeduper {
+    pub fn new(ttl: Duration) -> Self {
+        InMemoryNotificationDeduper { ttl, clock: system_clock(), claims: Mutex::new(HashMap::new()) }
+    }
+
+    /// Sets the clock that the age of claims is measured with.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    fn claim_at(&self, notification_uuid: &str, now: DateTime<Utc>) -> bool {
+        let ttl = duration_to_chrono(self.ttl);
+        let mut claims = self.claims.lock().unwrap();
+        claims.retain(|_, claimed_at| now - *claimed_at < ttl);
+
+        if claims.contains_key(notification_uuid) {
+            return false;
+        }
+
+        claims.insert(notification_uuid.to_string(), now);
+        true
+    }
+}
+
+impl Default for InMemoryNotificationDeduper {
+    /// Keeps claims for 7 days, longer than the App Store retries a notification.
+    fn default() -> Self {
+        Self::new(Duration::from_secs(7 * 24 * 60 * 60))
+    }
+}
+
+impl NotificationDeduper for InMemoryNotificationDeduper {
+    fn claim<'a>(&'a self, notification_uuid: &'a str) -> DeduperFuture<'a, bool> {
+        let claimed = self.claim_at(notification_uuid, self.clock.now());
+        Box::pin(async move { Ok(claimed) })
+    }
+
+    fn release<'a>(&'a self, notification_uuid: &'a str) -> DeduperFuture<'a, ()> {
+        self.claims.lock().unwrap().remove(notification_uuid);
+        Box::pin(async { Ok(()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FrozenClock;
+
+    #[tokio::test]
+    async fn test_in_memory_deduper() {
+        let clock = Arc::new(FrozenClock::new(Utc::now()));
+        let deduper = InMemoryNotificationDeduper::new(Duration::from_secs(60)).clock(clock.clone());
+
+        assert_eq!(Ok(true), deduper.claim("a").await);
+        assert_eq!(Ok(false), deduper.claim("a").await);
+        assert_eq!(Ok(true), deduper.claim("b").await);
+
+        assert_eq!(Ok(()), deduper.release("a").await);
+        assert_eq!(Ok(true), deduper.claim("a").await);
+
+        clock.advance(Duration::from_secs(61));
+        assert_eq!(Ok(true), deduper.claim("b").await);
+    }
+}
@@ -0,0 +1,391 @@
+use crate::clock::{duration_to_chrono, system_clock, Clock};
+use crate::notification_router::NotificationRouter;
+use crate::primitives::decoded_notification::DecodedNotification;
+use crate::signed_data_verifier::SignedDataVerifierError;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum NotificationProcessorError {
+    #[error("VerificationError: [{0}]")]
+    VerificationError(#[from] SignedDataVerifierError),
+
+    #[error("StoreError: [{0}]")]
+    StoreError(String),
+}
+
+/// The future returned by [`NotificationStore`] methods.
+pub type NotificationStoreFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, String>> + Send + 'a>>;
+
+/// The processing state of a stored notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessingState {
+    /// The notification is waiting to be processed, for the first time or again after a failure.
+    Pending,
+    /// The notification was dispatched successfully.
+    Processed,
+    /// The handler failed or was interrupted on every attempt, and the notification won't be retried.
+    Failed,
+}
+
+/// A verified notification and its processing state, as kept by a [`NotificationStore`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredNotification {
+    pub notification: DecodedNotification,
+    pub state: ProcessingState,
+    /// The number of times the notification was leased for processing, including attempts that were
+    /// interrupted before their outcome was recorded.
+    pub attempts: u32,
+    /// The number of times the handler ran and failed.
+    pub failed_attempts: u32,
+    /// The time from which the notification may be processed, while it's pending.
+    pub next_attempt_at: DateTime<Utc>,
+    /// The error of the last failed attempt.
+    pub last_error: Option<String>,
+}
+
+impl StoredNotification {
+    pub fn notification_uuid(&self) -> &str {
+        &self.notification.payload.notification_uuid
+    }
+}
+
+/// A persistent store of the notifications received by a [`NotificationProcessor`], keyed by `notificationUUID`.
+///
+/// Implement this trait over a shared database so that notifications survive restarts and can be
+/// processed by any server instance.
+///
+/// [notificationUUID](https://developer.apple.com/documentation/appstoreservernotifications/notificationuuid)
+pub trait NotificationStore: Send + Sync {
+    /// Stores a newly received, pending notification.
+    ///
+    /// Returns `false` without changing the store if a notification with the same `notificationUUID` is already stored.
+    fn insert(&self, notification: StoredNotification) -> NotificationStoreFuture<'_, bool>;
+
+    /// Returns up to `limit` pending notifications whose `next_attempt_at` isn't after `now`, defers
+    /// their `next_attempt_at` to `lease_until` so that they aren't returned again while they're being
+    /// processed, and increments their `attempts`.
+    fn lease(
+        &self,
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+        limit: usize,
+    ) -> NotificationStoreFuture<'_, Vec<StoredNotification>>;
+
+    /// Replaces the stored notification with the same `notificationUUID`.
+    fn update<'a>(&'a self, notification: &'a StoredNotification) -> NotificationStoreFuture<'a, ()>;
+}
+
+/// A [`NotificationStore`] that keeps notifications in memory.
+///
+/// Suited to a single server instance and to tests, since notifications are lost on restart.
+#[derive(Debug, Default)]
+pub struct InMemoryNotificationStore {
+    notifications: Mutex<HashMap<String, StoredNotification>>,
+}
+
+impl InMemoryNotificationStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the stored notification with the `notificationUUID`, if any.
+    pub fn get(&self, notification_uuid: &str) -> Option<StoredNotification> {
+        self.notifications.lock().unwrap().get(notification_uuid).cloned()
+    }
+}
+
+impl NotificationStore for InMemoryNotificationStore {
+    fn insert(&self, notification: StoredNotification) -> NotificationStoreFuture<'_, bool> {
+        let mut notifications = self.notifications.lock().unwrap();
+        let inserted = !notifications.contains_key(notification.notification_uuid());
+        if inserted {
+            notifications.insert(notification.notification_uuid().to_string(), notification);
+        }
+        Box::pin(async move { Ok(inserted) })
+    }
+
+    fn lease(
+        &self,
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+        limit: usize,
+    ) -> NotificationStoreFuture<'_, Vec<StoredNotification>> {
+        let mut notifications = self.notifications.lock().unwrap();
+        let mut due: Vec<&mut StoredNotification> = notifications
+            .values_mut()
+            .filter(|notification| notification.state == ProcessingState::Pending && notification.next_attempt_at <= now)
+            .collect();
+        due.sort_by_key(|notification| notification.next_attempt_at);
+
+        let leased = due
+            .into_iter()
+            .take(limit)
+            .map(|notification| {
+                notification.next_attempt_at = lease_until;
+                notification.attempts += 1;
+                notification.clone()
+            })
+            .collect();
+        Box::pin(async move { Ok(leased) })
+    }
+
+    fn update<'a>(&'a self, notification: &'a StoredNotification) -> NotificationStoreFuture<'a, ()> {
+        self.notifications.lock().unwrap().insert(notification.notification_uuid().to_string(), notification.clone());
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// The number of notifications in each state after a call to [`NotificationProcessor::process_due`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProcessingSummary {
+    /// Notifications that were dispatched successfully.
+    pub processed: usize,
+    /// Notifications whose handler failed and that will be retried.
+    pub retrying: usize,
+    /// Notifications whose handler failed for the last time.
+    pub failed: usize,
+}
+
+/// Stores verified notifications and processes them through a [`NotificationRouter`] asynchronously,
+/// retrying failed handlers with exponential backoff.
+///
+/// The webhook endpoint calls [`receive`](Self::receive) and responds with a success status as soon
+/// as the notification is stored, while a background task calls [`process_due`](Self::process_due)
+/// periodically. Notifications are processed at least once: one whose processing is interrupted is
+/// leased again once its lease expires, so handlers should be idempotent. Interrupted attempts count
+/// towards the maximum number of attempts.
+pub struct NotificationProcessor<E> {
+    router: NotificationRouter<E>,
+    store: Arc<dyn NotificationStore>,
+    clock: Arc<dyn Clock>,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    max_attempts: u32,
+    lease: Duration,
+    batch_size: usize,
+}
+
+impl<E: Display> NotificationProcessor<E> {
+    /// Creates a processor that verifies and dispatches notifications with `router`, keeping them in `store`.
+    ///
+    /// By default, a failed notification is retried after 1 minute, doubling up to 1 hour, and is
+    /// marked as failed after 10 attempts.
+    pub fn new(router: NotificationRouter<E>, store: Arc<dyn NotificationStore>) -> Self {
+        NotificationProcessor {
+            router,
+            store,
+            clock: system_clock(),
+            initial_backoff: Duration::from_secs(60),
+            max_backoff: Duration::from_secs(60 * 60),
+            max_attempts: 10,
+            lease: Duration::from_secs(5 * 60),
+            batch_size: 100,
+        }
+    }
+
+    /// Sets the delay before the first retry, doubled after each further failure up to `max`.
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Sets the number of attempts after which a failing notification is marked as failed.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Sets how long a notification is leased while it's processed, after which it's processed again.
+    pub fn lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+        self
+    }
+
+    /// Sets the maximum number of notifications processed by each call to [`process_due`](Self::process_due).
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Sets the clock that attempts are scheduled with.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Verifies and decodes a signed notification, then stores it for processing.
+    ///
+    /// A notification that fails verification is passed to the router's
+    /// [`dead_letter`](NotificationRouter::dead_letter) callback, if it has one.
+    ///
+    /// # Arguments
+    ///
+    /// * `signed_payload` - The `signedPayload` field of the notification request body.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(true)` if the notification was stored, or `Ok(false)` if it was already stored.
+    /// - `Err(NotificationProcessorError::VerificationError)` if the notification or its signed fields fail verification.
+    /// - `Err(NotificationProcessorError::StoreError)` if the store failed, in which case the webhook should respond with an error so that the App Store retries.
+    pub async fn receive(&self, signed_payload: &str) -> Result<bool, NotificationProcessorError> {
+        let notification = self.router.decode(signed_payload)?;
+        let stored = StoredNotification {
+            notification,
+            state: ProcessingState::Pending,
+            attempts: 0,
+            failed_attempts: 0,
+            next_attempt_at: self.clock.now(),
+            last_error: None,
+        };
+
+        self.store.insert(stored).await.map_err(NotificationProcessorError::StoreError)
+    }
+
+    /// Dispatches the pending notifications that are due, up to the batch size, and records the outcome of each.
+    pub async fn process_due(&self) -> Result<ProcessingSummary, NotificationProcessorError> {
+        let now = self.clock.now();
+        let leased = self
+            .store
+            .lease(now, now + duration_to_chrono(self.lease), self.batch_size)
+            .await
+            .map_err(NotificationProcessorError::StoreError)?;
+
+        let mut summary = ProcessingSummary::default();
+        for mut stored in leased {
+            // Every earlier attempt failed or was interrupted, so this one would exceed the maximum.
+            if stored.attempts > self.max_attempts {
+                stored.state = ProcessingState::Failed;
+                stored.last_error.get_or_insert_with(|| "The lease expired before processing completed".to_string());
+                summary.failed += 1;
+                self.store.update(&stored).await.map_err(NotificationProcessorError::StoreError)?;
+                continue;
+            }
+
+            match self.router.dispatch_decoded(stored.notification.clone()).await {
+                Ok(_) => {
+                    stored.state = ProcessingState::Processed;
+                    summary.processed += 1;
+                }
+                Err(error) => {
+                    stored.failed_attempts += 1;
+                    stored.last_error = Some(error.to_string());
+
+                    if stored.attempts >= self.max_attempts {
+                        stored.state = ProcessingState::Failed;
+                        summary.failed += 1;
+                    } else {
+                        stored.next_attempt_at = self.clock.now() + duration_to_chrono(self.backoff_after(stored.failed_attempts));
+                        summary.retrying += 1;
+                    }
+                }
+            }
+
+            self.store.update(&stored).await.map_err(NotificationProcessorError::StoreError)?;
+        }
+
+        Ok(summary)
+    }
+
+    fn backoff_after(&self, failed_attempts: u32) -> Duration {
+        let factor = 2u32.saturating_pow(failed_attempts.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FrozenClock;
+    use crate::primitives::notification_type_v2::NotificationTypeV2;
+    use crate::test_utils::{local_testing_verifier, signed_notification_fixture};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_process_with_retries() {
+        let clock = Arc::new(FrozenClock::new(Utc::now()));
+        let store = Arc::new(InMemoryNotificationStore::new());
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let router = NotificationRouter::new(local_testing_verifier())
+            .on(NotificationTypeV2::DidRenew, move |_| {
+                let attempt = counter.fetch_add(1, Ordering::SeqCst);
+                async move { if attempt < 2 { Err("unavailable".to_string()) } else { Ok(()) } }
+            })
+            .on(NotificationTypeV2::Refund, |_| async { Err("refund failed".to_string()) });
+        let processor = NotificationProcessor::new(router, store.clone())
+            .backoff(Duration::from_secs(10), Duration::from_secs(15))
+            .max_attempts(3)
+            .clock(clock.clone());
+
+        let did_renew = signed_notification_fixture(&NotificationTypeV2::DidRenew, None);
+        assert_eq!(Ok(true), processor.receive(&did_renew).await);
+        assert_eq!(Ok(false), processor.receive(&did_renew).await);
+        let uuid = local_testing_verifier().verify_and_decode_notification(&did_renew).unwrap().notification_uuid;
+
+        assert_eq!(ProcessingSummary { retrying: 1, ..Default::default() }, processor.process_due().await.unwrap());
+        let stored = store.get(&uuid).unwrap();
+        assert_eq!((ProcessingState::Pending, 1, 1), (stored.state, stored.attempts, stored.failed_attempts));
+        assert_eq!(Some("HandlerError: [unavailable]".to_string()), stored.last_error);
+        assert_eq!(clock.now() + chrono::Duration::seconds(10), stored.next_attempt_at);
+
+        assert_eq!(ProcessingSummary::default(), processor.process_due().await.unwrap());
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(ProcessingSummary { retrying: 1, ..Default::default() }, processor.process_due().await.unwrap());
+        assert_eq!(clock.now() + chrono::Duration::seconds(15), store.get(&uuid).unwrap().next_attempt_at);
+
+        clock.advance(Duration::from_secs(15));
+        assert_eq!(ProcessingSummary { processed: 1, ..Default::default() }, processor.process_due().await.unwrap());
+        assert_eq!(ProcessingState::Processed, store.get(&uuid).unwrap().state);
+        assert_eq!(3, attempts.load(Ordering::SeqCst));
+
+        let refund = signed_notification_fixture(&NotificationTypeV2::Refund, None);
+        assert_eq!(Ok(true), processor.receive(&refund).await);
+        processor.process_due().await.unwrap();
+        clock.advance(Duration::from_secs(10));
+        processor.process_due().await.unwrap();
+        clock.advance(Duration::from_secs(15));
+        assert_eq!(ProcessingSummary { failed: 1, ..Default::default() }, processor.process_due().await.unwrap());
+        assert!(matches!(processor.receive("invalid").await, Err(NotificationProcessorError::VerificationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_process_after_lease_expires() {
+        let clock = Arc::new(FrozenClock::new(Utc::now()));
+        let store = Arc::new(InMemoryNotificationStore::new());
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let router = NotificationRouter::new(local_testing_verifier()).on(NotificationTypeV2::DidRenew, move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async { Err("unavailable".to_string()) }
+        });
+        let processor = NotificationProcessor::new(router, store.clone())
+            .lease(Duration::from_secs(30))
+            .max_attempts(2)
+            .clock(clock.clone());
+
+        let did_renew = signed_notification_fixture(&NotificationTypeV2::DidRenew, None);
+        processor.receive(&did_renew).await.unwrap();
+        let uuid = local_testing_verifier().verify_and_decode_notification(&did_renew).unwrap().notification_uuid;
+
+        // A processor that crashes leaves the notification leased without recording an outcome.
+        for _ in 0..2 {
+            let leased = store.lease(clock.now(), clock.now() + chrono::Duration::seconds(30), 10).await.unwrap();
+            assert_eq!(1, leased.len());
+            assert_eq!(ProcessingSummary::default(), processor.process_due().await.unwrap());
+            clock.advance(Duration::from_secs(30));
+        }
+
+        assert_eq!(ProcessingSummary { failed: 1, ..Default::default() }, processor.process_due().await.unwrap());
+        let stored = store.get(&uuid).unwrap();
+        assert_eq!((ProcessingState::Failed, 3, 0), (stored.state, stored.attempts, stored.failed_attempts));
+        assert_eq!(0, attempts.load(Ordering::SeqCst));
+    }
+}
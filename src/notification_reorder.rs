@@ -0,0 +1,138 @@
+use crate::clock::{duration_to_chrono, system_clock, Clock};
+use crate::primitives::decoded_notification::DecodedNotification;
+use crate::primitives::original_transaction_id::OriginalTransactionId;
+use crate::primitives::timestamp::Timestamp;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+struct HeldNotification {
+    notification: DecodedNotification,
+    received_at: DateTime<Utc>,
+}
+
+/// Holds notifications for a while and releases them in the order they were signed, per original transaction.
+///
+/// The App Store doesn't guarantee the order in which notifications are delivered, so a `DID_RENEW`
+/// may arrive before the `SUBSCRIBED` that it follows. A notification is held for the window after
+/// it's pushed, and is released together with every held notification of its original transaction
+/// that was signed before it, sorted by `signedDate`. Notifications without an original transaction
+/// ID, such as `TEST`, aren't ordered and are released at once.
+///
+/// Push notifications as they're received, and periodically pass the ones returned by
+/// [`release`](Self::release) to [`NotificationRouter::dispatch_decoded`](crate::notification_router::NotificationRouter::dispatch_decoded).
+///
+/// [signedDate](https://developer.apple.com/documentation/appstoreservernotifications/signeddate)
+pub struct NotificationReorderBuffer {
+    window: Duration,
+    clock: Arc<dyn Clock>,
+    held: Mutex<Vec<HeldNotification>>,
+}
+
+impl NotificationReorderBuffer {
+    /// Creates a buffer that holds each notification for `window`.
+    pub fn new(window: Duration) -> Self {
+        NotificationReorderBuffer { window, clock: system_clock(), held: Mutex::new(Vec::new()) }
+    }
+
+    /// Sets the clock that the time notifications are held is measured with.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Holds a received notification until it's released.
+    pub fn push(&self, notification: DecodedNotification) {
+        let received_at = self.clock.now();
+        self.held.lock().unwrap().push(HeldNotification { notification, received_at });
+    }
+
+    /// Removes and returns, sorted by `signedDate`, the notifications that were held for the window
+    /// and the notifications of the same original transaction that were signed before them.
+    pub fn release(&self) -> Vec<DecodedNotification> {
+        let now = self.clock.now();
+        let window = duration_to_chrono(self.window);
+        let mut held = self.held.lock().unwrap();
+
+        // The latest signed date of the notifications of each original transaction that were held for the window.
+        let mut release_until: HashMap<OriginalTransactionId, Option<Timestamp>> = HashMap::new();
+        for waited in held.iter().filter(|held| now - held.received_at >= window) {
+            if let Some(original_transaction_id) = waited.notification.original_transaction_id() {
+                let signed_date = waited.notification.payload.signed_date;
+                let latest = release_until.entry(original_transaction_id.clone()).or_insert(signed_date);
+                *latest = (*latest).max(signed_date);
+            }
+        }
+
+        let (released, kept) = held.drain(..).partition(|held| match held.notification.original_transaction_id() {
+            Some(original_transaction_id) => release_until
+                .get(original_transaction_id)
+                .is_some_and(|latest| held.notification.payload.signed_date <= *latest),
+            None => true,
+        });
+        *held = kept;
+        sorted(released)
+    }
+
+    /// Removes and returns every held notification, sorted by `signedDate`, such as when shutting down.
+    pub fn flush(&self) -> Vec<DecodedNotification> {
+        sorted(self.held.lock().unwrap().drain(..).collect())
+    }
+
+    /// The number of held notifications.
+    pub fn len(&self) -> usize {
+        self.held.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn sorted(mut notifications: Vec<HeldNotification>) -> Vec<DecodedNotification> {
+    notifications.sort_by_key(|held| held.notification.payload.signed_date);
+    notifications.into_iter().map(|held| held.notification).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FrozenClock;
+    use crate::primitives::notification_type_v2::NotificationTypeV2;
+    use crate::primitives::subtype::Subtype;
+    use crate::primitives::timestamp;
+    use crate::test_utils::{local_testing_verifier, signed_notification_fixture};
+
+    fn notification(notification_type: NotificationTypeV2, subtype: Option<Subtype>, signed_date: i64) -> DecodedNotification {
+        let signed_payload = signed_notification_fixture(&notification_type, subtype.as_ref());
+        let mut notification = local_testing_verifier().verify_and_decode_notification_full(&signed_payload).unwrap();
+        notification.payload.signed_date = timestamp::from_millis(signed_date);
+        notification
+    }
+
+    #[test]
+    fn test_release_in_signed_order() {
+        let clock = Arc::new(FrozenClock::new(Utc::now()));
+        let buffer = NotificationReorderBuffer::new(Duration::from_secs(30)).clock(clock.clone());
+
+        buffer.push(notification(NotificationTypeV2::DidRenew, None, 1698148900000));
+        buffer.push(notification(NotificationTypeV2::Test, None, 1698148700000));
+        assert_eq!(vec![NotificationTypeV2::Test], types(buffer.release()));
+        assert_eq!(1, buffer.len());
+
+        clock.advance(Duration::from_secs(20));
+        buffer.push(notification(NotificationTypeV2::Subscribed, Some(Subtype::InitialBuy), 1698148800000));
+        buffer.push(notification(NotificationTypeV2::Expired, Some(Subtype::Voluntary), 1698149000000));
+        assert!(buffer.release().is_empty());
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(vec![NotificationTypeV2::Subscribed, NotificationTypeV2::DidRenew], types(buffer.release()));
+        assert_eq!(vec![NotificationTypeV2::Expired], types(buffer.flush()));
+        assert!(buffer.is_empty());
+    }
+
+    fn types(notifications: Vec<DecodedNotification>) -> Vec<NotificationTypeV2> {
+        notifications.into_iter().map(|notification| notification.payload.notification_type).collect()
+    }
+}
@@ -0,0 +1,289 @@
+use crate::notification_deduper::NotificationDeduper;
+use crate::primitives::decoded_notification::DecodedNotification;
+use crate::primitives::notification_type_v2::NotificationTypeV2;
+use crate::primitives::subtype::Subtype;
+use crate::signed_data_verifier::{SignedDataVerifier, SignedDataVerifierError};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum NotificationRouterError<E> {
+    #[error("VerificationError: [{0}]")]
+    VerificationError(#[from] SignedDataVerifierError),
+
+    #[error("HandlerError: [{0}]")]
+    HandlerError(E),
+
+    #[error("DeduplicationError: [{0}]")]
+    DeduplicationError(String),
+}
+
+/// The result of dispatching a notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DispatchOutcome {
+    /// A handler registered for the notification ran successfully.
+    Handled,
+    /// No handler is registered for the notification's type and subtype, and there's no fallback handler.
+    Unhandled,
+    /// The notification was already claimed by the router's [`NotificationDeduper`], so no handler ran.
+    Duplicate,
+}
+
+/// The future returned by a notification handler.
+pub type HandlerFuture<E> = Pin<Box<dyn Future<Output = Result<(), E>> + Send>>;
+
+type Handler<E> = Box<dyn Fn(DecodedNotification) -> HandlerFuture<E> + Send + Sync>;
+
+type DeadLetter = Box<dyn Fn(&str, &SignedDataVerifierError) + Send + Sync>;
+
+struct Route<E> {
+    notification_type: NotificationTypeV2,
+    subtype: Option<Subtype>,
+    handler: Handler<E>,
+}
+
+/// Verifies signed notifications and dispatches them to the handler registered for their type.
+///
+/// Handlers receive the notification with its signed transaction and renewal info already
+/// verified and decoded. A handler registered for a type and subtype takes precedence over one
+/// registered for the type alone, which takes precedence over the fallback handler. Only the
+/// most specific handler runs.
+pub struct NotificationRouter<E> {
+    verifier: SignedDataVerifier,
+    routes: Vec<Route<E>>,
+    fallback: Option<Handler<E>>,
+    deduper: Option<Arc<dyn NotificationDeduper>>,
+    dead_letter: Option<DeadLetter>,
+}
+
+impl<E> NotificationRouter<E> {
+    /// Creates a router that verifies notifications with `verifier`.
+    pub fn new(verifier: SignedDataVerifier) -> Self {
+        NotificationRouter { verifier, routes: Vec::new(), fallback: None, deduper: None, dead_letter: None }
+    }
+
+    /// Skips notifications whose `notificationUUID` was already claimed in `deduper`.
+    ///
+    /// A notification is claimed before its handler runs and released again if the handler fails,
+    /// so that the App Store's retry is processed.
+    pub fn deduper(mut self, deduper: Arc<dyn NotificationDeduper>) -> Self {
+        self.deduper = Some(deduper);
+        self
+    }
+
+    /// Calls `dead_letter` with the signed payload and the error whenever a notification fails
+    /// verification or decoding, before the error is returned.
+    ///
+    /// Use it to quarantine suspicious or malformed payloads for inspection. The callback runs
+    /// synchronously, so hand slow work such as writing to a database off to a task.
+    pub fn dead_letter<F>(mut self, dead_letter: F) -> Self
+    where
+        F: Fn(&str, &SignedDataVerifierError) + Send + Sync + 'static,
+    {
+        self.dead_letter = Some(Box::new(dead_letter));
+        self
+    }
+
+    /// Registers the handler for every notification of a type whose subtype has no handler of its own.
+    pub fn on<F, Fut>(self, notification_type: NotificationTypeV2, handler: F) -> Self
+    where
+        F: Fn(DecodedNotification) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+    {
+        self.route(notification_type, None, handler)
+    }
+
+    /// Registers the handler for notifications of a type and subtype.
+    pub fn on_subtype<F, Fut>(self, notification_type: NotificationTypeV2, subtype: Subtype, handler: F) -> Self
+    where
+        F: Fn(DecodedNotification) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+    {
+        self.route(notification_type, Some(subtype), handler)
+    }
+
+    /// Registers the handler for notifications that match no other handler.
+    pub fn fallback<F, Fut>(mut self, handler: F) -> Self
+    where
+        F: Fn(DecodedNotification) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+    {
+        self.fallback = Some(boxed(handler));
+        self
+    }
+
+    /// Verifies and decodes a signed notification, then runs the matching handler.
+    ///
+    /// # Arguments
+    ///
+    /// * `signed_payload` - The `signedPayload` field of the notification request body.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(DispatchOutcome)` telling whether a handler ran.
+    /// - `Err(NotificationRouterError::VerificationError)` if the notification or its signed fields fail verification.
+    /// - `Err(NotificationRouterError::HandlerError)` with the error returned by the handler.
+    pub async fn dispatch(&self, signed_payload: &str) -> Result<DispatchOutcome, NotificationRouterError<E>> {
+        let notification = self.decode(signed_payload)?;
+        self.dispatch_decoded(notification).await
+    }
+
+    /// Runs the handler matching an already verified and decoded notification.
+    pub async fn dispatch_decoded(
+        &self,
+        notification: DecodedNotification,
+    ) -> Result<DispatchOutcome, NotificationRouterError<E>> {
+        let Some(handler) = self.handler(notification.notification_type(), notification.subtype()) else {
+            return Ok(DispatchOutcome::Unhandled);
+        };
+
+        let Some(deduper) = &self.deduper else {
+            handler(notification).await.map_err(NotificationRouterError::HandlerError)?;
+            return Ok(DispatchOutcome::Handled);
+        };
+
+        let notification_uuid = notification.payload.notification_uuid.clone();
+        if !deduper.claim(&notification_uuid).await.map_err(NotificationRouterError::DeduplicationError)? {
+            return Ok(DispatchOutcome::Duplicate);
+        }
+
+        if let Err(error) = handler(notification).await {
+            deduper.release(&notification_uuid).await.map_err(NotificationRouterError::DeduplicationError)?;
+            return Err(NotificationRouterError::HandlerError(error));
+        }
+
+        Ok(DispatchOutcome::Handled)
+    }
+
+    fn route<F, Fut>(mut self, notification_type: NotificationTypeV2, subtype: Option<Subtype>, handler: F) -> Self
+    where
+        F: Fn(DecodedNotification) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+    {
+        self.routes.retain(|route| route.notification_type != notification_type || route.subtype != subtype);
+        self.routes.push(Route { notification_type, subtype, handler: boxed(handler) });
+        self
+    }
+
+    fn handler(&self, notification_type: &NotificationTypeV2, subtype: Option<&Subtype>) -> Option<&Handler<E>> {
+        let matching = |subtype: Option<&Subtype>| {
+            self.routes
+                .iter()
+                .find(|route| &route.notification_type == notification_type && route.subtype.as_ref() == subtype)
+                .map(|route| &route.handler)
+        };
+
+        subtype
+            .and_then(|subtype| matching(Some(subtype)))
+            .or_else(|| matching(None))
+            .or(self.fallback.as_ref())
+    }
+
+    pub(crate) fn decode(&self, signed_payload: &str) -> Result<DecodedNotification, SignedDataVerifierError> {
+        let decoded = self.verifier.verify_and_decode_notification_full(signed_payload);
+        if let (Err(error), Some(dead_letter)) = (&decoded, &self.dead_letter) {
+            dead_letter(signed_payload, error);
+        }
+        decoded
+    }
+}
+
+fn boxed<E, F, Fut>(handler: F) -> Handler<E>
+where
+    F: Fn(DecodedNotification) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), E>> + Send + 'static,
+{
+    Box::new(move |notification| Box::pin(handler(notification)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{local_testing_verifier, signed_notification_fixture};
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_dispatch() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let record = |name: &'static str| {
+            let calls = calls.clone();
+            move |notification: DecodedNotification| {
+                let calls = calls.clone();
+                async move {
+                    calls.lock().unwrap().push((name, notification.transaction_info.is_some(), notification.renewal_info.is_some()));
+                    Ok::<(), String>(())
+                }
+            }
+        };
+
+        let router = NotificationRouter::new(local_testing_verifier())
+            .on(NotificationTypeV2::Subscribed, record("subscribed"))
+            .on_subtype(NotificationTypeV2::Subscribed, Subtype::Resubscribe, record("resubscribe"))
+            .on(NotificationTypeV2::Refund, |_| async { Err("refund failed".to_string()) });
+
+        let resubscribe = signed_notification_fixture(&NotificationTypeV2::Subscribed, Some(&Subtype::Resubscribe));
+        let initial_buy = signed_notification_fixture(&NotificationTypeV2::Subscribed, Some(&Subtype::InitialBuy));
+        let test = signed_notification_fixture(&NotificationTypeV2::Test, None);
+        let refund = signed_notification_fixture(&NotificationTypeV2::Refund, None);
+
+        assert_eq!(Ok(DispatchOutcome::Handled), router.dispatch(&resubscribe).await);
+        assert_eq!(Ok(DispatchOutcome::Handled), router.dispatch(&initial_buy).await);
+        assert_eq!(Ok(DispatchOutcome::Unhandled), router.dispatch(&test).await);
+        assert_eq!(Err(NotificationRouterError::HandlerError("refund failed".to_string())), router.dispatch(&refund).await);
+        assert_eq!(vec![("resubscribe", true, true), ("subscribed", true, true)], *calls.lock().unwrap());
+
+        let router = router.fallback(record("fallback"));
+        assert_eq!(Ok(DispatchOutcome::Handled), router.dispatch(&test).await);
+        assert_eq!(("fallback", false, false), calls.lock().unwrap()[2]);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_with_deduper() {
+        use crate::notification_deduper::InMemoryNotificationDeduper;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let router = NotificationRouter::new(local_testing_verifier())
+            .deduper(Arc::new(InMemoryNotificationDeduper::default()))
+            .on(NotificationTypeV2::DidRenew, move |_| {
+                let attempt = counter.fetch_add(1, Ordering::SeqCst);
+                async move { if attempt == 0 { Err("unavailable".to_string()) } else { Ok(()) } }
+            });
+
+        let did_renew = signed_notification_fixture(&NotificationTypeV2::DidRenew, None);
+        assert!(matches!(router.dispatch(&did_renew).await, Err(NotificationRouterError::HandlerError(_))));
+        assert_eq!(Ok(DispatchOutcome::Handled), router.dispatch(&did_renew).await);
+        assert_eq!(Ok(DispatchOutcome::Duplicate), router.dispatch(&did_renew).await);
+        assert_eq!(2, attempts.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_invalid_payload() {
+        let router: NotificationRouter<String> = NotificationRouter::new(local_testing_verifier());
+
+        assert!(matches!(
+            router.dispatch("invalid").await,
+            Err(NotificationRouterError::VerificationError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_dead_letter() {
+        let dead_letters = Arc::new(Mutex::new(Vec::new()));
+        let quarantine = dead_letters.clone();
+        let router: NotificationRouter<String> = NotificationRouter::new(local_testing_verifier())
+            .dead_letter(move |signed_payload, error| quarantine.lock().unwrap().push((signed_payload.to_string(), error.to_string())));
+
+        let test = signed_notification_fixture(&NotificationTypeV2::Test, None);
+        assert_eq!(Ok(DispatchOutcome::Unhandled), router.dispatch(&test).await);
+        assert!(router.dispatch("invalid").await.is_err());
+
+        assert_eq!(
+            vec![("invalid".to_string(), "InternalJWTError: [InvalidToken]".to_string())],
+            *dead_letters.lock().unwrap()
+        );
+    }
+}
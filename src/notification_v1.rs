@@ -0,0 +1,119 @@
+use crate::primitives::response_body_v1::ResponseBodyV1;
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum NotificationV1Error {
+    #[error("InvalidBodyError: [{0}]")]
+    InvalidBodyError(String),
+
+    #[error("InvalidPasswordError")]
+    InvalidPasswordError,
+
+    #[error("InvalidAppIdentifier")]
+    InvalidAppIdentifier,
+}
+
+/// A decoder for legacy version 1 App Store Server Notifications.
+///
+/// Version 1 notifications aren't signed. The decoder authenticates them by comparing the `password`
+/// field with the app's shared secret, and checks that they belong to the expected app.
+///
+/// [App Store Server Notifications V1](https://developer.apple.com/documentation/appstoreservernotifications/app_store_server_notifications_v1)
+#[derive(Debug, Clone)]
+pub struct NotificationV1Decoder {
+    bundle_id: String,
+    shared_secret: Option<String>,
+}
+
+impl NotificationV1Decoder {
+    /// Creates a new `NotificationV1Decoder`.
+    ///
+    /// # Arguments
+    ///
+    /// * `bundle_id` - The bundle ID associated with the application.
+    /// * `shared_secret` - The app's shared secret. When `None`, the `password` field isn't checked.
+    pub fn new(bundle_id: impl Into<String>, shared_secret: Option<String>) -> Self {
+        NotificationV1Decoder { bundle_id: bundle_id.into(), shared_secret }
+    }
+
+    /// Decodes the JSON body of a version 1 notification request.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(ResponseBodyV1)` if the body is well formed and belongs to the app.
+    /// - `Err(NotificationV1Error::InvalidBodyError)` if the body can't be decoded.
+    /// - `Err(NotificationV1Error::InvalidPasswordError)` if the `password` doesn't match the shared secret.
+    /// - `Err(NotificationV1Error::InvalidAppIdentifier)` if the notification is for another app.
+    pub fn decode(&self, body: &[u8]) -> Result<ResponseBodyV1, NotificationV1Error> {
+        let notification: ResponseBodyV1 =
+            serde_json::from_slice(body).map_err(|e| NotificationV1Error::InvalidBodyError(e.to_string()))?;
+
+        if let Some(shared_secret) = &self.shared_secret {
+            let password = notification.password.as_deref().unwrap_or_default();
+            if !constant_time_eq(password.as_bytes(), shared_secret.as_bytes()) {
+                return Err(NotificationV1Error::InvalidPasswordError);
+            }
+        }
+
+        if notification.bid.as_ref().is_some_and(|bid| bid != &self.bundle_id) {
+            return Err(NotificationV1Error::InvalidAppIdentifier);
+        }
+
+        Ok(notification)
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::environment::Environment;
+    use crate::primitives::notification_type_v1::NotificationTypeV1;
+    use crate::primitives::verify_receipt_status::VerifyReceiptStatus;
+    use crate::primitives::timestamp;
+
+    #[test]
+    fn test_decode_notification_v1() {
+        let body = std::fs::read("assets/models/notificationV1.json").expect("Failed to read file");
+        let notification = NotificationV1Decoder::new("com.example", Some("secret".to_string()))
+            .decode(&body)
+            .unwrap();
+
+        assert_eq!(NotificationTypeV1::DidRenew, notification.notification_type);
+        assert_eq!(Some(Environment::Production), notification.environment);
+        assert_eq!(Some(true), notification.auto_renew_status);
+        assert_eq!(timestamp::from_millis(1698148800000), notification.auto_renew_status_change_date);
+        assert_eq!(Some("2000000000000000"), notification.original_transaction_id.as_deref());
+
+        let receipt = notification.unified_receipt.as_ref().unwrap();
+        assert_eq!(Some(VerifyReceiptStatus::Valid), receipt.status);
+        assert_eq!(1, receipt.pending_renewal_info.as_ref().unwrap().len());
+
+        let transactions = notification.transactions();
+        assert_eq!(1, transactions.len());
+        assert_eq!(Some("2000000000000001"), transactions[0].transaction_id.as_deref());
+        assert_eq!(Some("com.example".to_string()), transactions[0].bundle_id);
+        assert_eq!(Some(Environment::Production), transactions[0].environment);
+    }
+
+    #[test]
+    fn test_decode_notification_v1_rejected() {
+        let body = std::fs::read("assets/models/notificationV1.json").expect("Failed to read file");
+
+        assert_eq!(
+            Err(NotificationV1Error::InvalidPasswordError),
+            NotificationV1Decoder::new("com.example", Some("other".to_string())).decode(&body)
+        );
+        assert_eq!(
+            Err(NotificationV1Error::InvalidAppIdentifier),
+            NotificationV1Decoder::new("com.other", None).decode(&body)
+        );
+        assert!(matches!(
+            NotificationV1Decoder::new("com.example", None).decode(b"{}"),
+            Err(NotificationV1Error::InvalidBodyError(_))
+        ));
+    }
+}
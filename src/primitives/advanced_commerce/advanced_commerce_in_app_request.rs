@@ -0,0 +1,9 @@
+use serde::Serialize;
+
+/// A request the app sends through StoreKit to the Advanced Commerce API.
+///
+/// Implementations are serialized to JSON and embedded in the signature created by
+/// [`AdvancedCommerceInAppSignatureCreator`](crate::jws_signature_creator::AdvancedCommerceInAppSignatureCreator).
+///
+/// [Advanced Commerce API](https://developer.apple.com/documentation/advancedcommerceapi)
+pub trait AdvancedCommerceInAppRequest: Serialize {}
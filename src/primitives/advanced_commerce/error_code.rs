@@ -0,0 +1,73 @@
+use crate::primitives::error_payload::ErrorSeverity;
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+/// The error codes the Advanced Commerce API shares with the App Store Server API.
+///
+/// [Advanced Commerce API](https://developer.apple.com/documentation/advancedcommerceapi)
+#[derive(Debug, Clone, Copy, Deserialize_repr, Serialize_repr, PartialEq, Eq, Hash)]
+#[repr(i64)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// An error that indicates an invalid request.
+    GeneralBadRequest = 4000000,
+
+    /// An error that indicates an invalid app identifier.
+    InvalidAppIdentifier = 4000002,
+
+    /// An error that indicates an invalid transaction identifier.
+    InvalidTransactionId = 4000006,
+
+    /// An error that indicates an invalid request identifier.
+    InvalidRequestIdentifier = 4000011,
+
+    /// An error that indicates a transaction identifier wasn't found.
+    TransactionIdNotFound = 4040010,
+
+    /// An error that indicates that the request exceeded the rate limit.
+    RateLimitExceeded = 4290000,
+
+    /// An error that indicates a general internal error.
+    GeneralInternal = 5000000,
+
+    /// An error that indicates an unknown error occurred, but you can try again.
+    GeneralInternalRetryable = 5000001,
+}
+
+impl ErrorCode {
+    /// Whether the request that failed with this error can succeed when sent again later.
+    pub fn is_retryable(&self) -> bool {
+        self.severity() == ErrorSeverity::Retryable
+    }
+
+    /// How a request that failed with this error should be handled.
+    pub fn severity(&self) -> ErrorSeverity {
+        match self {
+            ErrorCode::GeneralInternalRetryable => ErrorSeverity::Retryable,
+            other => ErrorSeverity::from_code(*other as i64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::error_payload::APIError;
+
+    #[test]
+    fn test_severity() {
+        assert!(ErrorCode::GeneralInternalRetryable.is_retryable());
+        assert!(ErrorCode::RateLimitExceeded.is_retryable());
+        assert!(!ErrorCode::GeneralInternal.is_retryable());
+        assert_eq!(ErrorSeverity::Internal, ErrorCode::GeneralInternal.severity());
+        assert_eq!(ErrorSeverity::Validation, ErrorCode::InvalidTransactionId.severity());
+        assert_eq!(ErrorSeverity::NotFound, ErrorCode::TransactionIdNotFound.severity());
+
+        assert_eq!(ErrorSeverity::Retryable, APIError::AccountNotFoundRetryable.severity());
+        assert_eq!(ErrorSeverity::NotFound, APIError::AccountNotFound.severity());
+        assert_eq!(ErrorSeverity::Validation, APIError::InvalidStartDate.severity());
+        assert_eq!(ErrorSeverity::Retryable, APIError::RateLimitExceeded.severity());
+
+        let error_code: ErrorCode = serde_json::from_str("5000001").unwrap();
+        assert_eq!(ErrorCode::GeneralInternalRetryable, error_code);
+    }
+}
@@ -0,0 +1,2 @@
+pub mod advanced_commerce_in_app_request;
+pub mod error_code;
@@ -0,0 +1,248 @@
+use crate::primitives::consumption_request_reason::ConsumptionRequestReason;
+use crate::primitives::decoded_notification::DecodedNotification;
+use crate::primitives::external_purchase_token::ExternalPurchaseToken;
+use crate::primitives::jws_renewal_info_decoded_payload::JWSRenewalInfoDecodedPayload;
+use crate::primitives::jws_transaction_decoded_payload::JWSTransactionDecodedPayload;
+use crate::primitives::notification_type_v2::NotificationTypeV2;
+use crate::primitives::subtype::Subtype;
+use crate::primitives::summary::Summary;
+
+/// A business event described by a version 2 notification.
+///
+/// Combines the notification type, its subtype, and the payload that comes with them, so that
+/// each event carries exactly the data the App Store sends for it. Notifications whose type and
+/// subtype don't match a known event, or that lack the expected payload, become [`AppStoreEvent::Other`].
+///
+/// [notificationType](https://developer.apple.com/documentation/appstoreservernotifications/notificationtype)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppStoreEvent {
+    /// The customer subscribed, either for the first time or after their subscriptions expired.
+    Subscribed {
+        initial_buy: bool,
+        transaction: JWSTransactionDecodedPayload,
+        renewal_info: Option<JWSRenewalInfoDecodedPayload>,
+    },
+    /// The customer changed the product their subscription renews to.
+    ///
+    /// `subtype` is `Upgrade` or `Downgrade`, or `None` when the customer reverted to the current product.
+    DidChangeRenewalPref {
+        subtype: Option<Subtype>,
+        transaction: JWSTransactionDecodedPayload,
+        renewal_info: Option<JWSRenewalInfoDecodedPayload>,
+    },
+    /// The customer turned automatic renewal on or off.
+    DidChangeRenewalStatus {
+        auto_renew_enabled: bool,
+        transaction: JWSTransactionDecodedPayload,
+        renewal_info: Option<JWSRenewalInfoDecodedPayload>,
+    },
+    /// The customer redeemed a promotional offer or offer code.
+    OfferRedeemed {
+        subtype: Option<Subtype>,
+        transaction: JWSTransactionDecodedPayload,
+        renewal_info: Option<JWSRenewalInfoDecodedPayload>,
+    },
+    /// The subscription renewed, possibly after recovering from a billing failure.
+    DidRenew {
+        billing_recovery: bool,
+        transaction: JWSTransactionDecodedPayload,
+        renewal_info: Option<JWSRenewalInfoDecodedPayload>,
+    },
+    /// The subscription expired; `subtype` tells why.
+    Expired {
+        subtype: Option<Subtype>,
+        transaction: JWSTransactionDecodedPayload,
+        renewal_info: Option<JWSRenewalInfoDecodedPayload>,
+    },
+    /// The subscription failed to renew because of a billing issue.
+    DidFailToRenew {
+        grace_period: bool,
+        transaction: JWSTransactionDecodedPayload,
+        renewal_info: Option<JWSRenewalInfoDecodedPayload>,
+    },
+    /// The billing grace period ended without the subscription recovering.
+    GracePeriodExpired {
+        transaction: JWSTransactionDecodedPayload,
+        renewal_info: Option<JWSRenewalInfoDecodedPayload>,
+    },
+    /// The system informed the customer of a price increase, which they have or haven't accepted yet.
+    PriceIncrease {
+        accepted: bool,
+        transaction: JWSTransactionDecodedPayload,
+        renewal_info: Option<JWSRenewalInfoDecodedPayload>,
+    },
+    /// The App Store refunded a transaction.
+    Refund {
+        transaction: JWSTransactionDecodedPayload,
+        renewal_info: Option<JWSRenewalInfoDecodedPayload>,
+    },
+    /// The App Store declined a refund request.
+    RefundDeclined {
+        transaction: JWSTransactionDecodedPayload,
+        renewal_info: Option<JWSRenewalInfoDecodedPayload>,
+    },
+    /// The App Store reversed a previously granted refund.
+    RefundReversed {
+        transaction: JWSTransactionDecodedPayload,
+        renewal_info: Option<JWSRenewalInfoDecodedPayload>,
+    },
+    /// The customer requested a refund, and the App Store asks for consumption data.
+    ConsumptionRequest {
+        reason: Option<ConsumptionRequestReason>,
+        transaction: JWSTransactionDecodedPayload,
+        renewal_info: Option<JWSRenewalInfoDecodedPayload>,
+    },
+    /// The renewal date of the subscription was extended.
+    RenewalExtended {
+        transaction: JWSTransactionDecodedPayload,
+        renewal_info: Option<JWSRenewalInfoDecodedPayload>,
+    },
+    /// A renewal date extension requested for all subscribers completed.
+    RenewalExtensionSummary { summary: Summary },
+    /// A renewal date extension requested for all subscribers failed for this subscription.
+    RenewalExtensionFailure {
+        transaction: JWSTransactionDecodedPayload,
+        renewal_info: Option<JWSRenewalInfoDecodedPayload>,
+    },
+    /// Family Sharing access to a purchase was revoked.
+    Revoke {
+        transaction: JWSTransactionDecodedPayload,
+        renewal_info: Option<JWSRenewalInfoDecodedPayload>,
+    },
+    /// The customer purchased a consumable, non-consumable, or non-renewing subscription.
+    OneTimeCharge {
+        transaction: JWSTransactionDecodedPayload,
+    },
+    /// An external purchase token was created but not reported, or couldn't be reported.
+    ExternalPurchaseToken { unreported: bool, token: ExternalPurchaseToken },
+    /// A test notification requested through the App Store Server API.
+    Test,
+    /// A notification that doesn't match a known event.
+    Other(Box<DecodedNotification>),
+}
+
+impl From<DecodedNotification> for AppStoreEvent {
+    fn from(notification: DecodedNotification) -> Self {
+        let payload = &notification.payload;
+        let subtype = payload.subtype.clone();
+
+        match payload.notification_type {
+            NotificationTypeV2::Test => return AppStoreEvent::Test,
+            NotificationTypeV2::RenewalExtension if subtype == Some(Subtype::Summary) => {
+                if let Some(summary) = payload.summary.clone() {
+                    return AppStoreEvent::RenewalExtensionSummary { summary };
+                }
+            }
+            NotificationTypeV2::ExternalPurchaseToken => {
+                if let Some(token) = payload.external_purchase_token.clone() {
+                    return AppStoreEvent::ExternalPurchaseToken { unreported: subtype == Some(Subtype::Unreported), token };
+                }
+            }
+            _ => {}
+        }
+
+        let Some(transaction) = notification.transaction_info.clone() else {
+            return AppStoreEvent::Other(Box::new(notification));
+        };
+        let renewal_info = notification.renewal_info.clone();
+        let reason = payload.data.as_ref().and_then(|data| data.consumption_request_reason.clone());
+
+        match payload.notification_type {
+            NotificationTypeV2::Subscribed => AppStoreEvent::Subscribed {
+                initial_buy: subtype == Some(Subtype::InitialBuy),
+                transaction,
+                renewal_info,
+            },
+            NotificationTypeV2::DidChangeRenewalPref => {
+                AppStoreEvent::DidChangeRenewalPref { subtype, transaction, renewal_info }
+            }
+            NotificationTypeV2::DidChangeRenewalStatus => AppStoreEvent::DidChangeRenewalStatus {
+                auto_renew_enabled: subtype == Some(Subtype::AutoRenewEnabled),
+                transaction,
+                renewal_info,
+            },
+            NotificationTypeV2::OfferRedeemed => AppStoreEvent::OfferRedeemed { subtype, transaction, renewal_info },
+            NotificationTypeV2::DidRenew => AppStoreEvent::DidRenew {
+                billing_recovery: subtype == Some(Subtype::BillingRecovery),
+                transaction,
+                renewal_info,
+            },
+            NotificationTypeV2::Expired => AppStoreEvent::Expired { subtype, transaction, renewal_info },
+            NotificationTypeV2::DidFailToRenew => AppStoreEvent::DidFailToRenew {
+                grace_period: subtype == Some(Subtype::GracePeriod),
+                transaction,
+                renewal_info,
+            },
+            NotificationTypeV2::GracePeriodExpired => AppStoreEvent::GracePeriodExpired { transaction, renewal_info },
+            NotificationTypeV2::PriceIncrease => AppStoreEvent::PriceIncrease {
+                accepted: subtype == Some(Subtype::Accepted),
+                transaction,
+                renewal_info,
+            },
+            NotificationTypeV2::Refund => AppStoreEvent::Refund { transaction, renewal_info },
+            NotificationTypeV2::RefundDeclined => AppStoreEvent::RefundDeclined { transaction, renewal_info },
+            NotificationTypeV2::RefundReversed => AppStoreEvent::RefundReversed { transaction, renewal_info },
+            NotificationTypeV2::ConsumptionRequest => {
+                AppStoreEvent::ConsumptionRequest { reason, transaction, renewal_info }
+            }
+            NotificationTypeV2::RenewalExtended => AppStoreEvent::RenewalExtended { transaction, renewal_info },
+            NotificationTypeV2::RenewalExtension if subtype == Some(Subtype::Failure) => {
+                AppStoreEvent::RenewalExtensionFailure { transaction, renewal_info }
+            }
+            NotificationTypeV2::Revoke => AppStoreEvent::Revoke { transaction, renewal_info },
+            NotificationTypeV2::OneTimeCharge => AppStoreEvent::OneTimeCharge { transaction },
+            _ => AppStoreEvent::Other(Box::new(notification)),
+        }
+    }
+}
+
+impl DecodedNotification {
+    /// The business event described by the notification.
+    pub fn event(self) -> AppStoreEvent {
+        AppStoreEvent::from(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{local_testing_verifier, signed_notification_fixture};
+
+    fn event(notification_type: NotificationTypeV2, subtype: Option<Subtype>) -> AppStoreEvent {
+        let signed = signed_notification_fixture(&notification_type, subtype.as_ref());
+        local_testing_verifier().verify_and_decode_notification_full(&signed).unwrap().event()
+    }
+
+    #[test]
+    fn test_app_store_event() {
+        assert!(matches!(
+            event(NotificationTypeV2::Subscribed, Some(Subtype::InitialBuy)),
+            AppStoreEvent::Subscribed { initial_buy: true, renewal_info: Some(_), .. }
+        ));
+        assert!(matches!(
+            event(NotificationTypeV2::Subscribed, Some(Subtype::Resubscribe)),
+            AppStoreEvent::Subscribed { initial_buy: false, .. }
+        ));
+        assert!(matches!(
+            event(NotificationTypeV2::DidChangeRenewalStatus, Some(Subtype::AutoRenewDisabled)),
+            AppStoreEvent::DidChangeRenewalStatus { auto_renew_enabled: false, .. }
+        ));
+        assert!(matches!(
+            event(NotificationTypeV2::Expired, Some(Subtype::Voluntary)),
+            AppStoreEvent::Expired { subtype: Some(Subtype::Voluntary), .. }
+        ));
+        assert!(matches!(event(NotificationTypeV2::Test, None), AppStoreEvent::Test));
+    }
+
+    #[test]
+    fn test_every_notification_is_a_known_event() {
+        let verifier = local_testing_verifier();
+        crate::test_utils::assert_handles_every_signed_notification(|signed| {
+            match verifier.verify_and_decode_notification_full(signed).map(DecodedNotification::event) {
+                Ok(AppStoreEvent::Other(notification)) => Err(format!("{:?}", notification.subtype())),
+                Ok(_) => Ok(()),
+                Err(e) => Err(e.to_string()),
+            }
+        });
+    }
+}
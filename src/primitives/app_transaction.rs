@@ -1,5 +1,6 @@
+use crate::primitives::app_transaction_id::AppTransactionId;
 use crate::primitives::environment::Environment;
-use chrono::{DateTime, Utc};
+use crate::primitives::timestamp::Timestamp;
 use serde::{Deserialize, Serialize};
 use serde_with::formats::Flexible;
 use serde_with::TimestampMilliSeconds;
@@ -9,7 +10,7 @@ use uuid::Uuid;
 ///
 /// [AppTransaction](https://developer.apple.com/documentation/storekit/apptransaction)
 #[serde_with::serde_as]
-#[derive(Debug, Clone, Deserialize, Serialize, Hash)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct AppTransaction {
     /// The server environment that signs the app transaction.
     /// [environment](https://developer.apple.com/documentation/storekit/apptransaction/3963901-environment)
@@ -40,13 +41,13 @@ pub struct AppTransaction {
     /// [signedDate](https://developer.apple.com/documentation/storekit/apptransaction/3954449-signeddate)
     #[serde(rename = "receiptCreationDate")]
     #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
-    pub receipt_creation_date: Option<DateTime<Utc>>,
+    pub receipt_creation_date: Option<Timestamp>,
 
     /// The date the user originally purchased the app from the App Store.
     /// [originalPurchaseDate](https://developer.apple.com/documentation/storekit/apptransaction/3954448-originalpurchasedate)
     #[serde(rename = "originalPurchaseDate")]
     #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
-    pub original_purchase_date: Option<DateTime<Utc>>,
+    pub original_purchase_date: Option<Timestamp>,
 
     /// The app version that the user originally purchased from the App Store.
     /// [originalAppVersion](https://developer.apple.com/documentation/storekit/apptransaction/3954447-originalappversion)
@@ -67,13 +68,18 @@ pub struct AppTransaction {
     /// [preorderDate](https://developer.apple.com/documentation/storekit/apptransaction/4013175-preorderdate)
     #[serde(rename = "preorderDate")]
     #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
-    pub preorder_date: Option<DateTime<Utc>>,
+    pub preorder_date: Option<Timestamp>,
+
+    /// The unique identifier of the app download transaction.
+    /// [appTransactionID](https://developer.apple.com/documentation/storekit/apptransaction/apptransactionid)
+    #[serde(rename = "appTransactionId")]
+    pub app_transaction_id: Option<AppTransactionId>,
 }
 
 impl AppTransaction {
     /// The date that the App Store signed the JWS app transaction.
     /// [signedDate](https://developer.apple.com/documentation/storekit/apptransaction/3954449-signeddate)
-    pub fn signed_date(&self) -> Option<DateTime<Utc>> {
+    pub fn signed_date(&self) -> Option<Timestamp> {
         self.receipt_creation_date
     }
 }
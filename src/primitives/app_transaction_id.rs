@@ -0,0 +1,17 @@
+use crate::primitives::transaction_id::TransactionId;
+
+string_identifier! {
+    /// The unique identifier of an app download transaction, shared by every purchase the customer makes
+    /// in the app under the same Apple Account.
+    ///
+    /// [appTransactionId](https://developer.apple.com/documentation/appstoreserverapi/apptransactionid)
+    pub struct AppTransactionId;
+}
+
+impl From<AppTransactionId> for TransactionId {
+    /// Endpoints that look up a customer's purchases, such as Get Transaction History, also accept
+    /// an app transaction identifier in place of a transaction identifier.
+    fn from(value: AppTransactionId) -> Self {
+        TransactionId::new(value.into_inner())
+    }
+}
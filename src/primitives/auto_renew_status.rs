@@ -1,11 +1,9 @@
-use serde_repr::{Deserialize_repr, Serialize_repr};
-
-/// The renewal status for an auto-renewable subscription.
-///
-/// [autoRenewStatus](https://developer.apple.com/documentation/appstoreserverapi/autorenewstatus)
-#[derive(Debug, Clone, Deserialize_repr, Serialize_repr, Hash, PartialEq, Eq)]
-#[repr(u8)]
-pub enum AutoRenewStatus {
-    Off = 0,
-    On = 1,
+integer_enum! {
+    /// The renewal status for an auto-renewable subscription.
+    ///
+    /// [autoRenewStatus](https://developer.apple.com/documentation/appstoreserverapi/autorenewstatus)
+    pub enum AutoRenewStatus: u8 {
+        Off = 0,
+        On = 1,
+    }
 }
@@ -13,7 +13,7 @@ use crate::primitives::refund_preference::RefundPreference;
 /// The request body containing consumption information.
 ///
 /// [ConsumptionRequest](https://developer.apple.com/documentation/appstoreserverapi/consumptionrequest)
-#[derive(Debug, Clone, Deserialize, Serialize, Hash)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct ConsumptionRequest {
     /// A Boolean value that indicates whether the customer consented to provide consumption data to the App Store.
     ///
@@ -0,0 +1,241 @@
+use crate::primitives::raw_value::RawValue;
+
+string_enum! {
+    /// A three-letter ISO 4217 currency code.
+    ///
+    /// Codes that aren't in the ISO 4217 list of active currencies decode as [`Currency::Unknown`];
+    /// use [`Currency::from_code`] to reject them before sending a request.
+    ///
+    /// [currency](https://developer.apple.com/documentation/appstoreserverapi/currency)
+    pub enum Currency {
+        Aed = "AED",
+        Afn = "AFN",
+        All = "ALL",
+        Amd = "AMD",
+        Ang = "ANG",
+        Aoa = "AOA",
+        Ars = "ARS",
+        Aud = "AUD",
+        Awg = "AWG",
+        Azn = "AZN",
+        Bam = "BAM",
+        Bbd = "BBD",
+        Bdt = "BDT",
+        Bgn = "BGN",
+        Bhd = "BHD",
+        Bif = "BIF",
+        Bmd = "BMD",
+        Bnd = "BND",
+        Bob = "BOB",
+        Brl = "BRL",
+        Bsd = "BSD",
+        Btn = "BTN",
+        Bwp = "BWP",
+        Byn = "BYN",
+        Bzd = "BZD",
+        Cad = "CAD",
+        Cdf = "CDF",
+        Chf = "CHF",
+        Clp = "CLP",
+        Cny = "CNY",
+        Cop = "COP",
+        Crc = "CRC",
+        Cup = "CUP",
+        Cve = "CVE",
+        Czk = "CZK",
+        Djf = "DJF",
+        Dkk = "DKK",
+        Dop = "DOP",
+        Dzd = "DZD",
+        Egp = "EGP",
+        Ern = "ERN",
+        Etb = "ETB",
+        Eur = "EUR",
+        Fjd = "FJD",
+        Fkp = "FKP",
+        Gbp = "GBP",
+        Gel = "GEL",
+        Ghs = "GHS",
+        Gip = "GIP",
+        Gmd = "GMD",
+        Gnf = "GNF",
+        Gtq = "GTQ",
+        Gyd = "GYD",
+        Hkd = "HKD",
+        Hnl = "HNL",
+        Htg = "HTG",
+        Huf = "HUF",
+        Idr = "IDR",
+        Ils = "ILS",
+        Inr = "INR",
+        Iqd = "IQD",
+        Irr = "IRR",
+        Isk = "ISK",
+        Jmd = "JMD",
+        Jod = "JOD",
+        Jpy = "JPY",
+        Kes = "KES",
+        Kgs = "KGS",
+        Khr = "KHR",
+        Kmf = "KMF",
+        Kpw = "KPW",
+        Krw = "KRW",
+        Kwd = "KWD",
+        Kyd = "KYD",
+        Kzt = "KZT",
+        Lak = "LAK",
+        Lbp = "LBP",
+        Lkr = "LKR",
+        Lrd = "LRD",
+        Lsl = "LSL",
+        Lyd = "LYD",
+        Mad = "MAD",
+        Mdl = "MDL",
+        Mga = "MGA",
+        Mkd = "MKD",
+        Mmk = "MMK",
+        Mnt = "MNT",
+        Mop = "MOP",
+        Mru = "MRU",
+        Mur = "MUR",
+        Mvr = "MVR",
+        Mwk = "MWK",
+        Mxn = "MXN",
+        Myr = "MYR",
+        Mzn = "MZN",
+        Nad = "NAD",
+        Ngn = "NGN",
+        Nio = "NIO",
+        Nok = "NOK",
+        Npr = "NPR",
+        Nzd = "NZD",
+        Omr = "OMR",
+        Pab = "PAB",
+        Pen = "PEN",
+        Pgk = "PGK",
+        Php = "PHP",
+        Pkr = "PKR",
+        Pln = "PLN",
+        Pyg = "PYG",
+        Qar = "QAR",
+        Ron = "RON",
+        Rsd = "RSD",
+        Rub = "RUB",
+        Rwf = "RWF",
+        Sar = "SAR",
+        Sbd = "SBD",
+        Scr = "SCR",
+        Sdg = "SDG",
+        Sek = "SEK",
+        Sgd = "SGD",
+        Shp = "SHP",
+        Sle = "SLE",
+        Sos = "SOS",
+        Srd = "SRD",
+        Ssp = "SSP",
+        Stn = "STN",
+        Svc = "SVC",
+        Syp = "SYP",
+        Szl = "SZL",
+        Thb = "THB",
+        Tjs = "TJS",
+        Tmt = "TMT",
+        Tnd = "TND",
+        Top = "TOP",
+        Try = "TRY",
+        Ttd = "TTD",
+        Twd = "TWD",
+        Tzs = "TZS",
+        Uah = "UAH",
+        Ugx = "UGX",
+        Usd = "USD",
+        Uyu = "UYU",
+        Uzs = "UZS",
+        Ved = "VED",
+        Ves = "VES",
+        Vnd = "VND",
+        Vuv = "VUV",
+        Wst = "WST",
+        Xaf = "XAF",
+        Xcd = "XCD",
+        Xcg = "XCG",
+        Xof = "XOF",
+        Xpf = "XPF",
+        Yer = "YER",
+        Zar = "ZAR",
+        Zmw = "ZMW",
+        Zwg = "ZWG",
+    }
+}
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum CurrencyError {
+    #[error("InvalidCurrencyError: [{0}]")]
+    InvalidCurrencyError(String),
+}
+
+impl Currency {
+    /// The currency for an ISO 4217 code, or an error if the code isn't an active currency.
+    pub fn from_code(code: &str) -> Result<Self, CurrencyError> {
+        match Currency::from_raw(code) {
+            Currency::Unknown(code) => Err(CurrencyError::InvalidCurrencyError(code)),
+            currency => Ok(currency),
+        }
+    }
+
+    /// Whether the code is an active ISO 4217 currency.
+    pub fn is_known(&self) -> bool {
+        !matches!(self, Currency::Unknown(_))
+    }
+
+    /// The number of decimal places the currency uses, assuming two for unknown currencies.
+    pub fn minor_unit_digits(&self) -> u32 {
+        match self {
+            Currency::Bif
+            | Currency::Clp
+            | Currency::Djf
+            | Currency::Gnf
+            | Currency::Isk
+            | Currency::Jpy
+            | Currency::Kmf
+            | Currency::Krw
+            | Currency::Pyg
+            | Currency::Rwf
+            | Currency::Ugx
+            | Currency::Vnd
+            | Currency::Vuv
+            | Currency::Xaf
+            | Currency::Xof
+            | Currency::Xpf => 0,
+            Currency::Bhd
+            | Currency::Iqd
+            | Currency::Jod
+            | Currency::Kwd
+            | Currency::Lyd
+            | Currency::Omr
+            | Currency::Tnd => 3,
+            _ => 2,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_currency() {
+        assert_eq!(Ok(Currency::Usd), Currency::from_code("USD"));
+        assert_eq!(Err(CurrencyError::InvalidCurrencyError("usd".to_string())), Currency::from_code("usd"));
+        assert_eq!(Err(CurrencyError::InvalidCurrencyError("XYZ".to_string())), Currency::from_code("XYZ"));
+
+        let unknown: Currency = serde_json::from_str(r#""XYZ""#).unwrap();
+        assert!(!unknown.is_known());
+        assert_eq!(2, unknown.minor_unit_digits());
+        assert_eq!(0, Currency::Jpy.minor_unit_digits());
+        assert_eq!(3, Currency::Kwd.minor_unit_digits());
+        assert_eq!(Ok(Currency::Ved), Currency::from_code("VED"));
+        assert_eq!(2, Currency::Ved.minor_unit_digits());
+    }
+}
@@ -6,7 +6,7 @@ use crate::primitives::consumption_request_reason::ConsumptionRequestReason;
 /// The app metadata and the signed renewal and transaction information.
 ///
 /// [data](https://developer.apple.com/documentation/appstoreservernotifications/data)
-#[derive(Debug, Clone, Deserialize, Serialize, Hash)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct Data {
     /// The server environment that the notification applies to, either sandbox or production.
     ///
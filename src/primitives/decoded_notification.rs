@@ -0,0 +1,43 @@
+use crate::primitives::jws_renewal_info_decoded_payload::JWSRenewalInfoDecodedPayload;
+use crate::primitives::jws_transaction_decoded_payload::JWSTransactionDecodedPayload;
+use crate::primitives::notification_type_v2::NotificationTypeV2;
+use crate::primitives::original_transaction_id::OriginalTransactionId;
+use crate::primitives::response_body_v2_decoded_payload::ResponseBodyV2DecodedPayload;
+use crate::primitives::subtype::Subtype;
+
+/// A version 2 notification together with the verified and decoded contents of its signed fields.
+///
+/// [responseBodyV2DecodedPayload](https://developer.apple.com/documentation/appstoreservernotifications/responsebodyv2decodedpayload)
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, Hash, PartialEq, Eq)]
+pub struct DecodedNotification {
+    /// The decoded notification payload.
+    pub payload: ResponseBodyV2DecodedPayload,
+
+    /// The decoded `data.signedTransactionInfo`, if the notification contains one.
+    ///
+    /// [JWSTransactionDecodedPayload](https://developer.apple.com/documentation/appstoreservernotifications/jwstransactiondecodedpayload)
+    pub transaction_info: Option<JWSTransactionDecodedPayload>,
+
+    /// The decoded `data.signedRenewalInfo`, if the notification contains one.
+    ///
+    /// [JWSRenewalInfoDecodedPayload](https://developer.apple.com/documentation/appstoreservernotifications/jwsrenewalinfodecodedpayload)
+    pub renewal_info: Option<JWSRenewalInfoDecodedPayload>,
+}
+
+impl DecodedNotification {
+    pub fn notification_type(&self) -> &NotificationTypeV2 {
+        &self.payload.notification_type
+    }
+
+    pub fn subtype(&self) -> Option<&Subtype> {
+        self.payload.subtype.as_ref()
+    }
+
+    /// The original transaction identifier of the decoded transaction info, or else of the decoded renewal info.
+    pub fn original_transaction_id(&self) -> Option<&OriginalTransactionId> {
+        self.transaction_info
+            .as_ref()
+            .and_then(|transaction_info| transaction_info.original_transaction_id.as_ref())
+            .or_else(|| self.renewal_info.as_ref().and_then(|renewal_info| renewal_info.original_transaction_id.as_ref()))
+    }
+}
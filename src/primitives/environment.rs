@@ -1,17 +1,11 @@
-use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Clone, Deserialize, Serialize, Hash, PartialEq, Eq)]
-pub enum Environment {
-    #[serde(rename = "Sandbox")]
-    Sandbox,
-    #[serde(rename = "Production")]
-    Production,
-    #[serde(rename = "Xcode")]
-    Xcode,
-    #[serde(rename = "LocalTesting")]
-    LocalTesting, // Used for unit testing
-    #[serde(other)]
-    Unknown
+string_enum! {
+    pub enum Environment {
+        Sandbox = "Sandbox",
+        Production = "Production" | "PROD",
+        Xcode = "Xcode",
+        /// Used for unit testing
+        LocalTesting = "LocalTesting",
+    }
 }
 
 impl Environment {
@@ -23,4 +17,4 @@ impl Environment {
             _ => "https://api.storekit-sandbox.itunes.apple.com".to_string(),
         }
     }
-}
\ No newline at end of file
+}
@@ -2,8 +2,9 @@ use serde::{Deserialize, Deserializer, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
 /// Enum representing different API errors with associated status codes.
-#[derive(Debug, Clone, Deserialize_repr, Serialize_repr, PartialEq, Hash)]
+#[derive(Debug, Clone, Deserialize_repr, Serialize_repr, PartialEq, Eq, Hash)]
 #[repr(i64)]
+#[non_exhaustive]
 pub enum APIError {
     /// An error that indicates an invalid request.
     /// [Documentation](https://developer.apple.com/documentation/appstoreserverapi/generalbadrequesterror)
@@ -234,7 +235,52 @@ pub enum APIError {
     GeneralInternalRetryable = 5000001
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, Hash)]
+/// How a request that failed with an error code should be handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorSeverity {
+    /// The request was malformed or invalid, and fails the same way every time it's sent.
+    Validation,
+    /// The request referred to something the App Store doesn't know about.
+    NotFound,
+    /// The request can succeed when sent again later.
+    Retryable,
+    /// The App Store failed in a way that Apple doesn't document as retryable.
+    Internal,
+}
+
+impl ErrorSeverity {
+    /// Classifies the error codes that aren't documented as retryable by their HTTP status prefix.
+    pub(crate) fn from_code(code: i64) -> Self {
+        match code / 10000 {
+            404 => ErrorSeverity::NotFound,
+            429 => ErrorSeverity::Retryable,
+            500.. => ErrorSeverity::Internal,
+            _ => ErrorSeverity::Validation,
+        }
+    }
+}
+
+impl APIError {
+    /// Whether the request that failed with this error can succeed when sent again later.
+    pub fn is_retryable(&self) -> bool {
+        self.severity() == ErrorSeverity::Retryable
+    }
+
+    /// How a request that failed with this error should be handled.
+    pub fn severity(&self) -> ErrorSeverity {
+        match self {
+            APIError::AccountNotFoundRetryable
+            | APIError::AppNotFoundRetryable
+            | APIError::OriginalTransactionIdNotFoundRetryable
+            | APIError::RateLimitExceeded
+            | APIError::GeneralInternalRetryable => ErrorSeverity::Retryable,
+            other => ErrorSeverity::from_code(other.clone() as i64),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct ErrorPayload {
     #[serde(rename = "errorCode")]
     #[serde(default, deserialize_with = "deserialize_maybe_none")]
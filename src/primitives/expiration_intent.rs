@@ -1,14 +1,12 @@
-use serde_repr::{Deserialize_repr, Serialize_repr};
-
-/// The reason an auto-renewable subscription expired.
-///
-/// [expirationIntent](https://developer.apple.com/documentation/appstoreserverapi/expirationintent)
-#[derive(Debug, Clone, Deserialize_repr, Serialize_repr, Hash, PartialEq, Eq)]
-#[repr(u8)]
-pub enum ExpirationIntent {
-    CustomerCancelled = 1,
-    BillingError = 2,
-    CustomerDidNotConsentToPriceIncrease = 3,
-    ProductNotAvailable = 4,
-    Other = 5,
+integer_enum! {
+    /// The reason an auto-renewable subscription expired.
+    ///
+    /// [expirationIntent](https://developer.apple.com/documentation/appstoreserverapi/expirationintent)
+    pub enum ExpirationIntent: u8 {
+        CustomerCancelled = 1,
+        BillingError = 2,
+        CustomerDidNotConsentToPriceIncrease = 3,
+        ProductNotAvailable = 4,
+        Other = 5,
+    }
 }
@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 /// The request body that contains subscription-renewal-extension data for an individual subscription.
 ///
 /// [ExtendRenewalDateRequest](https://developer.apple.com/documentation/appstoreserverapi/extendrenewaldaterequest)
-#[derive(Debug, Clone, Deserialize, Serialize, Hash)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct ExtendRenewalDateRequest {
     /// The number of days to extend the subscription renewal date.
     ///
@@ -1,4 +1,6 @@
-use chrono::{DateTime, Utc};
+use crate::primitives::original_transaction_id::OriginalTransactionId;
+use crate::primitives::timestamp::Timestamp;
+use crate::primitives::web_order_line_item_id::WebOrderLineItemId;
 use serde::{Deserialize, Serialize};
 use serde_with::formats::Flexible;
 use serde_with::TimestampMilliSeconds;
@@ -7,19 +9,19 @@ use serde_with::TimestampMilliSeconds;
 ///
 /// [ExtendRenewalDateResponse](https://developer.apple.com/documentation/appstoreserverapi/extendrenewaldateresponse)
 #[serde_with::serde_as]
-#[derive(Debug, Clone, Deserialize, Serialize, Hash)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct ExtendRenewalDateResponse {
     /// The original transaction identifier of a purchase.
     ///
     /// [originalTransactionId](https://developer.apple.com/documentation/appstoreserverapi/originaltransactionid)
     #[serde(rename = "originalTransactionId")]
-    pub original_transaction_id: Option<String>,
+    pub original_transaction_id: Option<OriginalTransactionId>,
 
     /// The unique identifier of subscription-purchase events across devices, including renewals.
     ///
     /// [webOrderLineItemId](https://developer.apple.com/documentation/appstoreserverapi/weborderlineitemid)
     #[serde(rename = "webOrderLineItemId")]
-    pub web_order_line_item_id: Option<String>,
+    pub web_order_line_item_id: Option<WebOrderLineItemId>,
 
     /// A Boolean value that indicates whether the subscription-renewal-date extension succeeded.
     ///
@@ -32,5 +34,5 @@ pub struct ExtendRenewalDateResponse {
     /// [effectiveDate](https://developer.apple.com/documentation/appstoreserverapi/effectivedate)
     #[serde(rename = "effectiveDate")]
     #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
-    pub effective_date: Option<DateTime<Utc>>,
+    pub effective_date: Option<Timestamp>,
 }
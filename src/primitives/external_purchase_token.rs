@@ -1,4 +1,5 @@
-use chrono::{DateTime, Utc};
+use crate::primitives::environment::Environment;
+use crate::primitives::timestamp::Timestamp;
 use serde::{Deserialize, Serialize};
 use serde_with::formats::Flexible;
 use serde_with::TimestampMilliSeconds;
@@ -7,7 +8,7 @@ use serde_with::TimestampMilliSeconds;
 ///
 /// [externalPurchaseToken](https://developer.apple.com/documentation/appstoreservernotifications/externalpurchasetoken)
 #[serde_with::serde_as]
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ExternalPurchaseToken {
     /// The field of an external purchase token that uniquely identifies the token.
     ///
@@ -21,7 +22,7 @@ pub struct ExternalPurchaseToken {
     /// [tokenCreationDate](https://developer.apple.com/documentation/appstoreservernotifications/tokencreationdate)
     #[serde(rename = "tokenCreationDate")]
     #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
-    pub token_creation_date: Option<DateTime<Utc>>,
+    pub token_creation_date: Option<Timestamp>,
 
     /// The unique identifier of an app in the App Store.
     ///
@@ -34,4 +35,51 @@ pub struct ExternalPurchaseToken {
     /// [bundleId](https://developer.apple.com/documentation/appstoreservernotifications/bundleid)
     #[serde(rename = "bundleId")]
     pub bundle_id: Option<String>,
-}
\ No newline at end of file
+}
+
+impl ExternalPurchaseToken {
+    /// The prefix the App Store adds to the external purchase ID of tokens created in the sandbox environment.
+    pub const SANDBOX_PREFIX: &'static str = "SANDBOX_";
+
+    /// The part of [`SANDBOX_PREFIX`](Self::SANDBOX_PREFIX) that marks a sandbox token; the separator is optional.
+    const SANDBOX_MARKER: &'static str = "SANDBOX";
+
+    /// Returns `true` if the external purchase ID starts with `SANDBOX`, which is how the App Store
+    /// marks tokens created in the sandbox environment.
+    pub fn is_sandbox_token(&self) -> bool {
+        self.external_purchase_id
+            .as_deref()
+            .is_some_and(|id| Self::without_sandbox_prefix(id).is_some())
+    }
+
+    /// The environment the token was created in, derived from the external purchase ID prefix.
+    pub fn environment(&self) -> Environment {
+        if self.is_sandbox_token() {
+            Environment::Sandbox
+        } else {
+            Environment::Production
+        }
+    }
+
+    /// The external purchase ID with any sandbox prefix removed.
+    ///
+    /// Use this when storing or reporting tokens so sandbox and production IDs share a format.
+    pub fn normalized_external_purchase_id(&self) -> Option<&str> {
+        self.external_purchase_id.as_deref().map(Self::strip_sandbox_prefix)
+    }
+
+    /// Removes the sandbox prefix from an external purchase ID, if present.
+    ///
+    /// The prefix is `SANDBOX` followed by an optional `_`, the same rule
+    /// [`is_sandbox_token`](Self::is_sandbox_token) uses, so an ID is stripped exactly when it's
+    /// detected as a sandbox ID.
+    pub fn strip_sandbox_prefix(external_purchase_id: &str) -> &str {
+        Self::without_sandbox_prefix(external_purchase_id).unwrap_or(external_purchase_id)
+    }
+
+    fn without_sandbox_prefix(external_purchase_id: &str) -> Option<&str> {
+        external_purchase_id
+            .strip_prefix(Self::SANDBOX_MARKER)
+            .map(|id| id.strip_prefix('_').unwrap_or(id))
+    }
+}
@@ -0,0 +1,9 @@
+#[derive(thiserror::Error, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum HistoryRequestError {
+    #[error("InvalidDateRangeError: [{0}]")]
+    InvalidDateRangeError(String),
+
+    #[error("InvalidParameterError: [{0}]")]
+    InvalidParameterError(String),
+}
@@ -1,10 +1,12 @@
 use crate::primitives::environment::Environment;
+use crate::primitives::jws_transaction_decoded_payload::JWSTransactionDecodedPayload;
+use crate::signed_data_verifier::{SignedDataVerifier, SignedDataVerifierError};
 use serde::{Deserialize, Serialize};
 
 /// A response that contains the customer’s transaction history for an app.
 ///
 /// [HistoryResponse](https://developer.apple.com/documentation/appstoreserverapi/historyresponse)
-#[derive(Debug, Clone, Deserialize, Serialize, Hash)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct HistoryResponse {
     /// A token you use in a query to request the next set of transactions for the customer.
     ///
@@ -42,3 +44,47 @@ pub struct HistoryResponse {
     #[serde(rename = "signedTransactions")]
     pub signed_transactions: Option<Vec<String>>,
 }
+
+impl HistoryResponse {
+    /// Verifies and decodes each of the signed transactions with `verifier`.
+    ///
+    /// Returns one result per transaction, in the order of `signed_transactions`, or an empty vector
+    /// if the response has no transactions.
+    pub fn decode_all(
+        &self,
+        verifier: &SignedDataVerifier,
+    ) -> Vec<Result<JWSTransactionDecodedPayload, SignedDataVerifierError>> {
+        self.signed_transactions
+            .iter()
+            .flatten()
+            .map(|signed_transaction| verifier.verify_and_decode_signed_transaction(signed_transaction))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::notification_type_v2::NotificationTypeV2;
+    use crate::test_utils::{local_testing_verifier, signed_transaction_fixture};
+
+    #[test]
+    fn test_decode_all() {
+        let verifier = local_testing_verifier();
+        assert!(HistoryResponse::default().decode_all(&verifier).is_empty());
+
+        let response = HistoryResponse {
+            signed_transactions: Some(vec![
+                signed_transaction_fixture(&NotificationTypeV2::DidRenew, "1001"),
+                signed_transaction_fixture(&NotificationTypeV2::DidRenew, "1002"),
+                "invalid".to_string(),
+            ]),
+            ..Default::default()
+        };
+
+        let transactions = response.decode_all(&verifier);
+        assert_eq!(Some("1001"), transactions[0].as_ref().unwrap().transaction_id.as_deref());
+        assert_eq!(Some("1002"), transactions[1].as_ref().unwrap().transaction_id.as_deref());
+        assert!(transactions[2].is_err());
+    }
+}
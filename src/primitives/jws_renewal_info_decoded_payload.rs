@@ -1,19 +1,22 @@
+use crate::primitives::app_transaction_id::AppTransactionId;
 use crate::primitives::auto_renew_status::AutoRenewStatus;
+use crate::primitives::currency::Currency;
 use crate::primitives::environment::Environment;
 use crate::primitives::expiration_intent::ExpirationIntent;
 use crate::primitives::offer_type::OfferType;
+use crate::primitives::original_transaction_id::OriginalTransactionId;
 use crate::primitives::price_increase_status::PriceIncreaseStatus;
-use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_with::formats::Flexible;
 use serde_with::TimestampMilliSeconds;
 use crate::primitives::offer_discount_type::OfferDiscountType;
+use crate::primitives::timestamp::Timestamp;
 
 /// A decoded payload containing subscription renewal information for an auto-renewable subscription.
 ///
 /// [JWSRenewalInfoDecodedPayload](https://developer.apple.com/documentation/appstoreserverapi/jwsrenewalinfodecodedpayload)
 #[serde_with::serde_as]
-#[derive(Debug, Clone, Deserialize, Serialize, Hash)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct JWSRenewalInfoDecodedPayload {
     /// The reason the subscription expired.
     ///
@@ -25,7 +28,7 @@ pub struct JWSRenewalInfoDecodedPayload {
     ///
     /// [originalTransactionId](https://developer.apple.com/documentation/appstoreserverapi/originaltransactionid)
     #[serde(rename = "originalTransactionId")]
-    pub original_transaction_id: Option<String>,
+    pub original_transaction_id: Option<OriginalTransactionId>,
 
     /// The product identifier of the product that will renew at the next billing period.
     ///
@@ -62,7 +65,7 @@ pub struct JWSRenewalInfoDecodedPayload {
     /// [gracePeriodExpiresDate](https://developer.apple.com/documentation/appstoreserverapi/graceperiodexpiresdate)
     #[serde(rename = "gracePeriodExpiresDate")]
     #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
-    pub grace_period_expires_date: Option<DateTime<Utc>>,
+    pub grace_period_expires_date: Option<Timestamp>,
 
     /// The type of the subscription offer.
     ///
@@ -81,7 +84,7 @@ pub struct JWSRenewalInfoDecodedPayload {
     /// [signedDate](https://developer.apple.com/documentation/appstoreserverapi/signeddate)
     #[serde(rename = "signedDate")]
     #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
-    pub signed_date: Option<DateTime<Utc>>,
+    pub signed_date: Option<Timestamp>,
 
     /// The server environment, either sandbox or production.
     ///
@@ -94,19 +97,19 @@ pub struct JWSRenewalInfoDecodedPayload {
     /// [recentSubscriptionStartDate](https://developer.apple.com/documentation/appstoreserverapi/recentsubscriptionstartdate)
     #[serde(rename = "recentSubscriptionStartDate")]
     #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
-    pub recent_subscription_start_date: Option<DateTime<Utc>>,
+    pub recent_subscription_start_date: Option<Timestamp>,
 
     /// The UNIX time, in milliseconds, when the most recent auto-renewable subscription purchase expires.
     ///
     /// [renewalDate](https://developer.apple.com/documentation/appstoreserverapi/renewaldate)
     #[serde(rename = "renewalDate")]
     #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
-    pub renewal_date: Option<DateTime<Utc>>,
+    pub renewal_date: Option<Timestamp>,
 
     ///The currency code for the renewalPrice of the subscription.
     ///
     ///[currency](https://developer.apple.com/documentation/appstoreserverapi/currency)
-    pub currency: Option<String>,
+    pub currency: Option<Currency>,
 
     ///The renewal price, in milliunits, of the auto-renewable subscription that renews at the next billing period.
     ///
@@ -124,5 +127,11 @@ pub struct JWSRenewalInfoDecodedPayload {
     ///
     ///[eligibleWinBackOfferIds](https://developer.apple.com/documentation/appstoreserverapi/eligiblewinbackofferids)
     #[serde(rename = "eligibleWinBackOfferIds")]
-    pub eligible_win_back_offer_ids: Option<Vec<String>>
+    pub eligible_win_back_offer_ids: Option<Vec<String>>,
+
+    /// The unique identifier of the app download transaction.
+    ///
+    /// [appTransactionId](https://developer.apple.com/documentation/appstoreserverapi/apptransactionid)
+    #[serde(rename = "appTransactionId")]
+    pub app_transaction_id: Option<AppTransactionId>,
 }
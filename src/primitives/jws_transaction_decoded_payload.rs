@@ -1,11 +1,17 @@
+use crate::primitives::app_transaction_id::AppTransactionId;
+use crate::primitives::currency::Currency;
 use crate::primitives::environment::Environment;
 use crate::primitives::in_app_ownership_type::InAppOwnershipType;
+use crate::primitives::milliunits::Milliunits;
 use crate::primitives::offer_discount_type::OfferDiscountType;
 use crate::primitives::offer_type::OfferType;
+use crate::primitives::original_transaction_id::OriginalTransactionId;
 use crate::primitives::product_type::ProductType;
 use crate::primitives::revocation_reason::RevocationReason;
+use crate::primitives::timestamp::Timestamp;
+use crate::primitives::transaction_id::TransactionId;
 use crate::primitives::transaction_reason::TransactionReason;
-use chrono::{DateTime, Utc};
+use crate::primitives::web_order_line_item_id::WebOrderLineItemId;
 use serde_with::formats::Flexible;
 use serde_with::TimestampMilliSeconds;
 use uuid::Uuid;
@@ -14,25 +20,25 @@ use uuid::Uuid;
 ///
 /// [JWSTransactionDecodedPayload](https://developer.apple.com/documentation/appstoreserverapi/jwstransactiondecodedpayload)
 #[serde_with::serde_as]
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, Hash)]
+#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize, Hash, PartialEq, Eq)]
 pub struct JWSTransactionDecodedPayload {
     /// The original transaction identifier of a purchase.
     ///
     /// [originalTransactionId](https://developer.apple.com/documentation/appstoreserverapi/originaltransactionid)
     #[serde(rename = "originalTransactionId")]
-    pub original_transaction_id: Option<String>,
+    pub original_transaction_id: Option<OriginalTransactionId>,
 
     /// The unique identifier for a transaction such as an in-app purchase, restored in-app purchase, or subscription renewal.
     ///
     /// [transactionId](https://developer.apple.com/documentation/appstoreserverapi/transactionid)
     #[serde(rename = "transactionId")]
-    pub transaction_id: Option<String>,
+    pub transaction_id: Option<TransactionId>,
 
     /// The unique identifier of subscription-purchase events across devices, including renewals.
     ///
     /// [webOrderLineItemId](https://developer.apple.com/documentation/appstoreserverapi/weborderlineitemid)
     #[serde(rename = "webOrderLineItemId")]
-    pub web_order_line_item_id: Option<String>,
+    pub web_order_line_item_id: Option<WebOrderLineItemId>,
 
     /// The bundle identifier of an app.
     ///
@@ -57,21 +63,21 @@ pub struct JWSTransactionDecodedPayload {
     /// [purchaseDate](https://developer.apple.com/documentation/appstoreserverapi/purchasedate)
     #[serde(rename = "purchaseDate")]
     #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
-    pub purchase_date: Option<DateTime<Utc>>,
+    pub purchase_date: Option<Timestamp>,
 
     /// The purchase date of the transaction associated with the original transaction identifier.
     ///
     /// [originalPurchaseDate](https://developer.apple.com/documentation/appstoreserverapi/originalpurchasedate)
     #[serde(rename = "originalPurchaseDate")]
     #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
-    pub original_purchase_date: Option<DateTime<Utc>>,
+    pub original_purchase_date: Option<Timestamp>,
 
     /// The UNIX time, in milliseconds, an auto-renewable subscription expires or renews.
     ///
     /// [expiresDate](https://developer.apple.com/documentation/appstoreserverapi/expiresdate)
     #[serde(rename = "expiresDate")]
     #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
-    pub expires_date: Option<DateTime<Utc>>,
+    pub expires_date: Option<Timestamp>,
 
     /// The number of consumable products purchased.
     ///
@@ -91,6 +97,12 @@ pub struct JWSTransactionDecodedPayload {
     #[serde(rename = "appAccountToken")]
     pub app_account_token: Option<Uuid>,
 
+    /// The unique identifier of the app download transaction.
+    ///
+    /// [appTransactionId](https://developer.apple.com/documentation/appstoreserverapi/apptransactionid)
+    #[serde(rename = "appTransactionId")]
+    pub app_transaction_id: Option<AppTransactionId>,
+
     /// A string that describes whether the transaction was purchased by the user, or is available to them through Family Sharing.
     ///
     /// [inAppOwnershipType](https://developer.apple.com/documentation/appstoreserverapi/inappownershiptype)
@@ -102,7 +114,7 @@ pub struct JWSTransactionDecodedPayload {
     /// [signedDate](https://developer.apple.com/documentation/appstoreserverapi/signeddate)
     #[serde(rename = "signedDate")]
     #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
-    pub signed_date: Option<DateTime<Utc>>,
+    pub signed_date: Option<Timestamp>,
 
     /// The reason that the App Store refunded the transaction or revoked it from family sharing.
     ///
@@ -115,7 +127,7 @@ pub struct JWSTransactionDecodedPayload {
     /// [revocationDate](https://developer.apple.com/documentation/appstoreserverapi/revocationdate)
     #[serde(rename = "revocationDate")]
     #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
-    pub revocation_date: Option<DateTime<Utc>>,
+    pub revocation_date: Option<Timestamp>,
 
     /// The Boolean value that indicates whether the user upgraded to another subscription.
     ///
@@ -160,7 +172,7 @@ pub struct JWSTransactionDecodedPayload {
     /// The three-letter ISO 4217 currency code for the price of the product.
     ///
     /// [currency](https://developer.apple.com/documentation/appstoreserverapi/currency)
-    pub currency: Option<String>,
+    pub currency: Option<Currency>,
 
     /// The price, in milliunits, of the in-app purchase or subscription offer that you configured in App Store Connect.
     ///
@@ -173,3 +185,29 @@ pub struct JWSTransactionDecodedPayload {
     #[serde(rename = "offerDiscountType")]
     pub offer_discount_type: Option<OfferDiscountType>,
 }
+
+impl JWSTransactionDecodedPayload {
+    /// The price of the transaction as [`Milliunits`] of its `currency`.
+    pub fn price_milliunits(&self) -> Option<Milliunits> {
+        self.price.map(Milliunits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_default_and_equality() {
+        let transaction = JWSTransactionDecodedPayload {
+            transaction_id: Some("1".into()),
+            price: Some(12990),
+            ..Default::default()
+        };
+        let decoded: JWSTransactionDecodedPayload = serde_json::from_str(r#"{"transactionId":"1","price":12990}"#).unwrap();
+
+        assert_eq!(transaction, decoded);
+        assert!(HashSet::from([transaction]).contains(&decoded));
+    }
+}
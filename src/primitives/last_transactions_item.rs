@@ -1,10 +1,14 @@
+use crate::primitives::jws_renewal_info_decoded_payload::JWSRenewalInfoDecodedPayload;
+use crate::primitives::jws_transaction_decoded_payload::JWSTransactionDecodedPayload;
+use crate::primitives::original_transaction_id::OriginalTransactionId;
 use crate::primitives::status::Status;
+use crate::signed_data_verifier::{SignedDataVerifier, SignedDataVerifierError};
 use serde::{Deserialize, Serialize};
 
 /// The most recent App Store-signed transaction information and App Store-signed renewal information for an auto-renewable subscription.
 ///
 /// [lastTransactionsItem](https://developer.apple.com/documentation/appstoreserverapi/lasttransactionsitem)
-#[derive(Debug, Clone, Deserialize, Serialize, Hash, PartialEq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct LastTransactionsItem {
     /// The status of the auto-renewable subscription.
     ///
@@ -16,7 +20,7 @@ pub struct LastTransactionsItem {
     ///
     /// [originalTransactionId](https://developer.apple.com/documentation/appstoreserverapi/originaltransactionid)
     #[serde(rename = "originalTransactionId")]
-    pub original_transaction_id: Option<String>,
+    pub original_transaction_id: Option<OriginalTransactionId>,
 
     /// Transaction information signed by the App Store, in JSON Web Signature (JWS) format.
     ///
@@ -30,3 +34,27 @@ pub struct LastTransactionsItem {
     #[serde(rename = "signedRenewalInfo")]
     pub signed_renewal_info: Option<String>,
 }
+
+impl LastTransactionsItem {
+    /// Verifies and decodes the signed transaction info with `verifier`, or returns `Ok(None)` if the item has none.
+    pub fn decode_transaction_info(
+        &self,
+        verifier: &SignedDataVerifier,
+    ) -> Result<Option<JWSTransactionDecodedPayload>, SignedDataVerifierError> {
+        self.signed_transaction_info
+            .as_deref()
+            .map(|signed_transaction_info| verifier.verify_and_decode_signed_transaction(signed_transaction_info))
+            .transpose()
+    }
+
+    /// Verifies and decodes the signed renewal info with `verifier`, or returns `Ok(None)` if the item has none.
+    pub fn decode_renewal_info(
+        &self,
+        verifier: &SignedDataVerifier,
+    ) -> Result<Option<JWSRenewalInfoDecodedPayload>, SignedDataVerifierError> {
+        self.signed_renewal_info
+            .as_deref()
+            .map(|signed_renewal_info| verifier.verify_and_decode_renewal_info(signed_renewal_info))
+            .transpose()
+    }
+}
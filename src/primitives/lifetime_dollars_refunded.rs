@@ -1,17 +1,15 @@
-use serde_repr::{Deserialize_repr, Serialize_repr};
-
-/// A value that indicates the dollar amount of refunds the customer has received in your app, since purchasing the app, across all platforms.
-///
-/// [lifetimeDollarsRefunded](https://developer.apple.com/documentation/appstoreserverapi/lifetimedollarsrefunded)
-#[derive(Debug, Clone, Deserialize_repr, Serialize_repr, Hash, PartialEq, Eq)]
-#[repr(u8)]
-pub enum LifetimeDollarsRefunded {
-    Undeclared = 0,
-    ZeroDollars = 1,
-    OneCentToFortyNineDollarsAndNinetyNineCents = 2,
-    FiftyDollarsToNinetyNineDollarsAndNinetyNineCents = 3,
-    OneHundredDollarsToFourHundredNinetyNineDollarsAndNinetyNineCents = 4,
-    FiveHundredDollarsToNineHundredNinetyNineDollarsAndNinetyNineCents = 5,
-    OneThousandDollarsToOneThousandNineHundredNinetyNineDollarsAndNinetyNineCents = 6,
-    TwoThousandDollarsOrGreater = 7,
+integer_enum! {
+    /// A value that indicates the dollar amount of refunds the customer has received in your app, since purchasing the app, across all platforms.
+    ///
+    /// [lifetimeDollarsRefunded](https://developer.apple.com/documentation/appstoreserverapi/lifetimedollarsrefunded)
+    pub enum LifetimeDollarsRefunded: u8 {
+        Undeclared = 0,
+        ZeroDollars = 1,
+        OneCentToFortyNineDollarsAndNinetyNineCents = 2,
+        FiftyDollarsToNinetyNineDollarsAndNinetyNineCents = 3,
+        OneHundredDollarsToFourHundredNinetyNineDollarsAndNinetyNineCents = 4,
+        FiveHundredDollarsToNineHundredNinetyNineDollarsAndNinetyNineCents = 5,
+        OneThousandDollarsToOneThousandNineHundredNinetyNineDollarsAndNinetyNineCents = 6,
+        TwoThousandDollarsOrGreater = 7,
+    }
 }
@@ -0,0 +1,301 @@
+/// Defines an enum of the string values of a field, with an `Unknown` variant that keeps any value
+/// this version of the library doesn't recognize, so that payloads using values Apple adds later
+/// still decode. The enum implements [`RawValue`](crate::primitives::raw_value::RawValue).
+///
+/// Each variant is given the raw value it is serialized as, optionally followed by `|` and other
+/// raw values that are accepted when deserializing.
+macro_rules! string_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident = $raw:literal $(| $alias:literal)*,
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Hash, PartialEq, Eq)]
+        $vis enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant,
+            )*
+            /// A value that this version of the library doesn't recognize.
+            Unknown(String),
+        }
+
+        impl $name {
+            pub fn raw_value(&self) -> &str {
+                match self {
+                    $($name::$variant => $raw,)*
+                    $name::Unknown(raw) => raw,
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(raw: &str) -> Self {
+                match raw {
+                    $($raw $(| $alias)* => $name::$variant,)*
+                    _ => $name::Unknown(raw.to_string()),
+                }
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = std::convert::Infallible;
+
+            fn from_str(raw: &str) -> Result<Self, Self::Err> {
+                Ok($name::from(raw))
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.raw_value())
+            }
+        }
+
+        impl $crate::primitives::raw_value::RawValue for $name {
+            type Raw<'a> = &'a str;
+
+            fn raw_value(&self) -> &str {
+                $name::raw_value(self)
+            }
+
+            fn from_raw(raw: &str) -> Self {
+                $name::from(raw)
+            }
+
+            fn known_values() -> &'static [Self] {
+                const KNOWN_VALUES: &[$name] = &[$($name::$variant,)*];
+                KNOWN_VALUES
+            }
+        }
+
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.raw_value())
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let raw = String::deserialize(deserializer)?;
+                Ok($name::from(raw.as_str()))
+            }
+        }
+    };
+}
+
+/// Defines an enum of the integer values of a field, with an `Unknown` variant that keeps any value
+/// this version of the library doesn't recognize, so that payloads using values Apple adds later
+/// still decode. The enum implements [`RawValue`](crate::primitives::raw_value::RawValue).
+macro_rules! integer_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident: $repr:ident {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident = $raw:literal,
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Hash, PartialEq, Eq)]
+        $vis enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant,
+            )*
+            /// A value that this version of the library doesn't recognize.
+            Unknown($repr),
+        }
+
+        impl $name {
+            pub fn raw_value(&self) -> $repr {
+                match self {
+                    $($name::$variant => $raw,)*
+                    $name::Unknown(raw) => *raw,
+                }
+            }
+        }
+
+        impl From<$repr> for $name {
+            fn from(raw: $repr) -> Self {
+                match raw {
+                    $($raw => $name::$variant,)*
+                    _ => $name::Unknown(raw),
+                }
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = std::num::ParseIntError;
+
+            fn from_str(raw: &str) -> Result<Self, Self::Err> {
+                raw.parse::<$repr>().map($name::from)
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.raw_value())
+            }
+        }
+
+        impl $crate::primitives::raw_value::RawValue for $name {
+            type Raw<'a> = $repr;
+
+            fn raw_value(&self) -> $repr {
+                $name::raw_value(self)
+            }
+
+            fn from_raw(raw: $repr) -> Self {
+                $name::from(raw)
+            }
+
+            fn known_values() -> &'static [Self] {
+                const KNOWN_VALUES: &[$name] = &[$($name::$variant,)*];
+                KNOWN_VALUES
+            }
+        }
+
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serde::Serialize::serialize(&self.raw_value(), serializer)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                Ok($name::from(<$repr as serde::Deserialize>::deserialize(deserializer)?))
+            }
+        }
+    };
+}
+
+/// Defines a newtype over the `String` value of an identifier, so that identifiers of different kinds
+/// can't be passed in place of each other. The identifier serializes to and from the bare string and
+/// dereferences to `str`.
+macro_rules! string_identifier {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident;
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+        #[serde(transparent)]
+        $vis struct $name(String);
+
+        impl $name {
+            pub fn new(value: impl Into<String>) -> Self {
+                $name(value.into())
+            }
+
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            pub fn into_inner(self) -> String {
+                self.0
+            }
+        }
+
+        impl std::ops::Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(value: String) -> Self {
+                $name(value)
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                $name(value.to_string())
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::environment::Environment;
+    use crate::primitives::notification_type_v2::NotificationTypeV2;
+    use crate::primitives::original_transaction_id::OriginalTransactionId;
+    use crate::primitives::status::Status;
+    use crate::primitives::transaction_id::TransactionId;
+
+    #[test]
+    fn test_string_enum() {
+        assert_eq!(NotificationTypeV2::DidRenew, serde_json::from_str(r#""DID_RENEW""#).unwrap());
+        assert_eq!(r#""DID_RENEW""#, serde_json::to_string(&NotificationTypeV2::DidRenew).unwrap());
+
+        let unknown: NotificationTypeV2 = serde_json::from_str(r#""NEW_TYPE""#).unwrap();
+        assert_eq!(NotificationTypeV2::Unknown("NEW_TYPE".to_string()), unknown);
+        assert_eq!("NEW_TYPE", unknown.raw_value());
+        assert_eq!(r#""NEW_TYPE""#, serde_json::to_string(&unknown).unwrap());
+
+        assert_eq!(Environment::Production, serde_json::from_str(r#""PROD""#).unwrap());
+        assert_eq!(r#""Production""#, serde_json::to_string(&Environment::Production).unwrap());
+    }
+
+    #[test]
+    fn test_integer_enum() {
+        assert_eq!(Status::Revoked, serde_json::from_str("5").unwrap());
+        assert_eq!("5", serde_json::to_string(&Status::Revoked).unwrap());
+
+        let unknown: Status = serde_json::from_str("9").unwrap();
+        assert_eq!(Status::Unknown(9), unknown);
+        assert_eq!(9, unknown.raw_value());
+        assert_eq!("9", serde_json::to_string(&unknown).unwrap());
+    }
+
+    #[test]
+    fn test_string_identifier() {
+        let id: OriginalTransactionId = serde_json::from_str(r#""1000""#).unwrap();
+        assert_eq!("1000", id.as_str());
+        assert_eq!(id, "1000");
+        assert_eq!(r#""1000""#, serde_json::to_string(&id).unwrap());
+
+        let transaction_id = TransactionId::from(id);
+        assert_eq!("1000", transaction_id.to_string());
+    }
+}
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use crate::primitives::timestamp::Timestamp;
 use serde::{Deserialize, Serialize};
 use serde_with::formats::Flexible;
 use serde_with::TimestampMilliSeconds;
@@ -7,7 +7,7 @@ use serde_with::TimestampMilliSeconds;
 ///
 /// [MassExtendRenewalDateStatusResponse](https://developer.apple.com/documentation/appstoreserverapi/massextendrenewaldatestatusresponse)
 #[serde_with::serde_as]
-#[derive(Debug, Clone, Deserialize, Serialize, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct MassExtendRenewalDateStatusResponse {
     /// A string that contains a unique identifier you provide to track each subscription-renewal-date extension request.
     ///
@@ -25,7 +25,7 @@ pub struct MassExtendRenewalDateStatusResponse {
     /// [completeDate](https://developer.apple.com/documentation/appstoreserverapi/completedate)
     #[serde(rename = "completeDate")]
     #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
-    pub complete_date: Option<DateTime<Utc>>,
+    pub complete_date: Option<Timestamp>,
 
     /// The count of subscriptions that successfully receive a subscription-renewal-date extension.
     ///
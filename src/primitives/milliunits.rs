@@ -0,0 +1,261 @@
+use crate::primitives::currency::{Currency, CurrencyError};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum PriceParseError {
+    #[error("InvalidPriceError: [{0}]")]
+    InvalidPriceError(String),
+
+    #[error("CurrencyError: [{0}]")]
+    CurrencyError(#[from] CurrencyError),
+}
+
+/// A price in milliunits of a currency, the integer representation the App Store uses for prices.
+///
+/// One unit of the currency is 1000 milliunits, so `Milliunits(12990)` is 12.99 in the currency
+/// of the price. Milliunits serialize to and from the bare integer.
+///
+/// [price](https://developer.apple.com/documentation/appstoreserverapi/price)
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(transparent)]
+pub struct Milliunits(pub i64);
+
+impl Milliunits {
+    /// The number of milliunits in one unit of a currency.
+    pub const PER_UNIT: i64 = 1000;
+
+    pub const ZERO: Milliunits = Milliunits(0);
+
+    /// Creates a price from whole units of the currency, returning `None` on overflow.
+    pub fn from_units(units: i64) -> Option<Self> {
+        units.checked_mul(Self::PER_UNIT).map(Milliunits)
+    }
+
+    /// Creates a price from an amount in units of the currency, returning `None` if the amount
+    /// has more than three decimal places or doesn't fit.
+    #[cfg(feature = "decimal")]
+    pub fn from_decimal(amount: rust_decimal::Decimal) -> Option<Self> {
+        use rust_decimal::prelude::ToPrimitive;
+
+        let milliunits = amount.checked_mul(rust_decimal::Decimal::from(Self::PER_UNIT))?;
+        if !milliunits.fract().is_zero() {
+            return None;
+        }
+        milliunits.to_i64().map(Milliunits)
+    }
+
+    /// The amount in units of the currency.
+    #[cfg(feature = "decimal")]
+    pub fn to_decimal(self) -> rust_decimal::Decimal {
+        rust_decimal::Decimal::new(self.0, 3).normalize()
+    }
+
+    pub fn checked_add(self, other: Milliunits) -> Option<Self> {
+        self.0.checked_add(other.0).map(Milliunits)
+    }
+
+    pub fn checked_sub(self, other: Milliunits) -> Option<Self> {
+        self.0.checked_sub(other.0).map(Milliunits)
+    }
+
+    pub fn checked_mul(self, quantity: i64) -> Option<Self> {
+        self.0.checked_mul(quantity).map(Milliunits)
+    }
+
+    /// Parses a display price such as `4.99 USD` or `USD 4.99` into milliunits and its currency.
+    ///
+    /// The amount may have at most as many decimal places as the currency uses, so `4.999 USD` and
+    /// `120.5 JPY` are rejected rather than silently rounded.
+    pub fn parse(price: &str) -> Result<(Milliunits, Currency), PriceParseError> {
+        let invalid = || PriceParseError::InvalidPriceError(price.to_string());
+        let mut parts = price.split_whitespace();
+        let (amount, code) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(code), Some(amount), None) if code.starts_with(|c: char| c.is_ascii_alphabetic()) => (amount, code),
+            (Some(amount), Some(code), None) => (amount, code),
+            _ => return Err(invalid()),
+        };
+        let currency = Currency::from_code(code)?;
+
+        let (negative, amount) = match amount.strip_prefix('-') {
+            Some(amount) => (true, amount),
+            None => (false, amount),
+        };
+        let (units, fraction) = match amount.split_once('.') {
+            Some((_, "")) => return Err(invalid()),
+            Some((units, fraction)) => (units, fraction),
+            None => (amount, ""),
+        };
+        let is_digits = |digits: &str| digits.bytes().all(|b| b.is_ascii_digit());
+        if units.is_empty() || !is_digits(units) || !is_digits(fraction) || fraction.len() > currency.minor_unit_digits() as usize {
+            return Err(invalid());
+        }
+
+        let fraction: i64 = format!("{:0<3}", fraction).parse().map_err(|_| invalid())?;
+        let milliunits = units
+            .parse()
+            .ok()
+            .and_then(Milliunits::from_units)
+            .and_then(|milliunits| milliunits.checked_add(Milliunits(fraction)))
+            .ok_or_else(invalid)?;
+        Ok((if negative { -milliunits } else { milliunits }, currency))
+    }
+
+    /// Rounds the price half away from zero to the number of decimal places the currency uses,
+    /// such as `Milliunits(4995)` to `Milliunits(5000)` in USD, or `Milliunits(1500)` to `Milliunits(2000)` in JPY.
+    pub fn round(self, currency: &Currency) -> Milliunits {
+        let step = 10i64.pow(3 - currency.minor_unit_digits());
+        let remainder = self.0 % step;
+        let truncated = self.0 - remainder;
+        if remainder.abs() * 2 >= step {
+            Milliunits(truncated.saturating_add(step * remainder.signum()))
+        } else {
+            Milliunits(truncated)
+        }
+    }
+
+    /// Formats the price with the currency code and the number of decimal places the currency uses,
+    /// such as `USD 12.99` or `JPY 1200`. Milliunits beyond those decimal places are truncated.
+    pub fn format(self, currency: &Currency) -> String {
+        let decimals = currency.minor_unit_digits();
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let milliunits = self.0.unsigned_abs();
+        let units = milliunits / Self::PER_UNIT as u64;
+        let fraction = milliunits % Self::PER_UNIT as u64;
+
+        match decimals {
+            0 => format!("{} {}{}", currency, sign, units),
+            _ => {
+                let fraction = fraction / 10u64.pow(3 - decimals);
+                format!("{} {}{}.{:0width$}", currency, sign, units, fraction, width = decimals as usize)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Milliunits {
+    /// Writes the amount in units with three decimal places, such as `12.990`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let milliunits = self.0.unsigned_abs();
+        write!(f, "{}{}.{:03}", sign, milliunits / 1000, milliunits % 1000)
+    }
+}
+
+impl From<i64> for Milliunits {
+    fn from(value: i64) -> Self {
+        Milliunits(value)
+    }
+}
+
+impl From<Milliunits> for i64 {
+    fn from(value: Milliunits) -> Self {
+        value.0
+    }
+}
+
+impl Add for Milliunits {
+    type Output = Milliunits;
+
+    fn add(self, other: Milliunits) -> Milliunits {
+        Milliunits(self.0 + other.0)
+    }
+}
+
+impl AddAssign for Milliunits {
+    fn add_assign(&mut self, other: Milliunits) {
+        self.0 += other.0;
+    }
+}
+
+impl Sub for Milliunits {
+    type Output = Milliunits;
+
+    fn sub(self, other: Milliunits) -> Milliunits {
+        Milliunits(self.0 - other.0)
+    }
+}
+
+impl SubAssign for Milliunits {
+    fn sub_assign(&mut self, other: Milliunits) {
+        self.0 -= other.0;
+    }
+}
+
+impl Mul<i64> for Milliunits {
+    type Output = Milliunits;
+
+    fn mul(self, quantity: i64) -> Milliunits {
+        Milliunits(self.0 * quantity)
+    }
+}
+
+impl Neg for Milliunits {
+    type Output = Milliunits;
+
+    fn neg(self) -> Milliunits {
+        Milliunits(-self.0)
+    }
+}
+
+impl Sum for Milliunits {
+    fn sum<I: Iterator<Item = Milliunits>>(iter: I) -> Milliunits {
+        iter.fold(Milliunits::ZERO, Add::add)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_milliunits() {
+        let price: Milliunits = serde_json::from_str("12990").unwrap();
+        assert_eq!(Milliunits(12990), price);
+        assert_eq!("12990", serde_json::to_string(&price).unwrap());
+
+        assert_eq!("12.990", price.to_string());
+        assert_eq!("-0.500", Milliunits(-500).to_string());
+        assert_eq!("USD 12.99", price.format(&Currency::Usd));
+        assert_eq!("JPY 1200", Milliunits(1_200_000).format(&Currency::Jpy));
+        assert_eq!("KWD 1.250", Milliunits(1250).format(&Currency::Kwd));
+
+        assert_eq!(Milliunits(25980), price * 2);
+        assert_eq!(Milliunits(38970), [price, price, price].into_iter().sum());
+        assert_eq!(Some(Milliunits(5000)), Milliunits::from_units(5));
+        assert_eq!(None, Milliunits(i64::MAX).checked_add(price));
+    }
+
+    #[test]
+    fn test_parse_and_round() {
+        assert_eq!(Ok((Milliunits(4990), Currency::Usd)), Milliunits::parse("4.99 USD"));
+        assert_eq!(Ok((Milliunits(12990), Currency::Usd)), Milliunits::parse("USD 12.99"));
+        assert_eq!(Ok((Milliunits(-500), Currency::Eur)), Milliunits::parse("-0.5 EUR"));
+        assert_eq!(Ok((Milliunits(1_200_000), Currency::Jpy)), Milliunits::parse("JPY 1200"));
+        assert_eq!(Ok((Milliunits(1250), Currency::Kwd)), Milliunits::parse("1.250 KWD"));
+        for invalid in ["4.999 USD", "120.5 JPY", "4. USD", ".99 USD", "4,99 USD", "4.99", "4.99 USD extra", "99999999999999999 USD"] {
+            assert_eq!(Err(PriceParseError::InvalidPriceError(invalid.to_string())), Milliunits::parse(invalid));
+        }
+        assert!(matches!(Milliunits::parse("4.99 XYZ"), Err(PriceParseError::CurrencyError(_))));
+
+        assert_eq!(Milliunits(5000), Milliunits(4995).round(&Currency::Usd));
+        assert_eq!(Milliunits(4990), Milliunits(4994).round(&Currency::Usd));
+        assert_eq!(Milliunits(-5000), Milliunits(-4995).round(&Currency::Usd));
+        assert_eq!(Milliunits(2000), Milliunits(1500).round(&Currency::Jpy));
+        assert_eq!(Milliunits(1251), Milliunits(1251).round(&Currency::Kwd));
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn test_decimal() {
+        use rust_decimal::Decimal;
+        use std::str::FromStr;
+
+        assert_eq!(Some(Milliunits(12990)), Milliunits::from_decimal(Decimal::from_str("12.99").unwrap()));
+        assert_eq!(None, Milliunits::from_decimal(Decimal::from_str("0.0001").unwrap()));
+        assert_eq!(Decimal::from_str("12.99").unwrap(), Milliunits(12990).to_decimal());
+    }
+}
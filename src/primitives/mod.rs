@@ -1,3 +1,6 @@
+#[macro_use]
+mod macros;
+
 pub mod account_tenure;
 pub mod app_transaction;
 pub mod auto_renew_status;
@@ -54,3 +57,27 @@ pub mod user_status;
 pub mod external_purchase_token;
 pub mod consumption_request_reason;
 pub mod refund_preference;
+pub mod verify_receipt_status;
+pub mod verify_receipt_response;
+pub mod receipt;
+pub mod receipt_in_app_purchase;
+pub mod pending_renewal_info;
+pub mod web_order_line_item_id;
+pub mod transaction_id;
+pub mod original_transaction_id;
+pub mod app_transaction_id;
+pub mod advanced_commerce;
+pub mod decoded_notification;
+pub mod raw_value;
+pub mod milliunits;
+pub mod timestamp;
+pub mod currency;
+pub mod history_request_error;
+pub mod app_store_event;
+pub mod notification_type_v1;
+pub mod unified_receipt;
+pub mod response_body_v1;
+mod string_encoded;
+
+#[cfg(test)]
+mod round_trip;
@@ -1,6 +1,9 @@
+use crate::clock::{Clock, SystemClock};
+use crate::primitives::history_request_error::HistoryRequestError;
 use crate::primitives::notification_type_v2::NotificationTypeV2;
 use crate::primitives::subtype::Subtype;
-use chrono::{DateTime, Utc};
+use crate::primitives::timestamp::{self, Duration, Timestamp};
+use crate::primitives::transaction_id::TransactionId;
 use serde::{Deserialize, Serialize};
 use serde_with::formats::Flexible;
 use serde_with::TimestampMilliSeconds;
@@ -9,7 +12,7 @@ use serde_with::TimestampMilliSeconds;
 ///
 /// [NotificationHistoryRequest](https://developer.apple.com/documentation/appstoreserverapi/notificationhistoryrequest)
 #[serde_with::serde_as]
-#[derive(Debug, Clone, Deserialize, Serialize, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct NotificationHistoryRequest {
     /// The start date of the timespan for the requested App Store Server Notification history records.
     /// The startDate needs to precede the endDate. Choose a startDate that’s within the past 180 days from the current date.
@@ -17,7 +20,7 @@ pub struct NotificationHistoryRequest {
     /// [startDate](https://developer.apple.com/documentation/appstoreserverapi/startdate)
     #[serde(rename = "startDate")]
     #[serde_as(as = "Option<TimestampMilliSeconds<i64, Flexible>>")]
-    pub start_date: Option<DateTime<Utc>>,
+    pub start_date: Option<Timestamp>,
 
     /// The end date of the timespan for the requested App Store Server Notification history records.
     /// Choose an endDate that’s later than the startDate. If you choose an endDate in the future, the endpoint automatically uses the current date as the endDate.
@@ -25,7 +28,7 @@ pub struct NotificationHistoryRequest {
     /// [endDate](https://developer.apple.com/documentation/appstoreserverapi/enddate)
     #[serde(rename = "endDate")]
     #[serde_as(as = "Option<TimestampMilliSeconds<i64, Flexible>>")]
-    pub end_date: Option<DateTime<Utc>>,
+    pub end_date: Option<Timestamp>,
 
     /// A notification type. Provide this field to limit the notification history records to those with this one notification type.
     /// For a list of notifications types, see notificationType.
@@ -48,7 +51,7 @@ pub struct NotificationHistoryRequest {
     ///
     /// [transactionId](https://developer.apple.com/documentation/appstoreserverapi/transactionid)
     #[serde(rename = "transactionId")]
-    pub transaction_id: Option<String>,
+    pub transaction_id: Option<TransactionId>,
 
     /// A Boolean value you set to true to request only the notifications that haven’t reached your server successfully.
     /// The response also includes notifications that the App Store server is currently retrying to send to your server.
@@ -57,3 +60,201 @@ pub struct NotificationHistoryRequest {
     #[serde(rename = "onlyFailures")]
     pub only_failures: Option<bool>,
 }
+
+impl NotificationHistoryRequest {
+    /// The number of days for which the App Store keeps the notification history.
+    pub const MAX_HISTORY_DAYS: i64 = 180;
+
+    /// Creates a `NotificationHistoryRequestBuilder` for the notifications sent between `start_date` and `end_date`.
+    pub fn builder(start_date: Timestamp, end_date: Timestamp) -> NotificationHistoryRequestBuilder {
+        Self::builder_at(start_date, end_date, timestamp::now_of(&SystemClock))
+    }
+
+    /// Creates a `NotificationHistoryRequestBuilder` for the notifications sent within the last `duration`.
+    pub fn builder_for_last(duration: Duration) -> NotificationHistoryRequestBuilder {
+        Self::builder_for_last_with_clock(duration, &SystemClock)
+    }
+
+    /// Creates a `NotificationHistoryRequestBuilder` for the notifications sent within the last `duration`
+    /// before the time of `clock`, which the request is also validated against.
+    pub fn builder_for_last_with_clock(duration: Duration, clock: &dyn Clock) -> NotificationHistoryRequestBuilder {
+        let now = timestamp::now_of(clock);
+        Self::builder_at(now - duration, now, now)
+    }
+
+    fn builder_at(start_date: Timestamp, end_date: Timestamp, now: Timestamp) -> NotificationHistoryRequestBuilder {
+        NotificationHistoryRequestBuilder {
+            now,
+            request: NotificationHistoryRequest {
+                start_date: Some(start_date),
+                end_date: Some(end_date),
+                notification_type: None,
+                notification_subtype: None,
+                transaction_id: None,
+                only_failures: None,
+            },
+        }
+    }
+
+    /// Checks the request against the constraints of the App Store Server API, so that an invalid request
+    /// fails before it is sent.
+    pub fn validate(&self) -> Result<(), HistoryRequestError> {
+        self.validate_with_clock(&SystemClock)
+    }
+
+    /// Checks the request against the constraints of the App Store Server API as of the time of `clock`.
+    pub fn validate_with_clock(&self, clock: &dyn Clock) -> Result<(), HistoryRequestError> {
+        self.validate_at(timestamp::now_of(clock))
+    }
+
+    /// Checks the request against the constraints of the App Store Server API as of `now`.
+    ///
+    /// # Returns
+    ///
+    /// - `Err(HistoryRequestError::InvalidDateRangeError)` if a date is missing, the end date doesn't follow
+    ///   the start date, or the start date is more than [`MAX_HISTORY_DAYS`](Self::MAX_HISTORY_DAYS) days ago.
+    /// - `Err(HistoryRequestError::InvalidParameterError)` if both a notification type and a transaction ID are set,
+    ///   or a notification subtype is set without its notification type.
+    pub fn validate_at(&self, now: Timestamp) -> Result<(), HistoryRequestError> {
+        let (Some(start_date), Some(end_date)) = (self.start_date, self.end_date) else {
+            return Err(HistoryRequestError::InvalidDateRangeError("startDate and endDate are required".to_string()));
+        };
+
+        if end_date <= start_date {
+            return Err(HistoryRequestError::InvalidDateRangeError("endDate must follow startDate".to_string()));
+        }
+
+        if start_date < now - Duration::days(Self::MAX_HISTORY_DAYS) {
+            return Err(HistoryRequestError::InvalidDateRangeError(format!(
+                "startDate must be within the past {} days",
+                Self::MAX_HISTORY_DAYS
+            )));
+        }
+
+        if self.notification_type.is_some() && self.transaction_id.is_some() {
+            return Err(HistoryRequestError::InvalidParameterError(
+                "Include either the transactionId or the notificationType, but not both".to_string(),
+            ));
+        }
+
+        if self.notification_subtype.is_some() && self.notification_type.is_none() {
+            return Err(HistoryRequestError::InvalidParameterError(
+                "notificationSubtype requires its notificationType".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// A builder for [`NotificationHistoryRequest`], created by [`NotificationHistoryRequest::builder`].
+#[derive(Debug, Clone)]
+pub struct NotificationHistoryRequestBuilder {
+    /// The time the builder was created at, which the start date is checked against.
+    now: Timestamp,
+    request: NotificationHistoryRequest,
+}
+
+impl NotificationHistoryRequestBuilder {
+    /// Limits the history to notifications of a type.
+    pub fn notification_type(mut self, notification_type: NotificationTypeV2) -> Self {
+        self.request.notification_type = Some(notification_type);
+        self
+    }
+
+    /// Limits the history to notifications of a type and subtype.
+    pub fn notification_subtype(mut self, notification_type: NotificationTypeV2, notification_subtype: Subtype) -> Self {
+        self.request.notification_type = Some(notification_type);
+        self.request.notification_subtype = Some(notification_subtype);
+        self
+    }
+
+    /// Limits the history to the notifications of the customer a transaction belongs to.
+    pub fn transaction_id(mut self, transaction_id: impl Into<TransactionId>) -> Self {
+        self.request.transaction_id = Some(transaction_id.into());
+        self
+    }
+
+    /// Limits the history to notifications that haven't reached the server successfully.
+    pub fn only_failures(mut self, only_failures: bool) -> Self {
+        self.request.only_failures = Some(only_failures);
+        self
+    }
+
+    /// Creates the configured `NotificationHistoryRequest`, failing if it doesn't pass
+    /// [`NotificationHistoryRequest::validate_at`] the time the builder was created at.
+    pub fn build(self) -> Result<NotificationHistoryRequest, HistoryRequestError> {
+        self.request.validate_at(self.now)?;
+        Ok(self.request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FrozenClock;
+
+    #[test]
+    fn test_builder() {
+        let request = NotificationHistoryRequest::builder_for_last(Duration::days(7))
+            .notification_subtype(NotificationTypeV2::Subscribed, Subtype::InitialBuy)
+            .only_failures(true)
+            .build()
+            .unwrap();
+        assert_eq!(Some(NotificationTypeV2::Subscribed), request.notification_type);
+        assert_eq!(Some(Subtype::InitialBuy), request.notification_subtype);
+        assert_eq!(Some(true), request.only_failures);
+
+        let now = timestamp::now();
+        assert!(matches!(
+            NotificationHistoryRequest::builder(now, now - Duration::days(1)).build(),
+            Err(HistoryRequestError::InvalidDateRangeError(_))
+        ));
+        assert!(matches!(
+            NotificationHistoryRequest::builder(now, now).build(),
+            Err(HistoryRequestError::InvalidDateRangeError(_))
+        ));
+        assert!(NotificationHistoryRequest::builder_for_last(Duration::days(NotificationHistoryRequest::MAX_HISTORY_DAYS)).build().is_ok());
+        assert!(matches!(
+            NotificationHistoryRequest::builder_for_last(Duration::days(NotificationHistoryRequest::MAX_HISTORY_DAYS) + Duration::milliseconds(1)).build(),
+            Err(HistoryRequestError::InvalidDateRangeError(_))
+        ));
+        assert!(matches!(
+            NotificationHistoryRequest::builder_for_last(Duration::days(1))
+                .notification_type(NotificationTypeV2::Refund)
+                .transaction_id("1234")
+                .build(),
+            Err(HistoryRequestError::InvalidParameterError(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate() {
+        let now = timestamp::now();
+        let request = NotificationHistoryRequest {
+            start_date: Some(now - Duration::days(1)),
+            end_date: Some(now),
+            notification_subtype: Some(Subtype::InitialBuy),
+            ..Default::default()
+        };
+        assert!(matches!(request.validate(), Err(HistoryRequestError::InvalidParameterError(_))));
+
+        let request = NotificationHistoryRequest { notification_type: Some(NotificationTypeV2::Subscribed), ..request };
+        assert_eq!(Ok(()), request.validate());
+
+        let start_date = now - Duration::days(NotificationHistoryRequest::MAX_HISTORY_DAYS);
+        let request = NotificationHistoryRequest { start_date: Some(start_date), ..request };
+        assert_eq!(Ok(()), request.validate_at(now));
+        assert!(matches!(request.validate_at(now + Duration::milliseconds(1)), Err(HistoryRequestError::InvalidDateRangeError(_))));
+
+        let clock = FrozenClock::new(chrono::DateTime::from_timestamp(1698148800, 0).unwrap());
+        let request = NotificationHistoryRequest::builder_for_last_with_clock(Duration::days(1), &clock).build().unwrap();
+        assert_eq!(Some(1698148800000), request.end_date.map(timestamp::to_millis));
+        assert_eq!(Ok(()), request.validate_with_clock(&clock));
+        clock.advance(std::time::Duration::from_secs(180 * 24 * 60 * 60));
+        assert!(matches!(request.validate_with_clock(&clock), Err(HistoryRequestError::InvalidDateRangeError(_))));
+
+        let request = NotificationHistoryRequest { start_date: None, ..request };
+        assert!(matches!(request.validate(), Err(HistoryRequestError::InvalidDateRangeError(_))));
+    }
+}
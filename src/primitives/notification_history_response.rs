@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 /// A response that contains the App Store Server Notifications history for your app.
 ///
 /// [NotificationHistoryResponse](https://developer.apple.com/documentation/appstoreserverapi/notificationhistoryresponse)
-#[derive(Debug, Clone, Deserialize, Serialize, Hash)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct NotificationHistoryResponse {
     /// A pagination token that you return to the endpoint on a subsequent call to receive the next set of results.
     ///
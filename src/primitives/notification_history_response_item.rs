@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 /// The App Store server notification history record, including the signed notification payload and the result of the server’s first send attempt.
 ///
 /// [notificationHistoryResponseItem](https://developer.apple.com/documentation/appstoreserverapi/notificationhistoryresponseitem)
-#[derive(Debug, Clone, Deserialize, Serialize, Hash, PartialEq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct NotificationHistoryResponseItem {
     /// A cryptographically signed payload, in JSON Web Signature (JWS) format, containing the response body for a version 2 notification.
     ///
@@ -0,0 +1,20 @@
+string_enum! {
+    /// The type that describes the in-app purchase event for which the App Store sends the version 1 notification.
+    ///
+    /// [notification_type](https://developer.apple.com/documentation/appstoreservernotifications/notification_type)
+    pub enum NotificationTypeV1 {
+        Cancel = "CANCEL",
+        ConsumptionRequest = "CONSUMPTION_REQUEST",
+        DidChangeRenewalPref = "DID_CHANGE_RENEWAL_PREF",
+        DidChangeRenewalStatus = "DID_CHANGE_RENEWAL_STATUS",
+        DidFailToRenew = "DID_FAIL_TO_RENEW",
+        DidRecover = "DID_RECOVER",
+        DidRenew = "DID_RENEW",
+        InitialBuy = "INITIAL_BUY",
+        InteractiveRenewal = "INTERACTIVE_RENEWAL",
+        PriceIncreaseConsent = "PRICE_INCREASE_CONSENT",
+        Refund = "REFUND",
+        Renewal = "RENEWAL",
+        Revoke = "REVOKE",
+    }
+}
@@ -1,46 +1,26 @@
-use serde::{Deserialize, Serialize};
-
-/// The type that describes the in-app purchase or external purchase event for which the App Store sends the version 2 notification.
-///
-/// [notificationType](https://developer.apple.com/documentation/appstoreservernotifications/notificationtype)
-#[derive(Debug, Clone, Deserialize, Serialize, Hash, PartialEq, Eq)]
-pub enum NotificationTypeV2 {
-    #[serde(rename = "SUBSCRIBED")]
-    Subscribed,
-    #[serde(rename = "DID_CHANGE_RENEWAL_PREF")]
-    DidChangeRenewalPref,
-    #[serde(rename = "DID_CHANGE_RENEWAL_STATUS")]
-    DidChangeRenewalStatus,
-    #[serde(rename = "OFFER_REDEEMED")]
-    OfferRedeemed,
-    #[serde(rename = "DID_RENEW")]
-    DidRenew,
-    #[serde(rename = "EXPIRED")]
-    Expired,
-    #[serde(rename = "DID_FAIL_TO_RENEW")]
-    DidFailToRenew,
-    #[serde(rename = "GRACE_PERIOD_EXPIRED")]
-    GracePeriodExpired,
-    #[serde(rename = "PRICE_INCREASE")]
-    PriceIncrease,
-    #[serde(rename = "REFUND")]
-    Refund,
-    #[serde(rename = "REFUND_DECLINED")]
-    RefundDeclined,
-    #[serde(rename = "CONSUMPTION_REQUEST")]
-    ConsumptionRequest,
-    #[serde(rename = "RENEWAL_EXTENDED")]
-    RenewalExtended,
-    #[serde(rename = "REVOKE")]
-    Revoke,
-    #[serde(rename = "TEST")]
-    Test,
-    #[serde(rename = "RENEWAL_EXTENSION")]
-    RenewalExtension,
-    #[serde(rename = "REFUND_REVERSED")]
-    RefundReversed,
-    #[serde(rename = "EXTERNAL_PURCHASE_TOKEN")]
-    ExternalPurchaseToken,
-    #[serde(rename = "ONE_TIME_CHARGE")]
-    OneTimeCharge,
+string_enum! {
+    /// The type that describes the in-app purchase or external purchase event for which the App Store sends the version 2 notification.
+    ///
+    /// [notificationType](https://developer.apple.com/documentation/appstoreservernotifications/notificationtype)
+    pub enum NotificationTypeV2 {
+        Subscribed = "SUBSCRIBED",
+        DidChangeRenewalPref = "DID_CHANGE_RENEWAL_PREF",
+        DidChangeRenewalStatus = "DID_CHANGE_RENEWAL_STATUS",
+        OfferRedeemed = "OFFER_REDEEMED",
+        DidRenew = "DID_RENEW",
+        Expired = "EXPIRED",
+        DidFailToRenew = "DID_FAIL_TO_RENEW",
+        GracePeriodExpired = "GRACE_PERIOD_EXPIRED",
+        PriceIncrease = "PRICE_INCREASE",
+        Refund = "REFUND",
+        RefundDeclined = "REFUND_DECLINED",
+        ConsumptionRequest = "CONSUMPTION_REQUEST",
+        RenewalExtended = "RENEWAL_EXTENDED",
+        Revoke = "REVOKE",
+        Test = "TEST",
+        RenewalExtension = "RENEWAL_EXTENSION",
+        RefundReversed = "REFUND_REVERSED",
+        ExternalPurchaseToken = "EXTERNAL_PURCHASE_TOKEN",
+        OneTimeCharge = "ONE_TIME_CHARGE",
+    }
 }
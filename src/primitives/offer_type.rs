@@ -1,13 +1,11 @@
-use serde_repr::{Deserialize_repr, Serialize_repr};
-
-/// The type of subscription offer.
-///
-/// [offerType](https://developer.apple.com/documentation/appstoreserverapi/offertype)
-#[derive(Debug, Clone, Deserialize_repr, Serialize_repr, Hash, PartialEq, Eq)]
-#[repr(u8)]
-pub enum OfferType {
-    IntroductoryOffer = 1,
-    PromotionalOffer = 2,
-    SubscriptionOfferCode = 3,
-    WinBackOffer = 4,
+integer_enum! {
+    /// The type of subscription offer.
+    ///
+    /// [offerType](https://developer.apple.com/documentation/appstoreserverapi/offertype)
+    pub enum OfferType: u8 {
+        IntroductoryOffer = 1,
+        PromotionalOffer = 2,
+        SubscriptionOfferCode = 3,
+        WinBackOffer = 4,
+    }
 }
@@ -1,3 +1,5 @@
+use crate::primitives::jws_transaction_decoded_payload::JWSTransactionDecodedPayload;
+use crate::signed_data_verifier::{SignedDataVerifier, SignedDataVerifierError};
 use crate::primitives::order_lookup_status::OrderLookupStatus;
 use serde::{Deserialize, Serialize};
 
@@ -18,3 +20,18 @@ pub struct OrderLookupResponse {
     #[serde(rename = "signedTransactions")]
     pub signed_transactions: Vec<String>,
 }
+
+impl OrderLookupResponse {
+    /// Verifies and decodes each of the signed transactions with `verifier`.
+    ///
+    /// Returns one result per transaction, in the order of `signed_transactions`.
+    pub fn decode_all(
+        &self,
+        verifier: &SignedDataVerifier,
+    ) -> Vec<Result<JWSTransactionDecodedPayload, SignedDataVerifierError>> {
+        self.signed_transactions
+            .iter()
+            .map(|signed_transaction| verifier.verify_and_decode_signed_transaction(signed_transaction))
+            .collect()
+    }
+}
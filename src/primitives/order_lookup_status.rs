@@ -1,11 +1,9 @@
-use serde_repr::{Deserialize_repr, Serialize_repr};
-
-/// A value that indicates whether the order ID in the request is valid for your app.
-///
-/// [OrderLookupStatus](https://developer.apple.com/documentation/appstoreserverapi/orderlookupstatus)
-#[derive(Debug, Clone, Deserialize_repr, Serialize_repr, Hash, PartialEq, Eq)]
-#[repr(u8)]
-pub enum OrderLookupStatus {
-    Valid = 0,
-    Invalid = 1,
+integer_enum! {
+    /// A value that indicates whether the order ID in the request is valid for your app.
+    ///
+    /// [OrderLookupStatus](https://developer.apple.com/documentation/appstoreserverapi/orderlookupstatus)
+    pub enum OrderLookupStatus: u8 {
+        Valid = 0,
+        Invalid = 1,
+    }
 }
@@ -0,0 +1,15 @@
+use crate::primitives::transaction_id::TransactionId;
+
+string_identifier! {
+    /// The transaction identifier of the original purchase, shared by every renewal and restore of it.
+    ///
+    /// [originalTransactionId](https://developer.apple.com/documentation/appstoreserverapi/originaltransactionid)
+    pub struct OriginalTransactionId;
+}
+
+impl From<OriginalTransactionId> for TransactionId {
+    /// The original transaction identifier is the transaction identifier of the original purchase.
+    fn from(value: OriginalTransactionId) -> Self {
+        TransactionId::new(value.into_inner())
+    }
+}
@@ -0,0 +1,84 @@
+use crate::primitives::auto_renew_status::AutoRenewStatus;
+use crate::primitives::expiration_intent::ExpirationIntent;
+use crate::primitives::original_transaction_id::OriginalTransactionId;
+use crate::primitives::price_increase_status::PriceIncreaseStatus;
+use crate::primitives::string_encoded::{DigitBool, NumericString};
+use crate::primitives::timestamp::Timestamp;
+use serde::{Deserialize, Serialize};
+use serde_with::formats::Flexible;
+use serde_with::TimestampMilliSeconds;
+
+/// A pending renewal record for an auto-renewable subscription from a legacy `verifyReceipt` response.
+///
+/// [responseBody.Pending_renewal_info](https://developer.apple.com/documentation/appstorereceipts/responsebody/pending_renewal_info)
+#[serde_with::serde_as]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
+pub struct PendingRenewalInfo {
+    /// The current renewal preference for the auto-renewable subscription.
+    ///
+    /// [auto_renew_product_id](https://developer.apple.com/documentation/appstorereceipts/auto_renew_product_id)
+    pub auto_renew_product_id: Option<String>,
+
+    /// The current renewal status for the auto-renewable subscription.
+    ///
+    /// [auto_renew_status](https://developer.apple.com/documentation/appstorereceipts/auto_renew_status)
+    #[serde_as(as = "Option<NumericString>")]
+    #[serde(default)]
+    pub auto_renew_status: Option<AutoRenewStatus>,
+
+    /// The reason a subscription expired.
+    ///
+    /// [expiration_intent](https://developer.apple.com/documentation/appstorereceipts/expiration_intent)
+    #[serde_as(as = "Option<NumericString>")]
+    #[serde(default)]
+    pub expiration_intent: Option<ExpirationIntent>,
+
+    /// The time at which the grace period for subscription renewals expires.
+    ///
+    /// [grace_period_expires_date_ms](https://developer.apple.com/documentation/appstorereceipts/grace_period_expires_date_ms)
+    #[serde(rename = "grace_period_expires_date_ms")]
+    #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
+    #[serde(default)]
+    pub grace_period_expires_date: Option<Timestamp>,
+
+    /// A flag that indicates Apple is attempting to renew an expired subscription automatically.
+    ///
+    /// [is_in_billing_retry_period](https://developer.apple.com/documentation/appstorereceipts/is_in_billing_retry_period)
+    #[serde_as(as = "Option<DigitBool>")]
+    #[serde(default)]
+    pub is_in_billing_retry_period: Option<bool>,
+
+    /// The reference name of a subscription offer that you configured in App Store Connect.
+    ///
+    /// [offer_code_ref_name](https://developer.apple.com/documentation/appstorereceipts/offer_code_ref_name)
+    pub offer_code_ref_name: Option<String>,
+
+    /// The transaction identifier of the original purchase.
+    ///
+    /// [original_transaction_id](https://developer.apple.com/documentation/appstorereceipts/original_transaction_id)
+    pub original_transaction_id: Option<OriginalTransactionId>,
+
+    /// The price consent status for a subscription price increase.
+    ///
+    /// [price_consent_status](https://developer.apple.com/documentation/appstorereceipts/price_consent_status)
+    #[serde_as(as = "Option<NumericString>")]
+    #[serde(default)]
+    pub price_consent_status: Option<PriceIncreaseStatus>,
+
+    /// The status that indicates whether an auto-renewable subscription is subject to a price increase.
+    ///
+    /// [price_increase_status](https://developer.apple.com/documentation/appstorereceipts/price_increase_status)
+    #[serde_as(as = "Option<NumericString>")]
+    #[serde(default)]
+    pub price_increase_status: Option<PriceIncreaseStatus>,
+
+    /// The unique identifier of the product purchased.
+    ///
+    /// [product_id](https://developer.apple.com/documentation/appstorereceipts/product_id)
+    pub product_id: Option<String>,
+
+    /// The identifier of the promotional offer for an auto-renewable subscription that the user redeemed.
+    ///
+    /// [promotional_offer_id](https://developer.apple.com/documentation/appstorereceipts/promotional_offer_id)
+    pub promotional_offer_id: Option<String>,
+}
@@ -1,17 +1,15 @@
-use serde_repr::{Deserialize_repr, Serialize_repr};
-
-/// A value that indicates the amount of time that the customer used the app.
-///
-/// [playTime](https://developer.apple.com/documentation/appstoreserverapi/playtime)
-#[derive(Debug, Clone, Deserialize_repr, Serialize_repr, Hash, PartialEq, Eq)]
-#[repr(u8)]
-pub enum PlayTime {
-    Undeclared = 0,
-    ZeroToFiveMinutes = 1,
-    FiveToSixtyMinutes = 2,
-    OneToSixHours = 3,
-    SixHoursToTwentyFourHours = 4,
-    OneDayToFourDays = 5,
-    FourDaysToSixteenDays = 6,
-    OverSixteenDays = 7,
+integer_enum! {
+    /// A value that indicates the amount of time that the customer used the app.
+    ///
+    /// [playTime](https://developer.apple.com/documentation/appstoreserverapi/playtime)
+    pub enum PlayTime: u8 {
+        Undeclared = 0,
+        ZeroToFiveMinutes = 1,
+        FiveToSixtyMinutes = 2,
+        OneToSixHours = 3,
+        SixHoursToTwentyFourHours = 4,
+        OneDayToFourDays = 5,
+        FourDaysToSixteenDays = 6,
+        OverSixteenDays = 7,
+    }
 }
@@ -0,0 +1,121 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// An enum whose values correspond to the raw values Apple uses in requests and responses.
+///
+/// Every value converts to and from its raw value, and values Apple adds after this version of the
+/// library are kept in an `Unknown` variant rather than rejected. [`Display`] writes the raw value and
+/// [`FromStr`] parses it.
+pub trait RawValue: Sized + Display + FromStr + 'static {
+    /// The type of the raw value, a string or an integer.
+    type Raw<'a>;
+
+    /// The raw value Apple uses for this value.
+    fn raw_value(&self) -> Self::Raw<'_>;
+
+    /// The value for a raw value, `Unknown` if it isn't recognized.
+    fn from_raw(raw: Self::Raw<'_>) -> Self;
+
+    /// Every value known to this version of the library.
+    fn known_values() -> &'static [Self];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::account_tenure::AccountTenure;
+    use crate::primitives::auto_renew_status::AutoRenewStatus;
+    use crate::primitives::consumption_request_reason::ConsumptionRequestReason;
+    use crate::primitives::consumption_status::ConsumptionStatus;
+    use crate::primitives::currency::Currency;
+    use crate::primitives::delivery_status::DeliveryStatus;
+    use crate::primitives::environment::Environment;
+    use crate::primitives::expiration_intent::ExpirationIntent;
+    use crate::primitives::extend_reason_code::ExtendReasonCode;
+    use crate::primitives::first_send_attempt_result::FirstSendAttemptResult;
+    use crate::primitives::in_app_ownership_type::InAppOwnershipType;
+    use crate::primitives::lifetime_dollars_purchased::LifetimeDollarsPurchased;
+    use crate::primitives::lifetime_dollars_refunded::LifetimeDollarsRefunded;
+    use crate::primitives::notification_type_v1::NotificationTypeV1;
+    use crate::primitives::notification_type_v2::NotificationTypeV2;
+    use crate::primitives::offer_discount_type::OfferDiscountType;
+    use crate::primitives::offer_type::OfferType;
+    use crate::primitives::order_lookup_status::OrderLookupStatus;
+    use crate::primitives::platform::Platform;
+    use crate::primitives::play_time::PlayTime;
+    use crate::primitives::price_increase_status::PriceIncreaseStatus;
+    use crate::primitives::product_type::ProductType;
+    use crate::primitives::refund_preference::RefundPreference;
+    use crate::primitives::revocation_reason::RevocationReason;
+    use crate::primitives::send_attempt_result::SendAttemptResult;
+    use crate::primitives::status::Status;
+    use crate::primitives::subtype::Subtype;
+    use crate::primitives::transaction_history_request::{Order, ProductType as HistoryProductType};
+    use crate::primitives::transaction_reason::TransactionReason;
+    use crate::primitives::user_status::UserStatus;
+    use crate::primitives::verify_receipt_status::VerifyReceiptStatus;
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+    use std::fmt::Debug;
+
+    fn assert_round_trips<T>()
+    where
+        T: RawValue + Serialize + DeserializeOwned + PartialEq + Debug,
+        <T as FromStr>::Err: Debug,
+    {
+        assert!(!T::known_values().is_empty());
+
+        for value in T::known_values() {
+            assert_eq!(value, &T::from_raw(value.raw_value()));
+            assert_eq!(value, &value.to_string().parse::<T>().unwrap());
+
+            let json = serde_json::to_string(value).unwrap();
+            assert_eq!(value, &serde_json::from_str::<T>(&json).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_every_value_round_trips() {
+        assert_round_trips::<AccountTenure>();
+        assert_round_trips::<AutoRenewStatus>();
+        assert_round_trips::<ConsumptionRequestReason>();
+        assert_round_trips::<ConsumptionStatus>();
+        assert_round_trips::<Currency>();
+        assert_round_trips::<DeliveryStatus>();
+        assert_round_trips::<Environment>();
+        assert_round_trips::<ExpirationIntent>();
+        assert_round_trips::<ExtendReasonCode>();
+        assert_round_trips::<FirstSendAttemptResult>();
+        assert_round_trips::<InAppOwnershipType>();
+        assert_round_trips::<LifetimeDollarsPurchased>();
+        assert_round_trips::<LifetimeDollarsRefunded>();
+        assert_round_trips::<NotificationTypeV1>();
+        assert_round_trips::<NotificationTypeV2>();
+        assert_round_trips::<OfferDiscountType>();
+        assert_round_trips::<OfferType>();
+        assert_round_trips::<OrderLookupStatus>();
+        assert_round_trips::<Platform>();
+        assert_round_trips::<PlayTime>();
+        assert_round_trips::<PriceIncreaseStatus>();
+        assert_round_trips::<ProductType>();
+        assert_round_trips::<RefundPreference>();
+        assert_round_trips::<RevocationReason>();
+        assert_round_trips::<SendAttemptResult>();
+        assert_round_trips::<Status>();
+        assert_round_trips::<Subtype>();
+        assert_round_trips::<HistoryProductType>();
+        assert_round_trips::<Order>();
+        assert_round_trips::<TransactionReason>();
+        assert_round_trips::<UserStatus>();
+        assert_round_trips::<VerifyReceiptStatus>();
+    }
+
+    #[test]
+    fn test_unknown_values() {
+        assert_eq!(Subtype::Unknown("NEW".to_string()), "NEW".parse().unwrap());
+        assert_eq!("NEW", Subtype::Unknown("NEW".to_string()).to_string());
+        assert_eq!(Status::Unknown(42), "42".parse().unwrap());
+        assert!("active".parse::<Status>().is_err());
+        assert_eq!(VerifyReceiptStatus::InternalError(21150), VerifyReceiptStatus::from_raw(21150));
+    }
+}
@@ -0,0 +1,97 @@
+use crate::primitives::receipt_in_app_purchase::ReceiptInAppPurchase;
+use crate::primitives::timestamp::Timestamp;
+use serde::{Deserialize, Serialize};
+use serde_with::formats::Flexible;
+use serde_with::TimestampMilliSeconds;
+
+/// The decoded app receipt from a legacy `verifyReceipt` response.
+///
+/// [responseBody.Receipt](https://developer.apple.com/documentation/appstorereceipts/responsebody/receipt)
+#[serde_with::serde_as]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
+pub struct Receipt {
+    /// The unique identifier of the app, as assigned by the App Store.
+    ///
+    /// [adam_id](https://developer.apple.com/documentation/appstorereceipts/adam_id)
+    pub adam_id: Option<i64>,
+
+    /// The unique identifier of the app, as assigned by the App Store.
+    ///
+    /// [app_item_id](https://developer.apple.com/documentation/appstorereceipts/app_item_id)
+    pub app_item_id: Option<i64>,
+
+    /// The app’s version number.
+    ///
+    /// [application_version](https://developer.apple.com/documentation/appstorereceipts/application_version)
+    pub application_version: Option<String>,
+
+    /// The bundle identifier for the app to which the receipt belongs.
+    ///
+    /// [bundle_id](https://developer.apple.com/documentation/appstorereceipts/bundle_id)
+    pub bundle_id: Option<String>,
+
+    /// A unique identifier for the app download transaction.
+    ///
+    /// [download_id](https://developer.apple.com/documentation/appstorereceipts/download_id)
+    pub download_id: Option<i64>,
+
+    /// The time the receipt expires for apps purchased through the Volume Purchase Program.
+    ///
+    /// [expiration_date_ms](https://developer.apple.com/documentation/appstorereceipts/expiration_date_ms)
+    #[serde(rename = "expiration_date_ms")]
+    #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
+    #[serde(default)]
+    pub expiration_date: Option<Timestamp>,
+
+    /// An array that contains the in-app purchase receipt fields for all in-app purchase transactions.
+    ///
+    /// [in_app](https://developer.apple.com/documentation/appstorereceipts/responsebody/receipt/in_app)
+    pub in_app: Option<Vec<ReceiptInAppPurchase>>,
+
+    /// The version of the app that the user originally purchased.
+    ///
+    /// [original_application_version](https://developer.apple.com/documentation/appstorereceipts/original_application_version)
+    pub original_application_version: Option<String>,
+
+    /// The time of the original app purchase.
+    ///
+    /// [original_purchase_date_ms](https://developer.apple.com/documentation/appstorereceipts/original_purchase_date_ms)
+    #[serde(rename = "original_purchase_date_ms")]
+    #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
+    #[serde(default)]
+    pub original_purchase_date: Option<Timestamp>,
+
+    /// The time the user ordered the app available for pre-order.
+    ///
+    /// [preorder_date_ms](https://developer.apple.com/documentation/appstorereceipts/preorder_date_ms)
+    #[serde(rename = "preorder_date_ms")]
+    #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
+    #[serde(default)]
+    pub preorder_date: Option<Timestamp>,
+
+    /// The time the App Store generated the receipt.
+    ///
+    /// [receipt_creation_date_ms](https://developer.apple.com/documentation/appstorereceipts/receipt_creation_date_ms)
+    #[serde(rename = "receipt_creation_date_ms")]
+    #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
+    #[serde(default)]
+    pub receipt_creation_date: Option<Timestamp>,
+
+    /// The type of receipt generated, for example `Production` or `ProductionSandbox`.
+    ///
+    /// [receipt_type](https://developer.apple.com/documentation/appstorereceipts/receipt_type)
+    pub receipt_type: Option<String>,
+
+    /// The time that the request to the verifyReceipt endpoint was processed and the response was generated.
+    ///
+    /// [request_date_ms](https://developer.apple.com/documentation/appstorereceipts/request_date_ms)
+    #[serde(rename = "request_date_ms")]
+    #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
+    #[serde(default)]
+    pub request_date: Option<Timestamp>,
+
+    /// An arbitrary number that identifies a revision of your app.
+    ///
+    /// [version_external_identifier](https://developer.apple.com/documentation/appstorereceipts/version_external_identifier)
+    pub version_external_identifier: Option<i64>,
+}
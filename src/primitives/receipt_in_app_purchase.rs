@@ -0,0 +1,200 @@
+use crate::primitives::environment::Environment;
+use crate::primitives::in_app_ownership_type::InAppOwnershipType;
+use crate::primitives::jws_transaction_decoded_payload::JWSTransactionDecodedPayload;
+use crate::primitives::offer_type::OfferType;
+use crate::primitives::original_transaction_id::OriginalTransactionId;
+use crate::primitives::product_type::ProductType;
+use crate::primitives::revocation_reason::RevocationReason;
+use crate::primitives::string_encoded::NumericString;
+use crate::primitives::timestamp::Timestamp;
+use crate::primitives::transaction_id::TransactionId;
+use crate::primitives::web_order_line_item_id::WebOrderLineItemId;
+use serde::{Deserialize, Serialize};
+use serde_with::formats::Flexible;
+use serde_with::{DisplayFromStr, TimestampMilliSeconds};
+use uuid::Uuid;
+
+/// An in-app purchase record from a legacy `verifyReceipt` response.
+///
+/// Used for both the `in_app` array of the receipt and the `latest_receipt_info` array.
+/// Dates are read from the `_ms` variants of the fields; the formatted date strings are ignored.
+///
+/// [responseBody.Latest_receipt_info](https://developer.apple.com/documentation/appstorereceipts/responsebody/latest_receipt_info)
+#[serde_with::serde_as]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
+pub struct ReceiptInAppPurchase {
+    /// The UUID that an app optionally generates to map a customer’s in-app purchase with its resulting App Store transaction.
+    ///
+    /// [app_account_token](https://developer.apple.com/documentation/appstorereceipts/app_account_token)
+    pub app_account_token: Option<Uuid>,
+
+    /// The time the App Store refunded a transaction or revoked it from family sharing.
+    ///
+    /// [cancellation_date_ms](https://developer.apple.com/documentation/appstorereceipts/cancellation_date_ms)
+    #[serde(rename = "cancellation_date_ms")]
+    #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
+    #[serde(default)]
+    pub cancellation_date: Option<Timestamp>,
+
+    /// The reason for a refunded or revoked transaction.
+    ///
+    /// [cancellation_reason](https://developer.apple.com/documentation/appstorereceipts/cancellation_reason)
+    #[serde_as(as = "Option<NumericString>")]
+    #[serde(default)]
+    pub cancellation_reason: Option<RevocationReason>,
+
+    /// The time a subscription expires or when it will renew.
+    ///
+    /// [expires_date_ms](https://developer.apple.com/documentation/appstorereceipts/expires_date_ms)
+    #[serde(rename = "expires_date_ms")]
+    #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
+    #[serde(default)]
+    pub expires_date: Option<Timestamp>,
+
+    /// A value that indicates whether the user is the purchaser of the product or is a family member with access to the product through Family Sharing.
+    ///
+    /// [in_app_ownership_type](https://developer.apple.com/documentation/appstorereceipts/in_app_ownership_type)
+    pub in_app_ownership_type: Option<InAppOwnershipType>,
+
+    /// An indicator of whether an auto-renewable subscription is in the introductory price period.
+    ///
+    /// [is_in_intro_offer_period](https://developer.apple.com/documentation/appstorereceipts/is_in_intro_offer_period)
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub is_in_intro_offer_period: Option<bool>,
+
+    /// An indicator of whether a subscription is in the free trial period.
+    ///
+    /// [is_trial_period](https://developer.apple.com/documentation/appstorereceipts/is_trial_period)
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub is_trial_period: Option<bool>,
+
+    /// An indicator that a subscription has been canceled due to an upgrade.
+    ///
+    /// [is_upgraded](https://developer.apple.com/documentation/appstorereceipts/is_upgraded)
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub is_upgraded: Option<bool>,
+
+    /// The reference name of a subscription offer that you configured in App Store Connect.
+    ///
+    /// [offer_code_ref_name](https://developer.apple.com/documentation/appstorereceipts/offer_code_ref_name)
+    pub offer_code_ref_name: Option<String>,
+
+    /// The time of the original in-app purchase.
+    ///
+    /// [original_purchase_date_ms](https://developer.apple.com/documentation/appstorereceipts/original_purchase_date_ms)
+    #[serde(rename = "original_purchase_date_ms")]
+    #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
+    #[serde(default)]
+    pub original_purchase_date: Option<Timestamp>,
+
+    /// The transaction identifier of the original purchase.
+    ///
+    /// [original_transaction_id](https://developer.apple.com/documentation/appstorereceipts/original_transaction_id)
+    pub original_transaction_id: Option<OriginalTransactionId>,
+
+    /// The unique identifier of the product purchased.
+    ///
+    /// [product_id](https://developer.apple.com/documentation/appstorereceipts/product_id)
+    pub product_id: Option<String>,
+
+    /// The identifier of the subscription offer redeemed by the user.
+    ///
+    /// [promotional_offer_id](https://developer.apple.com/documentation/appstorereceipts/promotional_offer_id)
+    pub promotional_offer_id: Option<String>,
+
+    /// The time the App Store charged the user’s account for a purchased or restored product, or for a subscription purchase or renewal after a lapse.
+    ///
+    /// [purchase_date_ms](https://developer.apple.com/documentation/appstorereceipts/purchase_date_ms)
+    #[serde(rename = "purchase_date_ms")]
+    #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
+    #[serde(default)]
+    pub purchase_date: Option<Timestamp>,
+
+    /// The number of consumable products purchased.
+    ///
+    /// [quantity](https://developer.apple.com/documentation/appstorereceipts/quantity)
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub quantity: Option<i32>,
+
+    /// The identifier of the subscription group to which the subscription belongs.
+    ///
+    /// [subscription_group_identifier](https://developer.apple.com/documentation/appstorereceipts/subscription_group_identifier)
+    pub subscription_group_identifier: Option<String>,
+
+    /// A unique identifier for purchase events across devices, including subscription-renewal events.
+    ///
+    /// [web_order_line_item_id](https://developer.apple.com/documentation/appstorereceipts/web_order_line_item_id)
+    pub web_order_line_item_id: Option<WebOrderLineItemId>,
+
+    /// A unique identifier for a transaction such as a purchase, restore, or renewal.
+    ///
+    /// [transaction_id](https://developer.apple.com/documentation/appstorereceipts/transaction_id)
+    pub transaction_id: Option<TransactionId>,
+}
+
+impl ReceiptInAppPurchase {
+    /// The product type, as far as it can be derived from the receipt fields.
+    ///
+    /// Records with an expiration date or a subscription group are auto-renewable subscriptions.
+    /// Legacy receipts don't distinguish between the remaining product types, so `None` is returned for them.
+    pub fn inferred_product_type(&self) -> Option<ProductType> {
+        if self.expires_date.is_some() || self.subscription_group_identifier.is_some() {
+            return Some(ProductType::AutoRenewableSubscription);
+        }
+
+        None
+    }
+
+    /// The type of subscription offer redeemed for this purchase, if any.
+    pub fn inferred_offer_type(&self) -> Option<OfferType> {
+        if self.promotional_offer_id.is_some() {
+            Some(OfferType::PromotionalOffer)
+        } else if self.offer_code_ref_name.is_some() {
+            Some(OfferType::SubscriptionOfferCode)
+        } else if self.is_trial_period == Some(true) || self.is_in_intro_offer_period == Some(true) {
+            Some(OfferType::IntroductoryOffer)
+        } else {
+            None
+        }
+    }
+
+    /// Maps the legacy record onto the transaction model used by the App Store Server API.
+    ///
+    /// The receipt doesn't carry the bundle identifier or environment per purchase, so they are passed in.
+    /// Fields that have no legacy counterpart, such as the price or storefront, are left empty.
+    pub fn to_transaction(&self, bundle_id: Option<String>, environment: Option<Environment>) -> JWSTransactionDecodedPayload {
+        JWSTransactionDecodedPayload {
+            original_transaction_id: self.original_transaction_id.clone(),
+            transaction_id: self.transaction_id.clone(),
+            web_order_line_item_id: self.web_order_line_item_id.clone(),
+            bundle_id,
+            product_id: self.product_id.clone(),
+            subscription_group_identifier: self.subscription_group_identifier.clone(),
+            purchase_date: self.purchase_date,
+            original_purchase_date: self.original_purchase_date,
+            expires_date: self.expires_date,
+            quantity: self.quantity,
+            r#type: self.inferred_product_type(),
+            app_account_token: self.app_account_token,
+            app_transaction_id: None,
+            in_app_ownership_type: self.in_app_ownership_type.clone(),
+            signed_date: None,
+            revocation_reason: self.cancellation_reason.clone(),
+            revocation_date: self.cancellation_date,
+            is_upgraded: self.is_upgraded,
+            offer_type: self.inferred_offer_type(),
+            offer_identifier: self.promotional_offer_id.clone().or_else(|| self.offer_code_ref_name.clone()),
+            environment,
+            storefront: None,
+            storefront_id: None,
+            transaction_reason: None,
+            currency: None,
+            price: None,
+            offer_discount_type: None,
+        }
+    }
+}
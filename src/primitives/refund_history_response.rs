@@ -1,9 +1,11 @@
+use crate::primitives::jws_transaction_decoded_payload::JWSTransactionDecodedPayload;
+use crate::signed_data_verifier::{SignedDataVerifier, SignedDataVerifierError};
 use serde::{Deserialize, Serialize};
 
 /// A response that contains an array of signed JSON Web Signature (JWS) refunded transactions, and paging information.
 ///
 /// [RefundHistoryResponse](https://developer.apple.com/documentation/appstoreserverapi/refundhistoryresponse)
-#[derive(Debug, Clone, Deserialize, Serialize, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct RefundHistoryResponse {
     /// A list of up to 20 JWS transactions, or an empty array if the customer hasn't received any refunds in your app. The transactions are sorted in ascending order by revocationDate.
     ///
@@ -22,3 +24,45 @@ pub struct RefundHistoryResponse {
     #[serde(rename = "hasMore")]
     pub has_more: bool,
 }
+
+impl RefundHistoryResponse {
+    /// Verifies and decodes each of the signed transactions with `verifier`.
+    ///
+    /// Returns one result per transaction, in the order of `signed_transactions`, so that a transaction
+    /// that fails verification doesn't hide the others.
+    pub fn decode_all(
+        &self,
+        verifier: &SignedDataVerifier,
+    ) -> Vec<Result<JWSTransactionDecodedPayload, SignedDataVerifierError>> {
+        self.signed_transactions
+            .iter()
+            .map(|signed_transaction| verifier.verify_and_decode_signed_transaction(signed_transaction))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::notification_type_v2::NotificationTypeV2;
+    use crate::test_utils::{local_testing_verifier, signed_transaction_fixture};
+
+    #[test]
+    fn test_decode_all() {
+        let response = RefundHistoryResponse {
+            signed_transactions: vec![
+                signed_transaction_fixture(&NotificationTypeV2::Refund, "1001"),
+                "invalid".to_string(),
+            ],
+            revision: "revision".to_string(),
+            has_more: false,
+        };
+
+        let transactions = response.decode_all(&local_testing_verifier());
+        assert_eq!(2, transactions.len());
+        let refunded = transactions[0].as_ref().unwrap();
+        assert_eq!(Some("1001"), refunded.transaction_id.as_deref());
+        assert!(refunded.revocation_date.is_some());
+        assert!(transactions[1].is_err());
+    }
+}
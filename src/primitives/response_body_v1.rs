@@ -0,0 +1,104 @@
+use crate::primitives::environment::Environment;
+use crate::primitives::expiration_intent::ExpirationIntent;
+use crate::primitives::jws_transaction_decoded_payload::JWSTransactionDecodedPayload;
+use crate::primitives::notification_type_v1::NotificationTypeV1;
+use crate::primitives::original_transaction_id::OriginalTransactionId;
+use crate::primitives::string_encoded::NumericString;
+use crate::primitives::timestamp::Timestamp;
+use crate::primitives::unified_receipt::UnifiedReceipt;
+use serde::{Deserialize, Serialize};
+use serde_with::formats::Flexible;
+use serde_with::{DisplayFromStr, TimestampMilliSeconds};
+
+/// The JSON data sent in a version 1 server notification.
+///
+/// Dates are read from the `_ms` variants of the fields; the formatted date strings are ignored.
+///
+/// [responseBodyV1](https://developer.apple.com/documentation/appstoreservernotifications/responsebodyv1)
+#[serde_with::serde_as]
+#[derive(Debug, Clone, Deserialize, Serialize, Hash, PartialEq, Eq)]
+pub struct ResponseBodyV1 {
+    /// An identifier that App Store Connect generates and the App Store uses to uniquely identify the auto-renewable subscription that the user’s subscription renews.
+    ///
+    /// [auto_renew_adam_id](https://developer.apple.com/documentation/appstoreservernotifications/auto_renew_adam_id)
+    pub auto_renew_adam_id: Option<String>,
+
+    /// The product identifier of the auto-renewable subscription that the user’s subscription renews.
+    ///
+    /// [auto_renew_product_id](https://developer.apple.com/documentation/appstoreservernotifications/auto_renew_product_id)
+    pub auto_renew_product_id: Option<String>,
+
+    /// The current renewal status for an auto-renewable subscription product.
+    ///
+    /// [auto_renew_status](https://developer.apple.com/documentation/appstoreservernotifications/auto_renew_status)
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub auto_renew_status: Option<bool>,
+
+    /// The time at which the user turned on or off the renewal status for an auto-renewable subscription.
+    ///
+    /// [auto_renew_status_change_date_ms](https://developer.apple.com/documentation/appstoreservernotifications/auto_renew_status_change_date_ms)
+    #[serde(rename = "auto_renew_status_change_date_ms")]
+    #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
+    #[serde(default)]
+    pub auto_renew_status_change_date: Option<Timestamp>,
+
+    /// The environment for which the App Store generated the receipt.
+    ///
+    /// [environment](https://developer.apple.com/documentation/appstoreservernotifications/environment)
+    pub environment: Option<Environment>,
+
+    /// The reason a subscription expired.
+    ///
+    /// [expiration_intent](https://developer.apple.com/documentation/appstoreservernotifications/expiration_intent)
+    #[serde_as(as = "Option<NumericString>")]
+    #[serde(default)]
+    pub expiration_intent: Option<ExpirationIntent>,
+
+    /// The subscription event that triggered the notification.
+    ///
+    /// [notification_type](https://developer.apple.com/documentation/appstoreservernotifications/notification_type)
+    pub notification_type: NotificationTypeV1,
+
+    /// The same value as the shared secret you submit in the password field of the request JSON object.
+    ///
+    /// [password](https://developer.apple.com/documentation/appstoreservernotifications/password)
+    pub password: Option<String>,
+
+    /// An object that contains information about the most recent in-app purchase transactions for the app.
+    ///
+    /// [unified_receipt](https://developer.apple.com/documentation/appstoreservernotifications/unified_receipt)
+    pub unified_receipt: Option<UnifiedReceipt>,
+
+    /// A string that contains the app bundle ID.
+    ///
+    /// [bid](https://developer.apple.com/documentation/appstoreservernotifications/bid)
+    pub bid: Option<String>,
+
+    /// A string that contains the app bundle version.
+    ///
+    /// [bvrs](https://developer.apple.com/documentation/appstoreservernotifications/bvrs)
+    pub bvrs: Option<String>,
+
+    /// The transaction identifier of the original purchase.
+    ///
+    /// [original_transaction_id](https://developer.apple.com/documentation/appstoreservernotifications/original_transaction_id)
+    pub original_transaction_id: Option<OriginalTransactionId>,
+}
+
+impl ResponseBodyV1 {
+    /// The purchases in the unified receipt mapped onto the App Store Server API transaction model.
+    pub fn transactions(&self) -> Vec<JWSTransactionDecodedPayload> {
+        let Some(receipt) = &self.unified_receipt else {
+            return Vec::new();
+        };
+        let environment = receipt.environment.clone().or_else(|| self.environment.clone());
+
+        receipt
+            .latest_receipt_info
+            .iter()
+            .flatten()
+            .map(|purchase| purchase.to_transaction(self.bid.clone(), environment.clone()))
+            .collect()
+    }
+}
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 /// The response body the App Store sends in a version 2 server notification.
 ///
 /// [responseBodyV2](https://developer.apple.com/documentation/appstoreservernotifications/responsebodyv2)
-#[derive(Debug, Clone, Deserialize, Serialize, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct ResponseBodyV2 {
     /// A cryptographically signed payload, in JSON Web Signature (JWS) format, containing the response body for a version 2 notification.
     ///
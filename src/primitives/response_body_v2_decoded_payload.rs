@@ -2,7 +2,7 @@ use crate::primitives::data::Data;
 use crate::primitives::notification_type_v2::NotificationTypeV2;
 use crate::primitives::subtype::Subtype;
 use crate::primitives::summary::Summary;
-use ::chrono::{DateTime, Utc};
+use crate::primitives::timestamp::Timestamp;
 use serde_with::formats::Flexible;
 use serde_with::TimestampMilliSeconds;
 use crate::primitives::external_purchase_token::ExternalPurchaseToken;
@@ -11,7 +11,7 @@ use crate::primitives::external_purchase_token::ExternalPurchaseToken;
 ///
 /// [responseBodyV2DecodedPayload](https://developer.apple.com/documentation/appstoreservernotifications/responsebodyv2decodedpayload)
 #[serde_with::serde_as]
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, Hash)]
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, Hash, PartialEq, Eq)]
 pub struct ResponseBodyV2DecodedPayload {
     /// The in-app purchase event for which the App Store sends this version 2 notification.
     ///
@@ -47,7 +47,7 @@ pub struct ResponseBodyV2DecodedPayload {
     /// [signedDate](https://developer.apple.com/documentation/appstoreserverapi/signeddate)
     #[serde(rename = "signedDate")]
     #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
-    pub signed_date: Option<DateTime<Utc>>,
+    pub signed_date: Option<Timestamp>,
 
     /// The summary data that appears when the App Store server completes your request to extend a subscription renewal date for eligible subscribers.
     /// The data, summary, and externalPurchaseToken fields are mutually exclusive. The payload contains only one of these fields.
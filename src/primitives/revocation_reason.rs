@@ -1,11 +1,9 @@
-use serde_repr::{Deserialize_repr, Serialize_repr};
-
-/// The reason for a refunded transaction.
-///
-/// [revocationReason](https://developer.apple.com/documentation/appstoreserverapi/revocationreason)
-#[derive(Debug, Clone, Deserialize_repr, Serialize_repr, Hash, PartialEq, Eq)]
-#[repr(u8)]
-pub enum RevocationReason {
-    RefundedDueToIssue = 1,
-    RefundedForOtherReason = 0,
+integer_enum! {
+    /// The reason for a refunded transaction.
+    ///
+    /// [revocationReason](https://developer.apple.com/documentation/appstoreserverapi/revocationreason)
+    pub enum RevocationReason: u8 {
+        RefundedDueToIssue = 1,
+        RefundedForOtherReason = 0,
+    }
 }
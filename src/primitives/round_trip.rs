@@ -0,0 +1,106 @@
+//! Serialize, deserialize and serialize again every model decoded from the fixtures, so that renamed
+//! fields, dropped fields and lost timestamp precision show up as a failing test of the model.
+use crate::primitives::app_transaction::AppTransaction;
+use crate::primitives::check_test_notification_response::CheckTestNotificationResponse;
+use crate::primitives::error_payload::ErrorPayload;
+use crate::primitives::extend_renewal_date_response::ExtendRenewalDateResponse;
+use crate::primitives::history_response::HistoryResponse;
+use crate::primitives::jws_renewal_info_decoded_payload::JWSRenewalInfoDecodedPayload;
+use crate::primitives::jws_transaction_decoded_payload::JWSTransactionDecodedPayload;
+use crate::primitives::mass_extend_renewal_date_response::MassExtendRenewalDateResponse;
+use crate::primitives::mass_extend_renewal_date_status_response::MassExtendRenewalDateStatusResponse;
+use crate::primitives::notification_history_response::NotificationHistoryResponse;
+use crate::primitives::order_lookup_response::OrderLookupResponse;
+use crate::primitives::refund_history_response::RefundHistoryResponse;
+use crate::primitives::response_body_v2_decoded_payload::ResponseBodyV2DecodedPayload;
+use crate::primitives::send_test_notification_response::SendTestNotificationResponse;
+use crate::primitives::status_response::StatusResponse;
+use crate::primitives::transaction_info_response::TransactionInfoResponse;
+use crate::primitives::verify_receipt_response::VerifyReceiptResponse;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt::Debug;
+
+/// Decodes `json` into `T` and checks that:
+/// - encoding the model gives back the fields of `json`, except `null` and `ignored` fields,
+/// - encoding the model, decoding it and encoding it again gives the same JSON and the same model.
+///
+/// Values are compared as strings, since timestamps and some identifiers are accepted both as JSON
+/// numbers and as strings, and encoded as one of them.
+fn assert_round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(json: &str, ignored: &[&str]) {
+    let fixture: Value = serde_json::from_str(json).unwrap();
+    let model: T = serde_json::from_value(fixture.clone()).unwrap();
+    let encoded = serde_json::to_value(&model).unwrap();
+    assert_eq!(
+        normalize(fixture, ignored),
+        normalize(encoded.clone(), ignored),
+        "{} doesn't encode back to its fixture",
+        std::any::type_name::<T>()
+    );
+
+    let decoded: T = serde_json::from_value(encoded.clone()).unwrap();
+    assert_eq!(model, decoded);
+    assert_eq!(encoded, serde_json::to_value(&decoded).unwrap());
+}
+
+fn normalize(value: Value, ignored: &[&str]) -> Value {
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .into_iter()
+                .filter(|(name, value)| !value.is_null() && !ignored.contains(&name.as_str()))
+                .map(|(name, value)| (name, normalize(value, ignored)))
+                .collect(),
+        ),
+        Value::Array(array) => Value::Array(array.into_iter().map(|value| normalize(value, ignored)).collect()),
+        Value::Number(number) => Value::String(number.to_string()),
+        value => value,
+    }
+}
+
+/// Generates a round-trip test of a model for each fixture, given as `test_name: Model = "path"`,
+/// optionally followed by `ignoring [...]` and the fixture fields that the model doesn't decode.
+macro_rules! round_trip_tests {
+    ($($name:ident: $model:ty = $fixture:literal $(ignoring [$($ignored:literal),*])?,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                assert_round_trip::<$model>(include_str!(concat!("../../assets/", $fixture)), &[$($($ignored),*)?]);
+            }
+        )*
+    };
+}
+
+round_trip_tests! {
+    test_app_transaction: AppTransaction = "appTransaction.json",
+    test_transaction: JWSTransactionDecodedPayload = "signedTransaction.json",
+    test_renewal_info: JWSRenewalInfoDecodedPayload = "signedRenewalInfo.json",
+    test_notification: ResponseBodyV2DecodedPayload = "signedNotification.json",
+    test_consumption_request_notification: ResponseBodyV2DecodedPayload = "signedConsumptionRequestNotification.json",
+    test_external_purchase_token_notification: ResponseBodyV2DecodedPayload = "signedExternalPurchaseTokenNotification.json",
+    test_summary_notification: ResponseBodyV2DecodedPayload = "signedSummaryNotification.json",
+    test_api_exception: ErrorPayload = "models/apiException.json",
+    test_extend_renewal_date_response: ExtendRenewalDateResponse = "models/extendSubscriptionRenewalDateResponse.json",
+    test_mass_extend_renewal_date_response: MassExtendRenewalDateResponse = "models/extendRenewalDateForAllActiveSubscribersResponse.json",
+    test_mass_extend_renewal_date_status_response: MassExtendRenewalDateStatusResponse = "models/getStatusOfSubscriptionRenewalDateExtensionsResponse.json",
+    test_status_response: StatusResponse = "models/getAllSubscriptionStatusesResponse.json",
+    test_notification_history_response: NotificationHistoryResponse = "models/getNotificationHistoryResponse.json",
+    test_refund_history_response: RefundHistoryResponse = "models/getRefundHistoryResponse.json",
+    test_check_test_notification_response: CheckTestNotificationResponse = "models/getTestNotificationStatusResponse.json",
+    test_order_lookup_response: OrderLookupResponse = "models/lookupOrderIdResponse.json",
+    test_send_test_notification_response: SendTestNotificationResponse = "models/requestTestNotificationResponse.json",
+    test_history_response: HistoryResponse = "models/transactionHistoryResponse.json",
+    test_transaction_info_response: TransactionInfoResponse = "models/transactionInfoResponse.json",
+    test_verify_receipt_response: VerifyReceiptResponse = "models/verifyReceiptResponse.json" ignoring [
+        "expires_date", "expires_date_pst", "original_purchase_date", "original_purchase_date_pst",
+        "purchase_date", "purchase_date_pst", "receipt_creation_date", "receipt_creation_date_pst",
+        "request_date", "request_date_pst"
+    ],
+}
+
+#[test]
+fn test_millisecond_timestamps() {
+    assert_round_trip::<JWSTransactionDecodedPayload>(r#"{"purchaseDate": 1698148800123, "expiresDate": "1700827200999"}"#, &[]);
+    assert_round_trip::<JWSRenewalInfoDecodedPayload>(r#"{"gracePeriodExpiresDate": 1698148800001}"#, &[]);
+}
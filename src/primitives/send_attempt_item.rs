@@ -1,5 +1,5 @@
 use crate::primitives::send_attempt_result::SendAttemptResult;
-use chrono::{DateTime, Utc};
+use crate::primitives::timestamp::Timestamp;
 use serde::{Deserialize, Serialize};
 use serde_with::formats::Flexible;
 use serde_with::TimestampMilliSeconds;
@@ -7,14 +7,14 @@ use serde_with::TimestampMilliSeconds;
 ///
 /// [sendAttemptItem](https://developer.apple.com/documentation/appstoreserverapi/sendattemptitem)
 #[serde_with::serde_as]
-#[derive(Debug, Clone, Deserialize, Serialize, Hash, PartialEq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct SendAttemptItem {
     /// The date the App Store server attempts to send a notification.
     ///
     /// [attemptDate](https://developer.apple.com/documentation/appstoreservernotifications/attemptdate)
     #[serde(rename = "attemptDate")]
     #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
-    pub attempt_date: Option<DateTime<Utc>>,
+    pub attempt_date: Option<Timestamp>,
 
     /// The success or error information the App Store server records when it attempts to send an App Store server notification to your server.
     ///
@@ -1,30 +1,18 @@
-use serde::{Deserialize, Serialize};
-
-/// The success or error information the App Store server records when it attempts to send an App Store server notification to your server.
-///
-/// [sendAttemptResult](https://developer.apple.com/documentation/appstoreserverapi/sendattemptresult)
-#[derive(Debug, Clone, Deserialize, Serialize, Hash, PartialEq, Eq)]
-pub enum SendAttemptResult {
-    #[serde(rename = "SUCCESS")]
-    Success,
-    #[serde(rename = "TIMED_OUT")]
-    TimedOut,
-    #[serde(rename = "TLS_ISSUE")]
-    TlsIssue,
-    #[serde(rename = "CIRCULAR_REDIRECT")]
-    CircularRedirect,
-    #[serde(rename = "NO_RESPONSE")]
-    NoResponse,
-    #[serde(rename = "SOCKET_ISSUE")]
-    SocketIssue,
-    #[serde(rename = "UNSUPPORTED_CHARSET")]
-    UnsupportedCharset,
-    #[serde(rename = "INVALID_RESPONSE")]
-    InvalidResponse,
-    #[serde(rename = "PREMATURE_CLOSE")]
-    PrematureClose,
-    #[serde(rename = "UNSUCCESSFUL_HTTP_RESPONSE_CODE")]
-    UnsuccessfulHttpResponseCode,
-    #[serde(rename = "OTHER")]
-    Other,
+string_enum! {
+    /// The success or error information the App Store server records when it attempts to send an App Store server notification to your server.
+    ///
+    /// [sendAttemptResult](https://developer.apple.com/documentation/appstoreserverapi/sendattemptresult)
+    pub enum SendAttemptResult {
+        Success = "SUCCESS",
+        TimedOut = "TIMED_OUT",
+        TlsIssue = "TLS_ISSUE",
+        CircularRedirect = "CIRCULAR_REDIRECT",
+        NoResponse = "NO_RESPONSE",
+        SocketIssue = "SOCKET_ISSUE",
+        UnsupportedCharset = "UNSUPPORTED_CHARSET",
+        InvalidResponse = "INVALID_RESPONSE",
+        PrematureClose = "PREMATURE_CLOSE",
+        UnsuccessfulHttpResponseCode = "UNSUCCESSFUL_HTTP_RESPONSE_CODE",
+        Other = "OTHER",
+    }
 }
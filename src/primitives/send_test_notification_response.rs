@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 /// A response that contains the test notification token.
 ///
 /// [SendTestNotificationResponse](https://developer.apple.com/documentation/appstoreserverapi/sendtestnotificationresponse)
-#[derive(Debug, Clone, Deserialize, Serialize, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct SendTestNotificationResponse {
     /// A unique identifier for a notification test that the App Store server sends to your server.
     ///
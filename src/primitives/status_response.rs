@@ -1,11 +1,13 @@
 use crate::primitives::environment::Environment;
+use crate::primitives::last_transactions_item::LastTransactionsItem;
+use crate::primitives::status::Status;
 use crate::primitives::subscription_group_identifier_item::SubscriptionGroupIdentifierItem;
 use serde::{Deserialize, Serialize};
 
 /// The response that contains status information for all of a customer’s auto-renewable subscriptions in your app.
 ///
 /// [StatusResponse](https://developer.apple.com/documentation/appstoreserverapi/statusresponse)
-#[derive(Debug, Clone, Deserialize, Serialize, Hash)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct StatusResponse {
     /// The server environment, sandbox or production, in which the App Store generated the response.
     ///
@@ -27,3 +29,56 @@ pub struct StatusResponse {
     /// An array of information for auto-renewable subscriptions, including App Store-signed transaction information and App Store-signed renewal information.
     pub data: Vec<SubscriptionGroupIdentifierItem>,
 }
+
+impl StatusResponse {
+    /// Iterates over the latest transactions of every subscription group in the response.
+    pub fn last_transactions_iter(&self) -> impl Iterator<Item = &LastTransactionsItem> {
+        self.data.iter().flat_map(SubscriptionGroupIdentifierItem::last_transactions_iter)
+    }
+
+    /// Iterates over the latest transactions of every subscription group whose subscription has the status.
+    pub fn with_status<'a>(&'a self, status: &'a Status) -> impl Iterator<Item = &'a LastTransactionsItem> {
+        self.last_transactions_iter().filter(move |item| item.status.as_ref() == Some(status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::notification_type_v2::NotificationTypeV2;
+    use crate::test_utils::{local_testing_verifier, signed_transaction_fixture};
+
+    #[test]
+    fn test_traversal() {
+        let item = |status: Status, transaction_id: &str| LastTransactionsItem {
+            status: Some(status),
+            original_transaction_id: Some(transaction_id.into()),
+            signed_transaction_info: Some(signed_transaction_fixture(&NotificationTypeV2::DidRenew, transaction_id)),
+            signed_renewal_info: None,
+        };
+        let response = StatusResponse {
+            data: vec![
+                SubscriptionGroupIdentifierItem {
+                    subscription_group_identifier: Some("1".to_string()),
+                    last_transactions: Some(vec![item(Status::Active, "1001"), item(Status::Expired, "1002")]),
+                },
+                SubscriptionGroupIdentifierItem { subscription_group_identifier: Some("2".to_string()), last_transactions: None },
+                SubscriptionGroupIdentifierItem {
+                    subscription_group_identifier: Some("3".to_string()),
+                    last_transactions: Some(vec![item(Status::Active, "1003")]),
+                },
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(3, response.last_transactions_iter().count());
+
+        let verifier = local_testing_verifier();
+        let active: Vec<_> = response
+            .with_status(&Status::Active)
+            .map(|item| item.decode_transaction_info(&verifier).unwrap().unwrap().transaction_id.unwrap().into_inner())
+            .collect();
+        assert_eq!(vec!["1001", "1003"], active);
+        assert_eq!(Ok(None), response.data[0].last_transactions_iter().next().unwrap().decode_renewal_info(&verifier));
+    }
+}
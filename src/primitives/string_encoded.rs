@@ -0,0 +1,55 @@
+use serde::de::{Error, IntoDeserializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_with::{DeserializeAs, SerializeAs};
+
+/// Adapter for values the legacy receipt APIs encode as numeric strings (e.g. `"1"`).
+///
+/// The string is parsed as an integer and handed to the inner type's own deserializer,
+/// so integer-backed enums such as `AutoRenewStatus` can be reused unchanged.
+pub(crate) struct NumericString;
+
+impl<'de, T: Deserialize<'de>> DeserializeAs<'de, T> for NumericString {
+    fn deserialize_as<D>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        let number: i64 = value.trim().parse().map_err(D::Error::custom)?;
+        T::deserialize(IntoDeserializer::<D::Error>::into_deserializer(number))
+    }
+}
+
+impl<T: Serialize> SerializeAs<T> for NumericString {
+    fn serialize_as<S>(source: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let value = serde_json::to_value(source).map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(value.to_string().as_str())
+    }
+}
+
+/// Adapter for Boolean values the legacy receipt APIs encode as `"1"` or `"0"`.
+pub(crate) struct DigitBool;
+
+impl<'de> DeserializeAs<'de, bool> for DigitBool {
+    fn deserialize_as<D>(deserializer: D) -> Result<bool, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match String::deserialize(deserializer)?.as_str() {
+            "1" | "true" => Ok(true),
+            "0" | "false" => Ok(false),
+            other => Err(D::Error::custom(format!("invalid boolean value: {}", other))),
+        }
+    }
+}
+
+impl SerializeAs<bool> for DigitBool {
+    fn serialize_as<S>(source: &bool, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(if *source { "1" } else { "0" })
+    }
+}
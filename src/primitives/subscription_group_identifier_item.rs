@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 /// Information for auto-renewable subscriptions, including signed transaction information and signed renewal information, for one subscription group.
 ///
 /// [SubscriptionGroupIdentifierItem](https://developer.apple.com/documentation/appstoreserverapi/subscriptiongroupidentifieritem)
-#[derive(Debug, Clone, Deserialize, Serialize, Hash, PartialEq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct SubscriptionGroupIdentifierItem {
     /// The identifier of the subscription group that the subscription belongs to.
     ///
@@ -18,3 +18,10 @@ pub struct SubscriptionGroupIdentifierItem {
     #[serde(rename = "lastTransactions")]
     pub last_transactions: Option<Vec<LastTransactionsItem>>,
 }
+
+impl SubscriptionGroupIdentifierItem {
+    /// Iterates over the latest transactions of the subscription group, if any.
+    pub fn last_transactions_iter(&self) -> impl Iterator<Item = &LastTransactionsItem> {
+        self.last_transactions.iter().flatten()
+    }
+}
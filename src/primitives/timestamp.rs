@@ -0,0 +1,87 @@
+//! The date type of the primitives.
+//!
+//! Dates are `chrono::DateTime<Utc>` by default, and `time::OffsetDateTime` with the `time` feature.
+//! Either way they serialize to and from the milliseconds since the Unix epoch that Apple uses, and
+//! the functions in this module work with both.
+
+use crate::clock::Clock;
+
+/// A point in time, as used by the dates of the primitives.
+#[cfg(not(feature = "time"))]
+pub type Timestamp = chrono::DateTime<chrono::Utc>;
+
+/// A point in time, as used by the dates of the primitives.
+#[cfg(feature = "time")]
+pub type Timestamp = time::OffsetDateTime;
+
+/// The difference between two [`Timestamp`]s.
+#[cfg(not(feature = "time"))]
+pub type Duration = chrono::Duration;
+
+/// The difference between two [`Timestamp`]s.
+#[cfg(feature = "time")]
+pub type Duration = time::Duration;
+
+/// The current time.
+pub fn now() -> Timestamp {
+    #[cfg(not(feature = "time"))]
+    return chrono::Utc::now();
+
+    #[cfg(feature = "time")]
+    return time::OffsetDateTime::now_utc();
+}
+
+/// The current time of a [`Clock`].
+pub fn now_of(clock: &dyn Clock) -> Timestamp {
+    let now = clock.now();
+
+    #[cfg(not(feature = "time"))]
+    return now;
+
+    #[cfg(feature = "time")]
+    return match time::OffsetDateTime::from_unix_timestamp(now.timestamp()) {
+        Ok(seconds) => seconds + time::Duration::nanoseconds(now.timestamp_subsec_nanos() as i64),
+        // The range of chrono is wider than the range of time.
+        Err(_) if now.timestamp() < 0 => time::PrimitiveDateTime::MIN.assume_utc(),
+        Err(_) => time::PrimitiveDateTime::MAX.assume_utc(),
+    };
+}
+
+/// The time `millis` milliseconds after the Unix epoch, or `None` if it is out of range.
+pub fn from_millis(millis: i64) -> Option<Timestamp> {
+    #[cfg(not(feature = "time"))]
+    return chrono::DateTime::from_timestamp_millis(millis);
+
+    #[cfg(feature = "time")]
+    return time::OffsetDateTime::from_unix_timestamp_nanos(millis as i128 * 1_000_000).ok();
+}
+
+/// The number of milliseconds since the Unix epoch.
+pub fn to_millis(timestamp: Timestamp) -> i64 {
+    #[cfg(not(feature = "time"))]
+    return timestamp.timestamp_millis();
+
+    #[cfg(feature = "time")]
+    return (timestamp.unix_timestamp_nanos() / 1_000_000) as i64;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::jws_transaction_decoded_payload::JWSTransactionDecodedPayload;
+
+    #[test]
+    fn test_timestamp() {
+        let transaction: JWSTransactionDecodedPayload =
+            serde_json::from_str(r#"{"purchaseDate":1698148900123,"expiresDate":"1698148950000"}"#).unwrap();
+        assert_eq!(from_millis(1698148900123), transaction.purchase_date);
+        assert_eq!(Some(1698148950000), transaction.expires_date.map(to_millis));
+
+        let json = serde_json::to_value(&transaction).unwrap();
+        assert_eq!("1698148900123", json["purchaseDate"]);
+        assert_eq!(Duration::milliseconds(49877), transaction.expires_date.unwrap() - transaction.purchase_date.unwrap());
+
+        let clock = crate::clock::FrozenClock::new(chrono::DateTime::from_timestamp_millis(1698148900123).unwrap());
+        assert_eq!(transaction.purchase_date, Some(now_of(&clock)));
+    }
+}
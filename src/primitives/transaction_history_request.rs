@@ -1,21 +1,23 @@
+use crate::clock::{Clock, SystemClock};
+use crate::primitives::history_request_error::HistoryRequestError;
 use crate::primitives::in_app_ownership_type::InAppOwnershipType;
-use chrono::{DateTime, Utc};
+use crate::primitives::timestamp::{self, Duration, Timestamp};
 use serde::{Deserialize, Serialize};
 use serde_with::formats::Flexible;
 use serde_with::TimestampMilliSeconds;
 
 #[serde_with::serde_as]
-#[derive(Debug, Clone, Deserialize, Serialize, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct TransactionHistoryRequest {
     /// An optional start date of the timespan for the transaction history records you’re requesting.
     #[serde(rename = "startDate")]
     #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
-    pub start_date: Option<DateTime<Utc>>,
+    pub start_date: Option<Timestamp>,
 
     /// An optional end date of the timespan for the transaction history records you’re requesting.
     #[serde(rename = "endDate")]
     #[serde_as(as = "Option<TimestampMilliSeconds<String, Flexible>>")]
-    pub end_date: Option<DateTime<Utc>>,
+    pub end_date: Option<Timestamp>,
 
     /// An optional filter that indicates the product identifier to include in the transaction history.
     #[serde(rename = "productIds")]
@@ -40,42 +42,150 @@ pub struct TransactionHistoryRequest {
     pub revoked: Option<bool>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, Hash, PartialEq, Eq)]
-pub enum ProductType {
-    #[serde(rename = "AUTO_RENEWABLE")]
-    AutoRenewable,
-    #[serde(rename = "NON_RENEWABLE")]
-    NonRenewable,
-    #[serde(rename = "CONSUMABLE")]
-    Consumable,
-    #[serde(rename = "NON_CONSUMABLE")]
-    NonConsumable,
+impl TransactionHistoryRequest {
+    /// Creates a `TransactionHistoryRequestBuilder` for a request without any filters.
+    pub fn builder() -> TransactionHistoryRequestBuilder {
+        TransactionHistoryRequestBuilder::default()
+    }
+}
+
+/// A builder for [`TransactionHistoryRequest`], created by [`TransactionHistoryRequest::builder`].
+#[derive(Debug, Clone, Default)]
+pub struct TransactionHistoryRequestBuilder {
+    request: TransactionHistoryRequest,
 }
 
-impl ProductType {
-    pub fn raw_value(&self) -> &str {
-        match self {
-            ProductType::AutoRenewable => "AUTO_RENEWABLE",
-            ProductType::NonRenewable => "NON_RENEWABLE",
-            ProductType::Consumable => "CONSUMABLE",
-            ProductType::NonConsumable => "NON_CONSUMABLE",
+impl TransactionHistoryRequestBuilder {
+    /// Limits the history to transactions between `start_date` and `end_date`.
+    pub fn date_range(mut self, start_date: Timestamp, end_date: Timestamp) -> Self {
+        self.request.start_date = Some(start_date);
+        self.request.end_date = Some(end_date);
+        self
+    }
+
+    /// Limits the history to transactions since `start_date`.
+    pub fn since(mut self, start_date: Timestamp) -> Self {
+        self.request.start_date = Some(start_date);
+        self
+    }
+
+    /// Limits the history to transactions within the last `duration`.
+    pub fn last(self, duration: Duration) -> Self {
+        self.last_with_clock(duration, &SystemClock)
+    }
+
+    /// Limits the history to transactions within the last `duration` before the time of `clock`.
+    pub fn last_with_clock(self, duration: Duration, clock: &dyn Clock) -> Self {
+        let now = timestamp::now_of(clock);
+        self.date_range(now - duration, now)
+    }
+
+    /// Adds a product identifier to include in the history.
+    pub fn product_id(mut self, product_id: impl Into<String>) -> Self {
+        self.request.product_ids.get_or_insert_with(Vec::new).push(product_id.into());
+        self
+    }
+
+    /// Adds product identifiers to include in the history.
+    pub fn product_ids<I: IntoIterator<Item = S>, S: Into<String>>(mut self, product_ids: I) -> Self {
+        self.request.product_ids.get_or_insert_with(Vec::new).extend(product_ids.into_iter().map(Into::into));
+        self
+    }
+
+    /// Adds a product type to include in the history.
+    pub fn product_type(mut self, product_type: ProductType) -> Self {
+        self.request.product_types.get_or_insert_with(Vec::new).push(product_type);
+        self
+    }
+
+    /// Adds a subscription group identifier to include in the history.
+    pub fn subscription_group_identifier(mut self, subscription_group_identifier: impl Into<String>) -> Self {
+        self.request
+            .subscription_group_identifiers
+            .get_or_insert_with(Vec::new)
+            .push(subscription_group_identifier.into());
+        self
+    }
+
+    /// Sets the sort order of the history.
+    pub fn sort(mut self, sort: Order) -> Self {
+        self.request.sort = Some(sort);
+        self
+    }
+
+    /// Limits the history to transactions with the in-app ownership type.
+    pub fn in_app_ownership_type(mut self, in_app_ownership_type: InAppOwnershipType) -> Self {
+        self.request.in_app_ownership_type = Some(in_app_ownership_type);
+        self
+    }
+
+    /// Limits the history to revoked, or to non-revoked, transactions.
+    pub fn revoked(mut self, revoked: bool) -> Self {
+        self.request.revoked = Some(revoked);
+        self
+    }
+
+    /// Creates the configured `TransactionHistoryRequest`.
+    ///
+    /// Returns `HistoryRequestError::InvalidDateRangeError` if the end date doesn't follow the start date.
+    pub fn build(self) -> Result<TransactionHistoryRequest, HistoryRequestError> {
+        if let (Some(start_date), Some(end_date)) = (self.request.start_date, self.request.end_date) {
+            if end_date <= start_date {
+                return Err(HistoryRequestError::InvalidDateRangeError(
+                    "endDate must follow startDate".to_string(),
+                ));
+            }
         }
+
+        Ok(self.request)
     }
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, Hash, PartialEq, Eq)]
-pub enum Order {
-    #[serde(rename = "ASCENDING")]
-    Ascending,
-    #[serde(rename = "DESCENDING")]
-    Descending,
+string_enum! {
+    pub enum ProductType {
+        AutoRenewable = "AUTO_RENEWABLE",
+        NonRenewable = "NON_RENEWABLE",
+        Consumable = "CONSUMABLE",
+        NonConsumable = "NON_CONSUMABLE",
+    }
 }
 
-impl Order {
-    pub fn raw_value(&self) -> &str {
-        match self {
-            Order::Ascending => "ASCENDING",
-            Order::Descending => "DESCENDING",
-        }
+string_enum! {
+    pub enum Order {
+        Ascending = "ASCENDING",
+        Descending = "DESCENDING",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        let request = TransactionHistoryRequest::builder()
+            .last(Duration::days(30))
+            .product_ids(["com.example.a", "com.example.b"])
+            .product_type(ProductType::AutoRenewable)
+            .sort(Order::Descending)
+            .revoked(false)
+            .build()
+            .unwrap();
+        assert_eq!(Some(vec!["com.example.a".to_string(), "com.example.b".to_string()]), request.product_ids);
+        assert_eq!(Some(vec![ProductType::AutoRenewable]), request.product_types);
+        assert_eq!(Some(Order::Descending), request.sort);
+        assert_eq!(Some(false), request.revoked);
+        assert_eq!(Duration::days(30), request.end_date.unwrap() - request.start_date.unwrap());
+
+        let now = timestamp::now();
+        assert!(matches!(
+            TransactionHistoryRequest::builder().date_range(now, now - Duration::days(1)).build(),
+            Err(HistoryRequestError::InvalidDateRangeError(_))
+        ));
+        assert!(matches!(
+            TransactionHistoryRequest::builder().date_range(now, now).build(),
+            Err(HistoryRequestError::InvalidDateRangeError(_))
+        ));
+        assert!(TransactionHistoryRequest::builder().date_range(now, now + Duration::milliseconds(1)).build().is_ok());
     }
-}
\ No newline at end of file
+}
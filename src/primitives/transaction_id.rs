@@ -0,0 +1,9 @@
+string_identifier! {
+    /// The unique identifier of a transaction, such as a purchase, restore, or subscription renewal.
+    ///
+    /// Endpoints that look up a customer's purchases accept the identifier of any of their transactions,
+    /// including an original transaction identifier, which converts into a `TransactionId`.
+    ///
+    /// [transactionId](https://developer.apple.com/documentation/appstoreserverapi/transactionid)
+    pub struct TransactionId;
+}
@@ -0,0 +1,36 @@
+use crate::primitives::environment::Environment;
+use crate::primitives::pending_renewal_info::PendingRenewalInfo;
+use crate::primitives::receipt_in_app_purchase::ReceiptInAppPurchase;
+use crate::primitives::verify_receipt_status::VerifyReceiptStatus;
+use serde::{Deserialize, Serialize};
+
+/// An object that contains information about the most recent in-app purchase transactions for the app.
+///
+/// [unified_receipt](https://developer.apple.com/documentation/appstoreservernotifications/unified_receipt)
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
+pub struct UnifiedReceipt {
+    /// The environment for which the App Store generated the receipt.
+    ///
+    /// [environment](https://developer.apple.com/documentation/appstoreservernotifications/environment)
+    pub environment: Option<Environment>,
+
+    /// The latest Base64-encoded app receipt.
+    ///
+    /// [latest_receipt](https://developer.apple.com/documentation/appstoreservernotifications/latest_receipt)
+    pub latest_receipt: Option<String>,
+
+    /// An array that contains the latest 100 in-app purchase transactions of the decoded value in `latest_receipt`.
+    ///
+    /// [latest_receipt_info](https://developer.apple.com/documentation/appstoreservernotifications/unified_receipt/latest_receipt_info)
+    pub latest_receipt_info: Option<Vec<ReceiptInAppPurchase>>,
+
+    /// An array where each element contains the pending renewal information for each auto-renewable subscription.
+    ///
+    /// [pending_renewal_info](https://developer.apple.com/documentation/appstoreservernotifications/unified_receipt/pending_renewal_info)
+    pub pending_renewal_info: Option<Vec<PendingRenewalInfo>>,
+
+    /// The status code, where 0 indicates that the notification is valid.
+    ///
+    /// [status](https://developer.apple.com/documentation/appstoreservernotifications/status)
+    pub status: Option<VerifyReceiptStatus>,
+}
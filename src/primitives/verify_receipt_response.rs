@@ -0,0 +1,158 @@
+use crate::primitives::environment::Environment;
+use crate::primitives::jws_transaction_decoded_payload::JWSTransactionDecodedPayload;
+use crate::primitives::pending_renewal_info::PendingRenewalInfo;
+use crate::primitives::receipt::Receipt;
+use crate::primitives::receipt_in_app_purchase::ReceiptInAppPurchase;
+use crate::primitives::verify_receipt_status::VerifyReceiptStatus;
+use serde::{Deserialize, Serialize};
+
+/// The JSON data returned in the response from the legacy `verifyReceipt` endpoint.
+///
+/// [responseBody](https://developer.apple.com/documentation/appstorereceipts/responsebody)
+#[derive(Debug, Clone, Deserialize, Serialize, Hash, PartialEq, Eq)]
+pub struct VerifyReceiptResponse {
+    /// Either 0 if the receipt is valid, or a status code if there is an error.
+    ///
+    /// [status](https://developer.apple.com/documentation/appstorereceipts/status)
+    pub status: VerifyReceiptStatus,
+
+    /// The environment for which the receipt was generated.
+    ///
+    /// [environment](https://developer.apple.com/documentation/appstorereceipts/environment)
+    pub environment: Option<Environment>,
+
+    /// An indicator that an error occurred during the request.
+    ///
+    /// [is-retryable](https://developer.apple.com/documentation/appstorereceipts/is-retryable)
+    #[serde(rename = "is-retryable")]
+    pub is_retryable: Option<bool>,
+
+    /// A JSON representation of the receipt that was sent for verification.
+    ///
+    /// [receipt](https://developer.apple.com/documentation/appstorereceipts/responsebody/receipt)
+    pub receipt: Option<Receipt>,
+
+    /// The latest Base64 encoded app receipt. Only returned for receipts that contain auto-renewable subscriptions.
+    ///
+    /// [latest_receipt](https://developer.apple.com/documentation/appstorereceipts/latest_receipt)
+    pub latest_receipt: Option<String>,
+
+    /// An array that contains all in-app purchase transactions.
+    ///
+    /// [latest_receipt_info](https://developer.apple.com/documentation/appstorereceipts/responsebody/latest_receipt_info)
+    pub latest_receipt_info: Option<Vec<ReceiptInAppPurchase>>,
+
+    /// An array where each element contains the pending renewal information for each auto-renewable subscription.
+    ///
+    /// [pending_renewal_info](https://developer.apple.com/documentation/appstorereceipts/responsebody/pending_renewal_info)
+    pub pending_renewal_info: Option<Vec<PendingRenewalInfo>>,
+}
+
+impl VerifyReceiptResponse {
+    /// The purchases in the response mapped onto the App Store Server API transaction model.
+    ///
+    /// Uses `latest_receipt_info` when present, falling back to the `in_app` array of the receipt.
+    pub fn transactions(&self) -> Vec<JWSTransactionDecodedPayload> {
+        let bundle_id = self.receipt.as_ref().and_then(|r| r.bundle_id.clone());
+        let purchases = self
+            .latest_receipt_info
+            .as_ref()
+            .or_else(|| self.receipt.as_ref().and_then(|r| r.in_app.as_ref()));
+
+        purchases
+            .map(|purchases| {
+                purchases
+                    .iter()
+                    .map(|p| p.to_transaction(bundle_id.clone(), self.environment.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::auto_renew_status::AutoRenewStatus;
+    use crate::primitives::expiration_intent::ExpirationIntent;
+    use crate::primitives::in_app_ownership_type::InAppOwnershipType;
+    use crate::primitives::product_type::ProductType;
+    use crate::primitives::web_order_line_item_id::WebOrderLineItemId;
+    use crate::primitives::timestamp;
+
+    #[test]
+    fn test_verify_receipt_response() {
+        let json = std::fs::read_to_string("assets/models/verifyReceiptResponse.json").expect("Failed to read file");
+        let response: VerifyReceiptResponse = serde_json::from_str(&json).expect("Failed to parse response");
+
+        assert_eq!(VerifyReceiptStatus::Valid, response.status);
+        assert_eq!(Some(Environment::Sandbox), response.environment);
+        assert_eq!(Some("MIIUVAYJKoZIhvcNAQcCoIIURTCCFEECAQExCzAJBgUrDgMCGgUAMIIDkgYJKoZIhvcNAQcBoIIDgwSCA38xggN7MAoCAQgCAQEEAhYAMAoCARQCAQEEAgwAMAsCAQECAQEEAwIBADALAgELAgEBBAMCAQAwCwIBDwIBAQQDAgEA".to_string()), response.latest_receipt);
+
+        let receipt = response.receipt.expect("Expected receipt");
+        assert_eq!(Some("ProductionSandbox".to_string()), receipt.receipt_type);
+        assert_eq!(Some(0), receipt.adam_id);
+        assert_eq!(Some("com.example".to_string()), receipt.bundle_id);
+        assert_eq!(timestamp::from_millis(1698148900000), receipt.receipt_creation_date);
+        assert_eq!(timestamp::from_millis(1698148950000), receipt.request_date);
+        assert_eq!(1, receipt.in_app.expect("Expected in_app").len());
+
+        let latest = response.latest_receipt_info.expect("Expected latest_receipt_info");
+        assert_eq!(1, latest.len());
+        let purchase = &latest[0];
+        assert_eq!(Some(1), purchase.quantity);
+        assert_eq!(Some("com.example.product".to_string()), purchase.product_id);
+        assert_eq!(Some("2000000000000001"), purchase.transaction_id.as_deref());
+        assert_eq!(Some("2000000000000000"), purchase.original_transaction_id.as_deref());
+        assert_eq!(timestamp::from_millis(1698148800000), purchase.purchase_date);
+        assert_eq!(timestamp::from_millis(1698149100000), purchase.expires_date);
+        assert_eq!(Some(false), purchase.is_trial_period);
+        assert_eq!(Some(false), purchase.is_in_intro_offer_period);
+        assert_eq!(Some(InAppOwnershipType::Purchased), purchase.in_app_ownership_type);
+        assert_eq!(None, purchase.cancellation_date);
+        assert_eq!(None, purchase.cancellation_reason);
+
+        let pending = response.pending_renewal_info.expect("Expected pending_renewal_info");
+        assert_eq!(1, pending.len());
+        let renewal = &pending[0];
+        assert_eq!(Some(AutoRenewStatus::Off), renewal.auto_renew_status);
+        assert_eq!(Some(ExpirationIntent::CustomerCancelled), renewal.expiration_intent);
+        assert_eq!(Some(false), renewal.is_in_billing_retry_period);
+        assert_eq!(Some("com.example.product".to_string()), renewal.auto_renew_product_id);
+    }
+
+    #[test]
+    fn test_verify_receipt_status_codes() {
+        let response: VerifyReceiptResponse = serde_json::from_str(r#"{"status": 21007}"#).unwrap();
+        assert_eq!(VerifyReceiptStatus::SandboxReceiptSentToProduction, response.status);
+
+        let response: VerifyReceiptResponse = serde_json::from_str(r#"{"status": 21150, "is-retryable": true}"#).unwrap();
+        assert_eq!(VerifyReceiptStatus::InternalError(21150), response.status);
+        assert_eq!(Some(true), response.is_retryable);
+
+        let response: VerifyReceiptResponse = serde_json::from_str(r#"{"status": 12345}"#).unwrap();
+        assert_eq!(VerifyReceiptStatus::Unknown(12345), response.status);
+        assert_eq!(12345, response.status.raw_value());
+    }
+
+    #[test]
+    fn test_verify_receipt_response_transactions() {
+        let json = std::fs::read_to_string("assets/models/verifyReceiptResponse.json").expect("Failed to read file");
+        let response: VerifyReceiptResponse = serde_json::from_str(&json).expect("Failed to parse response");
+
+        let transactions = response.transactions();
+        assert_eq!(1, transactions.len());
+        let transaction = &transactions[0];
+        assert_eq!(Some("2000000000000001"), transaction.transaction_id.as_deref());
+        assert_eq!(Some("2000000000000000"), transaction.original_transaction_id.as_deref());
+        assert_eq!(Some(WebOrderLineItemId::from("2000000000000002")), transaction.web_order_line_item_id);
+        assert_eq!(Some("com.example".to_string()), transaction.bundle_id);
+        assert_eq!(Some("20000000".to_string()), transaction.subscription_group_identifier);
+        assert_eq!(Some(Environment::Sandbox), transaction.environment);
+        assert_eq!(Some(ProductType::AutoRenewableSubscription), transaction.r#type);
+        assert_eq!(timestamp::from_millis(1698148800000), transaction.purchase_date);
+        assert_eq!(timestamp::from_millis(1698149100000), transaction.expires_date);
+        assert_eq!(None, transaction.offer_type);
+        assert_eq!(None, transaction.price);
+    }
+}
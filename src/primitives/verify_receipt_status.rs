@@ -0,0 +1,133 @@
+use crate::primitives::raw_value::RawValue;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+/// The status code of a legacy `verifyReceipt` response.
+///
+/// [status](https://developer.apple.com/documentation/appstorereceipts/status)
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Hash, PartialEq, Eq)]
+#[serde(from = "i64", into = "i64")]
+pub enum VerifyReceiptStatus {
+    /// The receipt is valid.
+    Valid,
+    /// The request to the App Store wasn’t made using the HTTP POST request method.
+    InvalidRequestMethod,
+    /// This status code is no longer sent by the App Store.
+    NoLongerSent,
+    /// The data in the receipt-data property is malformed or the service experienced a temporary issue.
+    MalformedData,
+    /// The system couldn’t authenticate the receipt.
+    NotAuthenticated,
+    /// The shared secret you provided doesn’t match the shared secret on file for your account.
+    SharedSecretMismatch,
+    /// The receipt server was temporarily unable to provide the receipt.
+    ServerUnavailable,
+    /// The receipt is valid, but the subscription is in an expired state.
+    SubscriptionExpired,
+    /// The receipt is from the test environment, but you sent it to the production environment for verification.
+    SandboxReceiptSentToProduction,
+    /// The receipt is from the production environment, but you sent it to the test environment for verification.
+    ProductionReceiptSentToSandbox,
+    /// An internal data access error occurred.
+    InternalDataAccessError,
+    /// The system can’t find the user account or the user account has been deleted.
+    AccountNotFound,
+    /// An internal data access error in the `21100`-`21199` range.
+    InternalError(i64),
+    /// A status code that isn’t documented.
+    Unknown(i64),
+}
+
+impl VerifyReceiptStatus {
+    pub fn raw_value(&self) -> i64 {
+        match self {
+            VerifyReceiptStatus::Valid => 0,
+            VerifyReceiptStatus::InvalidRequestMethod => 21000,
+            VerifyReceiptStatus::NoLongerSent => 21001,
+            VerifyReceiptStatus::MalformedData => 21002,
+            VerifyReceiptStatus::NotAuthenticated => 21003,
+            VerifyReceiptStatus::SharedSecretMismatch => 21004,
+            VerifyReceiptStatus::ServerUnavailable => 21005,
+            VerifyReceiptStatus::SubscriptionExpired => 21006,
+            VerifyReceiptStatus::SandboxReceiptSentToProduction => 21007,
+            VerifyReceiptStatus::ProductionReceiptSentToSandbox => 21008,
+            VerifyReceiptStatus::InternalDataAccessError => 21009,
+            VerifyReceiptStatus::AccountNotFound => 21010,
+            VerifyReceiptStatus::InternalError(code) => *code,
+            VerifyReceiptStatus::Unknown(code) => *code,
+        }
+    }
+}
+
+impl From<i64> for VerifyReceiptStatus {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => VerifyReceiptStatus::Valid,
+            21000 => VerifyReceiptStatus::InvalidRequestMethod,
+            21001 => VerifyReceiptStatus::NoLongerSent,
+            21002 => VerifyReceiptStatus::MalformedData,
+            21003 => VerifyReceiptStatus::NotAuthenticated,
+            21004 => VerifyReceiptStatus::SharedSecretMismatch,
+            21005 => VerifyReceiptStatus::ServerUnavailable,
+            21006 => VerifyReceiptStatus::SubscriptionExpired,
+            21007 => VerifyReceiptStatus::SandboxReceiptSentToProduction,
+            21008 => VerifyReceiptStatus::ProductionReceiptSentToSandbox,
+            21009 => VerifyReceiptStatus::InternalDataAccessError,
+            21010 => VerifyReceiptStatus::AccountNotFound,
+            21100..=21199 => VerifyReceiptStatus::InternalError(value),
+            _ => VerifyReceiptStatus::Unknown(value),
+        }
+    }
+}
+
+impl FromStr for VerifyReceiptStatus {
+    type Err = ParseIntError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        raw.parse::<i64>().map(VerifyReceiptStatus::from)
+    }
+}
+
+impl fmt::Display for VerifyReceiptStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.raw_value())
+    }
+}
+
+impl RawValue for VerifyReceiptStatus {
+    type Raw<'a> = i64;
+
+    fn raw_value(&self) -> i64 {
+        VerifyReceiptStatus::raw_value(self)
+    }
+
+    fn from_raw(raw: i64) -> Self {
+        VerifyReceiptStatus::from(raw)
+    }
+
+    /// The documented status codes, excluding the `InternalError` range.
+    fn known_values() -> &'static [Self] {
+        &[
+            VerifyReceiptStatus::Valid,
+            VerifyReceiptStatus::InvalidRequestMethod,
+            VerifyReceiptStatus::NoLongerSent,
+            VerifyReceiptStatus::MalformedData,
+            VerifyReceiptStatus::NotAuthenticated,
+            VerifyReceiptStatus::SharedSecretMismatch,
+            VerifyReceiptStatus::ServerUnavailable,
+            VerifyReceiptStatus::SubscriptionExpired,
+            VerifyReceiptStatus::SandboxReceiptSentToProduction,
+            VerifyReceiptStatus::ProductionReceiptSentToSandbox,
+            VerifyReceiptStatus::InternalDataAccessError,
+            VerifyReceiptStatus::AccountNotFound,
+        ]
+    }
+}
+
+impl From<VerifyReceiptStatus> for i64 {
+    fn from(value: VerifyReceiptStatus) -> Self {
+        value.raw_value()
+    }
+}
@@ -0,0 +1,91 @@
+use crate::primitives::jws_transaction_decoded_payload::JWSTransactionDecodedPayload;
+use std::collections::HashMap;
+
+string_identifier! {
+    /// The unique identifier of subscription-purchase events across devices, including renewals.
+    ///
+    /// Every renewal of a subscription period shares the same ID across transaction history, notifications
+    /// and Apple's financial reports, which makes it the key for reconciling those sources.
+    ///
+    /// [webOrderLineItemId](https://developer.apple.com/documentation/appstoreserverapi/weborderlineitemid)
+    pub struct WebOrderLineItemId;
+}
+
+impl JWSTransactionDecodedPayload {
+    /// Returns `true` if both transactions belong to the same web order line item.
+    ///
+    /// Transactions without a web order line item ID never match.
+    pub fn shares_web_order_line_item(&self, other: &JWSTransactionDecodedPayload) -> bool {
+        match (&self.web_order_line_item_id, &other.web_order_line_item_id) {
+            (Some(lhs), Some(rhs)) => lhs == rhs,
+            _ => false,
+        }
+    }
+}
+
+/// Groups transactions by their web order line item ID, ordering each group by purchase date.
+///
+/// Transactions from different sources (transaction history, notifications, legacy receipts) can be
+/// combined; transactions without a web order line item ID are skipped.
+pub fn group_by_web_order_line_item_id<'a>(
+    transactions: impl IntoIterator<Item = &'a JWSTransactionDecodedPayload>,
+) -> HashMap<WebOrderLineItemId, Vec<&'a JWSTransactionDecodedPayload>> {
+    let mut groups: HashMap<WebOrderLineItemId, Vec<&'a JWSTransactionDecodedPayload>> = HashMap::new();
+
+    for transaction in transactions {
+        if let Some(id) = &transaction.web_order_line_item_id {
+            groups.entry(id.clone()).or_default().push(transaction);
+        }
+    }
+
+    for group in groups.values_mut() {
+        group.sort_by_key(|t| t.purchase_date);
+    }
+
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::transaction_with_fields;
+
+    fn transaction(transaction_id: &str, web_order_line_item_id: Option<&str>, purchase_date: i64) -> JWSTransactionDecodedPayload {
+        transaction_with_fields(serde_json::json!({
+            "transactionId": transaction_id,
+            "webOrderLineItemId": web_order_line_item_id,
+            "purchaseDate": purchase_date,
+        }))
+    }
+
+    #[test]
+    fn test_group_by_web_order_line_item_id() {
+        let renewal = transaction("2", Some("100"), 2000);
+        let purchase = transaction("1", Some("100"), 1000);
+        let other = transaction("3", Some("200"), 1500);
+        let consumable = transaction("4", None, 1200);
+
+        assert!(renewal.shares_web_order_line_item(&purchase));
+        assert!(!renewal.shares_web_order_line_item(&other));
+        assert!(!consumable.shares_web_order_line_item(&consumable));
+
+        let transactions = vec![renewal, purchase, other, consumable];
+        let groups = group_by_web_order_line_item_id(&transactions);
+
+        assert_eq!(2, groups.len());
+        let ids: Vec<&str> = groups[&WebOrderLineItemId::from("100")]
+            .iter()
+            .map(|t| t.transaction_id.as_deref().unwrap())
+            .collect();
+        assert_eq!(vec!["1", "2"], ids);
+        assert_eq!(1, groups[&WebOrderLineItemId::from("200")].len());
+    }
+
+    #[test]
+    fn test_web_order_line_item_id_serde() {
+        let id: WebOrderLineItemId = serde_json::from_str("\"34343\"").unwrap();
+        assert_eq!("34343", id.as_str());
+        assert_eq!(id, "34343");
+        assert_eq!("\"34343\"", serde_json::to_string(&id).unwrap());
+    }
+}
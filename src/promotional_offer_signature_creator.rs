@@ -1,9 +1,14 @@
 use base64::prelude::BASE64_STANDARD;
 use base64::Engine;
 use pem::{parse, PemError};
-use ring::signature::{EcdsaKeyPair, Signature, ECDSA_P256_SHA256_ASN1_SIGNING};
+use crate::clock::{system_clock, Clock};
+use crate::key_ring::KeyRing;
+use crate::signer::{fixed_signature_to_der, EcdsaSigner, KeyBytes, Signer, SignerError};
+use crate::utils::{validate_bundle_id, validate_key_id, validate_product_identifier};
+use ring::signature::{EcdsaKeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
 use ring::{error, rand};
 use std::fmt::{Display, Formatter};
+use std::sync::Arc;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -22,6 +27,7 @@ impl Display for KeyRejectedWrapped {
 }
 
 #[derive(thiserror::Error, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum PromotionalOfferSignatureCreatorError {
     #[error("UnspecifiedRingError: [{0}]")]
     UnspecifiedRingError(#[from] error::Unspecified),
@@ -31,21 +37,115 @@ pub enum PromotionalOfferSignatureCreatorError {
 
     #[error("InternalPemError: [{0}]")]
     InternalPemError(#[from] PemError),
+
+    #[error("InternalSignerError: [{0}]")]
+    InternalSignerError(#[from] SignerError),
+
+    #[error("InvalidConfigurationError: [{0}]")]
+    InvalidConfigurationError(String),
+
+    #[error("InvalidParameterError: [{0}]")]
+    InvalidParameterError(String),
+}
+
+/// The values of one promotional offer to sign in a batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromotionalOfferSignatureRequest {
+    pub product_identifier: String,
+    pub subscription_offer_id: String,
+    pub application_username: String,
+}
+
+/// A promotional offer signature with the values StoreKit needs to redeem it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromotionalOfferSignature {
+    pub key_id: String,
+    pub nonce: uuid::Uuid,
+    /// The signing time, in milliseconds since the Unix epoch.
+    pub timestamp: i64,
+    /// The Base64-encoded signature.
+    pub signature: String,
 }
 
 /// Struct responsible for creating promotional offer signatures.
 pub struct PromotionalOfferSignatureCreator {
-    ec_private_key: EcdsaKeyPair,
-    key_id: String,
+    keys: Arc<KeyRing<dyn Signer>>,
+    bundle_id: String,
+    clock: Arc<dyn Clock>,
+}
+
+/// Builder for [`PromotionalOfferSignatureCreator`] that validates its configuration up front.
+pub struct PromotionalOfferSignatureCreatorBuilder {
     bundle_id: String,
+    key_id: Option<String>,
+    private_key: Option<KeyBytes>,
+    signer: Option<Arc<dyn Signer>>,
+}
+
+impl PromotionalOfferSignatureCreatorBuilder {
+    /// Sets the private key, as PEM or DER-encoded PKCS#8 or SEC1.
+    pub fn private_key(mut self, private_key: &[u8]) -> Self {
+        self.private_key = Some(KeyBytes::from(private_key.to_vec()));
+        self.signer = None;
+        self
+    }
+
+    /// Sets a signer holding the private key, replacing any private key set before.
+    pub fn signer(mut self, signer: Arc<dyn Signer>) -> Self {
+        self.signer = Some(signer);
+        self.private_key = None;
+        self
+    }
+
+    /// Sets the ID of the private key from App Store Connect.
+    pub fn key_id(mut self, key_id: &str) -> Self {
+        self.key_id = Some(key_id.to_string());
+        self
+    }
+
+    /// Validates the configuration and creates the `PromotionalOfferSignatureCreator`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidConfigurationError` if the key ID or bundle ID is missing or malformed or no key
+    /// was set, and `InternalSignerError` if the private key can't be read.
+    pub fn build(self) -> Result<PromotionalOfferSignatureCreator, PromotionalOfferSignatureCreatorError> {
+        let key_id = self.key_id.ok_or_else(|| {
+            PromotionalOfferSignatureCreatorError::InvalidConfigurationError("Missing key ID".to_string())
+        })?;
+        validate_key_id(&key_id).map_err(PromotionalOfferSignatureCreatorError::InvalidConfigurationError)?;
+        validate_bundle_id(&self.bundle_id).map_err(PromotionalOfferSignatureCreatorError::InvalidConfigurationError)?;
+
+        let signer: Arc<dyn Signer> = match (self.signer, self.private_key) {
+            (Some(signer), _) => signer,
+            (None, Some(private_key)) => Arc::new(EcdsaSigner::from_key(private_key.as_slice())?),
+            (None, None) => {
+                return Err(PromotionalOfferSignatureCreatorError::InvalidConfigurationError(
+                    "Missing private key".to_string(),
+                ))
+            }
+        };
+
+        Ok(PromotionalOfferSignatureCreator::with_signer(signer, key_id, self.bundle_id))
+    }
 }
 
 impl PromotionalOfferSignatureCreator {
+    /// Creates a builder that validates the key, key ID and bundle ID when building.
+    pub fn builder(bundle_id: &str) -> PromotionalOfferSignatureCreatorBuilder {
+        PromotionalOfferSignatureCreatorBuilder {
+            bundle_id: bundle_id.to_string(),
+            key_id: None,
+            private_key: None,
+            signer: None,
+        }
+    }
+
     /// Creates a new `PromotionalOfferSignatureCreator` instance.
     ///
     /// # Arguments
     ///
-    /// * `private_key`: A PEM-encoded private key.
+    /// * `private_key`: A PEM-encoded PKCS#8 or SEC1 private key.
     /// * `key_id`: A String representing the key ID.
     /// * `bundle_id`: A String representing the bundle ID.
     ///
@@ -58,18 +158,108 @@ impl PromotionalOfferSignatureCreator {
         bundle_id: String,
     ) -> Result<Self, PromotionalOfferSignatureCreatorError> {
         let pem = parse(private_key)?;
-        let private_key = pem.contents();
-        let alg = &ECDSA_P256_SHA256_ASN1_SIGNING;
+        let is_sec1 = pem.tag() == "EC PRIVATE KEY";
+        let private_key = KeyBytes::from(pem.into_contents());
+
+        if is_sec1 {
+            let signer = EcdsaSigner::from_sec1_der(private_key.as_slice())?;
+            return Ok(Self::with_signer(Arc::new(signer), key_id, bundle_id));
+        }
+
+        let alg = &ECDSA_P256_SHA256_FIXED_SIGNING;
         let rng = rand::SystemRandom::new();
 
         let ec_private_key =
-            EcdsaKeyPair::from_pkcs8(alg, private_key, &rng).map_err(KeyRejectedWrapped)?;
+            EcdsaKeyPair::from_pkcs8(alg, private_key.as_slice(), &rng).map_err(KeyRejectedWrapped)?;
 
-        Ok(PromotionalOfferSignatureCreator {
-            ec_private_key,
-            key_id,
-            bundle_id,
-        })
+        Ok(Self::with_signer(Arc::new(EcdsaSigner::from_key_pair(ec_private_key)), key_id, bundle_id))
+    }
+
+    /// Creates a new `PromotionalOfferSignatureCreator` that signs with the given signer,
+    /// such as one backed by a key management service or an HSM.
+    ///
+    /// # Arguments
+    ///
+    /// * `signer`: The signer holding the private key.
+    /// * `key_id`: A String representing the key ID.
+    /// * `bundle_id`: A String representing the bundle ID.
+    pub fn with_signer(signer: Arc<dyn Signer>, key_id: String, bundle_id: String) -> Self {
+        Self::with_key_ring(Arc::new(KeyRing::with_key(&key_id, signer)), bundle_id)
+    }
+
+    /// Creates a new `PromotionalOfferSignatureCreator` that signs with the active key of the key ring.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys`: The key ring holding the private keys.
+    /// * `bundle_id`: A String representing the bundle ID.
+    pub fn with_key_ring(keys: Arc<KeyRing<dyn Signer>>, bundle_id: String) -> Self {
+        PromotionalOfferSignatureCreator { keys, bundle_id, clock: system_clock() }
+    }
+
+    /// Sets the clock that [`create_signatures`](Self::create_signatures) reads the signing time from.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// The ID of the key [`create_signature`](Self::create_signature) currently signs with.
+    ///
+    /// StoreKit needs this key ID alongside the signature. To avoid racing a key rotation,
+    /// read it once and pass it to [`create_signature_with_key`](Self::create_signature_with_key).
+    pub fn active_key_id(&self) -> Result<String, PromotionalOfferSignatureCreatorError> {
+        Ok(self.keys.active_key_id()?)
+    }
+
+    /// Signs many promotional offers with the same key, generating a nonce and timestamp for each.
+    ///
+    /// The active key is read once, so all signatures of a batch share a key ID even if the key
+    /// ring rotates meanwhile. With `parallelism` greater than 1, the requests are split across
+    /// that many threads.
+    ///
+    /// # Returns
+    ///
+    /// One result per request, in the order of `requests`, or an error if there's no active key.
+    pub fn create_signatures(
+        &self,
+        requests: &[PromotionalOfferSignatureRequest],
+        parallelism: usize,
+    ) -> Result<Vec<Result<PromotionalOfferSignature, PromotionalOfferSignatureCreatorError>>, PromotionalOfferSignatureCreatorError> {
+        let key_id = self.active_key_id()?;
+
+        let sign = |request: &PromotionalOfferSignatureRequest| {
+            let nonce = uuid::Uuid::new_v4();
+            let timestamp = self.clock.now().timestamp_millis();
+            let signature = self.create_signature_with_key(
+                &key_id,
+                &request.product_identifier,
+                &request.subscription_offer_id,
+                &request.application_username,
+                &nonce,
+                timestamp,
+            )?;
+
+            Ok(PromotionalOfferSignature { key_id: key_id.clone(), nonce, timestamp, signature })
+        };
+
+        if parallelism <= 1 || requests.len() <= 1 {
+            return Ok(requests.iter().map(sign).collect());
+        }
+
+        let chunk_size = requests.len().div_ceil(parallelism);
+        let results = std::thread::scope(|scope| {
+            let handles: Vec<_> = requests
+                .chunks(chunk_size)
+                .map(|chunk| scope.spawn(|| chunk.iter().map(sign).collect::<Vec<_>>()))
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("Signing thread panicked"))
+                .collect()
+        });
+
+        Ok(results)
     }
 
     /// Creates a digital signature for a promotional offer.
@@ -93,53 +283,111 @@ impl PromotionalOfferSignatureCreator {
         nonce: &uuid::Uuid,
         timestamp: i64,
     ) -> Result<String, PromotionalOfferSignatureCreatorError> {
+        let key_id = self.active_key_id()?;
+        self.create_signature_with_key(
+            &key_id,
+            product_identifier,
+            subscription_offer_id,
+            application_username,
+            nonce,
+            timestamp,
+        )
+    }
+
+    /// Creates a digital signature for a promotional offer with a specific key of the key ring.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_id`: The ID of the key to sign with.
+    /// * `product_identifier`: The product identifier.
+    /// * `subscription_offer_id`: The subscription offer identifier.
+    /// * `application_username`: The application username.
+    /// * `nonce`: A UUID representing a unique value.
+    /// * `timestamp`: A timestamp.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the Base64-encoded signature or an error. Returns `InvalidParameterError`
+    /// without signing if the product or offer identifier is empty or contains whitespace or control characters.
+    pub fn create_signature_with_key(
+        &self,
+        key_id: &str,
+        product_identifier: &str,
+        subscription_offer_id: &str,
+        application_username: &str,
+        nonce: &uuid::Uuid,
+        timestamp: i64,
+    ) -> Result<String, PromotionalOfferSignatureCreatorError> {
+        validate_product_identifier(product_identifier).map_err(PromotionalOfferSignatureCreatorError::InvalidParameterError)?;
+        validate_product_identifier(subscription_offer_id).map_err(PromotionalOfferSignatureCreatorError::InvalidParameterError)?;
+
         let payload = self.payload(
+            key_id,
             product_identifier,
             subscription_offer_id,
             application_username,
             nonce,
             timestamp,
         );
-        let signature = self.sign(payload.as_str())?;
-        let signature_base64 = BASE64_STANDARD.encode(signature.as_ref());
+        let signature = self.sign(key_id, payload.as_str())?;
+        let signature_base64 = BASE64_STANDARD.encode(signature);
 
         Ok(signature_base64)
     }
 
     fn payload(
         &self,
+        key_id: &str,
         product_identifier: &str,
         subscription_offer_id: &str,
         application_username: &str,
         nonce: &uuid::Uuid,
         timestamp: i64,
     ) -> String {
-        format!(
-            "{}\u{2063}{}\u{2063}{}\u{2063}{}\u{2063}{}\u{2063}{}\u{2063}{}",
-            self.bundle_id,
-            self.key_id,
+        promotional_offer_payload(
+            &self.bundle_id,
+            key_id,
             product_identifier,
             subscription_offer_id,
-            application_username.to_lowercase(),
-            nonce.to_string().to_lowercase(),
-            timestamp
+            application_username,
+            nonce,
+            timestamp,
         )
     }
 
-    fn sign(&self, payload: &str) -> Result<Signature, PromotionalOfferSignatureCreatorError> {
-        Ok(self
-            .ec_private_key
-            .sign(&ring::rand::SystemRandom::new(), payload.as_bytes())?)
+    fn sign(&self, key_id: &str, payload: &str) -> Result<Vec<u8>, PromotionalOfferSignatureCreatorError> {
+        let signature = self.keys.key(key_id)?.sign(payload.as_bytes())?;
+        Ok(fixed_signature_to_der(&signature)?)
     }
 
     #[cfg(test)]
-    fn public_key(&self) -> Vec<u8> {
-        use ring::signature::KeyPair;
-
-        return self.ec_private_key.public_key().as_ref().to_vec();
+    fn public_key(&self, key_id: &str) -> Vec<u8> {
+        return self.keys.key(key_id).unwrap().public_key().unwrap();
     }
 }
 
+/// The string signed for a promotional offer, with its fields joined by U+2063 (INVISIBLE SEPARATOR).
+pub(crate) fn promotional_offer_payload(
+    bundle_id: &str,
+    key_id: &str,
+    product_identifier: &str,
+    subscription_offer_id: &str,
+    application_username: &str,
+    nonce: &uuid::Uuid,
+    timestamp: i64,
+) -> String {
+    format!(
+        "{}\u{2063}{}\u{2063}{}\u{2063}{}\u{2063}{}\u{2063}{}\u{2063}{}",
+        bundle_id,
+        key_id,
+        product_identifier,
+        subscription_offer_id,
+        application_username.to_lowercase(),
+        nonce.to_string().to_lowercase(),
+        timestamp
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -178,16 +426,17 @@ mod tests {
         )
         .unwrap();
         let payload = creator.payload(
+            "L256SYR32L",
             "com.test.product",
             "com.test.offer",
             uuid::Uuid::new_v4().to_string().as_str(),
             &uuid::Uuid::new_v4(),
             i64::try_from(system_timestamp()).unwrap(),
         );
-        let signature = creator.sign(payload.as_str()).unwrap();
+        let signature = creator.sign("L256SYR32L", payload.as_str()).unwrap();
 
         // Verify
-        let public_key = creator.public_key();
+        let public_key = creator.public_key("L256SYR32L");
         let public_key = UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, public_key.as_slice());
         assert_eq!(
             (),
@@ -196,4 +445,113 @@ mod tests {
                 .unwrap()
         );
     }
+
+    #[test]
+    fn test_builder() {
+        let private_key = include_bytes!("../assets/SubscriptionKey_L256SYR32L.p8");
+        let creator = PromotionalOfferSignatureCreator::builder("com.test.app")
+            .private_key(private_key)
+            .key_id("L256SYR32L")
+            .build()
+            .unwrap();
+        assert_eq!("L256SYR32L", creator.active_key_id().unwrap());
+
+        let result = creator.create_signature(
+            "com.test.product",
+            "",
+            "username",
+            &uuid::Uuid::new_v4(),
+            i64::try_from(system_timestamp()).unwrap(),
+        );
+        assert!(matches!(result, Err(PromotionalOfferSignatureCreatorError::InvalidParameterError(_))));
+    }
+
+    #[test]
+    fn test_builder_validation() {
+        let private_key = include_bytes!("../assets/SubscriptionKey_L256SYR32L.p8");
+
+        let missing_key = PromotionalOfferSignatureCreator::builder("com.test.app").key_id("L256SYR32L").build();
+        assert!(matches!(missing_key, Err(PromotionalOfferSignatureCreatorError::InvalidConfigurationError(_))));
+
+        let invalid_key_id = PromotionalOfferSignatureCreator::builder("com.test.app")
+            .private_key(private_key)
+            .key_id("keyId")
+            .build();
+        assert!(matches!(invalid_key_id, Err(PromotionalOfferSignatureCreatorError::InvalidConfigurationError(_))));
+
+        let invalid_bundle_id = PromotionalOfferSignatureCreator::builder("com test")
+            .private_key(private_key)
+            .key_id("L256SYR32L")
+            .build();
+        assert!(matches!(invalid_bundle_id, Err(PromotionalOfferSignatureCreatorError::InvalidConfigurationError(_))));
+
+        let invalid_key = PromotionalOfferSignatureCreator::builder("com.test.app")
+            .private_key(b"invalid")
+            .key_id("L256SYR32L")
+            .build();
+        assert!(matches!(invalid_key, Err(PromotionalOfferSignatureCreatorError::InternalSignerError(_))));
+    }
+
+    #[test]
+    fn test_create_signatures() {
+        let private_key = include_str!("../assets/SubscriptionKey_L256SYR32L.p8");
+        let creator = PromotionalOfferSignatureCreator::new(
+            private_key,
+            "L256SYR32L".to_string(),
+            "com.test.app".to_string(),
+        )
+        .unwrap();
+        let public_key = creator.public_key("L256SYR32L");
+        let public_key = UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, public_key.as_slice());
+
+        let mut requests: Vec<_> = (0..5)
+            .map(|i| PromotionalOfferSignatureRequest {
+                product_identifier: "com.test.product".to_string(),
+                subscription_offer_id: "com.test.offer".to_string(),
+                application_username: format!("user{}", i),
+            })
+            .collect();
+        requests[3].subscription_offer_id = String::new();
+
+        for parallelism in [1, 2] {
+            let results = creator.create_signatures(&requests, parallelism).unwrap();
+            assert_eq!(5, results.len());
+            assert!(matches!(results[3], Err(PromotionalOfferSignatureCreatorError::InvalidParameterError(_))));
+
+            let signature = results[4].as_ref().unwrap();
+            assert_eq!("L256SYR32L", signature.key_id);
+            let payload = creator.payload(
+                &signature.key_id,
+                "com.test.product",
+                "com.test.offer",
+                "user4",
+                &signature.nonce,
+                signature.timestamp,
+            );
+            let der = BASE64_STANDARD.decode(&signature.signature).unwrap();
+            assert!(public_key.verify(payload.as_bytes(), &der).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_create_signatures_reads_the_clock() {
+        use crate::clock::FrozenClock;
+
+        let private_key = include_str!("../assets/SubscriptionKey_L256SYR32L.p8");
+        let creator = PromotionalOfferSignatureCreator::new(
+            private_key,
+            "L256SYR32L".to_string(),
+            "com.test.app".to_string(),
+        )
+        .unwrap()
+        .clock(Arc::new(FrozenClock::new(chrono::DateTime::from_timestamp(1698148800, 0).unwrap())));
+
+        let requests = [PromotionalOfferSignatureRequest {
+            product_identifier: "com.test.product".to_string(),
+            subscription_offer_id: "com.test.offer".to_string(),
+            application_username: "user".to_string(),
+        }];
+        let results = creator.create_signatures(&requests, 1).unwrap();
+        assert_eq!(1698148800000, results[0].as_ref().unwrap().timestamp);
+    }
 }
@@ -1,9 +1,13 @@
-use asn1_rs::{Any, Class, Error, Explicit, FromBer, Integer, OctetString, Oid, Sequence, Set, TaggedValue, Utf8String};
+use asn1_rs::{Any, Class, Error, FromBer, Integer, Oid, Tag, Utf8String};
 use base64::Engine;
-use base64::engine::general_purpose::STANDARD;
+use base64::alphabet;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::DecodePaddingMode;
 use regex::Regex;
+use crate::utils::decode_base64;
 
 #[derive(thiserror::Error, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum ReceiptUtilityError {
     #[error("InternalBase64DecodeError: [{0}]")]
     InternalBase64DecodeError(#[from] base64::DecodeError),
@@ -18,115 +22,219 @@ pub enum ReceiptUtilityError {
     InternalRegexError(#[from] regex::Error),
 }
 
+/// Base64 engine that accepts input with or without trailing padding.
+const LENIENT_BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Decodes base64 as found in the wild: whitespace and line breaks are ignored, the URL-safe
+/// alphabet is mapped to the standard one and padding is optional.
+fn decode_lenient_base64(encoded: &[u8]) -> Result<Vec<u8>, base64::DecodeError> {
+    let normalized: Vec<u8> = encoded
+        .iter()
+        .filter(|b| !b.is_ascii_whitespace())
+        .map(|b| match b {
+            b'-' => b'+',
+            b'_' => b'/',
+            b => *b,
+        })
+        .collect();
+
+    #[cfg(feature = "performance")]
+    if let Ok(decoded) = base64_simd::forgiving_decode_to_vec(&normalized) {
+        return Ok(decoded);
+    }
+
+    LENIENT_BASE64.decode(normalized)
+}
+
+fn is_base64_text(input: &[u8]) -> bool {
+    input
+        .iter()
+        .all(|b| b.is_ascii_alphanumeric() || b.is_ascii_whitespace() || matches!(b, b'+' | b'/' | b'-' | b'_' | b'='))
+}
+
+/// Returns the DER bytes of an app receipt given either its base64 encoding or the raw DER.
+fn app_receipt_der(app_receipt: &[u8]) -> Result<Vec<u8>, base64::DecodeError> {
+    const DER_SEQUENCE_TAG: u8 = 0x30;
+
+    // Base64-encoded receipts only contain ASCII text, while a DER receipt is a SEQUENCE
+    // whose length bytes fall outside of the base64 alphabet.
+    if app_receipt.first() == Some(&DER_SEQUENCE_TAG) && !is_base64_text(app_receipt) {
+        return Ok(app_receipt.to_vec());
+    }
+
+    decode_lenient_base64(app_receipt)
+}
+
 /// Extracts a transaction id from an encoded App Receipt. Throws if the receipt does not match the expected format.
 /// # Notes
 /// *NO validation* is performed on the receipt, and any data returned should only be used to call the App Store Server API.
 /// # Arguments
-/// * `app_receipt`: The unmodified app receipt
+/// * `app_receipt`: The app receipt, either base64 encoded (standard or URL-safe, whitespace is ignored) or as raw DER bytes
 /// # Returns
 /// * `Option<String>`: A transaction id from the array of in-app purchases, none if the receipt contains no in-app purchases
-pub fn extract_transaction_id_from_app_receipt(app_receipt: &str) -> Result<Option<String>, ReceiptUtilityError> {
-    let app_receipt_bytes = STANDARD.decode(app_receipt)?;
-
-    let (_, transaction_id) = Sequence::from_ber_and_then(app_receipt_bytes.as_slice(), |i| {
-        // Skip the first object identifier
-        let (i, _) = Oid::from_ber(i)?;
-        let (i, value) =
-            TaggedValue::<Sequence, Error, Explicit, { Class::CONTEXT_SPECIFIC }, 0>::from_ber(
-                i,
-            )?;
-
-        let seq = value.into_inner();
-        seq.and_then(|ii| {
-            let (ii, _) = Any::from_ber(&ii)?; // Skip
-            let (ii, _) = Any::from_ber(&ii)?; // Skip
-
-            let r: (&[u8], Option<String>) = Sequence::from_ber_and_then(&ii, |iii| {
-                let (iii, _) = Oid::from_ber(iii)?; // Skip
-
-                let (iii, value) = TaggedValue::<
-                    OctetString,
-                    Error,
-                    Explicit,
-                    { Class::CONTEXT_SPECIFIC },
-                    0,
-                >::from_ber(&iii)?;
-
-                let content = value.into_inner();
-                let transaction_id = extract_transaction_id_from_app_receipt_inner(content.as_ref())?;
-
-                return Ok((iii, transaction_id));
-            })?;
-
-            let (ii, _) = Any::from_ber(&ii)?; // Skip
-            let (ii, _) = Any::from_ber(&ii)?; // Skip
-            let (_, _) = Any::from_ber(&ii)?; // Skip
-
-            return Ok((i, r.1));
-        })
-    })?;
-
-    Ok(transaction_id)
-}
-
-fn extract_transaction_id_from_app_receipt_inner(app_receipt_content: &[u8]) -> Result<Option<String>, asn1_rs::Err<Error>> {
+pub fn extract_transaction_id_from_app_receipt(app_receipt: impl AsRef<[u8]>) -> Result<Option<String>, ReceiptUtilityError> {
     const IN_APP_TYPE_ID: u64 = 17u64;
+    const TRANSACTION_IDENTIFIER_TYPE_ID: u64 = 1703u64;
+    const ORIGINAL_TRANSACTION_IDENTIFIER_TYPE_ID: u64 = 1705u64;
 
-    let (_, octet_string) = OctetString::from_ber(app_receipt_content)?;
-    let (_, set) = Set::from_ber(octet_string.as_ref())?;
+    let app_receipt_bytes = app_receipt_der(app_receipt.as_ref())?;
 
-    for (_, item) in set.ber_iter::<Sequence, Error>().enumerate() {
-        if let Ok(seq) = item {
-            let (ii, t) = Integer::from_ber(&seq.content)?;
-            let (ii, _) = Integer::from_ber(&ii)?;
+    for attribute in receipt_attributes(&app_receipt_bytes)? {
+        let attribute = attribute?;
 
-            let t = t.as_u64()?;
+        if attribute.attribute_type != IN_APP_TYPE_ID {
+            continue;
+        }
 
-            if t == IN_APP_TYPE_ID {
-                return extract_transaction_id_from_in_app_receipt(ii);
+        for in_app_attribute in attribute.nested_attributes()? {
+            let in_app_attribute = in_app_attribute?;
+            let t = in_app_attribute.attribute_type;
+
+            if t == TRANSACTION_IDENTIFIER_TYPE_ID || t == ORIGINAL_TRANSACTION_IDENTIFIER_TYPE_ID {
+                let (_, transaction_id) = Utf8String::from_ber(in_app_attribute.value)?;
+                return Ok(Some(transaction_id.string()));
             }
         }
+
+        return Ok(None);
     }
 
-    return Ok(None);
+    Ok(None)
 }
 
-fn extract_transaction_id_from_in_app_receipt(app_receipt_content: &[u8]) -> Result<Option<String>, asn1_rs::Err<Error>> {
-    const TRANSACTION_IDENTIFIER_TYPE_ID: u64 = 1703u64;
-    const ORIGINAL_TRANSACTION_IDENTIFIER_TYPE_ID: u64 = 1705u64;
+/// A single attribute of an app receipt payload.
+///
+/// The value borrows from the receipt data, so iterating over a receipt doesn't allocate per attribute.
+/// See [Receipt Fields](https://developer.apple.com/library/archive/releasenotes/General/ValidateAppStoreReceipt/Chapters/ReceiptFields.html)
+/// for the meaning of the attribute types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReceiptAttribute<'a> {
+    /// The type of the attribute, e.g. `17` for an in-app purchase receipt.
+    pub attribute_type: u64,
+    /// The version of the attribute.
+    pub version: u64,
+    /// The DER-encoded value of the attribute, without the enclosing OCTET STRING.
+    pub value: &'a [u8],
+}
 
-    let (_, octet_string) = OctetString::from_ber(app_receipt_content)?;
-    let (_, set) = Set::from_ber(octet_string.as_ref())?;
+impl<'a> ReceiptAttribute<'a> {
+    /// Iterates over the attributes nested in this attribute's value, such as the fields of an in-app purchase receipt.
+    pub fn nested_attributes(&self) -> Result<ReceiptAttributes<'a>, ReceiptUtilityError> {
+        ReceiptAttributes::from_set(self.value)
+    }
+}
 
-    for (_, item) in set.ber_iter::<Sequence, Error>().enumerate() {
-        if let Ok(seq) = item {
-            let (ii, t) = Integer::from_ber(&seq.content)?;
-            let (ii, _) = Integer::from_ber(&ii)?;
+/// An iterator over the attributes of an app receipt payload, created by [`receipt_attributes`].
+#[derive(Debug, Clone)]
+pub struct ReceiptAttributes<'a> {
+    remaining: &'a [u8],
+}
 
-            let t = t.as_u64()?;
+impl<'a> ReceiptAttributes<'a> {
+    fn from_set(bytes: &'a [u8]) -> Result<Self, ReceiptUtilityError> {
+        let (_, set) = Any::from_ber(bytes)?;
+        set.header.assert_tag(Tag::Set)?;
 
-            if t == TRANSACTION_IDENTIFIER_TYPE_ID || t == ORIGINAL_TRANSACTION_IDENTIFIER_TYPE_ID {
-                let (_, octet_string) = OctetString::from_ber(&ii)?;
-                let (_, transaction_id) = Utf8String::from_ber(octet_string.as_ref())?;
-                return Ok(Some(transaction_id.string()));
+        Ok(ReceiptAttributes { remaining: set.data })
+    }
+
+    fn parse_next(&mut self) -> Result<ReceiptAttribute<'a>, asn1_rs::Err<Error>> {
+        let (rest, seq) = Any::from_ber(self.remaining)?;
+        self.remaining = rest;
+        seq.header.assert_tag(Tag::Sequence)?;
+
+        let (i, attribute_type) = Integer::from_ber(seq.data)?;
+        let (i, version) = Integer::from_ber(i)?;
+        let (_, value) = Any::from_ber(i)?;
+        value.header.assert_tag(Tag::OctetString)?;
+
+        Ok(ReceiptAttribute {
+            attribute_type: attribute_type.as_u64()?,
+            version: version.as_u64()?,
+            value: value.data,
+        })
+    }
+}
+
+impl<'a> Iterator for ReceiptAttributes<'a> {
+    type Item = Result<ReceiptAttribute<'a>, ReceiptUtilityError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining.is_empty() {
+            return None;
+        }
+
+        match self.parse_next() {
+            Ok(attribute) => Some(Ok(attribute)),
+            Err(e) => {
+                // Stop after the first malformed attribute, the remaining bytes can't be trusted.
+                self.remaining = &[];
+                Some(Err(e.into()))
             }
         }
     }
+}
 
-    return Ok(None);
+/// Returns an iterator over the top-level attributes of a DER-encoded app receipt.
+/// # Notes
+/// *NO validation* is performed on the receipt signature. The receipt is read from borrowed slices,
+/// so large receipts can be inspected without copying their payload.
+/// # Arguments
+/// * `app_receipt_der`: The app receipt as raw DER (or BER) bytes
+pub fn receipt_attributes(app_receipt_der: &[u8]) -> Result<ReceiptAttributes<'_>, ReceiptUtilityError> {
+    // ContentInfo ::= SEQUENCE { contentType OID, content [0] EXPLICIT SignedData }
+    let (_, content_info) = Any::from_ber(app_receipt_der)?;
+    content_info.header.assert_tag(Tag::Sequence)?;
+    let (i, _) = Oid::from_ber(content_info.data)?;
+    let (_, content) = Any::from_ber(i)?;
+    content.header.assert_class(Class::ContextSpecific)?;
+
+    // SignedData ::= SEQUENCE { version, digestAlgorithms, encapContentInfo, ... }
+    let (_, signed_data) = Any::from_ber(content.data)?;
+    signed_data.header.assert_tag(Tag::Sequence)?;
+    let (i, _) = Any::from_ber(signed_data.data)?; // Skip version
+    let (i, _) = Any::from_ber(i)?; // Skip digestAlgorithms
+    let (_, encap_content_info) = Any::from_ber(i)?;
+
+    // EncapsulatedContentInfo ::= SEQUENCE { eContentType OID, eContent [0] EXPLICIT OCTET STRING }
+    let (i, _) = Oid::from_ber(encap_content_info.data)?;
+    let (_, e_content) = Any::from_ber(i)?;
+    e_content.header.assert_class(Class::ContextSpecific)?;
+    let (_, octet_string) = Any::from_ber(e_content.data)?;
+    octet_string.header.assert_tag(Tag::OctetString)?;
+
+    // BER receipts wrap the payload in a constructed OCTET STRING holding a single primitive segment
+    let payload = if octet_string.header.is_constructed() {
+        let (_, segment) = Any::from_ber(octet_string.data)?;
+        segment.header.assert_tag(Tag::OctetString)?;
+        segment.data
+    } else {
+        octet_string.data
+    };
+
+    ReceiptAttributes::from_set(payload)
 }
 
 /// Extracts a transaction id from an encoded transactional receipt. Throws if the receipt does not match the expected format.
 /// # Notes
 /// *NO validation* is performed on the receipt, and any data returned should only be used to call the App Store Server API.
 /// # Arguments
-/// * `transaction_receipt`: The unmodified transactionReceipt
+/// * `transaction_receipt`: The transactionReceipt, either base64 encoded (standard or URL-safe, whitespace is ignored) or already decoded
 /// # Returns
 /// * `Option<String>`: A transaction id, or none if no transactionId is found in the receipt
 pub fn extract_transaction_id_from_transaction_receipt(
-    transaction_receipt: &str,
+    transaction_receipt: impl AsRef<[u8]>,
 ) -> Result<Option<String>, ReceiptUtilityError> {
-    let transaction_receipt_bytes = STANDARD.decode(transaction_receipt)?;
+    let transaction_receipt = transaction_receipt.as_ref();
+
+    // A decoded transaction receipt is a plist-style dictionary, which never starts with a base64 character.
+    let transaction_receipt_bytes = match transaction_receipt.trim_ascii_start().first() {
+        Some(b'{') => transaction_receipt.to_vec(),
+        _ => decode_lenient_base64(transaction_receipt)?,
+    };
 
     if let Ok(decoded_top_level_str) = String::from_utf8(transaction_receipt_bytes) {
         let purchase_info_regex_str = r#""purchase-info"\s+=\s+"([a-zA-Z0-9+/=]+)";"#;
@@ -134,7 +242,7 @@ pub fn extract_transaction_id_from_transaction_receipt(
 
         if let Some(purchase_info_match) = purchase_info_regex.captures(&decoded_top_level_str) {
             if let Some(encoded_transaction_id) = purchase_info_match.get(1) {
-                if let Ok(decoded_inner_level) = STANDARD.decode(encoded_transaction_id.as_str()) {
+                if let Ok(decoded_inner_level) = decode_base64(encoded_transaction_id.as_str()) {
                     if let Ok(decoded_inner_level_str) = String::from_utf8(decoded_inner_level) {
                         let transaction_id_regex_str =
                             r#""transaction-id"\s+=\s+"([a-zA-Z0-9+/=]+)";"#;
@@ -159,6 +267,7 @@ pub fn extract_transaction_id_from_transaction_receipt(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use base64::engine::general_purpose::STANDARD;
     use std::fs;
     const APP_RECEIPT_EXPECTED_TRANSACTION_ID: &str = "0";
     const TRANSACTION_RECEIPT_EXPECTED_TRANSACTION_ID: &str = "33993399";
@@ -195,4 +304,87 @@ mod tests {
             extracted_transaction_id.expect("REASON").as_deref()
         );
     }
+
+    #[test]
+    fn test_app_receipt_extraction_from_der() {
+        let receipt = fs::read_to_string("assets/xcode-app-receipt-with-transaction")
+            .expect("Failed to read file");
+        let der = STANDARD.decode(receipt.trim()).expect("Expect base64");
+        let extracted_transaction_id = extract_transaction_id_from_app_receipt(&der);
+
+        assert_eq!(
+            Some(APP_RECEIPT_EXPECTED_TRANSACTION_ID),
+            extracted_transaction_id.expect("REASON").as_deref()
+        );
+    }
+
+    #[test]
+    fn test_app_receipt_extraction_from_url_safe_wrapped_base64() {
+        let receipt = fs::read_to_string("assets/xcode-app-receipt-with-transaction")
+            .expect("Failed to read file");
+        let url_safe = receipt.trim().trim_end_matches('=').replace('+', "-").replace('/', "_");
+        let wrapped = url_safe
+            .as_bytes()
+            .chunks(76)
+            .map(|line| std::str::from_utf8(line).unwrap())
+            .collect::<Vec<_>>()
+            .join("\r\n");
+        let extracted_transaction_id = extract_transaction_id_from_app_receipt(wrapped);
+
+        assert_eq!(
+            Some(APP_RECEIPT_EXPECTED_TRANSACTION_ID),
+            extracted_transaction_id.expect("REASON").as_deref()
+        );
+    }
+
+    #[test]
+    fn test_transaction_receipt_extraction_from_decoded_bytes() {
+        let receipt = fs::read_to_string("assets/legacyTransaction")
+            .expect("Failed to read file");
+        let decoded = STANDARD.decode(receipt.trim()).expect("Expect base64");
+        let extracted_transaction_id = extract_transaction_id_from_transaction_receipt(decoded);
+
+        assert_eq!(
+            Some(TRANSACTION_RECEIPT_EXPECTED_TRANSACTION_ID),
+            extracted_transaction_id.expect("REASON").as_deref()
+        );
+    }
+
+    #[test]
+    fn test_receipt_attributes() {
+        const BUNDLE_ID_TYPE_ID: u64 = 2;
+        const IN_APP_TYPE_ID: u64 = 17;
+
+        let receipt = fs::read_to_string("assets/xcode-app-receipt-with-transaction")
+            .expect("Failed to read file");
+        let der = STANDARD.decode(receipt.trim()).expect("Expect base64");
+
+        let attributes: Vec<ReceiptAttribute> = receipt_attributes(&der)
+            .expect("Expect attributes")
+            .collect::<Result<_, _>>()
+            .expect("Expect valid attributes");
+
+        let bundle_id = attributes
+            .iter()
+            .find(|a| a.attribute_type == BUNDLE_ID_TYPE_ID)
+            .expect("Expect bundle id");
+        let (_, bundle_id) = Utf8String::from_ber(bundle_id.value).expect("Expect UTF8String");
+        assert_eq!("com.example.naturelab.backyardbirds.example", bundle_id.string());
+
+        let in_app = attributes
+            .iter()
+            .find(|a| a.attribute_type == IN_APP_TYPE_ID)
+            .expect("Expect in-app purchase");
+        let in_app_attributes = in_app
+            .nested_attributes()
+            .expect("Expect nested attributes")
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Expect valid nested attributes");
+        assert!(in_app_attributes.iter().any(|a| a.attribute_type == 1703));
+    }
+
+    #[test]
+    fn test_receipt_attributes_with_malformed_data() {
+        assert!(receipt_attributes(&[0x30, 0x03, 0x01, 0x02]).is_err());
+    }
 }
@@ -0,0 +1,143 @@
+use crate::primitives::decoded_notification::DecodedNotification;
+use crate::signed_data_verifier::{SignedDataVerifier, SignedDataVerifierError};
+use crate::webhook::{parse_signed_payload_with_limit, WebhookBodyError, DEFAULT_MAX_BODY_SIZE};
+use rocket::data::{self, ByteUnit, Data, FromData};
+use rocket::http::Status;
+use rocket::outcome::Outcome;
+use rocket::Request;
+
+/// The name of the limit on the size of notification request bodies, configured in the `limits` of
+/// the Rocket configuration.
+///
+/// Without it, bodies are limited to [`DEFAULT_MAX_BODY_SIZE`](crate::webhook::DEFAULT_MAX_BODY_SIZE).
+pub const NOTIFICATION_LIMIT: &str = "app-store-notification";
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum RocketNotificationError {
+    #[error("MissingVerifierError: [No SignedDataVerifier is managed by the Rocket instance]")]
+    MissingVerifierError,
+
+    #[error("InvalidBodyError: [{0}]")]
+    InvalidBodyError(String),
+
+    #[error("WebhookBodyError: [{0}]")]
+    WebhookBodyError(#[from] WebhookBodyError),
+
+    #[error("VerificationError: [{0}]")]
+    VerificationError(#[from] SignedDataVerifierError),
+}
+
+impl RocketNotificationError {
+    /// The status the request is answered with.
+    pub fn status(&self) -> Status {
+        match self {
+            RocketNotificationError::MissingVerifierError => Status::InternalServerError,
+            RocketNotificationError::InvalidBodyError(_) => Status::BadRequest,
+            RocketNotificationError::WebhookBodyError(WebhookBodyError::BodyTooLargeError { .. }) => {
+                Status::PayloadTooLarge
+            }
+            RocketNotificationError::WebhookBodyError(_) => Status::BadRequest,
+            RocketNotificationError::VerificationError(_) => Status::Forbidden,
+        }
+    }
+}
+
+/// A data guard for a verified and decoded App Store Server Notification.
+///
+/// Reads the `signedPayload` from the request body and verifies it, along with its signed transaction
+/// and renewal info, using the `SignedDataVerifier` managed by the Rocket instance. Requests whose
+/// body is malformed fail with `400 Bad Request`, those larger than the [`NOTIFICATION_LIMIT`] with
+/// `413 Payload Too Large`, and those that fail verification with `403 Forbidden`.
+///
+/// ```ignore
+/// #[post("/notifications", data = "<notification>")]
+/// async fn notifications(notification: VerifiedNotification) -> Status { /* ... */ }
+///
+/// rocket::build().manage(verifier).mount("/", routes![notifications])
+/// ```
+#[derive(Debug, Clone)]
+pub struct VerifiedNotification(pub DecodedNotification);
+
+#[rocket::async_trait]
+impl<'r> FromData<'r> for VerifiedNotification {
+    type Error = RocketNotificationError;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, Self> {
+        match verify(req, data).await {
+            Ok(notification) => Outcome::Success(VerifiedNotification(notification)),
+            Err(error) => Outcome::Error((error.status(), error)),
+        }
+    }
+}
+
+async fn verify(req: &Request<'_>, data: Data<'_>) -> Result<DecodedNotification, RocketNotificationError> {
+    let verifier = req
+        .rocket()
+        .state::<SignedDataVerifier>()
+        .ok_or(RocketNotificationError::MissingVerifierError)?;
+    let limit = req
+        .limits()
+        .get(NOTIFICATION_LIMIT)
+        .unwrap_or(ByteUnit::Byte(DEFAULT_MAX_BODY_SIZE as u64));
+    let max_body_size = usize::try_from(limit.as_u64()).unwrap_or(usize::MAX);
+
+    let body = data
+        .open(limit)
+        .into_bytes()
+        .await
+        .map_err(|e| RocketNotificationError::InvalidBodyError(e.to_string()))?;
+    if !body.is_complete() {
+        let size = max_body_size.saturating_add(1);
+        return Err(WebhookBodyError::BodyTooLargeError { size, limit: max_body_size }.into());
+    }
+
+    let signed_payload = parse_signed_payload_with_limit(&body, max_body_size)?;
+    Ok(verifier.verify_and_decode_notification_full(&signed_payload)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::notification_type_v2::NotificationTypeV2;
+    use crate::test_utils::{local_testing_verifier, signed_notification_fixture};
+    use rocket::data::Limits;
+    use rocket::local::asynchronous::Client;
+    use serde_json::json;
+
+    #[rocket::post("/", data = "<notification>")]
+    fn notification_type(notification: VerifiedNotification) -> String {
+        format!("{:?}", notification.0.notification_type())
+    }
+
+    async fn client(limits: Limits) -> Client {
+        let figment = rocket::Config::figment().merge(("limits", limits));
+        let rocket = rocket::custom(figment)
+            .manage(local_testing_verifier())
+            .mount("/", rocket::routes![notification_type]);
+        Client::untracked(rocket).await.unwrap()
+    }
+
+    #[rocket::async_test]
+    async fn test_verified_notification_guard() {
+        let client = client(Limits::default()).await;
+
+        let signed_payload = signed_notification_fixture(&NotificationTypeV2::Test, None);
+        let response = client.post("/").body(json!({ "signedPayload": signed_payload }).to_string()).dispatch().await;
+        assert_eq!(Status::Ok, response.status());
+        assert_eq!(Some("Test".to_string()), response.into_string().await);
+
+        let response = client.post("/").body(json!({ "signedPayload": "invalid" }).to_string()).dispatch().await;
+        assert_eq!(Status::Forbidden, response.status());
+
+        let response = client.post("/").body("{").dispatch().await;
+        assert_eq!(Status::BadRequest, response.status());
+
+        let response = client.post("/").body(vec![b' '; DEFAULT_MAX_BODY_SIZE + 1]).dispatch().await;
+        assert_eq!(Status::PayloadTooLarge, response.status());
+
+        let client = self::client(Limits::default().limit(NOTIFICATION_LIMIT, ByteUnit::Byte(8))).await;
+        let response = client.post("/").body(json!({ "signedPayload": "x" }).to_string()).dispatch().await;
+        assert_eq!(Status::PayloadTooLarge, response.status());
+    }
+}
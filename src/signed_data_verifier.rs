@@ -1,17 +1,20 @@
-use base64::engine::general_purpose::STANDARD;
-use base64::{DecodeError, Engine};
+use base64::DecodeError;
 
-use crate::chain_verifier::{verify_chain, ChainVerifierError};
+use crate::chain_verifier::{ChainVerifier, ChainVerifierError};
 use crate::primitives::app_transaction::AppTransaction;
+use crate::primitives::decoded_notification::DecodedNotification;
 use crate::primitives::environment::Environment;
 use crate::primitives::jws_renewal_info_decoded_payload::JWSRenewalInfoDecodedPayload;
 use crate::primitives::jws_transaction_decoded_payload::JWSTransactionDecodedPayload;
 use crate::primitives::response_body_v2_decoded_payload::ResponseBodyV2DecodedPayload;
-use crate::utils::{base64_url_to_base64, StringExt};
-use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use crate::utils::{base64_url_to_base64, decode_base64, decode_base64_url, StringExt};
+use jsonwebtoken::Algorithm;
 use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::sync::Arc;
 
 #[derive(thiserror::Error, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum SignedDataVerifierError {
     #[error("VerificationFailure")]
     VerificationFailure,
@@ -22,7 +25,7 @@ pub enum SignedDataVerifierError {
     #[error("InvalidEnvironment")]
     InvalidEnvironment,
 
-    #[error("InternalChainVerifierError")]
+    #[error("InternalChainVerifierError: [{0}]")]
     InternalChainVerifierError(#[from] ChainVerifierError),
 
     #[error("InternalDecodeError: [{0}]")]
@@ -34,8 +37,17 @@ pub enum SignedDataVerifierError {
 
 /// A verifier for signed data, commonly used for verifying and decoding
 /// signed Apple server notifications and transactions.
+///
+/// The verifier checks certificate chains with a [`ChainVerifier`], which caches the decoding key of
+/// each chain it has verified, so that payloads signed with the same leaf certificate skip chain
+/// verification. The root certificates and effective date of a verifier don't change, so a chain that
+/// verified once always verifies.
+///
+/// Clones share the chain verifier and its cache, so a verifier can be stored in the state of a web
+/// framework and cloned per request.
+#[derive(Clone)]
 pub struct SignedDataVerifier {
-    root_certificates: Vec<Vec<u8>>,
+    chain_verifier: Arc<ChainVerifier>,
     environment: Environment,
     bundle_id: String,
     app_apple_id: Option<i64>,
@@ -60,12 +72,74 @@ impl SignedDataVerifier {
         bundle_id: String,
         app_apple_id: Option<i64>,
     ) -> Self {
-        return SignedDataVerifier {
-            root_certificates,
+        SignedDataVerifier::builder(environment, bundle_id)
+            .root_certificates(root_certificates)
+            .app_apple_id(app_apple_id)
+            .build()
+    }
+
+    /// Creates a `SignedDataVerifierBuilder` for the given environment and bundle ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `environment` - The environment (e.g., `Environment::Production` or `Environment::Sandbox`).
+    /// * `bundle_id` - The bundle ID associated with the application.
+    pub fn builder(environment: Environment, bundle_id: impl Into<String>) -> SignedDataVerifierBuilder {
+        SignedDataVerifierBuilder {
+            root_certificates: vec![],
             environment,
-            bundle_id,
-            app_apple_id,
-        };
+            bundle_id: bundle_id.into(),
+            app_apple_id: None,
+            effective_date: None,
+        }
+    }
+}
+
+/// A builder for [`SignedDataVerifier`], created by [`SignedDataVerifier::builder`].
+#[derive(Debug, Clone)]
+pub struct SignedDataVerifierBuilder {
+    root_certificates: Vec<Vec<u8>>,
+    environment: Environment,
+    bundle_id: String,
+    app_apple_id: Option<i64>,
+    effective_date: Option<u64>,
+}
+
+impl SignedDataVerifierBuilder {
+    /// Sets the DER-encoded root certificates used for verification, replacing any added before.
+    pub fn root_certificates(mut self, root_certificates: Vec<Vec<u8>>) -> Self {
+        self.root_certificates = root_certificates;
+        self
+    }
+
+    /// Adds a DER-encoded root certificate used for verification.
+    pub fn root_certificate(mut self, root_certificate: Vec<u8>) -> Self {
+        self.root_certificates.push(root_certificate);
+        self
+    }
+
+    /// Sets the Apple ID of the application, required in `Environment::Production`.
+    pub fn app_apple_id(mut self, app_apple_id: Option<i64>) -> Self {
+        self.app_apple_id = app_apple_id;
+        self
+    }
+
+    /// Sets the Unix timestamp, in seconds, at which certificate chains are checked for validity.
+    ///
+    /// When unset, the validity periods of the certificates aren't checked.
+    pub fn effective_date(mut self, effective_date: Option<u64>) -> Self {
+        self.effective_date = effective_date;
+        self
+    }
+
+    /// Creates the configured `SignedDataVerifier`.
+    pub fn build(self) -> SignedDataVerifier {
+        SignedDataVerifier {
+            chain_verifier: Arc::new(ChainVerifier::new(self.root_certificates, self.effective_date)),
+            environment: self.environment,
+            bundle_id: self.bundle_id,
+            app_apple_id: self.app_apple_id,
+        }
     }
 }
 
@@ -160,15 +234,7 @@ impl SignedDataVerifier {
             bundle_id = external_purchase_token.bundle_id.clone();
             app_apple_id = external_purchase_token.app_apple_id.clone();
 
-            if let Some(external_purchase_id) = &external_purchase_token.external_purchase_id {
-                if external_purchase_id.starts_with("SANDBOX") {
-                    environment = Some(Environment::Sandbox)
-                } else {
-                    environment = Some(Environment::Production)
-                }
-            } else {
-                environment = Some(Environment::Production)
-            }
+            environment = Some(external_purchase_token.environment());
         } else {
             bundle_id = None;
             app_apple_id = None;
@@ -180,6 +246,36 @@ impl SignedDataVerifier {
         Ok(decoded_signed_notification)
     }
 
+    /// Verifies and decodes a signed notification along with its signed transaction and renewal info.
+    ///
+    /// # Arguments
+    ///
+    /// * `signed_payload` - The signed notification string to verify and decode.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(DecodedNotification)` with the `signedTransactionInfo` and `signedRenewalInfo` of the notification's data,
+    ///   when present, already verified and decoded.
+    /// - `Err(SignedDataVerifierError)` if verification or decoding of the notification or its signed fields fails.
+    pub fn verify_and_decode_notification_full(
+        &self,
+        signed_payload: &str,
+    ) -> Result<DecodedNotification, SignedDataVerifierError> {
+        let payload = self.verify_and_decode_notification(signed_payload)?;
+        let data = payload.data.as_ref();
+
+        let transaction_info = data
+            .and_then(|data| data.signed_transaction_info.as_deref())
+            .map(|signed| self.verify_and_decode_signed_transaction(signed))
+            .transpose()?;
+        let renewal_info = data
+            .and_then(|data| data.signed_renewal_info.as_deref())
+            .map(|signed| self.verify_and_decode_renewal_info(signed))
+            .transpose()?;
+
+        Ok(DecodedNotification { payload, transaction_info, renewal_info })
+    }
+
     fn verify_notification_app_identifier_and_environment(
         &self,
         bundle_id: Option<String>,
@@ -237,74 +333,146 @@ impl SignedDataVerifier {
         Ok(decoded_app_transaction)
     }
 
+    /// Performs a cheap structural check of a signed payload without any cryptographic work.
+    ///
+    /// The payload must have three segments, each segment must be valid base64url, the header must
+    /// parse and declare `ES256` (with an `x5c` chain outside of `Xcode` and `LocalTesting`), and the
+    /// body must be a JSON object. Passing the precheck does *not* mean the payload is authentic; it is
+    /// intended to reject garbage early, before the payload is handed to one of the `verify_and_decode_*` methods.
+    ///
+    /// # Arguments
+    ///
+    /// * `signed_obj` - The signed payload to check.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` if the payload is structurally well-formed.
+    /// - `Err(SignedDataVerifierError)` describing the first structural problem found.
+    pub fn precheck(&self, signed_obj: &str) -> Result<(), SignedDataVerifierError> {
+        const EXPECTED_JWT_SEGMENTS: usize = 3;
+
+        let segments: Vec<&str> = signed_obj.split('.').collect();
+
+        if segments.len() != EXPECTED_JWT_SEGMENTS || segments.iter().any(|s| s.is_empty()) {
+            return Err(SignedDataVerifierError::VerificationFailure);
+        }
+
+        let header = jsonwebtoken::decode_header(signed_obj)?;
+
+        if header.alg != Algorithm::ES256 {
+            return Err(SignedDataVerifierError::VerificationFailure);
+        }
+
+        let requires_chain = self.environment != Environment::Xcode && self.environment != Environment::LocalTesting;
+
+        if requires_chain && header.x5c.is_none_or(|x5c| x5c.is_empty()) {
+            return Err(SignedDataVerifierError::VerificationFailure);
+        }
+
+        let body = decode_base64_url(segments[1])?;
+
+        if serde_json::from_slice::<serde_json::Map<String, serde_json::Value>>(&body).is_err() {
+            return Err(SignedDataVerifierError::VerificationFailure);
+        }
+
+        let _ = decode_base64_url(segments[2])?;
+
+        Ok(())
+    }
+
     /// Private method used for decoding a signed object (internal use).
     fn decode_signed_object<T: DeserializeOwned>(
         &self,
         signed_obj: &str,
     ) -> Result<T, SignedDataVerifierError> {
-        // Data is not signed by the App Store, and verification should be skipped
-        // The environment MUST be checked in the public method calling this
-        if self.environment == Environment::Xcode || self.environment == Environment::LocalTesting {
-            const EXPECTED_JWT_SEGMENTS: usize = 3;
+        self.verify_signed_payload(signed_obj)?.claims()
+    }
 
-            let body_segments: Vec<&str> = signed_obj.split('.').collect();
+    /// Verifies a signed payload of any type, returning its claims without decoding them into a model.
+    ///
+    /// The claims can then be decoded into a type that borrows from the payload, which avoids copying the
+    /// strings of payloads that are verified in bulk. Unlike the typed methods, this doesn't check the
+    /// bundle ID, app Apple ID or environment in the claims.
+    ///
+    /// # Arguments
+    ///
+    /// * `signed_obj` - The JWS to verify.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(VerifiedPayload)` if the certificate chain and signature of the JWS are valid.
+    /// - `Err(SignedDataVerifierError)` if verification fails.
+    pub fn verify_signed_payload(&self, signed_obj: &str) -> Result<VerifiedPayload, SignedDataVerifierError> {
+        const EXPECTED_JWT_SEGMENTS: usize = 3;
 
-            if body_segments.len() != EXPECTED_JWT_SEGMENTS {
-                return Err(SignedDataVerifierError::VerificationFailure);
-            }
+        let header = jsonwebtoken::decode_header(signed_obj)?;
+        let body_segments: Vec<&str> = signed_obj.split('.').collect();
 
-            let _ = jsonwebtoken::decode_header(&signed_obj)?;
-            let body_data = base64_url_to_base64(body_segments[1]);
+        if body_segments.len() != EXPECTED_JWT_SEGMENTS {
+            return Err(SignedDataVerifierError::VerificationFailure);
+        }
 
-            let decoded_body = match STANDARD.decode(body_data) {
-                Ok(decoded_body) => match serde_json::from_slice(&decoded_body) {
-                    Ok(decoded) => decoded,
-                    Err(_) => return Err(SignedDataVerifierError::VerificationFailure),
-                },
-                Err(_) => return Err(SignedDataVerifierError::VerificationFailure),
+        // Data is not signed by the App Store, and verification should be skipped
+        // The environment MUST be checked in the public method calling this
+        if self.environment != Environment::Xcode && self.environment != Environment::LocalTesting {
+            let Some(x5c) = header.x5c else {
+                return Err(SignedDataVerifierError::VerificationFailure);
             };
 
-            return Ok(decoded_body);
-        }
+            if x5c.is_empty() {
+                return Err(SignedDataVerifierError::VerificationFailure);
+            }
 
-        let header = jsonwebtoken::decode_header(signed_obj)?;
+            if header.alg != Algorithm::ES256 {
+                return Err(SignedDataVerifierError::VerificationFailure);
+            }
 
-        let Some(x5c) = header.x5c else {
-            return Err(SignedDataVerifierError::VerificationFailure);
-        };
+            let chain: Result<Vec<Vec<u8>>, DecodeError> = x5c.iter().map(|c| c.as_der_bytes()).collect();
+            let decoding_key = self.chain_verifier.verify_decoding_key(&chain?)?;
+            let message_length = body_segments[0].len() + 1 + body_segments[1].len();
+            let message = &signed_obj.as_bytes()[..message_length];
 
-        if x5c.is_empty() {
-            return Err(SignedDataVerifierError::VerificationFailure);
+            if !jsonwebtoken::crypto::verify(body_segments[2], message, &decoding_key, Algorithm::ES256)? {
+                return Err(SignedDataVerifierError::VerificationFailure);
+            }
         }
 
-        let x5c: Result<Vec<Vec<u8>>, DecodeError> = x5c.iter().map(|c| c.as_der_bytes()).collect();
-        let chain = x5c?;
-
-        if header.alg != Algorithm::ES256 {
-            return Err(SignedDataVerifierError::VerificationFailure);
-        }
+        let claims = decode_base64(base64_url_to_base64(body_segments[1]))
+            .map_err(|_| SignedDataVerifierError::VerificationFailure)?;
 
-        let pub_key = verify_chain(&chain, &self.root_certificates, None)?;
-        let pub_key = &pub_key[pub_key.len() - 65..];
+        Ok(VerifiedPayload { claims })
+    }
+}
 
-        let decoding_key = DecodingKey::from_ec_der(pub_key);
-        let claims: [&str; 0] = [];
+/// The claims of a signed payload whose certificate chain and signature were verified,
+/// returned by [`SignedDataVerifier::verify_signed_payload`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedPayload {
+    claims: Vec<u8>,
+}
 
-        let mut validator = Validation::new(Algorithm::ES256);
-        validator.validate_exp = false;
-        validator.set_required_spec_claims(&claims);
+impl VerifiedPayload {
+    /// The JSON of the claims.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.claims
+    }
 
-        let payload = jsonwebtoken::decode::<T>(signed_obj, &decoding_key, &validator)
-            .expect("Expect Payload");
-        return Ok(payload.claims);
+    /// Decodes the claims, possibly into a type that borrows its strings from the payload.
+    ///
+    /// Borrow strings as `Cow<'a, str>` rather than `&'a str` when they may contain escape sequences.
+    pub fn claims<'a, T: Deserialize<'a>>(&'a self) -> Result<T, SignedDataVerifierError> {
+        serde_json::from_slice(&self.claims).map_err(|_| SignedDataVerifierError::VerificationFailure)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
     use crate::primitives::auto_renew_status::AutoRenewStatus;
     use crate::primitives::expiration_intent::ExpirationIntent;
+    use crate::primitives::external_purchase_token::ExternalPurchaseToken;
     use crate::primitives::in_app_ownership_type::InAppOwnershipType;
     use crate::primitives::notification_type_v2::NotificationTypeV2;
     use crate::primitives::offer_discount_type::OfferDiscountType;
@@ -314,6 +482,7 @@ mod tests {
     use crate::primitives::revocation_reason::RevocationReason;
     use crate::primitives::status::Status;
     use crate::primitives::subtype::Subtype;
+    use crate::primitives::timestamp;
     use crate::primitives::transaction_reason::TransactionReason;
     use ring::signature::ECDSA_P256_SHA256_FIXED_SIGNING;
     use serde_json::{Map, Value};
@@ -418,16 +587,19 @@ mod tests {
                 assert_eq!("002e14d5-51f5-4503-b5a8-c3a1af68eb20", &notification.notification_uuid);
                 assert_eq!("2.0", &notification.version.expect("Expect version"));
                 assert_eq!(
-                    1698148900,
-                    notification.signed_date.expect("Expect signed_date").timestamp()
+                    1698148900000,
+                    timestamp::to_millis(notification.signed_date.expect("Expect signed_date"))
                 );
                 assert!(notification.data.is_none());
                 assert!(notification.summary.is_none());
                 assert!(notification.external_purchase_token.is_some());
 
                 if let Some(external_purchase_token) = notification.external_purchase_token {
+                    assert!(!external_purchase_token.is_sandbox_token());
+                    assert_eq!(Environment::Production, external_purchase_token.environment());
+                    assert_eq!(Some("b2158121-7af9-49d4-9561-1f588205523e"), external_purchase_token.normalized_external_purchase_id());
                     assert_eq!("b2158121-7af9-49d4-9561-1f588205523e", &external_purchase_token.external_purchase_id.expect("Expect external_purchase_id"));
-                    assert_eq!(1698148950, external_purchase_token.token_creation_date.unwrap().timestamp());
+                    assert_eq!(1698148950000, timestamp::to_millis(external_purchase_token.token_creation_date.unwrap()));
                     assert_eq!(55555, external_purchase_token.app_apple_id.unwrap());
                     assert_eq!("com.example", &external_purchase_token.bundle_id.unwrap());
                 } else {
@@ -455,16 +627,19 @@ mod tests {
                 assert_eq!("002e14d5-51f5-4503-b5a8-c3a1af68eb20", &notification.notification_uuid);
                 assert_eq!("2.0", &notification.version.expect("Expect version"));
                 assert_eq!(
-                    1698148900,
-                    notification.signed_date.expect("Expect signed_date").timestamp()
+                    1698148900000,
+                    timestamp::to_millis(notification.signed_date.expect("Expect signed_date"))
                 );
                 assert!(notification.data.is_none());
                 assert!(notification.summary.is_none());
                 assert!(notification.external_purchase_token.is_some());
 
                 if let Some(external_purchase_token) = notification.external_purchase_token {
+                    assert!(external_purchase_token.is_sandbox_token());
+                    assert_eq!(Environment::Sandbox, external_purchase_token.environment());
+                    assert_eq!(Some("b2158121-7af9-49d4-9561-1f588205523e"), external_purchase_token.normalized_external_purchase_id());
                     assert_eq!("SANDBOX_b2158121-7af9-49d4-9561-1f588205523e", &external_purchase_token.external_purchase_id.expect("Expect external_purchase_id"));
-                    assert_eq!(1698148950, external_purchase_token.token_creation_date.unwrap().timestamp());
+                    assert_eq!(1698148950000, timestamp::to_millis(external_purchase_token.token_creation_date.unwrap()));
                     assert_eq!(55555, external_purchase_token.app_apple_id.unwrap());
                     assert_eq!("com.example", &external_purchase_token.bundle_id.unwrap());
                 } else {
@@ -475,6 +650,13 @@ mod tests {
                 panic!("Failed to verify and decode app transaction: {:?}", err)
             }
         }
+
+        let external_purchase_token = ExternalPurchaseToken { external_purchase_id: Some("SANDBOXb2158121".to_string()), ..Default::default() };
+        assert_eq!(Environment::Sandbox, external_purchase_token.environment());
+        assert!(external_purchase_token.is_sandbox_token());
+        assert_eq!(Some("b2158121"), external_purchase_token.normalized_external_purchase_id());
+        assert_eq!("b2158121", ExternalPurchaseToken::strip_sandbox_prefix("SANDBOX_b2158121"));
+        assert_eq!("b2158121", ExternalPurchaseToken::strip_sandbox_prefix("b2158121"));
     }
 
     #[test]
@@ -508,6 +690,120 @@ mod tests {
             .contains("InternalJWTError"));
     }
 
+    #[test]
+    fn test_precheck() {
+        let verifier = get_signed_data_verifier(Environment::Sandbox, "com.example", None);
+        assert_eq!(Ok(()), verifier.precheck(TEST_NOTIFICATION));
+        assert_eq!(Ok(()), verifier.precheck(RENEWAL_INFO));
+        assert_eq!(Err(SignedDataVerifierError::VerificationFailure), verifier.precheck("a.b.c.d"));
+        assert_eq!(Err(SignedDataVerifierError::VerificationFailure), verifier.precheck("a..c"));
+        assert!(matches!(verifier.precheck("a.b.c"), Err(SignedDataVerifierError::InternalJWTError(_))));
+        assert_eq!(Err(SignedDataVerifierError::VerificationFailure), verifier.precheck(MISSING_X5C_HEADER_CLAIM));
+
+        let segments: Vec<&str> = TEST_NOTIFICATION.split('.').collect();
+        let non_json_body = format!("{}.{}.{}", segments[0], URL_SAFE_NO_PAD.encode("not json"), segments[2]);
+        assert_eq!(Err(SignedDataVerifierError::VerificationFailure), verifier.precheck(&non_json_body));
+        let invalid_body = format!("{}.{}.{}", segments[0], "!!!", segments[2]);
+        assert!(matches!(verifier.precheck(&invalid_body), Err(SignedDataVerifierError::InternalDecodeError(_))));
+    }
+
+    #[test]
+    fn test_precheck_without_chain() {
+        let verifier = get_signed_data_verifier(Environment::Xcode, XCODE_BUNDLE_ID, None);
+        let encoded_transaction = fs::read_to_string("assets/xcode-signed-transaction").expect("Failed to read file");
+        assert_eq!(Ok(()), verifier.precheck(encoded_transaction.trim()));
+
+        let verifier = get_signed_data_verifier(Environment::LocalTesting, "com.example", None);
+        let signed_notification = create_signed_data_from_json("assets/signedNotification.json");
+        assert_eq!(Ok(()), verifier.precheck(&signed_notification));
+    }
+
+    #[test]
+    fn test_builder() {
+        let verifier = SignedDataVerifier::builder(Environment::Sandbox, "com.example")
+            .root_certificate(ROOT_CA_BASE64_ENCODED.as_der_bytes().unwrap())
+            .app_apple_id(Some(1234))
+            .build();
+        let notification = verifier.verify_and_decode_notification(TEST_NOTIFICATION).unwrap();
+        assert_eq!(notification.notification_type, NotificationTypeV2::Test);
+    }
+
+    #[test]
+    fn test_verify_and_decode_notification_full() {
+        use crate::test_utils::{local_testing_verifier, signed_notification_fixture};
+
+        let verifier = local_testing_verifier();
+        let signed_notification = signed_notification_fixture(&NotificationTypeV2::DidRenew, None);
+        let notification = verifier.verify_and_decode_notification_full(&signed_notification).unwrap();
+        assert_eq!(&NotificationTypeV2::DidRenew, notification.notification_type());
+        assert!(notification.transaction_info.is_some());
+        assert!(notification.renewal_info.is_some());
+
+        assert!(verifier.verify_and_decode_notification_full("a.b.c").is_err());
+    }
+
+    #[test]
+    fn test_builder_with_effective_date() {
+        let builder = SignedDataVerifier::builder(Environment::Sandbox, "com.example")
+            .root_certificates(vec![ROOT_CA_BASE64_ENCODED.as_der_bytes().unwrap()])
+            .app_apple_id(Some(1234));
+
+        let verifier = builder.clone().effective_date(Some(1681314324)).build();
+        assert!(verifier.verify_and_decode_notification(TEST_NOTIFICATION).is_ok());
+
+        let verifier = builder.effective_date(Some(1000000000)).build();
+        assert_eq!(
+            Err(SignedDataVerifierError::InternalChainVerifierError(
+                ChainVerifierError::VerificationFailure(crate::chain_verifier::ChainVerificationFailureReason::CertificateExpired)
+            )),
+            verifier.verify_and_decode_notification(TEST_NOTIFICATION).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn test_verify_signed_payload() {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Notification<'a> {
+            notification_type: &'a str,
+            #[serde(rename = "notificationUUID")]
+            notification_uuid: &'a str,
+        }
+
+        let verifier = SignedDataVerifier::builder(Environment::Sandbox, "com.example")
+            .root_certificates(vec![ROOT_CA_BASE64_ENCODED.as_der_bytes().unwrap()])
+            .app_apple_id(Some(1234))
+            .effective_date(Some(1681314324))
+            .build();
+
+        let payload = verifier.verify_signed_payload(TEST_NOTIFICATION).unwrap();
+        assert_eq!(payload, verifier.verify_signed_payload(TEST_NOTIFICATION).unwrap());
+        assert_eq!(1, verifier.chain_verifier.cached_chains());
+        assert!(Arc::ptr_eq(&verifier.chain_verifier, &verifier.clone().chain_verifier));
+        let notification: Notification = payload.claims().unwrap();
+        assert_eq!("TEST", notification.notification_type);
+        assert!(!notification.notification_uuid.is_empty());
+
+        let segments: Vec<&str> = TEST_NOTIFICATION.split('.').collect();
+        let tampered = format!("{}.{}.{}", segments[0], URL_SAFE_NO_PAD.encode(payload.as_bytes()).replace('A', "B"), segments[2]);
+        assert_eq!(Err(SignedDataVerifierError::VerificationFailure), verifier.verify_signed_payload(&tampered));
+    }
+
+    #[test]
+    fn test_error_source() {
+        use std::error::Error;
+
+        let error = SignedDataVerifierError::from(ChainVerifierError::VerificationFailure(
+            crate::chain_verifier::ChainVerificationFailureReason::CertificateExpired,
+        ));
+        assert_eq!("InternalChainVerifierError: [VerificationFailure: [CertificateExpired]]", error.to_string());
+
+        let source = error.source().unwrap();
+        assert_eq!(Some(&ChainVerifierError::VerificationFailure(
+            crate::chain_verifier::ChainVerificationFailureReason::CertificateExpired,
+        )), source.downcast_ref::<ChainVerifierError>());
+    }
+
     fn get_signed_data_verifier(
         environment: Environment,
         bundle_id: &str,
@@ -556,18 +852,12 @@ mod tests {
                         .expect("Expect version_external_identifier")
                 );
                 assert_eq!(
-                    1698148900,
-                    app_transaction
-                        .receipt_creation_date
-                        .expect("Expect receipt_creation_date")
-                        .timestamp()
+                    1698148900000,
+                    timestamp::to_millis(app_transaction.receipt_creation_date.expect("Expect receipt_creation_date"))
                 );
                 assert_eq!(
-                    1698148800,
-                    app_transaction
-                        .original_purchase_date
-                        .expect("Expect original_purchase_date")
-                        .timestamp()
+                    1698148800000,
+                    timestamp::to_millis(app_transaction.original_purchase_date.expect("Expect original_purchase_date"))
                 );
                 assert_eq!(
                     "1.1.2",
@@ -589,11 +879,8 @@ mod tests {
                         .to_string()
                 );
                 assert_eq!(
-                    1698148700,
-                    app_transaction
-                        .preorder_date
-                        .expect("Expect preorder_date")
-                        .timestamp()
+                    1698148700000,
+                    timestamp::to_millis(app_transaction.preorder_date.expect("Expect preorder_date"))
                 );
             }
             Err(err) => panic!("Failed to verify and decode app transaction: {:?}", err),
@@ -648,32 +935,20 @@ mod tests {
                         .expect("Expect subscription_group_identifier")
                 );
                 assert_eq!(
-                    1698148800,
-                    transaction
-                        .original_purchase_date
-                        .expect("Expect original_purchase_date")
-                        .timestamp()
+                    1698148800000,
+                    timestamp::to_millis(transaction.original_purchase_date.expect("Expect original_purchase_date"))
                 );
                 assert_eq!(
-                    1698148900,
-                    transaction
-                        .purchase_date
-                        .expect("Expect purchase_date")
-                        .timestamp()
+                    1698148900000,
+                    timestamp::to_millis(transaction.purchase_date.expect("Expect purchase_date"))
                 );
                 assert_eq!(
-                    1698148950,
-                    transaction
-                        .revocation_date
-                        .expect("Expect revocation_date")
-                        .timestamp()
+                    1698148950000,
+                    timestamp::to_millis(transaction.revocation_date.expect("Expect revocation_date"))
                 );
                 assert_eq!(
-                    1698149000,
-                    transaction
-                        .expires_date
-                        .expect("Expect expires_date")
-                        .timestamp()
+                    1698149000000,
+                    timestamp::to_millis(transaction.expires_date.expect("Expect expires_date"))
                 );
                 assert_eq!(1, transaction.quantity.expect("Expect quantity"));
                 assert_eq!(
@@ -694,11 +969,8 @@ mod tests {
                         .expect("Expect in_app_ownership_type")
                 );
                 assert_eq!(
-                    1698148900,
-                    transaction
-                        .signed_date
-                        .expect("Expect signed_date")
-                        .timestamp()
+                    1698148900000,
+                    timestamp::to_millis(transaction.signed_date.expect("Expect signed_date"))
                 );
                 assert_eq!(
                     RevocationReason::RefundedDueToIssue,
@@ -744,8 +1016,8 @@ mod tests {
                 );
                 assert_eq!(10990, transaction.price.expect("Expect price"));
                 assert_eq!(
-                    "USD",
-                    transaction.currency.as_deref().expect("Expect currency")
+                    crate::primitives::currency::Currency::Usd,
+                    transaction.currency.expect("Expect currency")
                 );
                 assert_eq!(
                     OfferDiscountType::PayAsYouGo,
@@ -807,11 +1079,8 @@ mod tests {
                         .expect("Expect price_increase_status")
                 );
                 assert_eq!(
-                    1698148900,
-                    renewal_info
-                        .grace_period_expires_date
-                        .expect("Expect grace_period_expires_date")
-                        .timestamp()
+                    1698148900000,
+                    timestamp::to_millis(renewal_info.grace_period_expires_date.expect("Expect grace_period_expires_date"))
                 );
                 assert_eq!(
                     OfferType::PromotionalOffer,
@@ -825,29 +1094,20 @@ mod tests {
                         .expect("Expect offer_identifier")
                 );
                 assert_eq!(
-                    1698148800,
-                    renewal_info
-                        .signed_date
-                        .expect("Expect signed_date")
-                        .timestamp()
+                    1698148800000,
+                    timestamp::to_millis(renewal_info.signed_date.expect("Expect signed_date"))
                 );
                 assert_eq!(
                     Environment::LocalTesting,
                     renewal_info.environment.expect("Expect environment")
                 );
                 assert_eq!(
-                    1698148800,
-                    renewal_info
-                        .recent_subscription_start_date
-                        .expect("Expect recent_subscription_start_date")
-                        .timestamp()
+                    1698148800000,
+                    timestamp::to_millis(renewal_info.recent_subscription_start_date.expect("Expect recent_subscription_start_date"))
                 );
                 assert_eq!(
-                    1698148850,
-                    renewal_info
-                        .renewal_date
-                        .expect("Expect renewal_date")
-                        .timestamp()
+                    1698148850000,
+                    timestamp::to_millis(renewal_info.renewal_date.expect("Expect renewal_date"))
                 );
             }
             Err(err) => panic!("Failed to verify and decode renewal info: {:?}", err),
@@ -879,11 +1139,8 @@ mod tests {
                     notification.version.as_deref().expect("Expect version")
                 );
                 assert_eq!(
-                    1698148900,
-                    notification
-                        .signed_date
-                        .expect("Expect signed_date")
-                        .timestamp()
+                    1698148900000,
+                    timestamp::to_millis(notification.signed_date.expect("Expect signed_date"))
                 );
                 assert!(notification.data.is_some());
                 assert!(notification.summary.is_none());
@@ -939,7 +1196,7 @@ mod tests {
                 assert!(notification.subtype.is_none());
                 assert_eq!("002e14d5-51f5-4503-b5a8-c3a1af68eb20", notification.notification_uuid);
                 assert_eq!("2.0", notification.version.unwrap());
-                assert_eq!(1698148900, notification.signed_date.unwrap().timestamp());
+                assert_eq!(1698148900000, timestamp::to_millis(notification.signed_date.unwrap()));
                 assert!(notification.data.is_some());
                 assert!(notification.summary.is_none());
                 assert!(notification.external_purchase_token.is_none());
@@ -988,11 +1245,8 @@ mod tests {
                     notification.version.as_deref().expect("Expect version")
                 );
                 assert_eq!(
-                    1698148900,
-                    notification
-                        .signed_date
-                        .expect("Expect signed_date")
-                        .timestamp()
+                    1698148900000,
+                    timestamp::to_millis(notification.signed_date.expect("Expect signed_date"))
                 );
                 assert!(notification.data.is_none());
                 assert!(notification.summary.is_some());
@@ -1039,7 +1293,7 @@ mod tests {
             assert_eq!(XCODE_BUNDLE_ID, app_transaction.bundle_id.as_deref().expect("Expect bundle_id"));
             assert_eq!("1", app_transaction.application_version.as_deref().expect("Expect application_version"));
             assert_eq!(None, app_transaction.version_external_identifier);
-            assert_eq!(-62135769600000, app_transaction.original_purchase_date.expect("Expect value").timestamp_millis());
+            assert_eq!(-62135769600000, timestamp::to_millis(app_transaction.original_purchase_date.expect("Expect value")));
             assert_eq!("1", app_transaction.original_application_version.as_deref().expect("Expect original_application_version"));
             assert_eq!("cYUsXc53EbYc0pOeXG5d6/31LGHeVGf84sqSN0OrJi5u/j2H89WWKgS8N0hMsMlf", app_transaction.device_verification.as_deref().expect("Expect device_verification"));
             assert_eq!("48c8b92d-ce0d-4229-bedf-e61b4f9cfc92", app_transaction.device_verification_nonce.expect("Expect device_verification_nonce").to_string());
@@ -1062,14 +1316,14 @@ mod tests {
             assert_eq!(XCODE_BUNDLE_ID, transaction.bundle_id.as_deref().expect("Expect bundle_id"));
             assert_eq!("pass.premium", transaction.product_id.as_deref().expect("Expect product_id"));
             assert_eq!("6F3A93AB", transaction.subscription_group_identifier.as_deref().expect("Expect subscription_group_identifier"));
-            assert_eq!(1697679936049, transaction.purchase_date.unwrap().timestamp_millis());
-            assert_eq!(1697679936049, transaction.original_purchase_date.unwrap().timestamp_millis());
-            assert_eq!(1700358336049, transaction.expires_date.unwrap().timestamp_millis());
+            assert_eq!(1697679936049, timestamp::to_millis(transaction.purchase_date.unwrap()));
+            assert_eq!(1697679936049, timestamp::to_millis(transaction.original_purchase_date.unwrap()));
+            assert_eq!(1700358336049, timestamp::to_millis(transaction.expires_date.unwrap()));
             assert_eq!(1, transaction.quantity.expect("Expect quantity"));
             assert_eq!(ProductType::AutoRenewableSubscription, transaction.r#type.expect("Expect type"));
             assert_eq!(None, transaction.app_account_token);
             assert_eq!(InAppOwnershipType::Purchased, transaction.in_app_ownership_type.expect("Expect in_app_ownership_type"));
-            assert_eq!(1697679936056, transaction.signed_date.unwrap().timestamp_millis());
+            assert_eq!(1697679936056, timestamp::to_millis(transaction.signed_date.unwrap()));
             assert_eq!(None, transaction.revocation_reason);
             assert_eq!(None, transaction.revocation_date);
             assert!(!transaction.is_upgraded.unwrap_or(false));
@@ -1100,10 +1354,10 @@ mod tests {
             assert_eq!(None, renewal_info.grace_period_expires_date);
             assert_eq!(None, renewal_info.offer_type);
             assert_eq!(None, renewal_info.offer_identifier);
-            assert_eq!(1697679936711, renewal_info.signed_date.unwrap().timestamp_millis());
+            assert_eq!(1697679936711, timestamp::to_millis(renewal_info.signed_date.unwrap()));
             assert_eq!(Environment::Xcode, renewal_info.environment.expect("Expect environment"));
-            assert_eq!(1697679936049, renewal_info.recent_subscription_start_date.unwrap().timestamp_millis());
-            assert_eq!(1700358336049, renewal_info.renewal_date.unwrap().timestamp_millis());
+            assert_eq!(1697679936049, timestamp::to_millis(renewal_info.recent_subscription_start_date.unwrap()));
+            assert_eq!(1700358336049, timestamp::to_millis(renewal_info.renewal_date.unwrap()));
         } else {
             panic!("Failed to verify and decode signed renewal info");
         }
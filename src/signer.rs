@@ -0,0 +1,406 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ring::rand::SystemRandom;
+use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+use serde::Serialize;
+use std::future::Future;
+use std::pin::Pin;
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum SignerError {
+    #[error("InvalidKeyError: [{0}]")]
+    InvalidKeyError(String),
+
+    #[error("UnsupportedKeyFormatError: [{0}]")]
+    UnsupportedKeyFormatError(String),
+
+    #[error("SigningError: [{0}]")]
+    SigningError(String),
+
+    #[error("UnknownKeyError: [{0}]")]
+    UnknownKeyError(String),
+}
+
+/// A buffer holding private key material.
+///
+/// With the `secure-keys` feature, the buffer is wiped when it's dropped.
+#[cfg(feature = "secure-keys")]
+pub(crate) type KeyBytes = zeroize::Zeroizing<Vec<u8>>;
+#[cfg(not(feature = "secure-keys"))]
+pub(crate) type KeyBytes = Vec<u8>;
+
+/// The future returned by [`AsyncSigner::sign`].
+pub type SignFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<u8>, SignerError>> + Send + 'a>>;
+
+/// A source of ES256 (ECDSA P-256 with SHA-256) signatures.
+///
+/// Implement this trait to sign with keys that never leave an HSM or a key management service.
+/// Signatures use the fixed-length `r || s` encoding (64 bytes) used by JWS.
+pub trait Signer: Send + Sync {
+    /// Signs `message`, hashing it with SHA-256.
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, SignerError>;
+
+    /// The uncompressed SEC1 public key matching the signing key, if the signer can provide it.
+    fn public_key(&self) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+/// The asynchronous counterpart of [`Signer`], for signers backed by a remote service.
+///
+/// Every [`Signer`] is also an `AsyncSigner`.
+pub trait AsyncSigner: Send + Sync {
+    /// Signs `message`, hashing it with SHA-256, and returns the fixed-length `r || s` signature.
+    fn sign<'a>(&'a self, message: &'a [u8]) -> SignFuture<'a>;
+}
+
+impl<T: Signer + ?Sized> AsyncSigner for T {
+    fn sign<'a>(&'a self, message: &'a [u8]) -> SignFuture<'a> {
+        let signature = Signer::sign(self, message);
+        Box::pin(async move { signature })
+    }
+}
+
+/// A [`Signer`] holding an in-memory P-256 private key.
+///
+/// With the `secure-keys` feature, the intermediate copies of the key created while parsing it
+/// are wiped once the signer has been created. The `Debug` output never includes the private key.
+pub struct EcdsaSigner {
+    key_pair: EcdsaKeyPair,
+}
+
+impl std::fmt::Debug for EcdsaSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EcdsaSigner").finish_non_exhaustive()
+    }
+}
+
+/// `AlgorithmIdentifier` for an `ecPublicKey` on the P-256 curve.
+const P256_ALGORITHM_IDENTIFIER: &[u8] = &[
+    0x30, 0x13, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03,
+    0x01, 0x07,
+];
+
+impl EcdsaSigner {
+    /// Creates a signer from a P-256 private key, detecting its format.
+    ///
+    /// Accepts PKCS#8 keys, such as the `.p8` files downloaded from App Store Connect, and SEC1
+    /// (`EC PRIVATE KEY`) keys, either PEM-encoded or as raw DER bytes.
+    pub fn from_key(private_key: &[u8]) -> Result<Self, SignerError> {
+        if private_key.trim_ascii_start().starts_with(b"-----BEGIN") {
+            return Self::from_pem(private_key);
+        }
+
+        match der_version(private_key) {
+            Some(0) => Self::from_pkcs8_der(private_key),
+            Some(1) => Self::from_sec1_der(private_key),
+            _ => Err(SignerError::UnsupportedKeyFormatError(
+                "Expected a PEM, PKCS#8 DER or SEC1 DER private key".to_string(),
+            )),
+        }
+    }
+
+    /// Creates a signer from a PEM-encoded PKCS#8 (`PRIVATE KEY`) or SEC1 (`EC PRIVATE KEY`) private key.
+    pub fn from_pem(private_key: &[u8]) -> Result<Self, SignerError> {
+        let pem = pem::parse(private_key).map_err(|e| SignerError::InvalidKeyError(e.to_string()))?;
+        let tag = pem.tag().to_string();
+        let der = KeyBytes::from(pem.into_contents());
+
+        match tag.as_str() {
+            "PRIVATE KEY" => Self::from_pkcs8_der(der.as_slice()),
+            "EC PRIVATE KEY" => Self::from_sec1_der(der.as_slice()),
+            other => Err(SignerError::UnsupportedKeyFormatError(format!("Unsupported PEM label: {}", other))),
+        }
+    }
+
+    /// Creates a signer from a DER-encoded PKCS#8 private key.
+    pub fn from_pkcs8_der(private_key: &[u8]) -> Result<Self, SignerError> {
+        let key_pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, private_key, &SystemRandom::new())
+            .map_err(|e| SignerError::InvalidKeyError(e.to_string()))?;
+
+        Ok(Self::from_key_pair(key_pair))
+    }
+
+    /// Creates a signer from a DER-encoded SEC1 `ECPrivateKey`.
+    ///
+    /// The key must include its public key, as generated by `openssl ecparam -genkey`.
+    pub fn from_sec1_der(private_key: &[u8]) -> Result<Self, SignerError> {
+        let mut private_key_info = vec![0x02, 0x01, 0x00];
+        private_key_info.extend_from_slice(P256_ALGORITHM_IDENTIFIER);
+        private_key_info.push(0x04);
+        private_key_info.extend(der_length(private_key.len()));
+        private_key_info.extend_from_slice(private_key);
+
+        let mut pkcs8 = vec![0x30];
+        pkcs8.extend(der_length(private_key_info.len()));
+        pkcs8.extend(private_key_info);
+
+        let pkcs8 = KeyBytes::from(pkcs8);
+        Self::from_pkcs8_der(pkcs8.as_slice())
+    }
+
+    pub(crate) fn from_key_pair(key_pair: EcdsaKeyPair) -> Self {
+        EcdsaSigner { key_pair }
+    }
+}
+
+impl Signer for EcdsaSigner {
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, SignerError> {
+        let signature = self
+            .key_pair
+            .sign(&SystemRandom::new(), message)
+            .map_err(|e| SignerError::SigningError(e.to_string()))?;
+
+        Ok(signature.as_ref().to_vec())
+    }
+
+    fn public_key(&self) -> Option<Vec<u8>> {
+        Some(self.key_pair.public_key().as_ref().to_vec())
+    }
+}
+
+/// The encoding of a private key, as detected by [`KeyFormat::detect`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyFormat {
+    /// A PEM-encoded PKCS#8 key, such as the `.p8` files downloaded from App Store Connect.
+    Pkcs8Pem,
+    /// A PEM-encoded SEC1 `EC PRIVATE KEY`.
+    Sec1Pem,
+    /// A PEM document with another label, such as `PUBLIC KEY`, or that can't be parsed.
+    OtherPem(String),
+    /// A DER-encoded PKCS#8 key.
+    Pkcs8Der,
+    /// A DER-encoded SEC1 `ECPrivateKey`.
+    Sec1Der,
+    /// Bytes that are neither PEM nor a DER-encoded private key.
+    Unknown,
+}
+
+impl KeyFormat {
+    /// Detects the encoding of a private key without validating the key itself.
+    pub fn detect(private_key: &[u8]) -> Self {
+        if private_key.trim_ascii_start().starts_with(b"-----BEGIN") {
+            return match pem::parse(private_key) {
+                Ok(pem) if pem.tag() == "PRIVATE KEY" => KeyFormat::Pkcs8Pem,
+                Ok(pem) if pem.tag() == "EC PRIVATE KEY" => KeyFormat::Sec1Pem,
+                Ok(pem) => KeyFormat::OtherPem(pem.tag().to_string()),
+                Err(_) => KeyFormat::OtherPem(String::new()),
+            };
+        }
+
+        match der_version(private_key) {
+            Some(0) => KeyFormat::Pkcs8Der,
+            Some(1) => KeyFormat::Sec1Der,
+            _ => KeyFormat::Unknown,
+        }
+    }
+}
+
+impl std::fmt::Display for KeyFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyFormat::Pkcs8Pem => f.write_str("PKCS#8 PEM"),
+            KeyFormat::Sec1Pem => f.write_str("SEC1 PEM"),
+            KeyFormat::OtherPem(label) if label.is_empty() => f.write_str("malformed PEM"),
+            KeyFormat::OtherPem(label) => write!(f, "PEM labelled {}", label),
+            KeyFormat::Pkcs8Der => f.write_str("PKCS#8 DER"),
+            KeyFormat::Sec1Der => f.write_str("SEC1 DER"),
+            KeyFormat::Unknown => f.write_str("unknown format"),
+        }
+    }
+}
+
+/// Reads the version of a DER `SEQUENCE` that starts with a small `INTEGER`,
+/// which is `0` for PKCS#8 and `1` for SEC1 private keys.
+fn der_version(der: &[u8]) -> Option<u8> {
+    if der.first() != Some(&0x30) {
+        return None;
+    }
+
+    let content = match *der.get(1)? {
+        length if length < 0x80 => 2,
+        length => 2 + usize::from(length & 0x7f),
+    };
+
+    match der.get(content..content + 3)? {
+        [0x02, 0x01, version] => Some(*version),
+        _ => None,
+    }
+}
+
+pub(crate) fn der_length(length: usize) -> Vec<u8> {
+    if length < 0x80 {
+        return vec![length as u8];
+    }
+
+    let bytes: Vec<u8> = length.to_be_bytes().into_iter().skip_while(|b| *b == 0).collect();
+    let mut encoded = vec![0x80 | bytes.len() as u8];
+    encoded.extend(bytes);
+    encoded
+}
+
+/// Returns the JWS signing input (`header.claims`) for an ES256 token with the given key ID.
+fn signing_input<C: Serialize>(key_id: &str, claims: &C) -> Result<String, SignerError> {
+    let header = serde_json::json!({ "typ": "JWT", "alg": "ES256", "kid": key_id });
+    let header = serde_json::to_vec(&header).map_err(|e| SignerError::SigningError(e.to_string()))?;
+    let claims = serde_json::to_vec(claims).map_err(|e| SignerError::SigningError(e.to_string()))?;
+
+    Ok(format!("{}.{}", URL_SAFE_NO_PAD.encode(header), URL_SAFE_NO_PAD.encode(claims)))
+}
+
+/// Creates a compact ES256 JWS for `claims` using a [`Signer`].
+pub(crate) fn encode_jws<C: Serialize>(signer: &dyn Signer, key_id: &str, claims: &C) -> Result<String, SignerError> {
+    let message = signing_input(key_id, claims)?;
+    let signature = signer.sign(message.as_bytes())?;
+
+    Ok(format!("{}.{}", message, URL_SAFE_NO_PAD.encode(signature)))
+}
+
+/// Creates a compact ES256 JWS for `claims` using an [`AsyncSigner`].
+pub(crate) async fn encode_jws_async<C: Serialize>(signer: &dyn AsyncSigner, key_id: &str, claims: &C) -> Result<String, SignerError> {
+    let message = signing_input(key_id, claims)?;
+    let signature = signer.sign(message.as_bytes()).await?;
+
+    Ok(format!("{}.{}", message, URL_SAFE_NO_PAD.encode(signature)))
+}
+
+/// Converts a fixed-length `r || s` P-256 signature into its ASN.1 DER encoding.
+pub(crate) fn fixed_signature_to_der(signature: &[u8]) -> Result<Vec<u8>, SignerError> {
+    const SCALAR_LENGTH: usize = 32;
+
+    if signature.len() != 2 * SCALAR_LENGTH {
+        return Err(SignerError::SigningError(format!(
+            "Expected a {} byte signature, got {}",
+            2 * SCALAR_LENGTH,
+            signature.len()
+        )));
+    }
+
+    fn der_integer(scalar: &[u8]) -> Vec<u8> {
+        let scalar = match scalar.iter().position(|b| *b != 0) {
+            Some(start) => &scalar[start..],
+            None => &scalar[scalar.len() - 1..],
+        };
+
+        let mut integer = vec![0x02];
+        if scalar[0] & 0x80 != 0 {
+            integer.push(scalar.len() as u8 + 1);
+            integer.push(0x00);
+        } else {
+            integer.push(scalar.len() as u8);
+        }
+        integer.extend_from_slice(scalar);
+        integer
+    }
+
+    let r = der_integer(&signature[..SCALAR_LENGTH]);
+    let s = der_integer(&signature[SCALAR_LENGTH..]);
+
+    let mut der = vec![0x30, (r.len() + s.len()) as u8];
+    der.extend(r);
+    der.extend(s);
+
+    Ok(der)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+    use ring::signature::{UnparsedPublicKey, ECDSA_P256_SHA256_ASN1};
+    use serde_json::{Map, Value};
+
+    fn signer() -> EcdsaSigner {
+        EcdsaSigner::from_pem(include_bytes!("../assets/testSigningKey.p8")).unwrap()
+    }
+
+    #[test]
+    fn test_encode_jws() {
+        let signer = signer();
+        let claims = serde_json::json!({ "aud": "appstoreconnect-v1", "bid": "com.example" });
+        let token = encode_jws(&signer, "keyId", &claims).unwrap();
+
+        let header = jsonwebtoken::decode_header(&token).unwrap();
+        assert_eq!(Algorithm::ES256, header.alg);
+        assert_eq!(Some("keyId".to_string()), header.kid);
+
+        let decoding_key = DecodingKey::from_ec_der(&signer.public_key().unwrap());
+        let mut validation = Validation::new(Algorithm::ES256);
+        validation.set_audience(&["appstoreconnect-v1"]);
+        validation.set_required_spec_claims(&["aud"]);
+        let decoded = jsonwebtoken::decode::<Map<String, Value>>(&token, &decoding_key, &validation).unwrap();
+        assert_eq!(Some("com.example"), decoded.claims["bid"].as_str());
+    }
+
+    #[tokio::test]
+    async fn test_encode_jws_async() {
+        let signer = signer();
+        let claims = serde_json::json!({ "bid": "com.example" });
+        let token = encode_jws_async(&signer, "keyId", &claims).await.unwrap();
+
+        assert_eq!(3, token.split('.').count());
+    }
+
+    #[test]
+    fn test_fixed_signature_to_der() {
+        let signer = signer();
+        let message = b"message";
+        let signature = fixed_signature_to_der(&Signer::sign(&signer, message).unwrap()).unwrap();
+
+        let public_key = signer.public_key().unwrap();
+        let public_key = UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, public_key.as_slice());
+        assert!(public_key.verify(message, &signature).is_ok());
+
+        let mut high_bits = vec![0x00; 31];
+        high_bits.push(0x01);
+        high_bits.extend([0xff; 32]);
+        let der = fixed_signature_to_der(&high_bits).unwrap();
+        assert_eq!(vec![0x30, 0x26, 0x02, 0x01, 0x01, 0x02, 0x21, 0x00], der[..8].to_vec());
+
+        assert!(fixed_signature_to_der(&[0x00; 10]).is_err());
+    }
+
+    #[test]
+    fn test_debug_omits_key() {
+        assert_eq!("EcdsaSigner { .. }", format!("{:?}", signer()));
+    }
+
+    #[test]
+    fn test_invalid_key() {
+        assert!(matches!(EcdsaSigner::from_pem(b"invalid"), Err(SignerError::InvalidKeyError(_))));
+    }
+
+    #[test]
+    fn test_key_formats() {
+        let public_key = signer().public_key();
+
+        let pkcs8_der = include_bytes!("../assets/testSigningKey.der");
+        let sec1_pem = include_bytes!("../assets/testSigningKeySec1.pem");
+        let sec1_der = pem::parse(sec1_pem).unwrap().into_contents();
+
+        for key in [&include_bytes!("../assets/testSigningKey.p8")[..], pkcs8_der, sec1_pem, &sec1_der] {
+            assert_eq!(public_key, EcdsaSigner::from_key(key).unwrap().public_key());
+        }
+    }
+
+    #[test]
+    fn test_unsupported_key_format() {
+        let public_key_pem = b"-----BEGIN PUBLIC KEY-----\nMAA=\n-----END PUBLIC KEY-----\n";
+
+        assert!(matches!(EcdsaSigner::from_key(public_key_pem), Err(SignerError::UnsupportedKeyFormatError(_))));
+        assert!(matches!(EcdsaSigner::from_key(b"not a key"), Err(SignerError::UnsupportedKeyFormatError(_))));
+        assert!(matches!(EcdsaSigner::from_key(&[0x30, 0x03, 0x02, 0x01, 0x05]), Err(SignerError::UnsupportedKeyFormatError(_))));
+    }
+
+    #[test]
+    fn test_detect_key_format() {
+        assert_eq!(KeyFormat::Pkcs8Pem, KeyFormat::detect(include_bytes!("../assets/testSigningKey.p8")));
+        assert_eq!(
+            KeyFormat::OtherPem("PUBLIC KEY".to_string()),
+            KeyFormat::detect(b"-----BEGIN PUBLIC KEY-----\nMAA=\n-----END PUBLIC KEY-----\n")
+        );
+        assert_eq!(KeyFormat::Sec1Der, KeyFormat::detect(&[0x30, 0x03, 0x02, 0x01, 0x01]));
+        assert_eq!(KeyFormat::Unknown, KeyFormat::detect(b"not a key"));
+    }
+}
@@ -0,0 +1,451 @@
+//! An in-process simulator of the App Store Server API, so that application flows that call the API
+//! and handle the notifications it triggers can be tested without network access or credentials.
+//!
+//! ```ignore
+//! let simulator = AppStoreSimulator::new();
+//! simulator.add_transaction(transaction);
+//! let client = AppStoreServerAPIClient::new(key, "keyId", "issuerId", FIXTURE_BUNDLE_ID, Environment::LocalTesting)
+//!     .with_transport(simulator.clone());
+//! ```
+//!
+//! The simulator signs the transactions, renewal infos and notifications it returns with a
+//! [`SignedDataCreator`], so the responses decode with
+//! [`local_testing_verifier`](crate::test_utils::local_testing_verifier) by default.
+use crate::api_client::{Transport, TransportFuture};
+use crate::primitives::consumption_request::ConsumptionRequest;
+use crate::primitives::environment::Environment;
+use crate::primitives::error_payload::APIError;
+use crate::primitives::jws_renewal_info_decoded_payload::JWSRenewalInfoDecodedPayload;
+use crate::primitives::jws_transaction_decoded_payload::JWSTransactionDecodedPayload;
+use crate::primitives::notification_type_v2::NotificationTypeV2;
+use crate::primitives::status::Status;
+use crate::primitives::timestamp;
+use crate::test_utils::{json_response, notification_fixture_json, SignedDataCreator, FIXTURE_APP_APPLE_ID, FIXTURE_BUNDLE_ID};
+use reqwest::Method;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// The endpoints that [`AppStoreSimulator`] implements.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SimulatedEndpoint {
+    /// [Get Transaction History](https://developer.apple.com/documentation/appstoreserverapi/get_transaction_history)
+    TransactionHistory,
+    /// [Get Transaction Info](https://developer.apple.com/documentation/appstoreserverapi/get_transaction_info)
+    TransactionInfo,
+    /// [Get All Subscription Statuses](https://developer.apple.com/documentation/appstoreserverapi/get_all_subscription_statuses)
+    AllSubscriptionStatuses,
+    /// [Request a Test Notification](https://developer.apple.com/documentation/appstoreserverapi/request_a_test_notification)
+    RequestTestNotification,
+    /// [Get Test Notification Status](https://developer.apple.com/documentation/appstoreserverapi/get_test_notification_status)
+    TestNotificationStatus,
+    /// [Send Consumption Information](https://developer.apple.com/documentation/appstoreserverapi/send_consumption_information)
+    SendConsumptionData,
+}
+
+struct SimulatedError {
+    http_status_code: u16,
+    api_error: Option<APIError>,
+}
+
+#[derive(Default)]
+struct SimulatorState {
+    transactions: Vec<JWSTransactionDecodedPayload>,
+    renewal_infos: HashMap<String, JWSRenewalInfoDecodedPayload>,
+    statuses: HashMap<String, Status>,
+    test_notifications: Vec<(String, String)>,
+    consumption_requests: Vec<(String, ConsumptionRequest)>,
+    errors: HashMap<SimulatedEndpoint, VecDeque<SimulatedError>>,
+}
+
+/// An in-memory App Store Server API that answers the requests of an
+/// [`AppStoreServerAPIClient`](crate::api_client::AppStoreServerAPIClient) installed with
+/// [`with_transport`](crate::api_client::AppStoreServerAPIClient::with_transport).
+///
+/// The simulator serves the transactions and renewal infos added to it, records the consumption
+/// information and test notifications it receives, and fails the next requests to an endpoint with
+/// the errors queued by [`fail_next`](Self::fail_next). Requests to endpoints it doesn't implement are
+/// answered with `404 Not Found`. Clones share their state, so a test can keep a clone to add fixtures
+/// and inspect requests after installing the simulator.
+#[derive(Clone)]
+pub struct AppStoreSimulator {
+    state: Arc<Mutex<SimulatorState>>,
+    creator: Arc<SignedDataCreator>,
+    environment: Environment,
+    bundle_id: String,
+    app_apple_id: Option<i64>,
+}
+
+impl Default for AppStoreSimulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AppStoreSimulator {
+    /// The number of transactions in each page of the transaction history.
+    pub const HISTORY_PAGE_SIZE: usize = 20;
+
+    /// Creates a simulator for the fixture app in `Environment::LocalTesting`, which signs with a throwaway key.
+    pub fn new() -> Self {
+        AppStoreSimulator {
+            state: Default::default(),
+            creator: Arc::new(SignedDataCreator::local_testing()),
+            environment: Environment::LocalTesting,
+            bundle_id: FIXTURE_BUNDLE_ID.to_string(),
+            app_apple_id: Some(FIXTURE_APP_APPLE_ID),
+        }
+    }
+
+    /// Signs the returned payloads with `creator`, such as one of a
+    /// [`TestCertificateAuthority`](crate::test_utils::TestCertificateAuthority).
+    pub fn signed_by(mut self, creator: SignedDataCreator) -> Self {
+        self.creator = Arc::new(creator);
+        self
+    }
+
+    /// Sets the app and environment reported in responses and test notifications.
+    pub fn app(mut self, bundle_id: &str, app_apple_id: Option<i64>, environment: Environment) -> Self {
+        self.bundle_id = bundle_id.to_string();
+        self.app_apple_id = app_apple_id;
+        self.environment = environment;
+        self
+    }
+
+    /// Adds a transaction, which must have a transaction ID and an original transaction ID.
+    pub fn add_transaction(&self, transaction: JWSTransactionDecodedPayload) {
+        assert!(
+            transaction.transaction_id.is_some() && transaction.original_transaction_id.is_some(),
+            "Simulated transactions need a transaction ID and an original transaction ID"
+        );
+        self.state.lock().unwrap().transactions.push(transaction);
+    }
+
+    /// Sets the renewal info of the subscription with the original transaction ID of `renewal_info`.
+    pub fn set_renewal_info(&self, renewal_info: JWSRenewalInfoDecodedPayload) {
+        let original_transaction_id = renewal_info
+            .original_transaction_id
+            .clone()
+            .expect("Simulated renewal infos need an original transaction ID")
+            .into_inner();
+        self.state.lock().unwrap().renewal_infos.insert(original_transaction_id, renewal_info);
+    }
+
+    /// Sets the status of a subscription. Without one, a subscription is revoked if its latest
+    /// transaction has a revocation date, expired if that transaction expired, and active otherwise.
+    pub fn set_status(&self, original_transaction_id: &str, status: Status) {
+        self.state.lock().unwrap().statuses.insert(original_transaction_id.to_string(), status);
+    }
+
+    /// Fails the next request to `endpoint` with `http_status_code` and, if set, the error code of `api_error`.
+    /// Errors queued for the same endpoint fail the following requests in order.
+    pub fn fail_next(&self, endpoint: SimulatedEndpoint, http_status_code: u16, api_error: Option<APIError>) {
+        let error = SimulatedError { http_status_code, api_error };
+        self.state.lock().unwrap().errors.entry(endpoint).or_default().push_back(error);
+    }
+
+    /// The transaction IDs and consumption information sent with Send Consumption Information, in order.
+    pub fn consumption_requests(&self) -> Vec<(String, ConsumptionRequest)> {
+        self.state.lock().unwrap().consumption_requests.clone()
+    }
+
+    /// The signed payloads of the test notifications requested so far, in order, ready to be posted
+    /// to the notification endpoint of the application.
+    pub fn test_notifications(&self) -> Vec<String> {
+        self.state.lock().unwrap().test_notifications.iter().map(|(_, signed_payload)| signed_payload.clone()).collect()
+    }
+
+    fn respond(&self, request: &reqwest::Request) -> (u16, Value) {
+        let path = request.url().path().trim_start_matches("/inApps/");
+        let segments: Vec<&str> = path.split('/').collect();
+        let query: Vec<(String, String)> = request.url().query_pairs().into_owned().collect();
+        let body = request.body().and_then(|body| body.as_bytes());
+
+        let endpoint = match (request.method(), segments.as_slice()) {
+            (&Method::GET, ["v1" | "v2", "history", _]) => SimulatedEndpoint::TransactionHistory,
+            (&Method::GET, ["v1", "transactions", _]) => SimulatedEndpoint::TransactionInfo,
+            (&Method::GET, ["v1", "subscriptions", _]) => SimulatedEndpoint::AllSubscriptionStatuses,
+            (&Method::POST, ["v1", "notifications", "test"]) => SimulatedEndpoint::RequestTestNotification,
+            (&Method::GET, ["v1", "notifications", "test", _]) => SimulatedEndpoint::TestNotificationStatus,
+            (&Method::PUT, ["v1", "transactions", "consumption", _]) => SimulatedEndpoint::SendConsumptionData,
+            _ => return (404, json!({})),
+        };
+
+        let mut state = self.state.lock().unwrap();
+        if let Some(error) = state.errors.get_mut(&endpoint).and_then(VecDeque::pop_front) {
+            return error_response(error.http_status_code, error.api_error);
+        }
+
+        let result = match endpoint {
+            SimulatedEndpoint::TransactionHistory => self.transaction_history(&state, segments[2], &query),
+            SimulatedEndpoint::TransactionInfo => self.transaction_info(&state, segments[2]),
+            SimulatedEndpoint::AllSubscriptionStatuses => self.subscription_statuses(&state, segments[2], &query),
+            SimulatedEndpoint::RequestTestNotification => self.request_test_notification(&mut state),
+            SimulatedEndpoint::TestNotificationStatus => test_notification_status(&state, segments[3]),
+            SimulatedEndpoint::SendConsumptionData => send_consumption_data(&mut state, segments[3], body),
+        };
+        result.unwrap_or_else(|(http_status_code, api_error)| error_response(http_status_code, Some(api_error)))
+    }
+
+    fn transaction_history(&self, state: &SimulatorState, transaction_id: &str, query: &[(String, String)]) -> Result<(u16, Value), (u16, APIError)> {
+        let original_transaction_id = original_transaction_id(state, transaction_id)?;
+        let product_ids: Vec<&str> = query_values(query, "productId").collect();
+        let mut transactions: Vec<&JWSTransactionDecodedPayload> = state
+            .transactions
+            .iter()
+            .filter(|transaction| transaction.original_transaction_id.as_deref() == Some(original_transaction_id))
+            .filter(|transaction| {
+                product_ids.is_empty() || transaction.product_id.as_deref().is_some_and(|product_id| product_ids.contains(&product_id))
+            })
+            .collect();
+        transactions.sort_by_key(|transaction| transaction.purchase_date.map(timestamp::to_millis));
+        if query_values(query, "sort").any(|sort| sort == "DESCENDING") {
+            transactions.reverse();
+        }
+
+        let start = match query_values(query, "revision").next() {
+            Some(revision) => revision.parse::<usize>().map_err(|_| (400, APIError::InvalidRequestRevision))?,
+            None => 0,
+        };
+        let end = transactions.len().min(start.saturating_add(Self::HISTORY_PAGE_SIZE));
+        let page = transactions.get(start..end).unwrap_or_default();
+        let signed_transactions = page.iter().map(|transaction| self.sign(*transaction)).collect::<Vec<_>>();
+
+        Ok((200, json!({
+            "revision": end.to_string(),
+            "hasMore": end < transactions.len(),
+            "bundleId": self.bundle_id,
+            "appAppleId": self.app_apple_id,
+            "environment": self.environment,
+            "signedTransactions": signed_transactions,
+        })))
+    }
+
+    fn transaction_info(&self, state: &SimulatorState, transaction_id: &str) -> Result<(u16, Value), (u16, APIError)> {
+        let transaction = state
+            .transactions
+            .iter()
+            .find(|transaction| transaction.transaction_id.as_deref() == Some(transaction_id))
+            .ok_or((404, APIError::TransactionIdNotFound))?;
+        Ok((200, json!({ "signedTransactionInfo": self.sign(transaction) })))
+    }
+
+    fn subscription_statuses(&self, state: &SimulatorState, transaction_id: &str, query: &[(String, String)]) -> Result<(u16, Value), (u16, APIError)> {
+        let original_transaction_id = original_transaction_id(state, transaction_id)?;
+        let latest = state
+            .transactions
+            .iter()
+            .filter(|transaction| transaction.original_transaction_id.as_deref() == Some(original_transaction_id))
+            .max_by_key(|transaction| transaction.purchase_date.map(timestamp::to_millis))
+            .ok_or((404, APIError::TransactionIdNotFound))?;
+
+        let status = match state.statuses.get(original_transaction_id) {
+            Some(status) => status.clone(),
+            None if latest.revocation_date.is_some() => Status::Revoked,
+            None if latest.expires_date.is_some_and(|expires_date| expires_date < timestamp::now()) => Status::Expired,
+            None => Status::Active,
+        };
+        let statuses: Vec<&str> = query_values(query, "status").collect();
+        let mut last_transactions = vec![];
+        if statuses.is_empty() || statuses.contains(&status.raw_value().to_string().as_str()) {
+            last_transactions.push(json!({
+                "status": status,
+                "originalTransactionId": original_transaction_id,
+                "signedTransactionInfo": self.sign(latest),
+                "signedRenewalInfo": state.renewal_infos.get(original_transaction_id).map(|renewal_info| self.sign(renewal_info)),
+            }));
+        }
+
+        Ok((200, json!({
+            "environment": self.environment,
+            "bundleId": self.bundle_id,
+            "appAppleId": self.app_apple_id,
+            "data": [{
+                "subscriptionGroupIdentifier": latest.subscription_group_identifier,
+                "lastTransactions": last_transactions,
+            }],
+        })))
+    }
+
+    fn request_test_notification(&self, state: &mut SimulatorState) -> Result<(u16, Value), (u16, APIError)> {
+        let mut notification = notification_fixture_json(&NotificationTypeV2::Test, None);
+        notification["notificationUUID"] = json!(Uuid::new_v4().to_string());
+        notification["signedDate"] = json!(timestamp::to_millis(timestamp::now()));
+        notification["data"] = json!({
+            "environment": self.environment,
+            "appAppleId": self.app_apple_id,
+            "bundleId": self.bundle_id,
+        });
+
+        let token = format!("{}_{}", Uuid::new_v4(), state.test_notifications.len());
+        state.test_notifications.push((token.clone(), self.sign(&notification)));
+        Ok((200, json!({ "testNotificationToken": token })))
+    }
+
+    fn sign<T: Serialize>(&self, claims: &T) -> String {
+        self.creator.sign(claims).expect("Failed to sign simulated payload")
+    }
+}
+
+impl Transport for AppStoreSimulator {
+    fn send<'a>(&'a self, _client: &'a reqwest::Client, request: reqwest::Request) -> TransportFuture<'a> {
+        let (status_code, body) = self.respond(&request);
+        let body = match body {
+            Value::Null => vec![],
+            body => serde_json::to_vec(&body).expect("Failed to encode simulated response"),
+        };
+        Box::pin(async move { Ok(json_response(request.url(), status_code, vec![], body)) })
+    }
+}
+
+fn test_notification_status(state: &SimulatorState, token: &str) -> Result<(u16, Value), (u16, APIError)> {
+    let (_, signed_payload) = state
+        .test_notifications
+        .iter()
+        .find(|(test_notification_token, _)| test_notification_token == token)
+        .ok_or((404, APIError::TestNotificationNotFound))?;
+    Ok((200, json!({
+        "signedPayload": signed_payload,
+        "sendAttempts": [{ "attemptDate": timestamp::to_millis(timestamp::now()), "sendAttemptResult": "SUCCESS" }],
+    })))
+}
+
+fn send_consumption_data(state: &mut SimulatorState, transaction_id: &str, body: Option<&[u8]>) -> Result<(u16, Value), (u16, APIError)> {
+    if !state.transactions.iter().any(|transaction| transaction.transaction_id.as_deref() == Some(transaction_id)) {
+        return Err((404, APIError::TransactionIdNotFound));
+    }
+    let consumption_request = body
+        .and_then(|body| serde_json::from_slice(body).ok())
+        .ok_or((400, APIError::GeneralBadRequest))?;
+    state.consumption_requests.push((transaction_id.to_string(), consumption_request));
+    Ok((202, Value::Null))
+}
+
+fn original_transaction_id<'a>(state: &'a SimulatorState, transaction_id: &str) -> Result<&'a str, (u16, APIError)> {
+    state
+        .transactions
+        .iter()
+        .find(|transaction| transaction.transaction_id.as_deref() == Some(transaction_id))
+        .and_then(|transaction| transaction.original_transaction_id.as_deref())
+        .ok_or((404, APIError::TransactionIdNotFound))
+}
+
+fn query_values<'a>(query: &'a [(String, String)], name: &'a str) -> impl Iterator<Item = &'a str> {
+    query.iter().filter(move |(key, _)| key == name).map(|(_, value)| value.as_str())
+}
+
+fn error_response(http_status_code: u16, api_error: Option<APIError>) -> (u16, Value) {
+    match api_error {
+        Some(api_error) => (http_status_code, json!({ "errorCode": api_error, "errorMessage": "Simulated error" })),
+        None => (http_status_code, json!({})),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_client::{AppStoreServerAPIClient, GetTransactionHistoryVersion};
+    use crate::primitives::transaction_history_request::TransactionHistoryRequest;
+    use crate::primitives::transaction_id::TransactionId;
+    use crate::test_utils::{local_testing_verifier, transaction_with_fields};
+
+    fn client(simulator: &AppStoreSimulator) -> AppStoreServerAPIClient {
+        let key = std::fs::read("assets/testSigningKey.p8").unwrap();
+        AppStoreServerAPIClient::new(key, "keyId", "issuerId", FIXTURE_BUNDLE_ID, Environment::LocalTesting, Box::new(|_, _| unreachable!()))
+            .with_transport(simulator.clone())
+    }
+
+    fn transaction(transaction_id: u64, purchase_date: i64) -> JWSTransactionDecodedPayload {
+        transaction_with_fields(json!({
+            "transactionId": transaction_id.to_string(),
+            "originalTransactionId": "1000",
+            "bundleId": FIXTURE_BUNDLE_ID,
+            "productId": "com.example.monthly",
+            "subscriptionGroupIdentifier": "1",
+            "purchaseDate": purchase_date,
+            "environment": "LocalTesting",
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_transaction_history_and_statuses() {
+        let simulator = AppStoreSimulator::new();
+        for index in 0..25 {
+            simulator.add_transaction(transaction(1000 + index, 1698148800000 + index as i64));
+        }
+        let client = client(&simulator);
+        let verifier = local_testing_verifier();
+
+        let request = TransactionHistoryRequest::default();
+        let first = client.get_transaction_history_with_version(&TransactionId::from("1000"), None, &request, GetTransactionHistoryVersion::V2).await.unwrap();
+        assert_eq!(Some(true), first.has_more);
+        assert_eq!(20, first.decode_all(&verifier).len());
+        let second = client
+            .get_transaction_history_with_version(&TransactionId::from("1000"), first.revision.as_deref(), &request, GetTransactionHistoryVersion::V2)
+            .await.unwrap();
+        assert_eq!(Some(false), second.has_more);
+        let last = second.decode_all(&verifier).pop().unwrap().unwrap();
+        assert_eq!(Some("1024"), last.transaction_id.as_deref());
+
+        let statuses = client.get_all_subscription_statuses(&TransactionId::from("1010"), None).await.unwrap();
+        let item = statuses.last_transactions_iter().next().unwrap();
+        assert_eq!(Some(Status::Active), item.status);
+        assert_eq!(Some("1024"), item.decode_transaction_info(&verifier).unwrap().unwrap().transaction_id.as_deref());
+
+        simulator.set_status("1000", Status::BillingRetry);
+        let statuses = client.get_all_subscription_statuses(&TransactionId::from("1010"), Some(&vec![Status::Active])).await.unwrap();
+        assert_eq!(0, statuses.last_transactions_iter().count());
+    }
+
+    #[tokio::test]
+    async fn test_notifications_consumption_and_errors() {
+        let simulator = AppStoreSimulator::new();
+        simulator.add_transaction(transaction(1000, 1698148800000));
+        let client = client(&simulator);
+
+        let token = client.request_test_notification().await.unwrap().test_notification_token.unwrap();
+        let status = client.get_test_notification_status(&token).await.unwrap();
+        assert_eq!(simulator.test_notifications(), vec![status.signed_payload.clone().unwrap()]);
+        let notification = local_testing_verifier().verify_and_decode_notification(&status.signed_payload.unwrap()).unwrap();
+        assert_eq!(NotificationTypeV2::Test, notification.notification_type);
+
+        let consumption_request = ConsumptionRequest { customer_consented: Some(true), ..Default::default() };
+        client.send_consumption_data(&TransactionId::from("1000"), &consumption_request).await.unwrap();
+        assert_eq!(vec![("1000".to_string(), consumption_request.clone())], simulator.consumption_requests());
+
+        let error = client.send_consumption_data(&TransactionId::from("9999"), &consumption_request).await.unwrap_err();
+        assert_eq!(Some(APIError::TransactionIdNotFound), error.api_error);
+
+        simulator.fail_next(SimulatedEndpoint::TransactionInfo, 429, Some(APIError::RateLimitExceeded));
+        let error = client.get_transaction_info(&TransactionId::from("1000")).await.unwrap_err();
+        assert_eq!((429, Some(APIError::RateLimitExceeded)), (error.http_status_code, error.api_error));
+        assert!(client.get_transaction_info(&TransactionId::from("1000")).await.is_ok());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_check_test_notification() {
+        use crate::api_client::TestNotificationCheckError;
+        use std::time::Duration;
+
+        let simulator = AppStoreSimulator::new();
+        let client = client(&simulator);
+        let verifier = local_testing_verifier();
+
+        simulator.fail_next(SimulatedEndpoint::TestNotificationStatus, 404, Some(APIError::TestNotificationNotFound));
+        let check = client.check_test_notification(&verifier, Duration::from_secs(1), Duration::from_secs(30)).await.unwrap();
+        assert_eq!(NotificationTypeV2::Test, check.notification.notification_type);
+        assert_eq!(1, check.send_attempts.len());
+
+        simulator.fail_next(SimulatedEndpoint::TestNotificationStatus, 500, Some(APIError::GeneralInternal));
+        let error = client.check_test_notification(&verifier, Duration::from_secs(1), Duration::from_secs(30)).await.unwrap_err();
+        assert!(matches!(error, TestNotificationCheckError::APIError(error) if error.http_status_code == 500));
+
+        simulator.fail_next(SimulatedEndpoint::TestNotificationStatus, 404, Some(APIError::TestNotificationNotFound));
+        let start = tokio::time::Instant::now();
+        client.check_test_notification(&verifier, Duration::ZERO, Duration::MAX).await.unwrap();
+        assert_eq!(Duration::from_secs(1), start.elapsed());
+    }
+}
@@ -0,0 +1,264 @@
+use crate::primitives::jws_transaction_decoded_payload::JWSTransactionDecodedPayload;
+use crate::primitives::timestamp::Timestamp;
+use crate::primitives::transaction_id::TransactionId;
+use std::collections::HashMap;
+
+/// The service levels of the subscriptions in a subscription group, as configured in App Store Connect.
+///
+/// Level 1 is the highest level of service. Several subscriptions can share a level.
+///
+/// [Offering auto-renewable subscriptions](https://developer.apple.com/app-store/subscriptions/#ranking)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProductRanking {
+    levels: HashMap<String, u32>,
+}
+
+impl ProductRanking {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the level of service of a product.
+    pub fn with_level(mut self, product_id: &str, level: u32) -> Self {
+        self.levels.insert(product_id.to_string(), level);
+        self
+    }
+
+    /// The level of service of a product, if it's configured.
+    pub fn level(&self, product_id: &str) -> Option<u32> {
+        self.levels.get(product_id).copied()
+    }
+}
+
+/// The kind of change between two subscriptions in the same subscription group.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum SubscriptionTransitionType {
+    /// A change to a higher level of service. Takes effect immediately, and the App Store
+    /// refunds the prorated remainder of the previous subscription.
+    Upgrade,
+    /// A change to a lower level of service. Takes effect at the next renewal date.
+    Downgrade,
+    /// A change to a subscription on the same level of service. Takes effect immediately when both
+    /// subscriptions have the same duration, and at the next renewal date otherwise.
+    Crossgrade,
+    /// A change between products whose levels aren't configured in the [`ProductRanking`].
+    Unknown,
+}
+
+/// A change of product between two consecutive transactions of a subscription.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubscriptionTransition {
+    pub transition_type: SubscriptionTransitionType,
+    pub from_product_id: String,
+    pub to_product_id: String,
+    /// The transaction identifier of the last transaction for the previous product.
+    pub from_transaction_id: Option<TransactionId>,
+    /// The transaction identifier of the first transaction for the new product.
+    pub to_transaction_id: Option<TransactionId>,
+    /// The time the new product took effect, which is the purchase date of its first transaction.
+    pub effective_date: Option<Timestamp>,
+}
+
+/// A period during which a transaction entitles the customer to a product.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntitlementWindow {
+    pub product_id: Option<String>,
+    pub transaction_id: Option<TransactionId>,
+    pub start: Timestamp,
+    /// The end of the entitlement, or `None` if the transaction doesn't expire.
+    pub end: Option<Timestamp>,
+}
+
+impl EntitlementWindow {
+    /// Whether the window includes the given time.
+    pub fn contains(&self, date: Timestamp) -> bool {
+        self.start <= date && self.end.is_none_or(|end| date < end)
+    }
+}
+
+/// Orders the transactions of a subscription group by purchase date, skipping those without one.
+fn chronological<'a>(
+    transactions: impl IntoIterator<Item = &'a JWSTransactionDecodedPayload>,
+) -> Vec<&'a JWSTransactionDecodedPayload> {
+    let mut transactions: Vec<_> = transactions.into_iter().filter(|t| t.purchase_date.is_some()).collect();
+    transactions.sort_by_key(|t| t.purchase_date);
+    transactions
+}
+
+/// Classifies the product changes in the transactions of a single subscription group.
+///
+/// Transactions are ordered by purchase date, and every pair of consecutive transactions for
+/// different products yields a transition. Levels from `ranking` take precedence; without them,
+/// a previous transaction marked as upgraded still identifies an upgrade.
+pub fn subscription_transitions<'a>(
+    transactions: impl IntoIterator<Item = &'a JWSTransactionDecodedPayload>,
+    ranking: &ProductRanking,
+) -> Vec<SubscriptionTransition> {
+    let transactions = chronological(transactions);
+
+    transactions
+        .windows(2)
+        .filter_map(|pair| {
+            let (from, to) = (pair[0], pair[1]);
+            let from_product_id = from.product_id.as_deref()?;
+            let to_product_id = to.product_id.as_deref()?;
+            if from_product_id == to_product_id {
+                return None;
+            }
+
+            let transition_type = match (ranking.level(from_product_id), ranking.level(to_product_id)) {
+                (Some(from_level), Some(to_level)) if to_level < from_level => SubscriptionTransitionType::Upgrade,
+                (Some(from_level), Some(to_level)) if to_level > from_level => SubscriptionTransitionType::Downgrade,
+                (Some(_), Some(_)) => SubscriptionTransitionType::Crossgrade,
+                _ if from.is_upgraded == Some(true) => SubscriptionTransitionType::Upgrade,
+                _ => SubscriptionTransitionType::Unknown,
+            };
+
+            Some(SubscriptionTransition {
+                transition_type,
+                from_product_id: from_product_id.to_string(),
+                to_product_id: to_product_id.to_string(),
+                from_transaction_id: from.transaction_id.clone(),
+                to_transaction_id: to.transaction_id.clone(),
+                effective_date: to.purchase_date,
+            })
+        })
+        .collect()
+}
+
+/// The periods during which the transactions of a single subscription group entitled the customer to a product.
+///
+/// A window starts at the purchase date and ends at the expiration date, or at the revocation date
+/// when the App Store refunded or revoked the transaction. A transaction marked as upgraded ends when
+/// the next transaction starts, since the upgrade replaces it immediately. Transactions revoked
+/// before their purchase date yield no window.
+pub fn entitlement_windows<'a>(
+    transactions: impl IntoIterator<Item = &'a JWSTransactionDecodedPayload>,
+) -> Vec<EntitlementWindow> {
+    let transactions = chronological(transactions);
+
+    transactions
+        .iter()
+        .enumerate()
+        .filter_map(|(index, transaction)| {
+            let start = transaction.purchase_date?;
+
+            let mut end = transaction.expires_date;
+            let mut cut_off = |date: Option<Timestamp>| {
+                if let Some(date) = date {
+                    end = Some(end.map_or(date, |end| end.min(date)));
+                }
+            };
+
+            cut_off(transaction.revocation_date);
+            if transaction.is_upgraded == Some(true) {
+                cut_off(transactions.get(index + 1).and_then(|next| next.purchase_date));
+            }
+
+            if end.is_some_and(|end| end < start) {
+                return None;
+            }
+
+            Some(EntitlementWindow {
+                product_id: transaction.product_id.clone(),
+                transaction_id: transaction.transaction_id.clone(),
+                start,
+                end,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::timestamp;
+    use crate::test_utils::transaction_with_fields;
+
+    fn transaction(
+        transaction_id: &str,
+        product_id: &str,
+        purchase_date: i64,
+        expires_date: i64,
+        is_upgraded: bool,
+    ) -> JWSTransactionDecodedPayload {
+        transaction_with_fields(serde_json::json!({
+            "transactionId": transaction_id,
+            "productId": product_id,
+            "purchaseDate": purchase_date,
+            "expiresDate": expires_date,
+            "isUpgraded": is_upgraded,
+        }))
+    }
+
+    fn timestamp(millis: i64) -> Timestamp {
+        timestamp::from_millis(millis).unwrap()
+    }
+
+    #[test]
+    fn test_subscription_transitions() {
+        let transactions = vec![
+            transaction("4", "com.example.basic", 4000, 5000, false),
+            transaction("1", "com.example.basic", 1000, 2000, false),
+            transaction("2", "com.example.premium", 1500, 2500, false),
+            transaction("3", "com.example.basic.yearly", 2500, 3500, false),
+        ];
+        let ranking = ProductRanking::new()
+            .with_level("com.example.premium", 1)
+            .with_level("com.example.basic", 2)
+            .with_level("com.example.basic.yearly", 2);
+
+        let transitions = subscription_transitions(&transactions, &ranking);
+        let types: Vec<_> = transitions.iter().map(|t| t.transition_type).collect();
+        assert_eq!(
+            vec![
+                SubscriptionTransitionType::Upgrade,
+                SubscriptionTransitionType::Downgrade,
+                SubscriptionTransitionType::Crossgrade
+            ],
+            types
+        );
+        assert_eq!("com.example.basic", transitions[0].from_product_id);
+        assert_eq!(Some("2"), transitions[0].to_transaction_id.as_deref());
+        assert_eq!(Some(timestamp(1500)), transitions[0].effective_date);
+
+        let transitions = subscription_transitions(&transactions, &ProductRanking::new());
+        assert!(transitions.iter().all(|t| t.transition_type == SubscriptionTransitionType::Unknown));
+    }
+
+    #[test]
+    fn test_subscription_transitions_without_ranking() {
+        let transactions = vec![
+            transaction("1", "com.example.basic", 1000, 2000, true),
+            transaction("2", "com.example.premium", 1500, 2500, false),
+        ];
+
+        let transitions = subscription_transitions(&transactions, &ProductRanking::new());
+        assert_eq!(1, transitions.len());
+        assert_eq!(SubscriptionTransitionType::Upgrade, transitions[0].transition_type);
+    }
+
+    #[test]
+    fn test_entitlement_windows() {
+        let mut revoked = transaction("3", "com.example.premium", 2500, 3500, false);
+        revoked.revocation_date = Some(timestamp(3000));
+        let transactions = vec![
+            transaction("1", "com.example.basic", 1000, 2000, true),
+            transaction("2", "com.example.premium", 1500, 2500, false),
+            revoked,
+        ];
+
+        let windows = entitlement_windows(&transactions);
+        let bounds: Vec<_> = windows.iter().map(|w| (w.start, w.end)).collect();
+        assert_eq!(
+            vec![
+                (timestamp(1000), Some(timestamp(1500))),
+                (timestamp(1500), Some(timestamp(2500))),
+                (timestamp(2500), Some(timestamp(3000))),
+            ],
+            bounds
+        );
+        assert!(windows[0].contains(timestamp(1499)));
+        assert!(!windows[0].contains(timestamp(1500)));
+    }
+}
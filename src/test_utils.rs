@@ -0,0 +1,1229 @@
+use crate::primitives::environment::Environment;
+use crate::primitives::external_purchase_token::ExternalPurchaseToken;
+use crate::promotional_offer_signature_creator::promotional_offer_payload;
+use crate::primitives::app_transaction::AppTransaction;
+use crate::primitives::jws_renewal_info_decoded_payload::JWSRenewalInfoDecodedPayload;
+use crate::primitives::jws_transaction_decoded_payload::JWSTransactionDecodedPayload;
+use crate::signer::{der_length, EcdsaSigner, Signer, SignerError};
+use crate::primitives::notification_type_v2::NotificationTypeV2;
+use crate::primitives::response_body_v2_decoded_payload::ResponseBodyV2DecodedPayload;
+use crate::primitives::subtype::Subtype;
+use crate::signed_data_verifier::SignedDataVerifier;
+#[cfg(feature = "api-client")]
+use crate::api_client::{Transport, TransportError, TransportFuture};
+use jsonwebtoken::Algorithm;
+use ring::rand::SystemRandom;
+use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_ASN1_SIGNING, ECDSA_P256_SHA256_FIXED_SIGNING};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use jsonwebtoken::{DecodingKey, Validation};
+use ring::signature::{UnparsedPublicKey, ECDSA_P256_SHA256_ASN1};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::fmt::Debug;
+use uuid::Uuid;
+
+/// The bundle identifier used by every fixture.
+pub const FIXTURE_BUNDLE_ID: &str = "com.example";
+
+/// The App Apple ID used by every fixture.
+pub const FIXTURE_APP_APPLE_ID: i64 = 41234;
+
+/// Every notification type and subtype combination documented by Apple.
+///
+/// [notificationType](https://developer.apple.com/documentation/appstoreservernotifications/notificationtype)
+pub const NOTIFICATION_TYPE_SUBTYPE_COMBINATIONS: &[(NotificationTypeV2, Option<Subtype>)] = &[
+    (NotificationTypeV2::ConsumptionRequest, None),
+    (NotificationTypeV2::DidChangeRenewalPref, Some(Subtype::Upgrade)),
+    (NotificationTypeV2::DidChangeRenewalPref, Some(Subtype::Downgrade)),
+    (NotificationTypeV2::DidChangeRenewalPref, None),
+    (NotificationTypeV2::DidChangeRenewalStatus, Some(Subtype::AutoRenewEnabled)),
+    (NotificationTypeV2::DidChangeRenewalStatus, Some(Subtype::AutoRenewDisabled)),
+    (NotificationTypeV2::DidFailToRenew, Some(Subtype::GracePeriod)),
+    (NotificationTypeV2::DidFailToRenew, None),
+    (NotificationTypeV2::DidRenew, Some(Subtype::BillingRecovery)),
+    (NotificationTypeV2::DidRenew, None),
+    (NotificationTypeV2::Expired, Some(Subtype::Voluntary)),
+    (NotificationTypeV2::Expired, Some(Subtype::BillingRetry)),
+    (NotificationTypeV2::Expired, Some(Subtype::PriceIncrease)),
+    (NotificationTypeV2::Expired, Some(Subtype::ProductNotForSale)),
+    (NotificationTypeV2::ExternalPurchaseToken, Some(Subtype::Unreported)),
+    (NotificationTypeV2::GracePeriodExpired, None),
+    (NotificationTypeV2::OfferRedeemed, Some(Subtype::Upgrade)),
+    (NotificationTypeV2::OfferRedeemed, Some(Subtype::Downgrade)),
+    (NotificationTypeV2::OfferRedeemed, None),
+    (NotificationTypeV2::OneTimeCharge, None),
+    (NotificationTypeV2::PriceIncrease, Some(Subtype::Pending)),
+    (NotificationTypeV2::PriceIncrease, Some(Subtype::Accepted)),
+    (NotificationTypeV2::Refund, None),
+    (NotificationTypeV2::RefundDeclined, None),
+    (NotificationTypeV2::RefundReversed, None),
+    (NotificationTypeV2::RenewalExtended, None),
+    (NotificationTypeV2::RenewalExtension, Some(Subtype::Summary)),
+    (NotificationTypeV2::RenewalExtension, Some(Subtype::Failure)),
+    (NotificationTypeV2::Revoke, None),
+    (NotificationTypeV2::Subscribed, Some(Subtype::InitialBuy)),
+    (NotificationTypeV2::Subscribed, Some(Subtype::Resubscribe)),
+    (NotificationTypeV2::Test, None),
+];
+
+const SIGNED_DATE: i64 = 1698148900000;
+const PURCHASE_DATE: i64 = 1698148800000;
+const EXPIRES_DATE: i64 = 1700827200000;
+
+/// Creates a JWS signed with a throwaway key.
+///
+/// Such payloads can only be decoded by a verifier in `Environment::LocalTesting`, see [`local_testing_verifier`].
+pub fn sign_fixture(payload: &Value) -> String {
+    SignedDataCreator::local_testing().sign(payload).expect("Failed to encode JWS")
+}
+
+#[derive(Serialize)]
+struct SignedDataHeader<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kid: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    typ: Option<&'static str>,
+    alg: &'static str,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    x5c: &'a [String],
+}
+
+/// Creates signed payloads in the format of the App Store, from the models that
+/// [`SignedDataVerifier`] decodes them into.
+///
+/// A creator with the private key and certificate chain of a test certificate authority produces
+/// payloads that a verifier trusting its root certificate accepts in `Environment::Sandbox` or
+/// `Environment::Production`, so that webhook handlers can be tested end to end. A creator from
+/// [`local_testing`](Self::local_testing) produces payloads for a verifier in `Environment::LocalTesting`,
+/// and one from [`xcode`](Self::xcode) payloads in the format of StoreKit Testing in Xcode.
+pub struct SignedDataCreator {
+    signer: EcdsaSigner,
+    x5c: Vec<String>,
+    key_id: Option<&'static str>,
+}
+
+impl SignedDataCreator {
+    /// Creates a creator that signs with the P-256 private key of the leaf certificate of
+    /// `certificate_chain`, and embeds the DER-encoded chain, leaf first, in the `x5c` header.
+    pub fn new(private_key: &[u8], certificate_chain: &[Vec<u8>]) -> Result<Self, SignerError> {
+        Ok(SignedDataCreator {
+            signer: EcdsaSigner::from_key(private_key)?,
+            x5c: certificate_chain.iter().map(|certificate| BASE64_STANDARD.encode(certificate)).collect(),
+            key_id: None,
+        })
+    }
+
+    /// Creates a creator that signs with a throwaway key and without a certificate chain.
+    pub fn local_testing() -> Self {
+        let private_key = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &SystemRandom::new())
+            .expect("Failed to generate private key");
+        SignedDataCreator {
+            signer: EcdsaSigner::from_pkcs8_der(private_key.as_ref()).expect("Failed to parse generated key"),
+            x5c: vec![],
+            key_id: None,
+        }
+    }
+
+    /// Creates a creator that signs like StoreKit Testing in Xcode: with a throwaway key, a self-signed
+    /// certificate in the `x5c` header, and the `Apple_Xcode_Key` key ID. Such payloads are accepted by
+    /// a verifier in `Environment::Xcode`, see [`xcode_verifier`].
+    pub fn xcode() -> Self {
+        let rng = SystemRandom::new();
+        let private_key = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng).expect("Failed to generate private key");
+        let key_pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, private_key.as_ref(), &rng)
+            .expect("Failed to parse generated key");
+        let now = chrono::Utc::now().timestamp();
+        let name = "StoreKit Testing in Xcode";
+        let extensions = [extension(OID_BASIC_CONSTRAINTS, &der(0x30, &[0x01, 0x01, 0xff]))];
+        let certificate = certificate(1, name, name, (now, now + 365 * 24 * 60 * 60), &key_pair, &key_pair, &extensions);
+
+        SignedDataCreator {
+            key_id: Some(XCODE_KEY_ID),
+            ..Self::new(private_key.as_ref(), &[certificate]).expect("Failed to parse generated key")
+        }
+    }
+
+    /// Signs any claims.
+    pub fn sign<T: Serialize>(&self, claims: &T) -> Result<String, SignerError> {
+        let header = SignedDataHeader {
+            kid: self.key_id,
+            typ: self.key_id.map(|_| "JWT"),
+            alg: "ES256",
+            x5c: &self.x5c,
+        };
+        let message = format!("{}.{}", base64_json(&header)?, base64_json(claims)?);
+        let signature = self.signer.sign(message.as_bytes())?;
+
+        Ok(format!("{}.{}", message, URL_SAFE_NO_PAD.encode(signature)))
+    }
+
+    pub fn sign_notification(&self, notification: &ResponseBodyV2DecodedPayload) -> Result<String, SignerError> {
+        self.sign(notification)
+    }
+
+    pub fn sign_transaction(&self, transaction: &JWSTransactionDecodedPayload) -> Result<String, SignerError> {
+        self.sign(transaction)
+    }
+
+    pub fn sign_renewal_info(&self, renewal_info: &JWSRenewalInfoDecodedPayload) -> Result<String, SignerError> {
+        self.sign(renewal_info)
+    }
+
+    pub fn sign_app_transaction(&self, app_transaction: &AppTransaction) -> Result<String, SignerError> {
+        self.sign(app_transaction)
+    }
+}
+
+/// A root, intermediate and leaf certificate generated on the fly, with the Apple-specific extensions
+/// that [`verify_chain`](crate::chain_verifier::verify_chain) requires, so that tests don't depend on
+/// checked-in certificates that eventually expire.
+///
+/// The certificates use P-256 keys and ECDSA with SHA-256 signatures.
+pub struct TestCertificateAuthority {
+    pub root_certificate: Vec<u8>,
+    pub intermediate_certificate: Vec<u8>,
+    pub leaf_certificate: Vec<u8>,
+    /// The DER-encoded PKCS#8 private key of the leaf certificate.
+    pub leaf_private_key: Vec<u8>,
+}
+
+const OID_ECDSA_WITH_SHA256: &[u8] = &[0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+const OID_EC_PUBLIC_KEY: &[u8] = &[0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+const OID_PRIME256V1: &[u8] = &[0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+const OID_COMMON_NAME: &[u8] = &[0x06, 0x03, 0x55, 0x04, 0x03];
+const OID_BASIC_CONSTRAINTS: &[u8] = &[0x06, 0x03, 0x55, 0x1d, 0x13];
+const OID_APPLE_INTERMEDIATE_MARKER: &[u8] = &[0x06, 0x0a, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x63, 0x64, 0x06, 0x02, 0x01];
+const OID_APPLE_LEAF_MARKER: &[u8] = &[0x06, 0x0a, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x63, 0x64, 0x06, 0x0b, 0x01];
+
+impl TestCertificateAuthority {
+    /// Generates certificates that are valid from a day ago until a year from now.
+    pub fn generate() -> Self {
+        let now = chrono::Utc::now().timestamp();
+        Self::generate_with_validity(now - 24 * 60 * 60, now + 365 * 24 * 60 * 60)
+    }
+
+    /// Generates certificates that are valid between two Unix timestamps, in seconds.
+    pub fn generate_with_validity(not_before: i64, not_after: i64) -> Self {
+        let rng = SystemRandom::new();
+        let generate = || {
+            let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng).expect("Failed to generate private key");
+            let key_pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8.as_ref(), &rng)
+                .expect("Failed to parse generated key");
+            (pkcs8.as_ref().to_vec(), key_pair)
+        };
+        let (_, root_key) = generate();
+        let (_, intermediate_key) = generate();
+        let (leaf_private_key, leaf_key) = generate();
+        let validity = (not_before, not_after);
+
+        let ca_extensions = [extension(OID_BASIC_CONSTRAINTS, &der(0x30, &[0x01, 0x01, 0xff]))];
+        let root_certificate = certificate(1, "Test Root CA", "Test Root CA", validity, &root_key, &root_key, &ca_extensions);
+        let intermediate_extensions = [ca_extensions[0].clone(), extension(OID_APPLE_INTERMEDIATE_MARKER, &[0x05, 0x00])];
+        let intermediate_certificate = certificate(
+            2,
+            "Test Root CA",
+            "Test Intermediate CA",
+            validity,
+            &root_key,
+            &intermediate_key,
+            &intermediate_extensions,
+        );
+        let leaf_extensions = [extension(OID_BASIC_CONSTRAINTS, &der(0x30, &[])), extension(OID_APPLE_LEAF_MARKER, &[0x05, 0x00])];
+        let leaf_certificate = certificate(
+            3,
+            "Test Intermediate CA",
+            "Test Leaf",
+            validity,
+            &intermediate_key,
+            &leaf_key,
+            &leaf_extensions,
+        );
+
+        TestCertificateAuthority { root_certificate, intermediate_certificate, leaf_certificate, leaf_private_key }
+    }
+
+    /// The certificate chain in the order of an `x5c` header, leaf first.
+    pub fn certificate_chain(&self) -> Vec<Vec<u8>> {
+        vec![self.leaf_certificate.clone(), self.intermediate_certificate.clone(), self.root_certificate.clone()]
+    }
+
+    /// A creator that signs with the leaf certificate.
+    pub fn signed_data_creator(&self) -> SignedDataCreator {
+        SignedDataCreator::new(&self.leaf_private_key, &self.certificate_chain()).expect("Failed to parse generated key")
+    }
+
+    /// A verifier in `environment` for the fixtures' bundle ID and app Apple ID that trusts the root certificate.
+    pub fn verifier(&self, environment: Environment) -> SignedDataVerifier {
+        SignedDataVerifier::builder(environment, FIXTURE_BUNDLE_ID)
+            .root_certificate(self.root_certificate.clone())
+            .app_apple_id(Some(FIXTURE_APP_APPLE_ID))
+            .build()
+    }
+}
+
+fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![tag];
+    encoded.extend(der_length(content.len()));
+    encoded.extend_from_slice(content);
+    encoded
+}
+
+fn extension(oid: &[u8], value: &[u8]) -> Vec<u8> {
+    der(0x30, &[oid, &der(0x04, value)].concat())
+}
+
+fn name(common_name: &str) -> Vec<u8> {
+    let attribute = der(0x30, &[OID_COMMON_NAME, &der(0x0c, common_name.as_bytes())].concat());
+    der(0x30, &der(0x31, &attribute))
+}
+
+fn time(timestamp: i64) -> Vec<u8> {
+    let time = chrono::DateTime::from_timestamp(timestamp, 0).expect("Invalid certificate validity");
+    match time.format("%Y").to_string().parse::<i32>() {
+        Ok(year) if (1950..2050).contains(&year) => der(0x17, time.format("%y%m%d%H%M%SZ").to_string().as_bytes()),
+        _ => der(0x18, time.format("%Y%m%d%H%M%SZ").to_string().as_bytes()),
+    }
+}
+
+fn certificate(
+    serial: u8,
+    issuer: &str,
+    subject: &str,
+    (not_before, not_after): (i64, i64),
+    issuer_key: &EcdsaKeyPair,
+    subject_key: &EcdsaKeyPair,
+    extensions: &[Vec<u8>],
+) -> Vec<u8> {
+    let signature_algorithm = der(0x30, OID_ECDSA_WITH_SHA256);
+    let public_key = [&[0x00], subject_key.public_key().as_ref()].concat();
+    let subject_public_key_info = der(
+        0x30,
+        &[der(0x30, &[OID_EC_PUBLIC_KEY, OID_PRIME256V1].concat()), der(0x03, &public_key)].concat(),
+    );
+
+    let tbs_certificate = der(
+        0x30,
+        &[
+            der(0xa0, &der(0x02, &[0x02])),
+            der(0x02, &[serial]),
+            signature_algorithm.clone(),
+            name(issuer),
+            der(0x30, &[time(not_before), time(not_after)].concat()),
+            name(subject),
+            subject_public_key_info,
+            der(0xa3, &der(0x30, &extensions.concat())),
+        ]
+        .concat(),
+    );
+
+    let signature = issuer_key.sign(&SystemRandom::new(), &tbs_certificate).expect("Failed to sign certificate");
+    let signature = [&[0x00], signature.as_ref()].concat();
+    der(0x30, &[tbs_certificate, signature_algorithm, der(0x03, &signature)].concat())
+}
+
+fn base64_json<T: Serialize>(value: &T) -> Result<String, SignerError> {
+    let json = serde_json::to_vec(value).map_err(|e| SignerError::SigningError(e.to_string()))?;
+    Ok(URL_SAFE_NO_PAD.encode(json))
+}
+
+/// The key ID in the header of the payloads that StoreKit Testing in Xcode signs.
+pub const XCODE_KEY_ID: &str = "Apple_Xcode_Key";
+
+/// A verifier in `Environment::Xcode` for the fixtures' bundle ID, which accepts the payloads of
+/// [`SignedDataCreator::xcode`].
+pub fn xcode_verifier() -> SignedDataVerifier {
+    SignedDataVerifier::new(vec![], Environment::Xcode, FIXTURE_BUNDLE_ID.to_string(), None)
+}
+
+/// A verifier that accepts the signed fixtures produced by this module.
+pub fn local_testing_verifier() -> SignedDataVerifier {
+    SignedDataVerifier::new(
+        vec![],
+        Environment::LocalTesting,
+        FIXTURE_BUNDLE_ID.to_string(),
+        Some(FIXTURE_APP_APPLE_ID),
+    )
+}
+
+/// Returns the raw JSON of the notification fixture for a notification type and subtype.
+///
+/// The `data` field carries a signed transaction and, for subscriptions, a signed renewal info whose
+/// contents match the event (status, expiration intent, revocation, and so on).
+pub fn notification_fixture_json(notification_type: &NotificationTypeV2, subtype: Option<&Subtype>) -> Value {
+    let index = NOTIFICATION_TYPE_SUBTYPE_COMBINATIONS
+        .iter()
+        .position(|(t, s)| t == notification_type && s.as_ref() == subtype)
+        .unwrap_or(NOTIFICATION_TYPE_SUBTYPE_COMBINATIONS.len());
+
+    let mut notification = json!({
+        "notificationType": notification_type,
+        "notificationUUID": Uuid::from_u128(0x002e14d5_51f5_4503_b5a8_000000000000 + index as u128).to_string(),
+        "version": "2.0",
+        "signedDate": SIGNED_DATE,
+    });
+
+    if let Some(subtype) = subtype {
+        notification["subtype"] = json!(subtype);
+    }
+
+    match notification_type {
+        NotificationTypeV2::ExternalPurchaseToken => {
+            notification["externalPurchaseToken"] = json!({
+                "externalPurchaseId": "b2158121-7af9-49d4-9561-1f588205523e",
+                "tokenCreationDate": SIGNED_DATE,
+                "appAppleId": FIXTURE_APP_APPLE_ID,
+                "bundleId": FIXTURE_BUNDLE_ID,
+            });
+        }
+        NotificationTypeV2::RenewalExtension if subtype == Some(&Subtype::Summary) => {
+            notification["summary"] = json!({
+                "environment": Environment::LocalTesting,
+                "appAppleId": FIXTURE_APP_APPLE_ID,
+                "bundleId": FIXTURE_BUNDLE_ID,
+                "productId": "com.example.product",
+                "requestIdentifier": "efb27071-45a4-4aca-9854-2a1e9146f265",
+                "storefrontCountryCodes": ["CAN", "USA", "MEX"],
+                "succeededCount": 5,
+                "failedCount": 2,
+            });
+        }
+        NotificationTypeV2::Test => {
+            notification["data"] = json!({
+                "environment": Environment::LocalTesting,
+                "appAppleId": FIXTURE_APP_APPLE_ID,
+                "bundleId": FIXTURE_BUNDLE_ID,
+            });
+        }
+        _ => notification["data"] = data_fixture_json(notification_type, subtype),
+    }
+
+    notification
+}
+
+fn transaction_fixture_json(notification_type: &NotificationTypeV2, subtype: Option<&Subtype>) -> Value {
+    let is_subscription = *notification_type != NotificationTypeV2::OneTimeCharge;
+
+    let mut transaction = json!({
+        "transactionId": "23456",
+        "originalTransactionId": "12345",
+        "bundleId": FIXTURE_BUNDLE_ID,
+        "productId": "com.example.product",
+        "purchaseDate": PURCHASE_DATE,
+        "originalPurchaseDate": PURCHASE_DATE,
+        "quantity": 1,
+        "inAppOwnershipType": "PURCHASED",
+        "signedDate": SIGNED_DATE,
+        "environment": Environment::LocalTesting,
+        "transactionReason": if *notification_type == NotificationTypeV2::DidRenew { "RENEWAL" } else { "PURCHASE" },
+        "storefront": "USA",
+        "storefrontId": "143441",
+        "price": 10990,
+        "currency": "USD",
+    });
+
+    if is_subscription {
+        transaction["webOrderLineItemId"] = json!("34343");
+        transaction["subscriptionGroupIdentifier"] = json!("55555");
+        transaction["expiresDate"] = json!(EXPIRES_DATE);
+        transaction["type"] = json!("Auto-Renewable Subscription");
+    } else {
+        transaction["type"] = json!("Consumable");
+    }
+
+    if matches!(notification_type, NotificationTypeV2::Refund | NotificationTypeV2::Revoke) {
+        transaction["revocationReason"] = json!(0);
+        transaction["revocationDate"] = json!(SIGNED_DATE);
+    }
+
+    if *notification_type == NotificationTypeV2::OfferRedeemed {
+        transaction["offerType"] = json!(2);
+        transaction["offerIdentifier"] = json!("abc.123");
+        transaction["offerDiscountType"] = json!("PAY_AS_YOU_GO");
+    }
+
+    if notification_type == &NotificationTypeV2::DidChangeRenewalPref && subtype == Some(&Subtype::Upgrade) {
+        transaction["isUpgraded"] = json!(true);
+    }
+
+    transaction
+}
+
+fn data_fixture_json(notification_type: &NotificationTypeV2, subtype: Option<&Subtype>) -> Value {
+    let is_subscription = *notification_type != NotificationTypeV2::OneTimeCharge;
+
+    let status = match (notification_type, subtype) {
+        (NotificationTypeV2::Expired, _) | (NotificationTypeV2::GracePeriodExpired, _) => 2,
+        (NotificationTypeV2::DidFailToRenew, Some(Subtype::GracePeriod)) => 4,
+        (NotificationTypeV2::DidFailToRenew, _) => 3,
+        (NotificationTypeV2::Revoke, _) | (NotificationTypeV2::Refund, _) => 5,
+        _ => 1,
+    };
+
+    let mut data = json!({
+        "environment": Environment::LocalTesting,
+        "appAppleId": FIXTURE_APP_APPLE_ID,
+        "bundleId": FIXTURE_BUNDLE_ID,
+        "bundleVersion": "1.2.3",
+        "signedTransactionInfo": sign_fixture(&transaction_fixture_json(notification_type, subtype)),
+    });
+
+    if *notification_type == NotificationTypeV2::ConsumptionRequest {
+        data["consumptionRequestReason"] = json!("UNINTENDED_PURCHASE");
+    }
+
+    if is_subscription {
+        data["status"] = json!(status);
+        data["signedRenewalInfo"] = sign_fixture(&renewal_info_fixture_json(notification_type, subtype)).into();
+    }
+
+    data
+}
+
+fn renewal_info_fixture_json(notification_type: &NotificationTypeV2, subtype: Option<&Subtype>) -> Value {
+    let auto_renew_off = matches!(
+        (notification_type, subtype),
+        (NotificationTypeV2::DidChangeRenewalStatus, Some(Subtype::AutoRenewDisabled)) | (NotificationTypeV2::Expired, _)
+    );
+
+    let mut renewal_info = json!({
+        "originalTransactionId": "12345",
+        "productId": "com.example.product",
+        "autoRenewProductId": "com.example.product",
+        "autoRenewStatus": if auto_renew_off { 0 } else { 1 },
+        "signedDate": SIGNED_DATE,
+        "environment": Environment::LocalTesting,
+        "recentSubscriptionStartDate": PURCHASE_DATE,
+        "renewalDate": EXPIRES_DATE,
+        "renewalPrice": 10990,
+        "currency": "USD",
+    });
+
+    if *notification_type == NotificationTypeV2::DidChangeRenewalPref && subtype.is_some() {
+        renewal_info["autoRenewProductId"] = json!("com.example.product.2");
+    }
+
+    let expiration_intent = match subtype {
+        Some(Subtype::Voluntary) => Some(1),
+        Some(Subtype::BillingRetry) => Some(2),
+        Some(Subtype::PriceIncrease) => Some(3),
+        Some(Subtype::ProductNotForSale) => Some(4),
+        _ => None,
+    };
+
+    if let Some(expiration_intent) = expiration_intent {
+        renewal_info["expirationIntent"] = json!(expiration_intent);
+    }
+
+    if *notification_type == NotificationTypeV2::DidFailToRenew {
+        renewal_info["isInBillingRetryPeriod"] = json!(true);
+
+        if subtype == Some(&Subtype::GracePeriod) {
+            renewal_info["gracePeriodExpiresDate"] = json!(EXPIRES_DATE + 16 * 24 * 60 * 60 * 1000);
+        }
+    }
+
+    if *notification_type == NotificationTypeV2::PriceIncrease {
+        renewal_info["priceIncreaseStatus"] = json!(if subtype == Some(&Subtype::Accepted) { 1 } else { 0 });
+        renewal_info["renewalPrice"] = json!(12990);
+    }
+
+    renewal_info
+}
+
+/// Returns the decoded notification fixture for a notification type and subtype.
+pub fn notification_fixture(notification_type: &NotificationTypeV2, subtype: Option<&Subtype>) -> ResponseBodyV2DecodedPayload {
+    serde_json::from_value(notification_fixture_json(notification_type, subtype)).expect("Failed to decode fixture")
+}
+
+/// Returns the notification fixture for a notification type and subtype as a signed payload,
+/// as it would arrive in the `signedPayload` field of a notification request.
+pub fn signed_notification_fixture(notification_type: &NotificationTypeV2, subtype: Option<&Subtype>) -> String {
+    sign_fixture(&notification_fixture_json(notification_type, subtype))
+}
+
+/// Returns a signed transaction as it appears in a notification of the type, with its transaction ID replaced,
+/// as it would arrive in the `signedTransactions` of an App Store Server API response.
+pub fn signed_transaction_fixture(notification_type: &NotificationTypeV2, transaction_id: &str) -> String {
+    let mut transaction = transaction_fixture_json(notification_type, None);
+    transaction["transactionId"] = json!(transaction_id);
+    sign_fixture(&transaction)
+}
+
+/// Returns a decoded transaction with only the given fields set, such as
+/// `json!({ "transactionId": "1", "purchaseDate": 1698148800000 })`.
+pub fn transaction_with_fields(fields: Value) -> JWSTransactionDecodedPayload {
+    serde_json::from_value(fields).expect("Failed to decode fixture")
+}
+
+/// Returns the decoded fixtures for every documented notification type and subtype combination.
+pub fn all_notification_fixtures() -> Vec<ResponseBodyV2DecodedPayload> {
+    NOTIFICATION_TYPE_SUBTYPE_COMBINATIONS
+        .iter()
+        .map(|(t, s)| notification_fixture(t, s.as_ref()))
+        .collect()
+}
+
+/// A notification fixture of the corpus returned by [`notification_corpus`].
+#[derive(Debug, Clone)]
+pub struct NotificationFixture {
+    pub notification_type: NotificationTypeV2,
+    pub subtype: Option<Subtype>,
+    /// The decoded notification, whose signed transaction and renewal info are those of `signed_payload`.
+    pub notification: ResponseBodyV2DecodedPayload,
+    /// The signed payload, as it would arrive in the `signedPayload` field of a notification request.
+    pub signed_payload: String,
+}
+
+/// Returns the fixtures for every documented notification type and subtype combination in `environment`,
+/// with the notifications and their transactions and renewal infos signed by `creator`.
+///
+/// With the creator and verifier of a [`TestCertificateAuthority`], the corpus covers the routing of
+/// notifications in `Environment::Sandbox` or `Environment::Production` against a verifier that checks
+/// signatures, rather than one in `Environment::LocalTesting`.
+pub fn notification_corpus(creator: &SignedDataCreator, environment: Environment) -> Vec<NotificationFixture> {
+    NOTIFICATION_TYPE_SUBTYPE_COMBINATIONS
+        .iter()
+        .map(|(notification_type, subtype)| {
+            let mut notification = notification_fixture_json(notification_type, subtype.as_ref());
+            resign_fixture(&mut notification, creator, &environment);
+            NotificationFixture {
+                notification_type: notification_type.clone(),
+                subtype: subtype.clone(),
+                signed_payload: creator.sign(&notification).expect("Failed to encode JWS"),
+                notification: serde_json::from_value(notification).expect("Failed to decode fixture"),
+            }
+        })
+        .collect()
+}
+
+/// Replaces the environment of a fixture and signs its signed fields again with `creator`.
+fn resign_fixture(value: &mut Value, creator: &SignedDataCreator, environment: &Environment) {
+    match value {
+        Value::Object(object) => {
+            for (name, value) in object.iter_mut() {
+                match (name.as_str(), value) {
+                    ("environment", value) => *value = json!(environment),
+                    // The environment of an external purchase token is derived from the prefix of its ID.
+                    ("externalPurchaseId", Value::String(id)) if *environment == Environment::Sandbox => {
+                        id.insert_str(0, ExternalPurchaseToken::SANDBOX_PREFIX);
+                    }
+                    ("signedTransactionInfo" | "signedRenewalInfo", Value::String(signed)) => {
+                        let claims = signed.split('.').nth(1).expect("Malformed fixture");
+                        let mut claims: Value = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(claims).expect("Malformed fixture"))
+                            .expect("Malformed fixture");
+                        resign_fixture(&mut claims, creator, environment);
+                        *signed = creator.sign(&claims).expect("Failed to encode JWS");
+                    }
+                    (_, value) => resign_fixture(value, creator, environment),
+                }
+            }
+        }
+        Value::Array(array) => array.iter_mut().for_each(|value| resign_fixture(value, creator, environment)),
+        _ => {}
+    }
+}
+
+/// Runs `handler` against every documented notification type and subtype combination.
+///
+/// # Panics
+///
+/// Panics listing every combination for which `handler` returned an error.
+pub fn assert_handles_every_notification<F, E>(mut handler: F)
+where
+    F: FnMut(&ResponseBodyV2DecodedPayload) -> Result<(), E>,
+    E: Debug,
+{
+    let failures: Vec<String> = NOTIFICATION_TYPE_SUBTYPE_COMBINATIONS
+        .iter()
+        .filter_map(|(t, s)| {
+            handler(&notification_fixture(t, s.as_ref()))
+                .err()
+                .map(|e| format!("{:?}/{:?}: {:?}", t, s, e))
+        })
+        .collect();
+
+    assert!(failures.is_empty(), "Unhandled notifications:\n{}", failures.join("\n"));
+}
+
+/// Runs `handler` against the signed payload of every documented notification type and subtype combination.
+///
+/// Decode the payloads with [`local_testing_verifier`].
+///
+/// # Panics
+///
+/// Panics listing every combination for which `handler` returned an error.
+pub fn assert_handles_every_signed_notification<F, E>(mut handler: F)
+where
+    F: FnMut(&str) -> Result<(), E>,
+    E: Debug,
+{
+    let failures: Vec<String> = NOTIFICATION_TYPE_SUBTYPE_COMBINATIONS
+        .iter()
+        .filter_map(|(t, s)| {
+            handler(&signed_notification_fixture(t, s.as_ref()))
+                .err()
+                .map(|e| format!("{:?}/{:?}: {:?}", t, s, e))
+        })
+        .collect();
+
+    assert!(failures.is_empty(), "Unhandled notifications:\n{}", failures.join("\n"));
+}
+
+/// Derives the uncompressed public key used by the verification helpers from a private key,
+/// in any format accepted by [`EcdsaSigner::from_key`].
+pub fn public_key_from_private_key(private_key: &[u8]) -> Vec<u8> {
+    EcdsaSigner::from_key(private_key)
+        .expect("Failed to read private key")
+        .public_key()
+        .expect("Missing public key")
+}
+
+/// Checks a signature created by [`PromotionalOfferSignatureCreator`](crate::promotional_offer_signature_creator::PromotionalOfferSignatureCreator)
+/// against the public key and the values that were signed.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_promotional_offer_signature(
+    public_key: &[u8],
+    signature: &str,
+    bundle_id: &str,
+    key_id: &str,
+    product_identifier: &str,
+    subscription_offer_id: &str,
+    application_username: &str,
+    nonce: &Uuid,
+    timestamp: i64,
+) -> bool {
+    let Ok(signature) = BASE64_STANDARD.decode(signature) else {
+        return false;
+    };
+
+    let payload = promotional_offer_payload(
+        bundle_id,
+        key_id,
+        product_identifier,
+        subscription_offer_id,
+        application_username,
+        nonce,
+        timestamp,
+    );
+
+    UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, public_key)
+        .verify(payload.as_bytes(), &signature)
+        .is_ok()
+}
+
+/// Checks a JWS created by one of the JWS signature creators, such as
+/// [`AdvancedCommerceInAppSignatureCreator`](crate::jws_signature_creator::AdvancedCommerceInAppSignatureCreator),
+/// against the public key and the expected audience, and returns its claims.
+pub fn verify_jws_signature(
+    public_key: &[u8],
+    token: &str,
+    audience: &str,
+) -> Result<Map<String, Value>, jsonwebtoken::errors::Error> {
+    let mut validation = Validation::new(Algorithm::ES256);
+    validation.set_audience(&[audience]);
+    validation.set_required_spec_claims(&["aud", "iss"]);
+
+    let decoded = jsonwebtoken::decode::<Map<String, Value>>(token, &DecodingKey::from_ec_der(public_key), &validation)?;
+    Ok(decoded.claims)
+}
+
+/// Checks a request sent through a [`MockTransport`], along with its body.
+#[cfg(feature = "api-client")]
+pub type RequestVerifier = dyn Fn(&reqwest::Request, Option<&[u8]>) + Send + Sync;
+
+/// A transport that answers the requests of an
+/// [`AppStoreServerAPIClient`](crate::api_client::AppStoreServerAPIClient) with queued responses
+/// instead of sending them to Apple, so that code wrapping the client can be unit-tested.
+///
+/// Install it with [`AppStoreServerAPIClient::with_transport`](crate::api_client::AppStoreServerAPIClient::with_transport).
+/// Each request takes the next queued response, and panics once there is none left.
+#[cfg(feature = "api-client")]
+pub struct MockTransport {
+    responses: std::sync::Mutex<std::collections::VecDeque<(u16, Vec<u8>)>>,
+    verifier: Option<Box<RequestVerifier>>,
+}
+
+#[cfg(feature = "api-client")]
+impl Default for MockTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "api-client")]
+impl MockTransport {
+    pub fn new() -> Self {
+        MockTransport { responses: Default::default(), verifier: None }
+    }
+
+    /// Queues a response with the given status code and JSON body.
+    pub fn respond_with(self, status_code: u16, body: impl Into<Vec<u8>>) -> Self {
+        self.responses.lock().unwrap().push_back((status_code, body.into()));
+        self
+    }
+
+    /// Calls `verifier` with every request and its body before answering it.
+    pub fn verify_requests<F>(mut self, verifier: F) -> Self
+    where
+        F: Fn(&reqwest::Request, Option<&[u8]>) + Send + Sync + 'static,
+    {
+        self.verifier = Some(Box::new(verifier));
+        self
+    }
+
+    /// Answers a request with the next queued response.
+    pub fn respond(&self, request: &reqwest::Request) -> reqwest::Response {
+        if let Some(verifier) = &self.verifier {
+            verifier(request, request.body().and_then(|body| body.as_bytes()));
+        }
+
+        let (status_code, body) = self
+            .responses
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or_else(|| panic!("No response queued for {} {}", request.method(), request.url()));
+
+        json_response(request.url(), status_code, Vec::new(), body)
+    }
+}
+
+#[cfg(feature = "api-client")]
+impl Transport for MockTransport {
+    fn send<'a>(&'a self, _client: &'a reqwest::Client, request: reqwest::Request) -> TransportFuture<'a> {
+        let response = self.respond(&request);
+        Box::pin(async move { Ok(response) })
+    }
+}
+
+#[cfg(feature = "api-client")]
+pub(crate) fn json_response(url: &reqwest::Url, status_code: u16, headers: Vec<(String, String)>, body: Vec<u8>) -> reqwest::Response {
+    use reqwest::ResponseBuilderExt;
+
+    let mut builder = http::Response::builder().status(status_code).url(url.clone());
+    if !headers.iter().any(|(name, _)| name == "content-type") {
+        builder = builder.header(reqwest::header::CONTENT_TYPE, "application/json");
+    }
+    for (name, value) in headers {
+        builder = builder.header(name, value);
+    }
+    reqwest::Response::from(builder.body(body).expect("Failed to build the response"))
+}
+
+/// A response recorded by a [`RecordingTransport`].
+#[cfg(feature = "api-client")]
+#[derive(Serialize, serde::Deserialize)]
+struct Recording {
+    request: String,
+    status_code: u16,
+    headers: std::collections::BTreeMap<String, String>,
+    body: Value,
+}
+
+/// Whether a [`RecordingTransport`] sends requests and records the responses, or replays recorded responses.
+#[cfg(feature = "api-client")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordingMode {
+    Record,
+    Replay,
+}
+
+/// A transport that records the responses of the App Store Server API to a directory, and replays
+/// them in later runs, so that integration tests only need sandbox credentials when recording.
+///
+/// Requests are identified by their method, path and query, and identical requests replay their
+/// recordings in order. Request headers, including the authorization token, are never recorded, and
+/// only the [`SELECTED_HEADERS`](crate::api_client::APIException::SELECTED_HEADERS) of responses are.
+/// The values of the fields registered with [`redact_field`](Self::redact_field) are replaced
+/// anywhere in the recorded JSON bodies.
+#[cfg(feature = "api-client")]
+pub struct RecordingTransport {
+    directory: std::path::PathBuf,
+    mode: RecordingMode,
+    inner: Option<Box<dyn Transport>>,
+    redacted_fields: Vec<String>,
+    counters: std::sync::Mutex<std::collections::HashMap<String, usize>>,
+}
+
+#[cfg(feature = "api-client")]
+impl RecordingTransport {
+    /// The value that replaces redacted fields.
+    pub const REDACTED: &'static str = "<redacted>";
+
+    pub fn new(directory: impl Into<std::path::PathBuf>, mode: RecordingMode) -> Self {
+        RecordingTransport {
+            directory: directory.into(),
+            mode,
+            inner: None,
+            redacted_fields: vec![],
+            counters: Default::default(),
+        }
+    }
+
+    /// Records when the environment variable `variable` is set, and replays otherwise.
+    pub fn from_env(directory: impl Into<std::path::PathBuf>, variable: &str) -> Self {
+        let mode = match std::env::var_os(variable) {
+            Some(_) => RecordingMode::Record,
+            None => RecordingMode::Replay,
+        };
+        Self::new(directory, mode)
+    }
+
+    /// Sends the recorded requests through `inner` rather than the HTTP client of the API client.
+    pub fn through(mut self, inner: impl Transport + 'static) -> Self {
+        self.inner = Some(Box::new(inner));
+        self
+    }
+
+    /// Replaces the value of `field` in recorded JSON bodies.
+    pub fn redact_field(mut self, field: &str) -> Self {
+        self.redacted_fields.push(field.to_string());
+        self
+    }
+
+    fn recording_path(&self, request: &reqwest::Request) -> (String, std::path::PathBuf) {
+        let url = request.url();
+        let key = match url.query() {
+            Some(query) => format!("{} {}?{}", request.method(), url.path(), query),
+            None => format!("{} {}", request.method(), url.path()),
+        };
+
+        let mut counters = self.counters.lock().unwrap();
+        let counter = counters.entry(key.clone()).or_insert(0);
+        let digest = ring::digest::digest(&ring::digest::SHA256, key.as_bytes());
+        let fingerprint: String = digest.as_ref()[..8].iter().map(|b| format!("{:02x}", b)).collect();
+        let file_name = format!("{}-{}-{}.json", request.method().as_str().to_lowercase(), fingerprint, counter);
+        *counter += 1;
+
+        (key, self.directory.join(file_name))
+    }
+
+    fn redact(&self, value: &mut Value) {
+        match value {
+            Value::Object(object) => {
+                for (name, value) in object.iter_mut() {
+                    if self.redacted_fields.iter().any(|field| field == name) {
+                        *value = Value::String(Self::REDACTED.to_string());
+                    } else {
+                        self.redact(value);
+                    }
+                }
+            }
+            Value::Array(array) => array.iter_mut().for_each(|value| self.redact(value)),
+            _ => {}
+        }
+    }
+
+    async fn record(&self, client: &reqwest::Client, request: reqwest::Request) -> Result<reqwest::Response, TransportError> {
+        let (key, path) = self.recording_path(&request);
+        let url = request.url().clone();
+        let response = match &self.inner {
+            Some(inner) => inner.send(client, request).await?,
+            None => client.execute(request).await?,
+        };
+
+        let status_code = response.status().as_u16();
+        let headers: std::collections::BTreeMap<String, String> = crate::api_client::APIException::SELECTED_HEADERS
+            .iter()
+            .filter_map(|name| Some((name.to_string(), response.headers().get(*name)?.to_str().ok()?.to_string())))
+            .collect();
+        let body = response.bytes().await?;
+
+        let mut recorded_body = match serde_json::from_slice(&body) {
+            Ok(json) => json,
+            Err(_) => Value::String(String::from_utf8_lossy(&body).into_owned()),
+        };
+        self.redact(&mut recorded_body);
+        let recording = Recording { request: key, status_code, headers: headers.clone(), body: recorded_body };
+        std::fs::create_dir_all(&self.directory)?;
+        std::fs::write(path, serde_json::to_vec_pretty(&recording)?)?;
+
+        Ok(json_response(&url, status_code, headers.into_iter().collect(), body.to_vec()))
+    }
+
+    fn replay(&self, request: &reqwest::Request) -> Result<reqwest::Response, TransportError> {
+        let (key, path) = self.recording_path(request);
+        let recording = std::fs::read(&path).map_err(|e| format!("No recording of {} at {}: {}", key, path.display(), e))?;
+        let recording: Recording = serde_json::from_slice(&recording)?;
+
+        let body = match recording.body {
+            Value::String(body) => body.into_bytes(),
+            body => serde_json::to_vec(&body)?,
+        };
+        Ok(json_response(request.url(), recording.status_code, recording.headers.into_iter().collect(), body))
+    }
+}
+
+#[cfg(feature = "api-client")]
+impl Transport for RecordingTransport {
+    fn send<'a>(&'a self, client: &'a reqwest::Client, request: reqwest::Request) -> TransportFuture<'a> {
+        Box::pin(async move {
+            match self.mode {
+                RecordingMode::Record => self.record(client, request).await,
+                RecordingMode::Replay => self.replay(&request),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::status::Status;
+
+    // Fails to compile when a notification type is added, as a reminder to extend the corpus and `all_types` below.
+    fn is_covered(notification_type: &NotificationTypeV2) -> bool {
+        match notification_type {
+            NotificationTypeV2::Subscribed
+            | NotificationTypeV2::DidChangeRenewalPref
+            | NotificationTypeV2::DidChangeRenewalStatus
+            | NotificationTypeV2::OfferRedeemed
+            | NotificationTypeV2::DidRenew
+            | NotificationTypeV2::Expired
+            | NotificationTypeV2::DidFailToRenew
+            | NotificationTypeV2::GracePeriodExpired
+            | NotificationTypeV2::PriceIncrease
+            | NotificationTypeV2::Refund
+            | NotificationTypeV2::RefundDeclined
+            | NotificationTypeV2::ConsumptionRequest
+            | NotificationTypeV2::RenewalExtended
+            | NotificationTypeV2::Revoke
+            | NotificationTypeV2::Test
+            | NotificationTypeV2::RenewalExtension
+            | NotificationTypeV2::RefundReversed
+            | NotificationTypeV2::ExternalPurchaseToken
+            | NotificationTypeV2::OneTimeCharge => NOTIFICATION_TYPE_SUBTYPE_COMBINATIONS
+                .iter()
+                .any(|(t, _)| t == notification_type),
+            NotificationTypeV2::Unknown(_) => true,
+        }
+    }
+
+    #[test]
+    fn test_corpus_covers_every_notification_type() {
+        let all_types = [
+            NotificationTypeV2::Subscribed,
+            NotificationTypeV2::DidChangeRenewalPref,
+            NotificationTypeV2::DidChangeRenewalStatus,
+            NotificationTypeV2::OfferRedeemed,
+            NotificationTypeV2::DidRenew,
+            NotificationTypeV2::Expired,
+            NotificationTypeV2::DidFailToRenew,
+            NotificationTypeV2::GracePeriodExpired,
+            NotificationTypeV2::PriceIncrease,
+            NotificationTypeV2::Refund,
+            NotificationTypeV2::RefundDeclined,
+            NotificationTypeV2::ConsumptionRequest,
+            NotificationTypeV2::RenewalExtended,
+            NotificationTypeV2::Revoke,
+            NotificationTypeV2::Test,
+            NotificationTypeV2::RenewalExtension,
+            NotificationTypeV2::RefundReversed,
+            NotificationTypeV2::ExternalPurchaseToken,
+            NotificationTypeV2::OneTimeCharge,
+        ];
+
+        for notification_type in &all_types {
+            assert!(is_covered(notification_type), "{:?} is missing from the corpus", notification_type);
+        }
+        assert_eq!(32, all_notification_fixtures().len());
+    }
+
+    #[cfg(feature = "api-client")]
+    #[tokio::test]
+    async fn test_mock_transport() {
+        let transport = MockTransport::new()
+            .respond_with(200, r#"{"signedTransactionInfo": "signed"}"#)
+            .verify_requests(|request, body| {
+                assert_eq!("/inApps/v1/transactions/1234", request.url().path());
+                assert_eq!(None, body);
+            });
+
+        let client = reqwest::Client::new();
+        let request = client.get("https://example.com/inApps/v1/transactions/1234").build().unwrap();
+        let response = transport.send(&client, request).await.unwrap();
+        assert_eq!(200, response.status().as_u16());
+        assert_eq!("https://example.com/inApps/v1/transactions/1234", response.url().as_str());
+        assert_eq!(r#"{"signedTransactionInfo": "signed"}"#, response.text().await.unwrap());
+    }
+
+    #[test]
+    fn test_signed_data_creator() {
+        let transaction = JWSTransactionDecodedPayload {
+            transaction_id: Some("1000".into()),
+            bundle_id: Some(FIXTURE_BUNDLE_ID.to_string()),
+            environment: Some(Environment::LocalTesting),
+            ..Default::default()
+        };
+        let signed_transaction = SignedDataCreator::local_testing().sign_transaction(&transaction).unwrap();
+        assert_eq!(transaction, local_testing_verifier().verify_and_decode_signed_transaction(&signed_transaction).unwrap());
+
+        let certificate = vec![1, 2, 3];
+        let creator = SignedDataCreator::new(include_bytes!("../assets/testSigningKey.p8"), &[certificate]).unwrap();
+        let header = jsonwebtoken::decode_header(&creator.sign(&json!({})).unwrap()).unwrap();
+        assert_eq!(Algorithm::ES256, header.alg);
+        assert_eq!(Some(vec!["AQID".to_string()]), header.x5c);
+    }
+
+    #[test]
+    fn test_xcode_signed_data_creator() {
+        let transaction = JWSTransactionDecodedPayload {
+            transaction_id: Some("1000".into()),
+            bundle_id: Some(FIXTURE_BUNDLE_ID.to_string()),
+            environment: Some(Environment::Xcode),
+            ..Default::default()
+        };
+        let signed_transaction = SignedDataCreator::xcode().sign_transaction(&transaction).unwrap();
+        assert_eq!(transaction, xcode_verifier().verify_and_decode_signed_transaction(&signed_transaction).unwrap());
+
+        let header_keys = |signed: &str| {
+            let header = URL_SAFE_NO_PAD.decode(signed.split('.').next().unwrap()).unwrap();
+            let mut keys: Vec<String> = serde_json::from_slice::<Map<String, Value>>(&header).unwrap().keys().cloned().collect();
+            keys.sort();
+            keys
+        };
+        let fixture = std::fs::read_to_string("assets/xcode-signed-transaction").unwrap();
+        assert_eq!(header_keys(&fixture), header_keys(&signed_transaction));
+        let header = jsonwebtoken::decode_header(&signed_transaction).unwrap();
+        assert_eq!(Some(XCODE_KEY_ID.to_string()), header.kid);
+        assert_eq!(1, header.x5c.unwrap().len());
+    }
+
+    #[test]
+    fn test_notification_corpus() {
+        let authority = TestCertificateAuthority::generate();
+        let verifier = authority.verifier(Environment::Sandbox);
+        let corpus = notification_corpus(&authority.signed_data_creator(), Environment::Sandbox);
+        assert_eq!(NOTIFICATION_TYPE_SUBTYPE_COMBINATIONS.len(), corpus.len());
+
+        for fixture in corpus {
+            let decoded = verifier.verify_and_decode_notification_full(&fixture.signed_payload).unwrap();
+            assert_eq!(fixture.notification, decoded.payload);
+            assert_eq!(fixture.notification_type, decoded.payload.notification_type);
+            if let Some(transaction) = decoded.transaction_info {
+                assert_eq!(Some(Environment::Sandbox), transaction.environment);
+            }
+        }
+    }
+
+    #[test]
+    fn test_certificate_authority() {
+        let authority = TestCertificateAuthority::generate();
+        let transaction = JWSTransactionDecodedPayload {
+            transaction_id: Some("1000".into()),
+            bundle_id: Some(FIXTURE_BUNDLE_ID.to_string()),
+            environment: Some(Environment::Sandbox),
+            ..Default::default()
+        };
+        let signed_transaction = authority.signed_data_creator().sign_transaction(&transaction).unwrap();
+
+        let verifier = authority.verifier(Environment::Sandbox);
+        assert_eq!(transaction, verifier.verify_and_decode_signed_transaction(&signed_transaction).unwrap());
+
+        let other_authority = TestCertificateAuthority::generate();
+        assert!(other_authority.verifier(Environment::Sandbox).verify_and_decode_signed_transaction(&signed_transaction).is_err());
+    }
+
+    #[cfg(feature = "api-client")]
+    #[tokio::test]
+    async fn test_recording_transport() {
+        use crate::api_client::AppStoreServerAPIClient;
+        use crate::primitives::transaction_id::TransactionId;
+
+        let directory = std::env::temp_dir().join(format!("recordings-{}", Uuid::new_v4()));
+        let client = |transport: RecordingTransport| {
+            let key = std::fs::read("assets/testSigningKey.p8").unwrap();
+            AppStoreServerAPIClient::new(key, "keyId", "issuerId", FIXTURE_BUNDLE_ID, Environment::LocalTesting, Box::new(|_, _| unreachable!()))
+                .with_transport(transport)
+        };
+
+        let body = r#"{"signedTransactionInfo": "signed", "appAccountToken": "secret"}"#;
+        let recorder = RecordingTransport::new(&directory, RecordingMode::Record)
+            .through(MockTransport::new().respond_with(200, body))
+            .redact_field("appAccountToken");
+        let recorded = client(recorder).get_transaction_info(&TransactionId::from("1234")).await.unwrap();
+        assert_eq!(Some("signed".to_string()), recorded.signed_transaction_info);
+
+        let recordings: Vec<_> = std::fs::read_dir(&directory).unwrap().map(|entry| entry.unwrap().path()).collect();
+        assert_eq!(1, recordings.len());
+        let recording = std::fs::read_to_string(&recordings[0]).unwrap();
+        assert!(recording.contains(RecordingTransport::REDACTED) && !recording.contains("secret"));
+
+        let replaying = client(RecordingTransport::new(&directory, RecordingMode::Replay));
+        let replayed = replaying.get_transaction_info(&TransactionId::from("1234")).await.unwrap();
+        assert_eq!(recorded, replayed);
+        assert!(replaying.get_transaction_info(&TransactionId::from("1234")).await.is_err());
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_signed_fixtures_verify() {
+        let verifier = local_testing_verifier();
+
+        assert_handles_every_signed_notification(|payload| {
+            let notification = verifier.verify_and_decode_notification(payload)?;
+
+            if let Some(data) = notification.data {
+                if let Some(signed_transaction_info) = data.signed_transaction_info {
+                    verifier.verify_and_decode_signed_transaction(&signed_transaction_info)?;
+                }
+                if let Some(signed_renewal_info) = data.signed_renewal_info {
+                    verifier.verify_and_decode_renewal_info(&signed_renewal_info)?;
+                }
+            }
+
+            Ok::<(), crate::signed_data_verifier::SignedDataVerifierError>(())
+        });
+    }
+
+    #[test]
+    fn test_fixture_contents() {
+        let expired = notification_fixture(&NotificationTypeV2::Expired, Some(&Subtype::Voluntary));
+        assert_eq!(Some(Status::Expired), expired.data.unwrap().status);
+
+        let summary = notification_fixture(&NotificationTypeV2::RenewalExtension, Some(&Subtype::Summary));
+        assert!(summary.summary.is_some());
+        assert!(summary.data.is_none());
+
+        let token = notification_fixture(&NotificationTypeV2::ExternalPurchaseToken, Some(&Subtype::Unreported));
+        assert!(token.external_purchase_token.is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "Unhandled notifications")]
+    fn test_harness_reports_unhandled_notifications() {
+        assert_handles_every_notification(|notification| match notification.notification_type {
+            NotificationTypeV2::Refund => Err("not handled"),
+            _ => Ok(()),
+        });
+    }
+
+    #[test]
+    fn test_verify_created_signatures() {
+        use crate::jws_signature_creator::AdvancedCommerceInAppSignatureCreator;
+        use crate::primitives::advanced_commerce::advanced_commerce_in_app_request::AdvancedCommerceInAppRequest;
+        use crate::promotional_offer_signature_creator::PromotionalOfferSignatureCreator;
+
+        #[derive(serde::Serialize)]
+        struct Request {}
+        impl AdvancedCommerceInAppRequest for Request {}
+
+        let private_key = include_str!("../assets/testSigningKey.p8");
+        let public_key = public_key_from_private_key(private_key.as_bytes());
+        let nonce = Uuid::new_v4();
+
+        let creator = PromotionalOfferSignatureCreator::new(private_key, "keyId".to_string(), FIXTURE_BUNDLE_ID.to_string()).unwrap();
+        let signature = creator.create_signature("com.example.product", "com.example.offer", "User", &nonce, 1000).unwrap();
+        assert!(verify_promotional_offer_signature(&public_key, &signature, FIXTURE_BUNDLE_ID, "keyId", "com.example.product", "com.example.offer", "user", &nonce, 1000));
+        assert!(!verify_promotional_offer_signature(&public_key, &signature, FIXTURE_BUNDLE_ID, "keyId", "com.example.product", "com.example.offer", "user", &nonce, 1001));
+
+        let creator = AdvancedCommerceInAppSignatureCreator::new(private_key, "keyId".to_string(), "issuerId".to_string(), FIXTURE_BUNDLE_ID.to_string()).unwrap();
+        let token = creator.create_signature(&Request {}).unwrap();
+        let claims = verify_jws_signature(&public_key, &token, "advanced-commerce-api").unwrap();
+        assert_eq!(Some(FIXTURE_BUNDLE_ID), claims["bid"].as_str());
+        assert!(verify_jws_signature(&public_key, &token, "appstoreconnect-v1").is_err());
+    }
+}
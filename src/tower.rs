@@ -0,0 +1,166 @@
+use crate::primitives::decoded_notification::DecodedNotification;
+use crate::signed_data_verifier::SignedDataVerifier;
+use crate::webhook::{parse_signed_payload_with_limit, WebhookBodyError, DEFAULT_MAX_BODY_SIZE};
+use bytes::Bytes;
+use http::{Request, Response, StatusCode};
+use http_body::Body;
+use http_body_util::{BodyExt, Full, LengthLimitError, Limited};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// A layer that verifies and decodes the App Store Server Notification in the body of each request
+/// before the inner service runs.
+///
+/// The inner service receives the request with its buffered body and the [`DecodedNotification`],
+/// along with its verified transaction and renewal info, as a request extension. Requests whose body
+/// is malformed are answered with `400 Bad Request`, those larger than the body limit with
+/// `413 Payload Too Large`, and those that fail verification with `403 Forbidden`, without calling
+/// the inner service.
+///
+/// ```ignore
+/// let app = Router::new()
+///     .route("/notifications", post(|Extension(notification): Extension<DecodedNotification>| async { /* ... */ }))
+///     .layer(VerifiedNotificationLayer::new(verifier));
+/// ```
+#[derive(Clone)]
+pub struct VerifiedNotificationLayer {
+    verifier: SignedDataVerifier,
+    max_body_size: usize,
+}
+
+impl VerifiedNotificationLayer {
+    /// Creates a layer that verifies notifications with `verifier`, limiting bodies to
+    /// [`DEFAULT_MAX_BODY_SIZE`](crate::webhook::DEFAULT_MAX_BODY_SIZE).
+    pub fn new(verifier: SignedDataVerifier) -> Self {
+        VerifiedNotificationLayer { verifier, max_body_size: DEFAULT_MAX_BODY_SIZE }
+    }
+
+    /// Sets the maximum accepted size of a request body, in bytes.
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+}
+
+impl<S> Layer<S> for VerifiedNotificationLayer {
+    type Service = VerifiedNotificationService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        VerifiedNotificationService { inner, verifier: self.verifier.clone(), max_body_size: self.max_body_size }
+    }
+}
+
+/// The service created by [`VerifiedNotificationLayer`].
+#[derive(Clone)]
+pub struct VerifiedNotificationService<S> {
+    inner: S,
+    verifier: SignedDataVerifier,
+    max_body_size: usize,
+}
+
+impl<S, B, ResBody> Service<Request<B>> for VerifiedNotificationService<S>
+where
+    S: Service<Request<Full<Bytes>>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    ResBody: Default,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        // The service that was polled ready handles the request, and a clone takes its place.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let verifier = self.verifier.clone();
+        let max_body_size = self.max_body_size;
+
+        Box::pin(async move {
+            let (parts, body) = request.into_parts();
+            let body = match Limited::new(body, max_body_size).collect().await {
+                Ok(body) => body.to_bytes(),
+                Err(error) if error.is::<LengthLimitError>() => return Ok(rejection(StatusCode::PAYLOAD_TOO_LARGE)),
+                Err(_) => return Ok(rejection(StatusCode::BAD_REQUEST)),
+            };
+
+            let signed_payload = match parse_signed_payload_with_limit(&body, max_body_size) {
+                Ok(signed_payload) => signed_payload,
+                Err(WebhookBodyError::BodyTooLargeError { .. }) => return Ok(rejection(StatusCode::PAYLOAD_TOO_LARGE)),
+                Err(_) => return Ok(rejection(StatusCode::BAD_REQUEST)),
+            };
+            let notification: DecodedNotification = match verifier.verify_and_decode_notification_full(&signed_payload) {
+                Ok(notification) => notification,
+                Err(_) => return Ok(rejection(StatusCode::FORBIDDEN)),
+            };
+
+            let mut request = Request::from_parts(parts, Full::new(body));
+            request.extensions_mut().insert(notification);
+            inner.call(request).await
+        })
+    }
+}
+
+fn rejection<ResBody: Default>(status_code: StatusCode) -> Response<ResBody> {
+    let mut response = Response::new(ResBody::default());
+    *response.status_mut() = status_code;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::notification_type_v2::NotificationTypeV2;
+    use crate::test_utils::{local_testing_verifier, signed_notification_fixture};
+    use serde_json::json;
+    use std::convert::Infallible;
+
+    #[derive(Clone)]
+    struct NotificationType;
+
+    impl Service<Request<Full<Bytes>>> for NotificationType {
+        type Response = Response<String>;
+        type Error = Infallible;
+        type Future = std::future::Ready<Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: Request<Full<Bytes>>) -> Self::Future {
+            let notification = request.extensions().get::<DecodedNotification>().unwrap();
+            std::future::ready(Ok(Response::new(format!("{:?}", notification.notification_type()))))
+        }
+    }
+
+    async fn call(service: &mut VerifiedNotificationService<NotificationType>, body: String) -> Response<String> {
+        std::future::poll_fn(|cx| Service::<Request<Full<Bytes>>>::poll_ready(service, cx)).await.unwrap();
+        service.call(Request::new(Full::new(Bytes::from(body)))).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_verified_notification_layer() {
+        let mut service = VerifiedNotificationLayer::new(local_testing_verifier()).layer(NotificationType);
+
+        let signed_payload = signed_notification_fixture(&NotificationTypeV2::Test, None);
+        let response = call(&mut service, json!({ "signedPayload": signed_payload }).to_string()).await;
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!("Test", response.body());
+
+        let response = call(&mut service, json!({ "signedPayload": "invalid" }).to_string()).await;
+        assert_eq!(StatusCode::FORBIDDEN, response.status());
+        assert_eq!(StatusCode::BAD_REQUEST, call(&mut service, "{".to_string()).await.status());
+
+        let mut service = VerifiedNotificationLayer::new(local_testing_verifier()).max_body_size(8).layer(NotificationType);
+        assert_eq!(StatusCode::PAYLOAD_TOO_LARGE, call(&mut service, json!({ "signedPayload": "x" }).to_string()).await.status());
+    }
+}
@@ -1,4 +1,4 @@
-use base64::engine::general_purpose::STANDARD;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
 use base64::{DecodeError, Engine};
 use std::time::SystemTime;
 
@@ -21,6 +21,29 @@ pub(crate) fn system_timestamp() -> u64 {
     }
 }
 
+/// Decodes padded base64 in the standard alphabet.
+///
+/// With the `performance` feature, input is decoded with `base64-simd`, and only input it rejects
+/// is decoded again to report the error.
+pub(crate) fn decode_base64(encoded: impl AsRef<[u8]>) -> Result<Vec<u8>, DecodeError> {
+    #[cfg(feature = "performance")]
+    if let Ok(decoded) = base64_simd::STANDARD.decode_to_vec(encoded.as_ref()) {
+        return Ok(decoded);
+    }
+
+    STANDARD.decode(encoded)
+}
+
+/// Decodes unpadded base64 in the URL-safe alphabet, like [`decode_base64`].
+pub(crate) fn decode_base64_url(encoded: impl AsRef<[u8]>) -> Result<Vec<u8>, DecodeError> {
+    #[cfg(feature = "performance")]
+    if let Ok(decoded) = base64_simd::URL_SAFE_NO_PAD.decode_to_vec(encoded.as_ref()) {
+        return Ok(decoded);
+    }
+
+    URL_SAFE_NO_PAD.decode(encoded)
+}
+
 /// Converts a base64URL-encoded string to a standard base64-encoded string.
 ///
 /// Replaces '/' with '+' and '_' with '-', and adds padding if needed.
@@ -42,6 +65,44 @@ pub(crate) fn base64_url_to_base64(encoded_string: &str) -> String {
     replaced_string
 }
 
+/// Checks that a key ID has the shape of an App Store Connect key ID, such as `2X9R4HXF34`.
+pub(crate) fn validate_key_id(key_id: &str) -> Result<(), String> {
+    if key_id.len() == 10 && key_id.bytes().all(|b| b.is_ascii_uppercase() || b.is_ascii_digit()) {
+        Ok(())
+    } else {
+        Err(format!("Invalid key ID: {}", key_id))
+    }
+}
+
+/// Checks that an issuer ID is a UUID, as shown on the Keys page in App Store Connect.
+pub(crate) fn validate_issuer_id(issuer_id: &str) -> Result<(), String> {
+    uuid::Uuid::parse_str(issuer_id)
+        .map(|_| ())
+        .map_err(|_| format!("Invalid issuer ID: {}", issuer_id))
+}
+
+/// Checks that a bundle ID is a reverse-DNS string of alphanumerics, hyphens and periods.
+pub(crate) fn validate_bundle_id(bundle_id: &str) -> Result<(), String> {
+    let is_valid = bundle_id
+        .split('.')
+        .all(|segment| !segment.is_empty() && segment.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-'));
+
+    if is_valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid bundle ID: {}", bundle_id))
+    }
+}
+
+/// Checks that a product or offer identifier is non-empty and free of whitespace and control characters.
+pub(crate) fn validate_product_identifier(identifier: &str) -> Result<(), String> {
+    if !identifier.is_empty() && !identifier.chars().any(|c| c.is_whitespace() || c.is_control() || c == '\u{2063}') {
+        Ok(())
+    } else {
+        Err(format!("Invalid identifier: {:?}", identifier))
+    }
+}
+
 /// A trait for extending the functionality of Rust strings.
 pub trait StringExt {
     /// Converts the string into a DER-encoded byte vector.
@@ -60,13 +121,13 @@ pub trait StringExt {
 
 impl StringExt for String {
     fn as_der_bytes(&self) -> Result<Vec<u8>, DecodeError> {
-        STANDARD.decode(self)
+        decode_base64(self)
     }
 }
 
 impl StringExt for &str {
     fn as_der_bytes(&self) -> Result<Vec<u8>, DecodeError> {
-        STANDARD.decode(self)
+        decode_base64(self)
     }
 }
 
@@ -86,4 +147,29 @@ mod tests {
         let result_padding = base64_url_to_base64(encoded_string_padding);
         assert_eq!(result_padding, "aGVsbG8gd29ybz==");
     }
+
+    #[test]
+    fn test_decode_base64() {
+        assert_eq!(Ok(b"hello world?".to_vec()), decode_base64("aGVsbG8gd29ybGQ/"));
+        assert_eq!(Err(DecodeError::InvalidByte(15, b'_')), decode_base64("aGVsbG8gd29ybGQ_"));
+        assert_eq!(Ok(b"hello world?".to_vec()), decode_base64_url("aGVsbG8gd29ybGQ_"));
+        assert_eq!(Err(DecodeError::InvalidByte(15, b'/')), decode_base64_url("aGVsbG8gd29ybGQ/"));
+    }
+
+    #[test]
+    fn test_validate_identifiers() {
+        assert!(validate_key_id("2X9R4HXF34").is_ok());
+        assert!(validate_key_id("keyId").is_err());
+
+        assert!(validate_issuer_id("57246542-96fe-1a63-e053-0824d011072a").is_ok());
+        assert!(validate_issuer_id("issuerId").is_err());
+
+        assert!(validate_bundle_id("com.example-app.Name").is_ok());
+        assert!(validate_bundle_id("com..example").is_err());
+        assert!(validate_bundle_id("com.example app").is_err());
+
+        assert!(validate_product_identifier("com.example.offer_1").is_ok());
+        assert!(validate_product_identifier("").is_err());
+        assert!(validate_product_identifier("offer\u{2063}id").is_err());
+    }
 }
@@ -0,0 +1,151 @@
+use crate::primitives::decoded_notification::DecodedNotification;
+use crate::signed_data_verifier::{SignedDataVerifier, SignedDataVerifierError};
+use crate::webhook::{parse_signed_payload_with_limit, WebhookBodyError, DEFAULT_MAX_BODY_SIZE};
+use bytes::Buf;
+use futures_util::{Stream, StreamExt};
+use std::sync::Arc;
+use warp::http::StatusCode;
+use warp::reject::Reject;
+use warp::{Filter, Rejection, Reply};
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum WarpNotificationError {
+    #[error("InvalidBodyError: [{0}]")]
+    InvalidBodyError(String),
+
+    #[error("WebhookBodyError: [{0}]")]
+    WebhookBodyError(#[from] WebhookBodyError),
+
+    #[error("VerificationError: [{0}]")]
+    VerificationError(#[from] SignedDataVerifierError),
+}
+
+impl WarpNotificationError {
+    /// The status the request is answered with.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            WarpNotificationError::InvalidBodyError(_) => StatusCode::BAD_REQUEST,
+            WarpNotificationError::WebhookBodyError(WebhookBodyError::BodyTooLargeError { .. }) => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            WarpNotificationError::WebhookBodyError(_) => StatusCode::BAD_REQUEST,
+            WarpNotificationError::VerificationError(_) => StatusCode::FORBIDDEN,
+        }
+    }
+}
+
+impl Reject for WarpNotificationError {}
+
+/// A filter that extracts a verified and decoded App Store Server Notification from the request body,
+/// limiting bodies to [`DEFAULT_MAX_BODY_SIZE`](crate::webhook::DEFAULT_MAX_BODY_SIZE).
+///
+/// See [`verified_notification_with_limit`].
+pub fn verified_notification(
+    verifier: SignedDataVerifier,
+) -> impl Filter<Extract = (DecodedNotification,), Error = Rejection> + Clone {
+    verified_notification_with_limit(verifier, DEFAULT_MAX_BODY_SIZE)
+}
+
+/// A filter that extracts a verified and decoded App Store Server Notification from the request body.
+///
+/// Reads the `signedPayload` from the body and verifies it, along with its signed transaction and
+/// renewal info, using `verifier`. Requests are rejected with a [`WarpNotificationError`], which
+/// [`recover_notification_error`] answers with `400 Bad Request` for malformed bodies,
+/// `413 Payload Too Large` for bodies larger than `max_body_size` bytes, and `403 Forbidden` for
+/// payloads that fail verification.
+///
+/// ```ignore
+/// let route = warp::post()
+///     .and(warp::path("notifications"))
+///     .and(verified_notification(verifier))
+///     .map(|notification: DecodedNotification| { /* ... */ warp::reply() })
+///     .recover(recover_notification_error);
+/// ```
+pub fn verified_notification_with_limit(
+    verifier: SignedDataVerifier,
+    max_body_size: usize,
+) -> impl Filter<Extract = (DecodedNotification,), Error = Rejection> + Clone {
+    let verifier = Arc::new(verifier);
+
+    warp::body::stream().and_then(move |body| {
+        let verifier = verifier.clone();
+        async move {
+            let body = read_body(body, max_body_size).await.map_err(warp::reject::custom)?;
+            let signed_payload = parse_signed_payload_with_limit(&body, max_body_size)
+                .map_err(|e| warp::reject::custom(WarpNotificationError::from(e)))?;
+
+            verifier
+                .verify_and_decode_notification_full(&signed_payload)
+                .map_err(|e| warp::reject::custom(WarpNotificationError::from(e)))
+        }
+    })
+}
+
+/// Answers the rejections of [`verified_notification`] with their status code, passing on any other rejection.
+pub async fn recover_notification_error(rejection: Rejection) -> Result<impl Reply, Rejection> {
+    match rejection.find::<WarpNotificationError>() {
+        Some(error) => Ok(warp::reply::with_status(error.to_string(), error.status_code())),
+        None => Err(rejection),
+    }
+}
+
+/// Reads a request body, stopping as soon as it exceeds `max_body_size` bytes.
+async fn read_body<B: Buf>(
+    body: impl Stream<Item = Result<B, warp::Error>>,
+    max_body_size: usize,
+) -> Result<Vec<u8>, WarpNotificationError> {
+    let mut body = std::pin::pin!(body);
+    let mut buffer = Vec::new();
+
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|e| WarpNotificationError::InvalidBodyError(e.to_string()))?;
+        let size = buffer.len() + chunk.remaining();
+        if size > max_body_size {
+            return Err(WebhookBodyError::BodyTooLargeError { size, limit: max_body_size }.into());
+        }
+        buffer.extend_from_slice(chunk.chunk());
+    }
+
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::notification_type_v2::NotificationTypeV2;
+    use crate::test_utils::{local_testing_verifier, signed_notification_fixture};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_verified_notification_filter() {
+        let route = warp::post()
+            .and(verified_notification(local_testing_verifier()))
+            .map(|notification: DecodedNotification| format!("{:?}", notification.notification_type()))
+            .recover(recover_notification_error);
+
+        let signed_payload = signed_notification_fixture(&NotificationTypeV2::Test, None);
+        let body = json!({ "signedPayload": signed_payload }).to_string();
+        let response = warp::test::request().method("POST").body(body).reply(&route).await;
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!("Test", response.body());
+
+        let body = json!({ "signedPayload": "invalid" }).to_string();
+        let response = warp::test::request().method("POST").body(body).reply(&route).await;
+        assert_eq!(StatusCode::FORBIDDEN, response.status());
+
+        let response = warp::test::request().method("POST").body("{").reply(&route).await;
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+
+        let body = vec![b' '; DEFAULT_MAX_BODY_SIZE + 1];
+        let response = warp::test::request().method("POST").body(body).reply(&route).await;
+        assert_eq!(StatusCode::PAYLOAD_TOO_LARGE, response.status());
+
+        let route = verified_notification_with_limit(local_testing_verifier(), 8)
+            .map(|_| warp::reply())
+            .recover(recover_notification_error);
+        let body = json!({ "signedPayload": "x" }).to_string();
+        let response = warp::test::request().method("POST").body(body).reply(&route).await;
+        assert_eq!(StatusCode::PAYLOAD_TOO_LARGE, response.status());
+    }
+}
@@ -0,0 +1,78 @@
+use crate::primitives::response_body_v2::ResponseBodyV2;
+
+/// The default limit on the size of a notification request body, in bytes.
+pub const DEFAULT_MAX_BODY_SIZE: usize = 256 * 1024;
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum WebhookBodyError {
+    #[error("BodyTooLargeError: [Body of {size} bytes exceeds the limit of {limit} bytes]")]
+    BodyTooLargeError { size: usize, limit: usize },
+
+    #[error("InvalidBodyError: [{0}]")]
+    InvalidBodyError(String),
+
+    #[error("MissingSignedPayloadError")]
+    MissingSignedPayloadError,
+}
+
+/// Extracts the signed payload from the raw body of a notification request, limited to [`DEFAULT_MAX_BODY_SIZE`].
+///
+/// See [`parse_signed_payload_with_limit`].
+pub fn parse_signed_payload(body: &[u8]) -> Result<String, WebhookBodyError> {
+    parse_signed_payload_with_limit(body, DEFAULT_MAX_BODY_SIZE)
+}
+
+/// Extracts the signed payload from the raw body of a notification request.
+///
+/// The App Store sends version 2 notifications, including test notifications, as a JSON object with a
+/// `signedPayload` field. The same envelope is returned by the Get Test Notification Status endpoint.
+/// Other fields are ignored.
+///
+/// [responseBodyV2](https://developer.apple.com/documentation/appstoreservernotifications/responsebodyv2)
+///
+/// # Arguments
+///
+/// * `body` - The request body as received.
+/// * `max_size` - The maximum accepted size of the body, in bytes.
+///
+/// # Returns
+///
+/// - `Ok(String)` with the signed payload, still to be verified.
+/// - `Err(WebhookBodyError::BodyTooLargeError)` if the body exceeds `max_size`.
+/// - `Err(WebhookBodyError::InvalidBodyError)` if the body isn't a JSON object.
+/// - `Err(WebhookBodyError::MissingSignedPayloadError)` if the body has no or an empty `signedPayload`.
+pub fn parse_signed_payload_with_limit(body: &[u8], max_size: usize) -> Result<String, WebhookBodyError> {
+    if body.len() > max_size {
+        return Err(WebhookBodyError::BodyTooLargeError { size: body.len(), limit: max_size });
+    }
+
+    let body: ResponseBodyV2 =
+        serde_json::from_slice(body).map_err(|e| WebhookBodyError::InvalidBodyError(e.to_string()))?;
+
+    body.signed_payload
+        .filter(|signed_payload| !signed_payload.is_empty())
+        .ok_or(WebhookBodyError::MissingSignedPayloadError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_signed_payload() {
+        assert_eq!(Ok("a.b.c".to_string()), parse_signed_payload(br#"{"signedPayload": "a.b.c"}"#));
+        assert_eq!(
+            Ok("a.b.c".to_string()),
+            parse_signed_payload(br#"{"signedPayload": "a.b.c", "sendAttempts": []}"#)
+        );
+
+        assert_eq!(Err(WebhookBodyError::MissingSignedPayloadError), parse_signed_payload(b"{}"));
+        assert_eq!(Err(WebhookBodyError::MissingSignedPayloadError), parse_signed_payload(br#"{"signedPayload": ""}"#));
+        assert!(matches!(parse_signed_payload(b"a.b.c"), Err(WebhookBodyError::InvalidBodyError(_))));
+        assert_eq!(
+            Err(WebhookBodyError::BodyTooLargeError { size: 26, limit: 10 }),
+            parse_signed_payload_with_limit(br#"{"signedPayload": "a.b.c"}"#, 10)
+        );
+    }
+}